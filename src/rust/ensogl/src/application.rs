@@ -1,6 +1,8 @@
 //! Application top-level structure definition. Handles views, keyboard shortcuts and more.
 
 pub mod command;
+pub mod keymap;
+pub mod palette;
 pub mod shortcut;
 pub mod view;
 
@@ -37,7 +39,7 @@ impl Application {
         let logger    = Logger::new("Application");
         let display   = World::new(dom);
         let commands  = command::Registry::create(&logger);
-        let shortcuts = shortcut::Registry::new(&logger, &commands);
+        let shortcuts = shortcut::Registry::new(&logger,display.scene(),&commands);
         let views     = view::Registry::create(&logger,&display,&commands,&shortcuts);
         let themes    = theme::Manager::from(&display.scene().style_sheet);
         Self {logger,display,commands,shortcuts,views,themes}
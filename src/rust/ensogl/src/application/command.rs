@@ -85,6 +85,11 @@ impl ProviderInstance {
     pub fn check_alive(&self) -> bool {
         self.network.upgrade().is_some()
     }
+
+    /// The current value of the status endpoint. Unknown statuses are false.
+    pub fn status(&self, label:&str) -> bool {
+        self.status_map.get(label).map(|t| t.frp.value()).unwrap_or(false)
+    }
 }
 
 
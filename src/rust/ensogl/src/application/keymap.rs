@@ -0,0 +1,291 @@
+//! User keymaps, allowing remapping and disabling the default shortcuts of views without
+//! rebuilding the application.
+//!
+//! A keymap is a JSON file listing shortcuts. Each of them names the target `command::Provider`
+//! label, the command label from its `command::CommandApi`, the action and an optional condition.
+//! The actions and conditions are written in the same format as in the shortcut listing:
+//!
+//! ```text
+//! { "shortcuts" :
+//!     [ { "target" : "GraphEditor", "command" : "add_node_at_cursor", "action" : "Control+n" }
+//!     , { "target" : "GraphEditor", "command" : "remove_selected_nodes", "action" : "Delete" }
+//!     , { "target" : "GraphEditor", "command" : "auto_arrange_selected_nodes", "action" : null }
+//!     , { "target"  : "GraphEditor"
+//!       , "command" : "edit_comment_for_selected_node"
+//!       , "action"  : "Control+k c"
+//!       , "when"    : "is_active"
+//!       }
+//!     ]
+//! }
+//! ```
+//!
+//! All the default shortcuts of a command mentioned in the keymap are replaced with the ones from
+//! the keymap. A shortcut without an action disables the default shortcuts of its command. Invalid
+//! shortcuts are skipped, so their commands keep the default shortcuts.
+
+use crate::prelude::*;
+
+use super::shortcut::Action;
+use super::shortcut::Condition;
+use super::shortcut::ParseError;
+use super::shortcut::Shortcut;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+
+
+// =============
+// === Error ===
+// =============
+
+/// An error of loading a keymap.
+#[derive(Clone,Debug,Eq,Fail,PartialEq)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[fail(display="Invalid keymap file: {}",message)]
+    InvalidFile { message:String },
+    #[fail(display="Invalid action '{}' of {}.{}: {}",action,target,command,error)]
+    InvalidAction { target:String, command:String, action:String, error:ParseError },
+    #[fail(display="Invalid condition '{}' of {}.{}: {}",condition,target,command,error)]
+    InvalidCondition { target:String, command:String, condition:String, error:ParseError },
+    #[fail(display="Unknown shortcut target {}.",target)]
+    UnknownTarget { target:String },
+    #[fail(display="Unknown command {}.{}.",target,command)]
+    UnknownCommand { target:String, command:String },
+}
+
+
+
+// ============
+// === File ===
+// ============
+
+/// The contents of a keymap file.
+#[derive(Clone,Debug,Default,Deserialize,Eq,PartialEq,Serialize)]
+#[allow(missing_docs)]
+pub struct File {
+    pub shortcuts : Vec<ShortcutDefinition>,
+}
+
+/// A shortcut defined in a keymap file. The `action` is missing if the shortcut disables the
+/// default ones, and the `when` condition is missing if the shortcut is always active.
+#[derive(Clone,Debug,Deserialize,Eq,PartialEq,Serialize)]
+#[allow(missing_docs)]
+pub struct ShortcutDefinition {
+    pub target  : String,
+    pub command : String,
+    pub action  : Option<String>,
+    pub when    : Option<String>,
+}
+
+
+
+// =============
+// === Entry ===
+// =============
+
+/// A parsed `ShortcutDefinition`. The `shortcut` is `None` if the entry disables the default
+/// shortcuts of the command.
+#[derive(Clone,Debug,Eq,PartialEq)]
+#[allow(missing_docs)]
+pub struct Entry {
+    pub target   : String,
+    pub command  : String,
+    pub shortcut : Option<Shortcut>,
+}
+
+impl Entry {
+    /// Constructor. Parses the action and the condition of the definition.
+    pub fn new(definition:ShortcutDefinition) -> Result<Self,Error> {
+        let ShortcutDefinition {target,command,action,when} = definition;
+        let condition = match when {
+            None       => Condition::Ok,
+            Some(when) => when.parse::<Condition>().map_err(|error| {
+                let (target,command,condition) = (target.clone(),command.clone(),when.clone());
+                Error::InvalidCondition {target,command,condition,error}
+            })?,
+        };
+        let shortcut = match action {
+            None         => None,
+            Some(action) => {
+                let parsed = action.parse::<Action>().map_err(|error| {
+                    let (target,command) = (target.clone(),command.clone());
+                    Error::InvalidAction {target,command,action,error}
+                })?;
+                Some(Shortcut::new_when(parsed,target.as_str(),command.as_str(),condition))
+            }
+        };
+        Ok(Self {target,command,shortcut})
+    }
+}
+
+
+
+// ==============
+// === Keymap ===
+// ==============
+
+/// User keymap, a set of shortcuts overriding the default ones. See the module docs to learn more.
+#[derive(Clone,Debug,Default)]
+pub struct Keymap {
+    entries : Vec<Entry>,
+}
+
+impl Keymap {
+    /// Constructor.
+    pub fn new(entries:Vec<Entry>) -> Self {
+        Self {entries}
+    }
+
+    /// Parses the keymap from the contents of a keymap file. Fails only if the file itself is
+    /// malformed. The entries with invalid actions or conditions are skipped, and errors
+    /// describing them are returned together with the keymap, like in `validate`.
+    pub fn from_json(json:&str) -> Result<(Self,Vec<Error>),Error> {
+        let file = serde_json::from_str::<File>(json).map_err(|error| {
+            Error::InvalidFile {message:error.to_string()}
+        })?;
+        let mut errors = Vec::new();
+        let entries    = file.shortcuts.into_iter().filter_map(|definition| {
+            Entry::new(definition).map_err(|error| errors.push(error)).ok()
+        }).collect();
+        Ok((Self {entries},errors))
+    }
+
+    /// The keymap entries.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Removes the entries naming targets or commands which are not present in `commands`, a map
+    /// from target labels to their command labels, and returns errors describing them.
+    pub fn validate(&mut self, commands:&HashMap<String,HashSet<String>>) -> Vec<Error> {
+        let mut errors = Vec::new();
+        self.entries.retain(|entry| {
+            let target  = entry.target.clone();
+            let command = entry.command.clone();
+            let error   = match commands.get(&entry.target) {
+                None => Some(Error::UnknownTarget {target}),
+                Some(labels) if !labels.contains(&entry.command) =>
+                    Some(Error::UnknownCommand {target,command}),
+                _ => None,
+            };
+            let is_valid = error.is_none();
+            errors.extend(error);
+            is_valid
+        });
+        errors
+    }
+
+    /// The shortcuts of the target after applying this keymap over its default shortcuts. The
+    /// defaults of the commands mentioned in the keymap are replaced with the keymap shortcuts.
+    pub fn apply(&self, target:&str, defaults:Vec<Shortcut>) -> Vec<Shortcut> {
+        let entries    = self.entries.iter().filter(|entry| entry.target == target).collect_vec();
+        let overridden = entries.iter().map(|entry| entry.command.as_str()).collect::<HashSet<_>>();
+        let defaults   = defaults.into_iter().filter(|shortcut| {
+            !overridden.contains(shortcut.command().as_str())
+        });
+        let shortcuts = entries.into_iter().filter_map(|entry| entry.shortcut.clone());
+        defaults.chain(shortcuts).collect()
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::frp::io::keyboard::Key;
+
+    const KEYMAP : &str = r#"
+        { "shortcuts" :
+            [ { "target" : "Editor", "command" : "save", "action" : "Control+k Control+s" }
+            , { "target" : "Editor", "command" : "save", "action" : "Meta+s", "when" : "!a" }
+            , { "target" : "Editor", "command" : "close", "action" : null }
+            ]
+        }"#;
+
+    fn editor_shortcut(action:&str, command:&str) -> Shortcut {
+        Shortcut::new(action.parse::<Action>().unwrap(),"Editor",command)
+    }
+
+    #[test]
+    fn parsing() {
+        let (keymap,errors) = Keymap::from_json(KEYMAP).unwrap();
+        let entries         = keymap.entries();
+        let not_a           = Condition::not(Condition::Simple("a".into()));
+        let meta_s          = Action::press(&[Key::Meta,Key::Character("s".into())]);
+        let save_meta       = Shortcut::new_when(meta_s,"Editor","save",not_a);
+        assert!(errors.is_empty());
+        assert_eq!(entries.len(),3);
+        assert_eq!(entries[0].shortcut,Some(editor_shortcut("Control+k Control+s","save")));
+        assert_eq!(entries[1].shortcut,Some(save_meta));
+        assert_eq!(entries[2].shortcut,None);
+        assert_eq!(entries[2].command,"close");
+    }
+
+    #[test]
+    fn parsing_errors() {
+        let json = r#"{"shortcuts":
+            [ {"target":"Editor" , "command":"save"  , "action":"Ctrl+s"}
+            , {"target":"Editor" , "command":"close" , "action":"Escape"}
+            , {"target":"Editor" , "command":"save"  , "action":null , "when":"a &&"}
+            ]}"#;
+        let invalid_action = Error::InvalidAction {
+            target  : "Editor".into(),
+            command : "save".into(),
+            action  : "Ctrl+s".into(),
+            error   : ParseError::UnknownKey {name:"Ctrl".into()},
+        };
+        let invalid_condition = Error::InvalidCondition {
+            target    : "Editor".into(),
+            command   : "save".into(),
+            condition : "a &&".into(),
+            error     : ParseError::UnexpectedEnd,
+        };
+        let (keymap,errors) = Keymap::from_json(json).unwrap();
+        assert_eq!(errors,vec![invalid_action,invalid_condition]);
+        assert_eq!(keymap.entries().len(),1);
+        assert_eq!(keymap.entries()[0].command,"close");
+        let missing_field = Keymap::from_json(r#"{"shortcuts":[{"target":"Editor"}]}"#);
+        assert!(matches!(missing_field,Err(Error::InvalidFile{..})));
+    }
+
+    #[test]
+    fn validation() {
+        let json       = r#"{"shortcuts":
+            [ {"target":"Editor" , "command":"save" , "action":"Control+s"}
+            , {"target":"Editor" , "command":"sav"  , "action":"Control+s"}
+            , {"target":"Editro" , "command":"save" , "action":"Control+s"}
+            ]}"#;
+        let mut keymap = Keymap::from_json(json).unwrap().0;
+        let commands   : HashSet<String> = vec!["save".into(),"close".into()].into_iter().collect();
+        let commands   = vec![("Editor".to_string(),commands)].into_iter().collect();
+        let errors     = keymap.validate(&commands);
+        assert_eq!(errors,vec!
+            [ Error::UnknownCommand {target:"Editor".into(),command:"sav".into()}
+            , Error::UnknownTarget  {target:"Editro".into()}
+            ]);
+        assert_eq!(keymap.entries().len(),1);
+        assert_eq!(keymap.entries()[0].command,"save");
+    }
+
+    #[test]
+    fn applying() {
+        let keymap   = Keymap::from_json(KEYMAP).unwrap().0;
+        let defaults = vec!
+            [ editor_shortcut("Control+s","save")
+            , editor_shortcut("Escape","close")
+            , editor_shortcut("Control+z","undo")
+            ];
+        let shortcuts = keymap.apply("Editor",defaults.clone());
+        let actions   = shortcuts.iter().map(|t| t.action().to_string()).collect_vec();
+        assert_eq!(actions,vec!["Control+z","Control+k Control+s","Meta+s"]);
+        assert_eq!(keymap.apply("Viewer",defaults.clone()),defaults);
+    }
+}
@@ -0,0 +1,84 @@
+//! Command palette model. Lists the commands of the live instances of registered command
+//! providers, together with their current shortcuts and status, and allows running them. See the
+//! `search` module to learn how the commands are searched for.
+
+pub mod search;
+
+pub use search::Searchable;
+
+use crate::prelude::*;
+
+use super::command;
+use super::view;
+
+use crate::frp;
+
+
+
+// ============
+// === Item ===
+// ============
+
+/// A command of a live `command::ProviderInstance`, an entry of the command palette.
+#[derive(Clone,Debug)]
+#[allow(missing_docs)]
+pub struct Item {
+    pub target    : String,
+    pub command   : String,
+    pub caption   : String,
+    pub shortcuts : Vec<String>,
+    pub enabled   : bool,
+    pub frp       : frp::Source,
+}
+
+impl Item {
+    /// Runs the command.
+    pub fn run(&self) {
+        self.frp.emit(())
+    }
+}
+
+impl Searchable for Item {
+    fn search_texts(&self) -> Vec<&str> {
+        vec![&self.caption,&self.command]
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+
+
+// =============
+// === Items ===
+// =============
+
+/// All commands of the live instances of registered command providers, sorted by the provider
+/// and command labels. The shortcuts are the ones currently used by the views, including the user
+/// keymap. A command is enabled if it has no shortcuts or if the condition of any of them is
+/// currently true.
+pub fn items(commands:&command::Registry, views:&view::Registry) -> Vec<Item> {
+    let instances = commands.instances.borrow();
+    let mut items = Vec::new();
+    for target in instances.keys().sorted() {
+        let view_shortcuts = views.shortcuts(target);
+        for instance in instances[target].iter().filter(|t| t.check_alive()) {
+            for command in instance.command_map.keys().sorted() {
+                let endpoint = &instance.command_map[command];
+                let bindings = view_shortcuts.iter().filter(|t| t.command().as_str() == command);
+                let bindings = bindings.collect_vec();
+                let enabled  = bindings.is_empty() || bindings.iter().any(|shortcut| {
+                    shortcut.condition().eval(&|name| instance.status(name))
+                });
+                let shortcuts = bindings.iter().map(|t| t.action().to_string()).collect();
+                let target  = target.clone();
+                let command = command.clone();
+                let caption = endpoint.caption.clone();
+                let frp     = endpoint.frp.clone_ref();
+                items.push(Item {target,command,caption,shortcuts,enabled,frp});
+            }
+        }
+    }
+    items
+}
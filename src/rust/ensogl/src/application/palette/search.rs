@@ -0,0 +1,144 @@
+//! Fuzzy search and ranking of the command palette items.
+
+use crate::prelude::*;
+
+use std::cmp::Reverse;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The score of every query character found in the text.
+const MATCH_SCORE : usize = 1;
+
+/// The bonus for a character matched right after the previous matched one.
+const CONSECUTIVE_BONUS : usize = 2;
+
+/// The bonus for a character matched at the beginning of a word, like `n` in `add_node` or
+/// `E` in `GraphEditor`.
+const WORD_START_BONUS : usize = 3;
+
+
+
+// ==================
+// === Searchable ===
+// ==================
+
+/// An item which can be searched for.
+pub trait Searchable {
+    /// Texts the query is matched against. The best matching one determines the item score.
+    fn search_texts(&self) -> Vec<&str>;
+
+    /// Checks whether the item can be used now. Disabled items are ranked after the enabled ones.
+    fn is_enabled(&self) -> bool {
+        true
+    }
+}
+
+
+
+// ==============
+// === Search ===
+// ==============
+
+/// The score of the fuzzy match of the query in the text, or `None` if the text does not contain
+/// all the query characters in order. The letter case and the whitespace in the query are
+/// ignored. The characters are matched greedily, and the matches at word beginnings and runs of
+/// consecutive matches score higher.
+pub fn score(query:&str, text:&str) -> Option<usize> {
+    let query         = query.to_lowercase();
+    let mut query     = query.chars().filter(|c| !c.is_whitespace()).peekable();
+    let mut score     = 0;
+    let mut previous  = None;
+    let mut was_match = false;
+    for c in text.chars() {
+        let lowercase = c.to_lowercase().next().unwrap_or(c);
+        let is_match  = query.peek() == Some(&lowercase);
+        if is_match {
+            let is_word_start = previous.map(|p:char| {
+                !p.is_alphanumeric() || p.is_lowercase() && c.is_uppercase()
+            }).unwrap_or(true);
+            score += MATCH_SCORE;
+            score += if was_match     { CONSECUTIVE_BONUS } else { 0 };
+            score += if is_word_start { WORD_START_BONUS  } else { 0 };
+            query.next();
+        }
+        was_match = is_match;
+        previous  = Some(c);
+    }
+    query.peek().is_none().as_some(score)
+}
+
+/// The items matching the query, the best ones first. The enabled items are listed before the
+/// disabled ones, and the items with equal rank keep their order.
+pub fn search<'a,T:Searchable>(query:&str, items:&'a [T]) -> Vec<&'a T> {
+    let mut matches = items.iter().filter_map(|item| {
+        let texts = item.search_texts().into_iter();
+        texts.filter_map(|text| score(query,text)).max().map(|score| (item,score))
+    }).collect_vec();
+    matches.sort_by_key(|(item,score)| (!item.is_enabled(),Reverse(*score)));
+    matches.into_iter().map(|(item,_)| item).collect()
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug,PartialEq)]
+    struct Item {
+        text    : &'static str,
+        enabled : bool,
+    }
+
+    impl Searchable for Item {
+        fn search_texts(&self) -> Vec<&str> {
+            vec![self.text]
+        }
+
+        fn is_enabled(&self) -> bool {
+            self.enabled
+        }
+    }
+
+    #[test]
+    fn scoring() {
+        let cases = vec!
+            [ (""     , "add_node_at_cursor" , Some(0))
+            , ("anac" , "add_node_at_cursor" , Some(16))
+            , ("ANAC" , "add_node_at_cursor" , Some(16))
+            , ("a n"  , "add_node"           , Some(8))
+            , ("an"   , "banana"             , Some(4))
+            , ("ge"   , "GraphEditor"        , Some(8))
+            , ("add"  , "Add node"           , Some(10))
+            , ("add"  , "Padded"             , Some(7))
+            , ("nodes", "Add node"           , None)
+            , ("dda"  , "Add node"           , None)
+            ];
+        for (query,text,expected) in cases {
+            assert_eq!(score(query,text),expected,"'{}' in '{}'",query,text);
+        }
+    }
+
+    #[test]
+    fn ranking() {
+        let items = vec!
+            [ Item {text:"Padded"               , enabled:true}
+            , Item {text:"Add comment"          , enabled:false}
+            , Item {text:"Add node"             , enabled:true}
+            , Item {text:"Remove selected nodes", enabled:true}
+            ];
+        let texts = |query| search(query,&items).into_iter().map(|t| t.text).collect_vec();
+        assert_eq!(texts("add"),vec!["Add node","Padded","Add comment"]);
+        assert_eq!(texts("rsn"),vec!["Remove selected nodes"]);
+        assert_eq!(texts("xyz"),Vec::<&str>::new());
+        assert_eq!(texts(""),vec!["Padded","Add node","Remove selected nodes","Add comment"]);
+    }
+}
@@ -4,11 +4,35 @@ use crate::prelude::*;
 
 use super::command;
 
+use crate::control::callback;
 use crate::control::io::keyboard::listener::KeyboardFrpBindings;
+use crate::control::io::mouse::Button;
+use crate::control::io::mouse;
+use crate::display::scene::Scene;
+use crate::frp::io::keyboard::Code;
+use crate::frp::io::keyboard::CodeMask;
+use crate::frp::io::keyboard::Key;
 use crate::frp::io::keyboard::KeyMask;
 use crate::frp::io::keyboard::Keyboard;
+use crate::frp::io::keyboard::KeyboardState;
+use crate::frp::io::keyboard::key_name;
 use crate::frp;
-use crate::system::web;
+
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The maximum number of steps of a multi-step shortcut, like `Control+k Control+s`.
+pub const MAX_SEQUENCE_LENGTH : usize = 8;
+
+/// The maximum number of status variables of conditions checked for overlapping. Conditions with
+/// more variables are assumed to overlap.
+const MAX_OVERLAP_VARIABLES : usize = 12;
 
 
 
@@ -16,7 +40,7 @@ use crate::system::web;
 // === Registry ===
 // ================
 
-type RuleMap   = HashMap<KeyMask,Vec<WeakHandle>>;
+type RuleMap   = HashMap<Chord,Vec<WeakHandle>>;
 type ActionMap = HashMap<ActionType,RuleMap>;
 
 /// Keyboard shortcut registry. You can add new shortcuts by using the `add` method and get a
@@ -24,23 +48,35 @@ type ActionMap = HashMap<ActionType,RuleMap>;
 /// when defining shortcuts by GUI components. When a component is unloaded, all its default
 /// shortcuts should be removed as well.
 ///
+/// Shortcuts can consist of several steps, like `Control+k Control+s`, which need to be pressed one
+/// after another, with no more than `sequence_timeout_ms` between them. If shortcuts with different
+/// number of steps match the same input, only the longest ones whose targets are live and whose
+/// conditions hold are triggered. A step can contain mouse buttons as well. Such chords are pressed
+/// when the button is pressed while the keys are held.
+///
 /// Note: we should probably handle user shortcuts in a slightly different way. User shortcuts
 /// should persist and should probably not return handles. Alternatively, there should be an user
 /// shortcut manager which will own and manage the handles.
 #[derive(Clone,CloneRef,Debug)]
 pub struct Registry {
-    model   : RegistryModel,
-    network : frp::Network,
+    model         : RegistryModel,
+    network       : frp::Network,
+    mouse_handles : Rc<Vec<callback::Handle>>,
 }
 
 /// Internal representation of `Registry`.
 #[derive(Clone,CloneRef,Debug)]
 pub struct RegistryModel {
-    logger            : Logger,
-    keyboard          : Keyboard,
-    keyboard_bindings : Rc<KeyboardFrpBindings>,
-    command_registry  : command::Registry,
-    action_map        : Rc<RefCell<ActionMap>>,
+    logger              : Logger,
+    keyboard            : Keyboard,
+    keyboard_bindings   : Rc<KeyboardFrpBindings>,
+    command_registry    : command::Registry,
+    action_map          : Rc<RefCell<ActionMap>>,
+    clock               : frp::Clock,
+    keys                : Rc<RefCell<Input>>,
+    buttons             : Rc<Cell<ButtonMask>>,
+    history             : Rc<RefCell<History>>,
+    sequence_timeout_ms : f64,
 }
 
 impl Deref for Registry {
@@ -58,100 +94,179 @@ impl RegistryModel {
         let keyboard_bindings = Rc::new(KeyboardFrpBindings::new(&logger,&keyboard));
         let command_registry  = command_registry.clone_ref();
         let action_map        = default();
+        let clock             = frp::Clock::web();
+        let keys              = default();
+        let buttons           = default();
+        let history           = default();
+        // TODO move to theme configuration.
+        let sequence_timeout_ms = 1000.0;
 
-        Self {logger,keyboard,keyboard_bindings,command_registry,action_map}
+        Self {logger,keyboard,keyboard_bindings,command_registry,action_map,clock,keys,buttons
+             ,history,sequence_timeout_ms}
     }
 }
 
 impl Registry {
     /// Constructor.
-    pub fn new(logger:&Logger, command_registry:&command::Registry) -> Self {
+    pub fn new(logger:&Logger, scene:&Scene, command_registry:&command::Registry) -> Self {
         let model = RegistryModel::new(logger,command_registry);
 
         // TODO move to theme configuration.
         let double_press_threshold_ms = 300.0;
+        let clock                     = model.clock.clone_ref();
+        let state                     = model.keyboard.state.clone_ref();
+        let on_repeat                 = model.keyboard.on_repeat.clone_ref();
         frp::new_network! { network
-            let key_mask = model.keyboard.key_mask.clone_ref();
-            nothing_pressed      <- key_mask.map(|m| *m == default());
+            input                <- state.map(Input::new);
+            eval input ((input) model.on_keyboard_input(input));
+            nothing_pressed      <- input.map(|m| m.is_empty());
             nothing_pressed_prev <- nothing_pressed.previous();
-            press                <- key_mask.gate_not(&nothing_pressed);
+            press                <- input.gate_not(&nothing_pressed);
             single_press         <- press.gate(&nothing_pressed_prev);
             eval press ((m) model.process_action(ActionType::Press,m));
 
             single_press_prev  <- single_press.previous();
-            press_time         <- single_press.map(|_| web::performance().now());
-            press_time_prev    <- press_time.previous();
-            time_delta         <- press_time.map2(&press_time_prev, |t1,t2| (t1-t2));
-            is_double_press    <- time_delta.map4(&press,&single_press_prev,&nothing_pressed_prev,
-                move |delta,t,s,g| *g && *delta < double_press_threshold_ms && t == s);
+            // The timeout state from before the current press tells whether the previous single
+            // press was recent enough.
+            press_timed_out    <- single_press.timeout(&clock,double_press_threshold_ms);
+            was_timed_out      <- press_timed_out.previous();
+            is_double_press    <- single_press.map3(&single_press_prev,&was_timed_out,
+                |t,s,timed_out| !timed_out && t == s);
             double_press       <- press.gate(&is_double_press);
             eval double_press ((m) model.process_action(ActionType::DoublePress,m));
 
-            let prev_key = model.keyboard.previous_key_mask.clone_ref();
-            the_same_key       <- prev_key.map2(&key_mask,|t,s| t == s);
-            release            <- prev_key.gate_not(&the_same_key);
+            repeat             <- input.sample(&on_repeat);
+            eval repeat       ((m) model.process_action(ActionType::Repeat,m));
+
+            prev_input         <- input.previous();
+            the_same_input     <- prev_input.map2(&input,|t,s| t == s);
+            release            <- prev_input.gate_not(&the_same_input);
             eval release      ((m) model.process_action(ActionType::Release,m));
         }
-        Self {model,network}
+
+        let mouse_manager = &scene.mouse.mouse_manager;
+        let on_down       = mouse_manager.on_down.add(f!([model](event:&mouse::OnDown) {
+            model.on_mouse_down(event.button())
+        }));
+        let on_up         = mouse_manager.on_up.add(f!([model](event:&mouse::OnUp) {
+            model.on_mouse_up(event.button())
+        }));
+        let mouse_handles = Rc::new(vec![on_down,on_up]);
+        Self {model,network,mouse_handles}
     }
 }
 
 impl RegistryModel {
+    /// Live shortcuts which can be triggered together with the provided one. See
+    /// `Shortcut::conflicts_with` to learn more.
+    pub fn conflicts(&self, shortcut:&Shortcut) -> Vec<Handle> {
+        let action_map = self.action_map.borrow();
+        let rule_map   = action_map.get(&shortcut.action.tp);
+        let rules      = rule_map.and_then(|t| t.get(shortcut.action.last_step()));
+        let handles    = rules.into_iter().flatten().filter_map(|rule| rule.upgrade());
+        handles.filter(|handle| handle.conflicts_with(shortcut)).collect()
+    }
 
-    fn process_action(&self, action_type:ActionType, key_mask:&KeyMask) {
-        let action_map_mut = &mut self.action_map.borrow_mut();
-        if let Some(rule_map) = action_map_mut.get_mut(&action_type) {
-            if let Some(rules) = rule_map.get_mut(key_mask) {
-                self.process_rules(rules)
-            }
+    fn on_keyboard_input(&self, input:&Input) {
+        *self.keys.borrow_mut() = input.clone();
+        let input = input.with_buttons(self.buttons.get());
+        self.history.borrow_mut().update(&input,self.clock.now(),self.sequence_timeout_ms);
+    }
+
+    fn on_mouse_down(&self, button:Button) {
+        let mut buttons = self.buttons.get();
+        buttons.set(button,true);
+        self.buttons.set(buttons);
+        let input = self.keys.borrow().with_buttons(buttons);
+        self.history.borrow_mut().update(&input,self.clock.now(),self.sequence_timeout_ms);
+        self.process_action(ActionType::Press,&input);
+    }
+
+    fn on_mouse_up(&self, button:Button) {
+        let mut buttons = self.buttons.get();
+        if buttons.contains(button) {
+            let input = self.keys.borrow().with_buttons(buttons);
+            self.process_action(ActionType::Release,&input);
+            buttons.set(button,false);
+            self.buttons.set(buttons);
+            let input = self.keys.borrow().with_buttons(buttons);
+            self.history.borrow_mut().update(&input,self.clock.now(),self.sequence_timeout_ms);
         }
     }
 
-    fn process_rules(&self, rules:&mut Vec<WeakHandle>) {
+    /// Triggers the commands of the shortcuts matching the action. The targets and conditions of
+    /// the shortcuts are checked first, and only the commands of the remaining shortcuts with the
+    /// biggest number of steps are triggered. For example, `Control+k Control+s` takes precedence
+    /// over `Control+s`, unless its condition does not hold.
+    fn process_action(&self, action_type:ActionType, input:&Input) {
+        let rules       = self.matching_rules(action_type,input);
         let mut targets = Vec::new();
         {
             let borrowed_command_map = self.command_registry.instances.borrow();
-            rules.retain(|weak_rule| {
-                weak_rule.upgrade().map(|rule| {
-                    let target = &rule.target;
-                    borrowed_command_map.get(target).for_each(|commands| {
-                        for command in commands {
-                            if Self::condition_checker(&rule.when,&command.status_map) {
-                                let command_name = &rule.command.name;
-                                match command.command_map.get(command_name){
-                                    Some(t) => targets.push(t.frp.clone_ref()),
-                                    None    => warning!(&self.logger,
-                                        "Command {command_name} was not found on {target}."),
-                                }
+            for rule in &rules {
+                let target    = &rule.target;
+                let steps_len = rule.action.steps.len();
+                borrowed_command_map.get(target).for_each(|commands| {
+                    for command in commands {
+                        if rule.when.eval(&|name| command.status(name)) {
+                            let command_name = &rule.command.name;
+                            match command.command_map.get(command_name){
+                                Some(t) => targets.push((steps_len,t.frp.clone_ref())),
+                                None    => warning!(&self.logger,
+                                    "Command {command_name} was not found on {target}."),
                             }
                         }
-                    })
-                }).is_some()
-            })
+                    }
+                })
+            }
         }
-        for target in targets {
-            target.emit(())
+        let max_len = targets.iter().map(|(steps_len,_)| *steps_len).max().unwrap_or_default();
+        for (steps_len,target) in targets {
+            if steps_len == max_len {
+                target.emit(())
+            }
         }
     }
 
-
-    fn condition_checker
-    (condition:&Condition, status_map:&HashMap<String,command::Status>) -> bool {
-        match condition {
-            Condition::Ok           => true,
-            Condition::Simple(name) => status_map.get(name).map(|t| t.frp.value()).unwrap_or(false)
+    /// Live shortcuts triggered by the action, regardless of their number of steps.
+    fn matching_rules(&self, action_type:ActionType, input:&Input) -> Vec<Handle> {
+        let mut rules      = Vec::new();
+        let action_map_mut = &mut self.action_map.borrow_mut();
+        if let Some(rule_map) = action_map_mut.get_mut(&action_type) {
+            for chord in input.chords() {
+                if let Some(weak_rules) = rule_map.get_mut(&chord) {
+                    weak_rules.retain(|weak_rule| {
+                        weak_rule.upgrade().map(|rule| rules.push(rule)).is_some()
+                    })
+                }
+            }
         }
+        let history = self.history.borrow();
+        rules.retain(|rule| history.matches(&rule.action));
+        rules
     }
 }
 
 impl Add<Shortcut> for &Registry {
     type Output = Handle;
     fn add(self, shortcut:Shortcut) -> Handle {
-        let handle     = Handle::new(shortcut.rule);
+        for conflict in self.conflicts(&shortcut) {
+            let action        = &shortcut.action;
+            let target        = &shortcut.target;
+            let command       = &shortcut.command.name;
+            let other_target  = &conflict.target;
+            let other_command = &conflict.command.name;
+            warning!(&self.logger,
+                "Shortcut '{action}' of {target}.{command} conflicts with the one of \
+                {other_target}.{other_command}.");
+        }
+        let chord      = shortcut.action.last_step().clone();
+        let tp         = shortcut.action.tp;
+        let handle     = Handle::new(shortcut);
         let instance   = handle.downgrade();
         let action_map = &mut self.action_map.borrow_mut();
-        let rule_map   = action_map.entry(shortcut.action.tp).or_default();
-        let rules      = rule_map.entry(shortcut.action.key_mask).or_default();
+        let rule_map   = action_map.entry(tp).or_default();
+        let rules      = rule_map.entry(chord).or_default();
         rules.push(instance);
         handle
     }
@@ -167,31 +282,192 @@ impl Add<Shortcut> for &Registry {
 /// `Registry`.
 #[derive(Clone,CloneRef,Debug,Shrinkwrap)]
 pub struct Handle {
-    rule : Rc<Rule>
+    shortcut : Rc<Shortcut>
 }
 
 impl Handle {
     /// Constructor.
-    pub fn new(rule:Rule) -> Self {
-        let rule = Rc::new(rule);
-        Self {rule}
+    pub fn new(shortcut:Shortcut) -> Self {
+        let shortcut = Rc::new(shortcut);
+        Self {shortcut}
     }
 
     fn downgrade(&self) -> WeakHandle {
-        let rule = Rc::downgrade(&self.rule);
-        WeakHandle {rule}
+        let shortcut = Rc::downgrade(&self.shortcut);
+        WeakHandle {shortcut}
     }
 }
 
 /// Weak version of the `Handle`.
 #[derive(Clone,CloneRef,Debug)]
 pub struct WeakHandle {
-    rule : Weak<Rule>
+    shortcut : Weak<Shortcut>
 }
 
 impl WeakHandle {
     fn upgrade(&self) -> Option<Handle> {
-        self.rule.upgrade().map(|rule| Handle {rule})
+        self.shortcut.upgrade().map(|shortcut| Handle {shortcut})
+    }
+}
+
+
+
+// ==================
+// === ButtonMask ===
+// ==================
+
+/// The mouse button bitmask (each bit represents one button).
+#[derive(Clone,Copy,Debug,Default,Eq,Hash,PartialEq)]
+pub struct ButtonMask {
+    bits : u8,
+}
+
+impl ButtonMask {
+    /// Check if button bit is on.
+    pub fn contains(self, button:Button) -> bool {
+        self.bits & (1 << button as u8) != 0
+    }
+
+    /// Set the `button` bit for new state.
+    pub fn set(&mut self, button:Button, state:bool) {
+        if state { self.bits |= 1 << button as u8    }
+        else     { self.bits &= !(1 << button as u8) }
+    }
+
+    /// Check if no bit is on.
+    pub fn is_empty(self) -> bool {
+        self.bits == 0
+    }
+
+    /// Check if all buttons of this mask are in the other one.
+    pub fn is_subset(self, other:Self) -> bool {
+        self.bits & !other.bits == 0
+    }
+
+    /// The buttons in the mask.
+    pub fn buttons(self) -> impl Iterator<Item=Button> {
+        let all = vec![mouse::Button0,mouse::Button1,mouse::Button2,mouse::Button3,mouse::Button4];
+        all.into_iter().filter(move |button| self.contains(*button))
+    }
+}
+
+impl From<Button> for ButtonMask {
+    fn from(button:Button) -> Self {
+        let mut mask = Self::default();
+        mask.set(button,true);
+        mask
+    }
+}
+
+impl From<&[Button]> for ButtonMask {
+    fn from(buttons:&[Button]) -> Self {
+        let mut mask = Self::default();
+        for button in buttons {
+            mask.set(*button,true)
+        }
+        mask
+    }
+}
+
+
+
+// =============
+// === Input ===
+// =============
+
+/// The keys and mouse buttons being held.
+#[derive(Clone,Debug,Default,Eq,PartialEq)]
+#[allow(missing_docs)]
+pub struct Input {
+    pub keys    : KeyMask,
+    pub codes   : CodeMask,
+    pub buttons : ButtonMask,
+}
+
+impl Input {
+    /// Constructor. No mouse buttons are held.
+    pub fn new(state:&KeyboardState) -> Self {
+        let keys    = state.key_mask().clone();
+        let codes   = *state.code_mask();
+        let buttons = default();
+        Self {keys,codes,buttons}
+    }
+
+    /// Copy of this input with the provided mouse buttons held.
+    pub fn with_buttons(&self, buttons:ButtonMask) -> Self {
+        Self {buttons,..self.clone()}
+    }
+
+    /// Check if nothing is held.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.codes.is_empty() && self.buttons.is_empty()
+    }
+
+    /// Check if everything held in this input is held in the other one.
+    pub fn is_subset(&self, other:&Self) -> bool {
+        self.keys.is_subset(&other.keys)
+            && self.codes.is_subset(&other.codes)
+            && self.buttons.is_subset(other.buttons)
+    }
+
+    /// The logical and physical chords describing this input.
+    pub fn chords(&self) -> Vec<Chord> {
+        let logical  = Chord::new(self.keys.clone(),self.buttons);
+        let physical = Chord::new(self.codes,self.buttons);
+        vec![logical,physical]
+    }
+}
+
+
+
+// ===============
+// === History ===
+// ===============
+
+/// Recently pressed steps of multi-step shortcuts.
+#[derive(Clone,Debug,Default)]
+pub struct History {
+    steps          : Vec<Input>,
+    last_input     : Input,
+    last_step_time : f64,
+}
+
+impl History {
+    /// Records the input change. The input is a new step if something was pressed since the last
+    /// change. If the last step is still held, it is replaced instead, as the user is still
+    /// completing the chord, for example by pressing `k` after `Control`. The history is cleared
+    /// if more than `timeout` passed since the last step.
+    pub fn update(&mut self, input:&Input, time:f64, timeout:f64) {
+        let is_new_step = input != &self.last_input && self.last_input.is_subset(input);
+        if is_new_step {
+            if time - self.last_step_time > timeout {
+                self.steps.clear()
+            }
+            if self.steps.last() == Some(&self.last_input) {
+                self.steps.pop();
+            }
+            self.steps.push(input.clone());
+            if self.steps.len() > MAX_SEQUENCE_LENGTH {
+                self.steps.remove(0);
+            }
+            self.last_step_time = time;
+        }
+        self.last_input = input.clone();
+    }
+
+    /// Checks whether the steps of the action were pressed recently. Single-step actions do not
+    /// depend on the history.
+    pub fn matches(&self, action:&Action) -> bool {
+        let steps = &action.steps;
+        if steps.len() <= 1 {
+            true
+        } else {
+            let offset = self.steps.len().checked_sub(steps.len());
+            offset.map(|offset| {
+                let history = &self.steps[offset..];
+                steps.iter().zip(history).all(|(step,input)| step.matches(input))
+            }).unwrap_or(false)
+        }
     }
 }
 
@@ -205,41 +481,223 @@ impl WeakHandle {
 #[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
 #[allow(missing_docs)]
 pub enum ActionType {
-    Press, Release, DoublePress
+    Press, Release, DoublePress,
+    /// Emitted for every repeated key down event generated by holding the keys down.
+    Repeat,
 }
 
-/// Keyboard action defined as `ActionType` and `KeyMask`, like "release key 'n'". Please note that
-/// the release action happens as soon as the key mask is no longer valid. So for example, after
-/// pressing key "n", and then pressing key "a" (while holding "n"), the release event of the key
-/// "n" will be emitted.
-#[derive(Clone,Copy,Debug)]
+impl ActionType {
+    /// Checks whether the last step of the action can contain mouse buttons. Double presses and
+    /// repeats are detected for keyboard input only.
+    pub fn supports_buttons(self) -> bool {
+        matches!(self,Self::Press|Self::Release)
+    }
+}
+
+/// Keys which need to be held in order to perform an action. Logical keys are matched by their
+/// meaning in the current keyboard layout, like "the key producing `z`". Physical keys are matched
+/// by their location, like "the key labeled `Z` on the US keyboard", which is `Y` on the German
+/// one. Physical keys are useful for shortcuts which should stay in place regardless of layout,
+/// or which use keys producing different characters in different layouts.
+#[derive(Clone,Debug,Eq,Hash,PartialEq)]
+#[allow(missing_docs)]
+pub enum KeyBinding {
+    Logical  (KeyMask),
+    Physical (CodeMask),
+}
+
+impl KeyBinding {
+    /// Names of the keys. Logical modifier keys go first, like in `Control+Space`. Physical keys
+    /// are prefixed with `@`, like `@KeyZ`. The space and plus characters are named `Space` and
+    /// `Plus` in order to be readable and unambiguous.
+    pub fn key_names(&self) -> Vec<String> {
+        const MODIFIERS:&[&str] = &["Alt","Control","Meta","Shift"];
+        match self {
+            Self::Logical(mask) => {
+                let is_modifier = |name:&&str| MODIFIERS.contains(name);
+                let modifiers   = mask.key_names().filter(is_modifier);
+                let others      = mask.key_names().filter(|name| !is_modifier(name));
+                modifiers.chain(others).map(|name| match name {
+                    " " => "Space".into(),
+                    "+" => "Plus".into(),
+                    _   => name.into(),
+                }).collect()
+            }
+            Self::Physical(mask) => mask.code_names().map(|name| format!("@{}",name)).collect(),
+        }
+    }
+}
+
+impl From<KeyMask>  for KeyBinding { fn from(mask:KeyMask)  -> Self { Self::Logical(mask)  } }
+impl From<CodeMask> for KeyBinding { fn from(mask:CodeMask) -> Self { Self::Physical(mask) } }
+
+/// Keys and mouse buttons which need to be held at the same time, a single step of an `Action`.
+#[derive(Clone,Debug,Eq,Hash,PartialEq)]
+#[allow(missing_docs)]
+pub struct Chord {
+    pub keys    : KeyBinding,
+    pub buttons : ButtonMask,
+}
+
+impl Chord {
+    /// Constructor.
+    pub fn new(keys:impl Into<KeyBinding>, buttons:impl Into<ButtonMask>) -> Self {
+        let keys    = keys.into();
+        let buttons = buttons.into();
+        Self {keys,buttons}
+    }
+
+    /// Checks whether the input consists of exactly the keys and buttons of this chord.
+    pub fn matches(&self, input:&Input) -> bool {
+        let keys_match = match &self.keys {
+            KeyBinding::Logical  (mask) => mask == &input.keys,
+            KeyBinding::Physical (mask) => mask == &input.codes,
+        };
+        keys_match && self.buttons == input.buttons
+    }
+
+    /// The chord with physical keys replaced by the logical ones they produce in the US layout, if
+    /// all of them have a meaning there. Used to compare chords written in different forms.
+    pub fn normalized(&self) -> Chord {
+        let keys = match &self.keys {
+            KeyBinding::Physical(mask) => mask.us_layout_key_mask().map(KeyBinding::Logical),
+            KeyBinding::Logical(_)     => None,
+        };
+        let keys = keys.unwrap_or_else(|| self.keys.clone());
+        Chord::new(keys,self.buttons)
+    }
+}
+
+impl Display for Chord {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys    = self.keys.key_names().into_iter();
+        let buttons = self.buttons.buttons().map(|button| format!("{:?}",button));
+        write!(f,"{}",keys.chain(buttons).join("+"))
+    }
+}
+
+impl From<KeyBinding> for Chord {
+    fn from(t:KeyBinding) -> Self { Chord::new(t,ButtonMask::default()) }
+}
+
+impl From<ButtonMask> for Chord {
+    fn from(t:ButtonMask) -> Self { Chord::new(KeyMask::default(),t) }
+}
+
+impl From<KeyMask>  for Chord { fn from(t:KeyMask)  -> Self { KeyBinding::from(t).into() } }
+impl From<CodeMask> for Chord { fn from(t:CodeMask) -> Self { KeyBinding::from(t).into() } }
+impl From<Button>   for Chord { fn from(t:Button)   -> Self { ButtonMask::from(t).into() } }
+
+impl From<&[Key]>    for Chord { fn from(t:&[Key])    -> Self { KeyMask::from(t).into() } }
+impl From<&[Key;1]>  for Chord { fn from(t:&[Key;1])  -> Self { KeyMask::from(t).into() } }
+impl From<&[Key;2]>  for Chord { fn from(t:&[Key;2])  -> Self { KeyMask::from(t).into() } }
+impl From<&[Key;3]>  for Chord { fn from(t:&[Key;3])  -> Self { KeyMask::from(t).into() } }
+impl From<&[Key;4]>  for Chord { fn from(t:&[Key;4])  -> Self { KeyMask::from(t).into() } }
+impl From<&[Key;5]>  for Chord { fn from(t:&[Key;5])  -> Self { KeyMask::from(t).into() } }
+impl From<&[Code]>   for Chord { fn from(t:&[Code])   -> Self { CodeMask::from(t).into() } }
+impl From<&[Code;1]> for Chord { fn from(t:&[Code;1]) -> Self { CodeMask::from(t).into() } }
+impl From<&[Code;2]> for Chord { fn from(t:&[Code;2]) -> Self { CodeMask::from(t).into() } }
+impl From<&[Code;3]> for Chord { fn from(t:&[Code;3]) -> Self { CodeMask::from(t).into() } }
+impl From<&[Code;4]> for Chord { fn from(t:&[Code;4]) -> Self { CodeMask::from(t).into() } }
+impl From<&[Code;5]> for Chord { fn from(t:&[Code;5]) -> Self { CodeMask::from(t).into() } }
+
+/// Keyboard action defined as `ActionType` and a sequence of `Chord`s, like "release key 'n'" or
+/// "press `Control+k`, then `Control+s`". The action type applies to the last step, while all the
+/// previous ones need to be pressed before. Please note that the release action happens as soon
+/// as the key mask is no longer valid. So for example, after pressing key "n", and then pressing
+/// key "a" (while holding "n"), the release event of the key "n" will be emitted.
+///
+/// Only the press and release actions can contain mouse buttons in their last step. The
+/// constructors panic when used with buttons otherwise, and parsing such actions fails.
+#[derive(Clone,Debug,Eq,PartialEq)]
 #[allow(missing_docs)]
 pub struct Action {
-    pub tp       : ActionType,
-    pub key_mask : KeyMask,
+    pub tp    : ActionType,
+    pub steps : Vec<Chord>,
 }
 
 impl Action {
     /// Constructor.
-    pub fn new(tp:impl Into<ActionType>, key_mask:impl Into<KeyMask>) -> Self {
-        let tp       = tp.into();
-        let key_mask = key_mask.into();
-        Self {tp,key_mask}
+    pub fn new(tp:impl Into<ActionType>, keys:impl Into<Chord>) -> Self {
+        let tp    = tp.into();
+        let steps = vec![keys.into()];
+        Self {tp,steps}.validated()
     }
 
     /// Smart constructor for the `Press` action.
-    pub fn press(key_mask:impl Into<KeyMask>) -> Self {
-        Self::new(ActionType::Press,key_mask)
+    pub fn press(keys:impl Into<Chord>) -> Self {
+        Self::new(ActionType::Press,keys)
     }
 
     /// Smart constructor for the `Release` action.
-    pub fn release(key_mask:impl Into<KeyMask>) -> Self {
-        Self::new(ActionType::Release,key_mask)
+    pub fn release(keys:impl Into<Chord>) -> Self {
+        Self::new(ActionType::Release,keys)
     }
 
     /// Smart constructor for the `DoublePress` action.
-    pub fn double_press(key_mask:impl Into<KeyMask>) -> Self {
-        Self::new(ActionType::DoublePress,key_mask)
+    pub fn double_press(keys:impl Into<Chord>) -> Self {
+        Self::new(ActionType::DoublePress,keys)
+    }
+
+    /// Smart constructor for the `Repeat` action.
+    pub fn repeat(keys:impl Into<Chord>) -> Self {
+        Self::new(ActionType::Repeat,keys)
+    }
+
+    /// Adds the next step of the action, like `Control+s` in `Control+k Control+s`.
+    pub fn then(mut self, keys:impl Into<Chord>) -> Self {
+        self.steps.push(keys.into());
+        self.validated()
+    }
+
+    /// Adds the mouse buttons to the last step of the action, like `Button0` in
+    /// `Control+Button0`.
+    pub fn with_buttons(mut self, buttons:impl Into<ButtonMask>) -> Self {
+        let buttons = buttons.into();
+        if let Some(step) = self.steps.last_mut() {
+            step.buttons = ButtonMask {bits:step.buttons.bits | buttons.bits};
+        }
+        self.validated()
+    }
+
+    /// The step the action type applies to.
+    pub fn last_step(&self) -> &Chord {
+        self.steps.last().expect("Action without steps.")
+    }
+
+    /// Checks whether the mouse buttons of the last step are supported by the action type.
+    pub fn validate(&self) -> Result<(),ParseError> {
+        if self.tp.supports_buttons() || self.last_step().buttons.is_empty() {
+            Ok(())
+        } else {
+            Err(ParseError::UnsupportedButtons {action:self.to_string()})
+        }
+    }
+
+    /// The action with all steps normalized, see `Chord::normalized`.
+    pub fn normalized(&self) -> Action {
+        let tp    = self.tp;
+        let steps = self.steps.iter().map(Chord::normalized).collect();
+        Action {tp,steps}
+    }
+
+    fn validated(self) -> Self {
+        if let Err(err) = self.validate() {
+            panic!("Invalid shortcut action. {}",err)
+        }
+        self
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = match self.tp {
+            ActionType::Press       => "",
+            ActionType::Release     => "release ",
+            ActionType::DoublePress => "double_press ",
+            ActionType::Repeat      => "repeat ",
+        };
+        write!(f,"{}{}",prefix,self.steps.iter().join(" "))
     }
 }
 
@@ -250,7 +708,7 @@ impl Action {
 // ================
 
 /// A keyboard shortcut, an `Action` associated with a `Rule`.
-#[derive(Clone,Debug,Shrinkwrap)]
+#[derive(Clone,Debug,Eq,PartialEq,Shrinkwrap)]
 pub struct Shortcut {
     #[shrinkwrap(main_field)]
     rule   : Rule,
@@ -273,6 +731,19 @@ impl Shortcut {
         let action = action.into();
         Self {rule,action}
     }
+
+    /// The action triggering the shortcut.
+    pub fn action(&self) -> &Action {
+        &self.action
+    }
+
+    /// Checks whether both shortcuts can be triggered by the same input at the same time, that is,
+    /// whether they have the same action and their conditions can be true at the same time. The
+    /// actions are compared after normalization, so a physical key conflicts with the logical one
+    /// it produces in the US layout.
+    pub fn conflicts_with(&self, other:&Shortcut) -> bool {
+        self.action.normalized() == other.action.normalized() && self.when.overlaps(&other.when)
+    }
 }
 
 
@@ -284,7 +755,7 @@ impl Shortcut {
 /// A shortcut rule. Consist of target identifier (`command::ProviderTag`), a `Command` that will
 /// be evaluated on the target, and a `Condition` which needs to be true in order for the command
 /// to be executed.
-#[derive(Clone,Debug)]
+#[derive(Clone,Debug,Eq,PartialEq)]
 pub struct Rule {
     target  : String,
     command : Command,
@@ -305,6 +776,21 @@ impl Rule {
         let command = command.into();
         Self {target,when,command}
     }
+
+    /// The label of the target `command::Provider`.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// The command evaluated on the target.
+    pub fn command(&self) -> &Command {
+        &self.command
+    }
+
+    /// The condition which needs to be true in order for the command to be executed.
+    pub fn condition(&self) -> &Condition {
+        &self.when
+    }
 }
 
 
@@ -331,15 +817,371 @@ impl From<&str> for Command {
 // === Condition ===
 // =================
 
-// TODO: Uncomment and handle more complex cases. Left here to show the intention of future dev.
-/// Condition expression.
-#[derive(Clone,Debug)]
+/// Condition expression. The variables are labels of `command::Status` endpoints.
+#[derive(Clone,Debug,Eq,PartialEq)]
 #[allow(missing_docs)]
 pub enum Condition {
     Ok,
     Simple (String),
-    // Or     (Box<Condition>, Box<Condition>),
-    // And    (Box<Condition>, Box<Condition>),
+    Not    (Box<Condition>),
+    Or     (Box<Condition>, Box<Condition>),
+    And    (Box<Condition>, Box<Condition>),
+}
+
+impl Condition {
+    /// Smart constructor for the `Not` condition.
+    pub fn not(condition:Condition) -> Self {
+        Self::Not(Box::new(condition))
+    }
+
+    /// Smart constructor for the `Or` condition.
+    pub fn or(left:Condition, right:Condition) -> Self {
+        Self::Or(Box::new(left),Box::new(right))
+    }
+
+    /// Smart constructor for the `And` condition.
+    pub fn and(left:Condition, right:Condition) -> Self {
+        Self::And(Box::new(left),Box::new(right))
+    }
+
+    /// Evaluates the condition with the provided values of variables.
+    pub fn eval(&self, status:&dyn Fn(&str)->bool) -> bool {
+        match self {
+            Self::Ok                => true,
+            Self::Simple (name)     => status(name),
+            Self::Not    (t)        => !t.eval(status),
+            Self::Or     (t1,t2) => t1.eval(status) || t2.eval(status),
+            Self::And    (t1,t2) => t1.eval(status) && t2.eval(status),
+        }
+    }
+
+    /// All variables used in the condition.
+    pub fn variables(&self) -> BTreeSet<&str> {
+        let mut variables = BTreeSet::new();
+        self.collect_variables(&mut variables);
+        variables
+    }
+
+    fn collect_variables<'a>(&'a self, variables:&mut BTreeSet<&'a str>) {
+        match self {
+            Self::Ok                => {},
+            Self::Simple (name)     => { variables.insert(name); },
+            Self::Not    (t)        => t.collect_variables(variables),
+            Self::Or     (t1,t2)
+            | Self::And  (t1,t2)    => {
+                t1.collect_variables(variables);
+                t2.collect_variables(variables);
+            }
+        }
+    }
+
+    /// Checks whether both conditions can be true at the same time. All combinations of variable
+    /// values are checked, unless there are too many of them, in which case the conditions are
+    /// assumed to overlap.
+    pub fn overlaps(&self, other:&Condition) -> bool {
+        let mut variables = self.variables();
+        variables.extend(other.variables());
+        let variables = variables.into_iter().collect_vec();
+        if variables.len() > MAX_OVERLAP_VARIABLES {
+            true
+        } else {
+            (0..1_usize << variables.len()).any(|values| {
+                let status = |name:&str| {
+                    let index = variables.iter().position(|t| *t == name);
+                    index.map(|index| values & (1 << index) != 0).unwrap_or(false)
+                };
+                self.eval(&status) && other.eval(&status)
+            })
+        }
+    }
+
+    fn is_compound(&self) -> bool {
+        match self {
+            Self::Or(..) | Self::And(..) => true,
+            _                            => false,
+        }
+    }
+}
+
+impl Display for Condition {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        let operand = |t:&Condition, parenthesize:bool| {
+            if parenthesize { format!("({})",t) } else { t.to_string() }
+        };
+        match self {
+            Self::Ok            => write!(f,"true"),
+            Self::Simple (name) => write!(f,"{}",name),
+            Self::Not    (t)    => write!(f,"!{}",operand(t,t.is_compound())),
+            Self::Or     (t1,t2) => write!(f,"{} || {}",t1,t2),
+            Self::And    (t1,t2) => {
+                let is_or = |t:&Condition| matches!(t,Self::Or(..));
+                write!(f,"{} && {}",operand(t1,is_or(t1)),operand(t2,is_or(t2)))
+            }
+        }
+    }
+}
+
+
+
+// ===============
+// === Parsing ===
+// ===============
+
+/// An error of parsing actions and conditions from their textual form, the one used by their
+/// `Display` implementations.
+#[derive(Clone,Debug,Eq,Fail,PartialEq)]
+#[allow(missing_docs)]
+pub enum ParseError {
+    #[fail(display="Unknown key '{}'.",name)]
+    UnknownKey { name:String },
+    #[fail(display="Missing key name in '{}'.",chord)]
+    MissingKey { chord:String },
+    #[fail(display="Chord '{}' mixes logical and physical keys.",chord)]
+    MixedKeys { chord:String },
+    #[fail(display="The action has no steps.")]
+    EmptyAction,
+    #[fail(display="The action has too many steps ({}).",count)]
+    TooManySteps { count:usize },
+    #[fail(display="Mouse buttons cannot be used in '{}'. Only press and release actions support \
+        them.",action)]
+    UnsupportedButtons { action:String },
+    #[fail(display="Unexpected '{}' in the condition.",token)]
+    UnexpectedToken { token:String },
+    #[fail(display="Unexpected end of the condition.")]
+    UnexpectedEnd,
+}
+
+fn parse_button(name:&str) -> Option<Button> {
+    match name {
+        "Button0" => Some(mouse::Button0),
+        "Button1" => Some(mouse::Button1),
+        "Button2" => Some(mouse::Button2),
+        "Button3" => Some(mouse::Button3),
+        "Button4" => Some(mouse::Button4),
+        _         => None,
+    }
+}
+
+fn parse_key(name:&str) -> Result<Key,ParseError> {
+    let key = match name {
+        "Space" => Ok(Key::Character(" ".into())),
+        "Plus"  => Ok(Key::Character("+".into())),
+        _       => name.parse::<Key>(),
+    };
+    let unknown = || ParseError::UnknownKey {name:name.into()};
+    key.ok().filter(|key| key_name(key).is_some()).ok_or_else(unknown)
+}
+
+fn parse_code(name:&str) -> Result<Code,ParseError> {
+    let unknown = || ParseError::UnknownKey {name:format!("@{}",name)};
+    name.parse::<Code>().ok().filter(|code| *code != Code::Unidentified).ok_or_else(unknown)
+}
+
+impl FromStr for Chord {
+    type Err = ParseError;
+
+    /// Parses chords like `Control+Space`, `@KeyZ+@ControlLeft` or `Meta+Button0`.
+    fn from_str(chord:&str) -> Result<Self,Self::Err> {
+        let mut keys    = Vec::new();
+        let mut codes   = Vec::new();
+        let mut buttons = ButtonMask::default();
+        for name in chord.split('+') {
+            if name.is_empty() {
+                return Err(ParseError::MissingKey {chord:chord.into()})
+            } else if let Some(button) = parse_button(name) {
+                buttons.set(button,true)
+            } else if name.starts_with('@') {
+                codes.push(parse_code(&name[1..])?)
+            } else {
+                keys.push(parse_key(name)?)
+            }
+        }
+        match (keys.is_empty(),codes.is_empty()) {
+            (_,true)     => Ok(Chord::new(KeyMask::from(&keys[..]),buttons)),
+            (true,false) => Ok(Chord::new(CodeMask::from(&codes[..]),buttons)),
+            _            => Err(ParseError::MixedKeys {chord:chord.into()}),
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = ParseError;
+
+    /// Parses actions like `Control+k Control+s` or `double_press Shift`.
+    fn from_str(action:&str) -> Result<Self,Self::Err> {
+        let mut words = action.split_whitespace().peekable();
+        let tp        = match words.peek() {
+            Some(&"release")      => Some(ActionType::Release),
+            Some(&"double_press") => Some(ActionType::DoublePress),
+            Some(&"repeat")       => Some(ActionType::Repeat),
+            _                     => None,
+        };
+        if tp.is_some() {
+            words.next();
+        }
+        let tp    = tp.unwrap_or(ActionType::Press);
+        let steps = words.map(|word| word.parse::<Chord>()).collect::<Result<Vec<_>,_>>()?;
+        if steps.is_empty() {
+            Err(ParseError::EmptyAction)
+        } else if steps.len() > MAX_SEQUENCE_LENGTH {
+            Err(ParseError::TooManySteps {count:steps.len()})
+        } else {
+            let action = Self {tp,steps};
+            action.validate().map(|_| action)
+        }
+    }
+}
+
+impl FromStr for Condition {
+    type Err = ParseError;
+
+    /// Parses conditions like `!(a && b) || c`. The `&&` operator binds stronger than `||`.
+    fn from_str(condition:&str) -> Result<Self,Self::Err> {
+        let tokens     = ConditionParser::tokens(condition)?;
+        let mut parser = ConditionParser {tokens,position:0};
+        let condition  = parser.or()?;
+        match parser.next() {
+            None        => Ok(condition),
+            Some(token) => Err(ParseError::UnexpectedToken {token}),
+        }
+    }
+}
+
+/// Recursive descent parser of `Condition` expressions.
+#[derive(Debug)]
+struct ConditionParser {
+    tokens   : Vec<String>,
+    position : usize,
+}
+
+impl ConditionParser {
+    fn tokens(condition:&str) -> Result<Vec<String>,ParseError> {
+        let is_name_char = |c:char| c.is_alphanumeric() || c == '_';
+        let mut tokens   = Vec::new();
+        let mut chars    = condition.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                _ if c.is_whitespace() => {},
+                '!' | '(' | ')'        => tokens.push(c.to_string()),
+                '&' | '|' if chars.peek() == Some(&c) => {
+                    chars.next();
+                    tokens.push(format!("{}{}",c,c))
+                }
+                _ if is_name_char(c) => {
+                    let mut name = c.to_string();
+                    while let Some(&c) = chars.peek().filter(|c| is_name_char(**c)) {
+                        name.push(c);
+                        chars.next();
+                    }
+                    tokens.push(name)
+                }
+                _ => return Err(ParseError::UnexpectedToken {token:c.to_string()}),
+            }
+        }
+        Ok(tokens)
+    }
+
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(|t| t.as_str())
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Condition,ParseError> {
+        let mut condition = self.and()?;
+        while self.peek() == Some("||") {
+            self.position += 1;
+            condition = Condition::or(condition,self.and()?);
+        }
+        Ok(condition)
+    }
+
+    fn and(&mut self) -> Result<Condition,ParseError> {
+        let mut condition = self.not()?;
+        while self.peek() == Some("&&") {
+            self.position += 1;
+            condition = Condition::and(condition,self.not()?);
+        }
+        Ok(condition)
+    }
+
+    fn not(&mut self) -> Result<Condition,ParseError> {
+        if self.peek() == Some("!") {
+            self.position += 1;
+            Ok(Condition::not(self.not()?))
+        } else {
+            self.atom()
+        }
+    }
+
+    fn atom(&mut self) -> Result<Condition,ParseError> {
+        match self.next() {
+            None                           => Err(ParseError::UnexpectedEnd),
+            Some(token) if token == "true" => Ok(Condition::Ok),
+            Some(token) if token == "("    => {
+                let condition = self.or()?;
+                match self.next() {
+                    Some(token) if token == ")" => Ok(condition),
+                    Some(token)                 => Err(ParseError::UnexpectedToken {token}),
+                    None                        => Err(ParseError::UnexpectedEnd),
+                }
+            }
+            Some(token) => {
+                let is_name = token.chars().all(|c| c.is_alphanumeric() || c == '_');
+                if is_name { Ok(Condition::Simple(token)) }
+                else       { Err(ParseError::UnexpectedToken {token}) }
+            }
+        }
+    }
+}
+
+
+
+// ===========
+// === Doc ===
+// ===========
+
+/// Documentation of a shortcut, a row of the shortcut listing.
+#[derive(Clone,Debug,Eq,PartialEq)]
+#[allow(missing_docs)]
+pub struct Doc {
+    pub target    : String,
+    pub command   : String,
+    pub caption   : String,
+    pub action    : String,
+    pub condition : Option<String>,
+}
+
+impl Doc {
+    /// Constructor.
+    pub fn new(shortcut:&Shortcut, caption:impl Into<String>) -> Self {
+        let target    = shortcut.target.clone();
+        let command   = shortcut.command.name.clone();
+        let caption   = caption.into().trim().to_string();
+        let action    = shortcut.action.to_string();
+        let condition = (shortcut.when != Condition::Ok).as_some_from(|| shortcut.when.to_string());
+        Self {target,command,caption,action,condition}
+    }
+}
+
+/// Renders the shortcut docs as a Markdown table, in the format of `docs/shortcuts.md`. The pipe
+/// characters, like the ones of `||` conditions, are escaped, as they would split the cells.
+pub fn docs_table(docs:&[Doc]) -> String {
+    let escape    = |text:&str| text.replace('|',"\\|");
+    let mut table = String::from("| Shortcut | Action |\n| --- | --- |\n");
+    for doc in docs {
+        let condition = doc.condition.as_ref().map(|t| format!("Only when `{}`.",t));
+        let caption   = iter::once(doc.caption.clone()).chain(condition);
+        let caption   = caption.filter(|t| !t.is_empty()).join(" ");
+        let action    = escape(&doc.action);
+        let caption   = escape(&caption);
+        table.push_str(&format!("| <kbd>{}</kbd> | {} |\n",action,caption));
+    }
+    table
 }
 
 
@@ -355,6 +1197,17 @@ pub trait DefaultShortcutProvider : command::Provider {
         default()
     }
 
+    /// Documentation of the default shortcuts, with the captions of their commands taken from the
+    /// `command::CommandApi` docs.
+    fn default_shortcut_docs() -> Vec<Doc> {
+        let captions = Self::command_api_docs().into_iter().map(|t| (t.label,t.caption));
+        let captions = captions.collect::<HashMap<_,_>>();
+        Self::default_shortcuts().iter().map(|shortcut| {
+            let caption = captions.get(&shortcut.command.name).cloned().unwrap_or_default();
+            Doc::new(shortcut,caption)
+        }).collect()
+    }
+
     /// Helper for defining shortcut targeting this object.
     fn self_shortcut_when<A,C>(action:A, command:C, condition:Condition) -> Shortcut
     where A:Into<Action>, C:Into<Command> {
@@ -376,3 +1229,255 @@ impl<T:command::Provider> DefaultShortcutProvider for T {
         default()
     }
 }
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn character(c:&str) -> Key {
+        Key::Character(c.into())
+    }
+
+    fn keys(keys:&[Key]) -> Input {
+        let keys = KeyMask::from(keys);
+        Input {keys,..default()}
+    }
+
+    fn simple(name:&str) -> Condition {
+        Condition::Simple(name.into())
+    }
+
+    #[test]
+    fn condition_overlapping() {
+        let a     = || simple("a");
+        let b     = || simple("b");
+        let cases = vec!
+            [ (Condition::Ok                             , a()                  , true)
+            , (a()                                       , b()                  , true)
+            , (a()                                       , Condition::not(a())  , false)
+            , (Condition::and(a(),b())                   , Condition::not(b())  , false)
+            , (Condition::or(a(),b())                    , Condition::not(b())  , true)
+            , (Condition::and(a(),Condition::not(a()))   , Condition::Ok        , false)
+            ];
+        for (left,right,expected) in cases {
+            assert_eq!(left.overlaps(&right),expected,"{} and {}",left,right);
+            assert_eq!(right.overlaps(&left),expected,"{} and {}",right,left);
+        }
+    }
+
+    #[test]
+    fn condition_display() {
+        let a     = || simple("a");
+        let b     = || simple("b");
+        let c     = || simple("c");
+        let cases = vec!
+            [ (Condition::Ok                                  , "true")
+            , (Condition::not(a())                            , "!a")
+            , (Condition::not(Condition::and(a(),b()))        , "!(a && b)")
+            , (Condition::and(Condition::or(a(),b()),c())     , "(a || b) && c")
+            , (Condition::or(Condition::and(a(),b()),c())     , "a && b || c")
+            ];
+        for (condition,expected) in cases {
+            assert_eq!(condition.to_string(),expected);
+        }
+    }
+
+    #[test]
+    fn action_display() {
+        let control_k = [Key::Control,character("k")];
+        let control_s = [Key::Control,character("s")];
+        let control_z = [Code::ControlLeft,Code::KeyZ];
+        let cases = vec!
+            [ (Action::press(&[Key::Control,character(" ")])            , "Control+Space")
+            , (Action::release(&[Key::Shift,Key::Alt])                  , "release Alt+Shift")
+            , (Action::press(&control_k).then(&control_s)               , "Control+k Control+s")
+            , (Action::double_press(&control_z) , "double_press @KeyZ+@ControlLeft")
+            , (Action::press(&[Key::Meta]).with_buttons(mouse::Button0) , "Meta+Button0")
+            , (Action::press(mouse::Button2)                            , "Button2")
+            ];
+        for (action,expected) in cases {
+            assert_eq!(action.to_string(),expected);
+        }
+    }
+
+    #[test]
+    fn action_parsing() {
+        let actions = vec!
+            [ Action::press(&[Key::Control,character(" ")])
+            , Action::press(&[character("+")])
+            , Action::release(&[Key::Shift,Key::Alt])
+            , Action::press(&[Key::Control,character("k")]).then(&[Key::Control,character("s")])
+            , Action::double_press(&[Code::ControlLeft,Code::KeyZ])
+            , Action::repeat(&[Key::ArrowLeft])
+            , Action::press(&[Key::Meta]).with_buttons(mouse::Button0)
+            , Action::press(mouse::Button2)
+            ];
+        for action in actions {
+            assert_eq!(action.to_string().parse::<Action>(),Ok(action));
+        }
+        assert_eq!("Control+K".parse(),Ok(Action::press(&[Key::Control,character("k")])));
+        let repeat = "repeat Button1";
+        let errors = vec!
+            [ (""                  , ParseError::EmptyAction)
+            , ("release"           , ParseError::EmptyAction)
+            , ("Ctrl+s"            , ParseError::UnknownKey {name:"Ctrl".into()})
+            , ("@KeyQ+@Foo"        , ParseError::UnknownKey {name:"@Foo".into()})
+            , ("Control++"         , ParseError::MissingKey {chord:"Control++".into()})
+            , ("Control+@KeyS"     , ParseError::MixedKeys  {chord:"Control+@KeyS".into()})
+            , ("a b c d e f g h i" , ParseError::TooManySteps {count:9})
+            , (repeat              , ParseError::UnsupportedButtons {action:repeat.into()})
+            ];
+        for (action,error) in errors {
+            assert_eq!(action.parse::<Action>(),Err(error),"{}",action);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected="Invalid shortcut action.")]
+    fn double_press_with_buttons() {
+        let _action = Action::double_press(&[Key::Control]).with_buttons(mouse::Button0);
+    }
+
+    #[test]
+    fn condition_parsing() {
+        let a     = || simple("a");
+        let b     = || simple("b");
+        let c     = || simple("c");
+        let cases = vec!
+            [ ("true"               , Condition::Ok)
+            , ("is_active"          , simple("is_active"))
+            , ("!!a"                , Condition::not(Condition::not(a())))
+            , ("a || b && c"        , Condition::or(a(),Condition::and(b(),c())))
+            , ("(a || b) && c"      , Condition::and(Condition::or(a(),b()),c()))
+            , (" ! ( a&&b ) "       , Condition::not(Condition::and(a(),b())))
+            , ("a && b && c"        , Condition::and(Condition::and(a(),b()),c()))
+            ];
+        for (text,condition) in cases {
+            assert_eq!(text.parse::<Condition>(),Ok(condition.clone()),"{}",text);
+            assert_eq!(condition.to_string().parse::<Condition>(),Ok(condition));
+        }
+        let errors = vec!
+            [ (""       , ParseError::UnexpectedEnd)
+            , ("a &&"   , ParseError::UnexpectedEnd)
+            , ("(a || b", ParseError::UnexpectedEnd)
+            , ("a & b"  , ParseError::UnexpectedToken {token:"&".into()})
+            , ("a b"    , ParseError::UnexpectedToken {token:"b".into()})
+            , ("a && )" , ParseError::UnexpectedToken {token:")".into()})
+            ];
+        for (text,error) in errors {
+            assert_eq!(text.parse::<Condition>(),Err(error),"{}",text);
+        }
+    }
+
+    #[test]
+    fn chord_matching() {
+        let control_z = [Key::Control,character("z")];
+        let codes     = CodeMask::from(&[Code::ControlLeft,Code::KeyY]);
+        let input     = Input {codes,..keys(&control_z)};
+        assert!(Chord::from(&control_z).matches(&input));
+        assert!(Chord::from(&[Code::ControlLeft,Code::KeyY]).matches(&input));
+        assert!(!Chord::from(&[Code::ControlLeft,Code::KeyZ]).matches(&input));
+        assert!(!Chord::from(&control_z).matches(&input.with_buttons(mouse::Button0.into())));
+    }
+
+    #[test]
+    fn sequence_history() {
+        let control   = keys(&[Key::Control]);
+        let control_k = keys(&[Key::Control,character("k")]);
+        let control_s = keys(&[Key::Control,character("s")]);
+        let sequence  = Action::press(&[Key::Control,character("k")])
+            .then(&[Key::Control,character("s")]);
+        let timeout   = 1000.0;
+        let none      = Input::default();
+        let cases : Vec<(&str,Vec<(f64,&Input)>,bool)> = vec!
+            [ ("Holding Control.",
+               vec![(0.0,&control),(1.0,&control_k),(2.0,&control),(3.0,&control_s)], true)
+            , ("Releasing all.",
+               vec![(0.0,&control_k),(1.0,&none),(2.0,&control),(3.0,&control_s)], true)
+            , ("Timeout.",
+               vec![(0.0,&control_k),(1.0,&control),(1500.0,&control_s)], false)
+            , ("Other step between.",
+               vec![(0.0,&control_k),(1.0,&control),(2.0,&control_k),(3.0,&control),
+                    (4.0,&control_s)], true)
+            , ("Wrong order.",
+               vec![(0.0,&control_s),(1.0,&control),(2.0,&control_k)], false)
+            ];
+        for (description,inputs,expected) in cases {
+            let mut history = History::default();
+            for (time,input) in inputs {
+                history.update(input,time,timeout);
+            }
+            assert_eq!(history.matches(&sequence),expected,"{}",description);
+            assert!(history.matches(&Action::press(&[Key::Control,character("s")])));
+        }
+    }
+
+    #[test]
+    fn shortcut_conflicts() {
+        let save     = || Action::press(&[Key::Control,character("s")]);
+        let shortcut = |action,condition| Shortcut::new_when(action,"Editor","save",condition);
+        let base     = shortcut(save(),simple("is_active"));
+        assert!(base.conflicts_with(&shortcut(save(),Condition::Ok)));
+        assert!(!base.conflicts_with(&shortcut(save(),Condition::not(simple("is_active")))));
+        let release  = Action::release(&[Key::Control,character("s")]);
+        assert!(!base.conflicts_with(&shortcut(release,Condition::Ok)));
+        assert!(!base.conflicts_with(&shortcut(save().then(&[character("x")]),Condition::Ok)));
+        let physical = Action::press(&[Code::ControlLeft,Code::KeyS]);
+        assert!(base.conflicts_with(&shortcut(physical,Condition::Ok)));
+        let physical = Action::press(&[Code::ControlLeft,Code::KeyD]);
+        assert!(!base.conflicts_with(&shortcut(physical,Condition::Ok)));
+        let numpad   = || shortcut(Action::press(&[Code::Numpad1]),Condition::Ok);
+        assert!(numpad().conflicts_with(&numpad()));
+    }
+
+    #[test]
+    fn shortcut_docs() {
+        #[derive(Debug)]
+        struct Editor {network:frp::Network}
+        impl command::FrpNetworkProvider for Editor {
+            fn network(&self) -> &frp::Network { &self.network }
+        }
+        impl command::CommandApi for Editor {
+            fn command_api_docs() -> Vec<command::EndpointDocs> {
+                vec![command::EndpointDocs::new("save","Save the file.")]
+            }
+            fn command_api(&self) -> Vec<command::CommandEndpoint> { default() }
+        }
+        impl command::StatusApi for Editor {
+            fn status_api_docs() -> Vec<command::EndpointDocs> { default() }
+            fn status_api(&self) -> Vec<command::StatusEndpoint> { default() }
+        }
+        impl command::Provider for Editor {
+            fn label() -> &'static str { "Editor" }
+        }
+        impl DefaultShortcutProvider for Editor {
+            fn default_shortcuts() -> Vec<Shortcut> {
+                let action = Action::press(&[Key::Control,character("k")]);
+                let action = action.then(&[Key::Control,character("s")]);
+                let close     = Action::press(&[Key::Escape]);
+                let is_hidden = Condition::or(simple("is_hidden"),simple("is_empty"));
+                vec! [ Self::self_shortcut_when(action,"save",simple("is_modified"))
+                     , Self::self_shortcut(close.clone(),"close")
+                     , Self::self_shortcut_when(close,"hide",Condition::not(is_hidden))
+                     ]
+            }
+        }
+
+        let docs = Editor::default_shortcut_docs();
+        assert_eq!(docs[0].target,"Editor");
+        assert_eq!(docs[0].caption,"Save the file.");
+        assert_eq!(docs[1].caption,"");
+        assert_eq!(docs_table(&docs),
+            "| Shortcut | Action |\n\
+             | --- | --- |\n\
+             | <kbd>Control+k Control+s</kbd> | Save the file. Only when `is_modified`. |\n\
+             | <kbd>Escape</kbd> |  |\n\
+             | <kbd>Escape</kbd> | Only when `!(is_hidden \\|\\| is_empty)`. |\n");
+    }
+}
@@ -4,6 +4,8 @@ use crate::prelude::*;
 
 use crate::display::world::World;
 use super::command;
+use super::keymap::Keymap;
+use super::keymap;
 use super::shortcut;
 
 
@@ -24,10 +26,14 @@ pub trait View : command::Provider + shortcut::DefaultShortcutProvider {
 // === Definition ===
 // ==================
 
-/// View definition. Keeps shortcut handles for each registered view.
+/// View definition. Keeps the command captions, default shortcuts, and handles and documentation of
+/// the shortcuts in use for each registered view.
 #[derive(Debug)]
 pub struct Definition {
-    shortcut_handles : Vec<shortcut::Handle>
+    commands          : HashMap<String,String>,
+    default_shortcuts : Vec<shortcut::Shortcut>,
+    shortcut_handles  : Vec<shortcut::Handle>,
+    shortcut_docs     : Vec<shortcut::Doc>,
 }
 
 
@@ -46,6 +52,7 @@ pub struct Registry {
     pub command_registry  : command::Registry,
     pub shortcut_registry : shortcut::Registry,
     pub definitions       : Rc<RefCell<HashMap<String,Definition>>>,
+    pub keymap            : Rc<RefCell<Keymap>>,
 }
 
 impl Registry {
@@ -61,18 +68,78 @@ impl Registry {
         let command_registry  = command_registry.clone_ref();
         let shortcut_registry = shortcut_registry.clone_ref();
         let definitions       = default();
-        Self {logger,display,command_registry,shortcut_registry,definitions}
+        let keymap            = default();
+        Self {logger,display,command_registry,shortcut_registry,definitions,keymap}
     }
 
-    /// View registration.
-    pub fn register<V:View>(&self) {
-        let label            = V::label().into();
-        let shortcut_handles = V::default_shortcuts().into_iter().map(|shortcut| {
+    /// View registration. The default shortcuts of the view are overridden by the loaded keymap.
+    /// Command providers which are not views, but have default shortcuts, can be registered here
+    /// as well.
+    pub fn register<V:shortcut::DefaultShortcutProvider>(&self) {
+        let label             = V::label();
+        let commands          = V::command_api_docs().into_iter().map(|t| (t.label,t.caption));
+        let commands          = commands.collect();
+        let default_shortcuts = V::default_shortcuts();
+        let shortcut_handles  = default();
+        let shortcut_docs     = default();
+        let mut definition    = Definition {commands,default_shortcuts,shortcut_handles
+                                           ,shortcut_docs};
+        self.apply_keymap(&mut definition,label);
+        self.definitions.borrow_mut().insert(label.into(),definition);
+        self.command_registry.register::<V>();
+    }
+
+    /// Loads the user keymap, overriding the default shortcuts of the registered views. Views
+    /// should be registered before, as the keymap entries naming unknown views or their unknown
+    /// commands are reported and skipped.
+    pub fn load_keymap(&self, mut keymap:Keymap) -> Vec<keymap::Error> {
+        let errors = {
+            let definitions = self.definitions.borrow();
+            let commands    = definitions.iter().map(|(label,definition)| {
+                (label.clone(),definition.commands.keys().cloned().collect())
+            }).collect();
+            keymap.validate(&commands)
+        };
+        for error in &errors {
+            warning!(&self.logger,"{error} The shortcut will be ignored.");
+        }
+        *self.keymap.borrow_mut() = keymap;
+        for (label,definition) in self.definitions.borrow_mut().iter_mut() {
+            self.apply_keymap(definition,label);
+        }
+        errors
+    }
+
+    fn apply_keymap(&self, definition:&mut Definition, label:&str) {
+        let defaults  = definition.default_shortcuts.clone();
+        let shortcuts = self.keymap.borrow().apply(label,defaults);
+        let commands  = &definition.commands;
+        definition.shortcut_docs = shortcuts.iter().map(|shortcut| {
+            let caption = commands.get(shortcut.command().as_str()).cloned().unwrap_or_default();
+            shortcut::Doc::new(shortcut,caption)
+        }).collect();
+        // The previous handles are dropped first, so the replaced shortcuts are not reported as
+        // conflicting with the new ones.
+        definition.shortcut_handles.clear();
+        definition.shortcut_handles = shortcuts.into_iter().map(|shortcut| {
             self.shortcut_registry.add(shortcut)
         }).collect();
-        let definition = Definition {shortcut_handles};
-        self.definitions.borrow_mut().insert(label,definition);
-        self.command_registry.register::<V>();
+    }
+
+    /// Shortcuts of the registered view currently in use, that is, its default shortcuts
+    /// overridden by the user keymap.
+    pub fn shortcuts(&self, label:&str) -> Vec<shortcut::Shortcut> {
+        let definitions = self.definitions.borrow();
+        let handles     = definitions.get(label).map(|t| &t.shortcut_handles);
+        handles.into_iter().flatten().map(|handle| shortcut::Shortcut::clone(handle)).collect()
+    }
+
+    /// Documentation of shortcuts of all registered views, sorted by the view label. Use
+    /// `shortcut::docs_table` to render it as a Markdown table.
+    pub fn shortcut_docs(&self) -> Vec<shortcut::Doc> {
+        let definitions = self.definitions.borrow();
+        let labels      = definitions.keys().sorted();
+        labels.flat_map(|label| definitions[label].shortcut_docs.iter().cloned()).collect()
     }
 
     /// New view constructor.
@@ -5,7 +5,9 @@ use wasm_bindgen::prelude::*;
 
 use crate::system::web;
 
+use enso_frp::io::keyboard::Code;
 use enso_frp::io::keyboard::Key;
+use enso_frp::io::keyboard::KeyEvent;
 use enso_frp::io::Keyboard;
 use js_sys::Function;
 use wasm_bindgen::JsCast;
@@ -67,6 +69,13 @@ impl Drop for Listener {
     }
 }
 
+/// Converts the web keyboard event. Returns `None` if the key is not recognized.
+pub fn key_event(event:&KeyboardEvent) -> Option<KeyEvent> {
+    let key  = event.key().parse::<Key>().ok()?;
+    let code = event.code().parse::<Code>().unwrap_or(Code::Unidentified);
+    Some(KeyEvent::new(key,code,event.repeat()))
+}
+
 /// A handle of listener emitting events on bound FRP graph.
 #[derive(Debug)]
 pub struct KeyboardFrpBindings {
@@ -77,17 +86,17 @@ pub struct KeyboardFrpBindings {
 impl KeyboardFrpBindings {
     /// Create new Keyboard and Frp bindings.
     pub fn new(logger:impl AnyLogger, keyboard:&Keyboard) -> Self {
-        let key_down = Listener::new_key_down(&logger,enclose!((keyboard.on_pressed => frp)
+        let key_down = Listener::new_key_down(&logger,enclose!((keyboard.on_key_down => frp)
             move |event:KeyboardEvent| {
-                if let Ok(key) = event.key().parse::<Key>() {
-                    frp.emit(key);
+                if let Some(event) = key_event(&event) {
+                    frp.emit(event);
                 }
             }
         ));
-        let key_up = Listener::new_key_up(&logger,enclose!((keyboard.on_released => frp)
+        let key_up = Listener::new_key_up(&logger,enclose!((keyboard.on_key_up => frp)
             move |event:KeyboardEvent| {
-                if let Ok(key) = event.key().parse::<Key>() {
-                    frp.emit(key);
+                if let Some(event) = key_event(&event) {
+                    frp.emit(event);
                 }
             }
         ));
@@ -4,8 +4,8 @@
 //! We do it this way, because this is the only way for handling clipboard operations.
 use crate::prelude::*;
 
+use crate::control::io::keyboard::listener::key_event;
 use enso_frp::*;
-use enso_frp::io::keyboard;
 use enso_frp::io::keyboard::Keyboard;
 use std::fmt::Debug;
 use std::fmt::Formatter;
@@ -160,14 +160,14 @@ impl Debug for KeyboardBinding {
 /// Until the returned `KeyboardBinding` structure lives, the js events will emit the proper
 /// source events in this graph.
 pub fn bind_frp_to_js_keyboard_actions(frp:&Keyboard, binding:&mut KeyboardBinding) {
-    binding.set_key_down_handler(enclose!((frp.on_pressed => frp) move |event:KeyboardEvent| {
-        if let Ok(key) = event.key().parse::<keyboard::Key>() {
-            frp.emit(key);
+    binding.set_key_down_handler(enclose!((frp.on_key_down => frp) move |event:KeyboardEvent| {
+        if let Some(event) = key_event(&event) {
+            frp.emit(event);
         }
     }));
-    binding.set_key_up_handler(enclose!((frp.on_released => frp) move |event:KeyboardEvent| {
-        if let Ok(key) = event.key().parse::<keyboard::Key>() {
-            frp.emit(key);
+    binding.set_key_up_handler(enclose!((frp.on_key_up => frp) move |event:KeyboardEvent| {
+        if let Some(event) = key_event(&event) {
+            frp.emit(event);
         }
     }));
     binding.set_window_defocus_handler(enclose!((frp.on_defocus => frp) move || {
@@ -0,0 +1,237 @@
+//! Collecting syntax errors from the AST.
+//!
+//! The parser does not fail on invalid code. Instead, it emits nodes with special shapes, like
+//! `Unrecognized` or `TextUnclosed`. This module finds such nodes and describes them as located
+//! diagnostics, which can be displayed to the user.
+
+use crate::prelude::*;
+
+use crate::Crumb;
+use crate::Crumbs;
+use crate::crumbs::Crumbable;
+use crate::HasLength;
+use crate::HasRepr;
+use crate::HasTokens;
+use crate::Id;
+use crate::Shape;
+use crate::Token;
+use crate::TokenConsumer;
+
+use data::text::Index;
+use data::text::Size;
+use data::text::Span;
+
+
+
+// ==================
+// === Diagnostic ===
+// ==================
+
+/// Kind of syntax error found in the AST.
+#[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
+pub enum Kind {
+    /// A token that the parser could not recognize.
+    Unrecognized,
+    /// A quote not belonging to any text literal.
+    InvalidQuote,
+    /// An expression directly followed by an invalid suffix, like `foo'bar`.
+    InvalidSuffix,
+    /// A text literal lacking its closing quote.
+    UnclosedText,
+    /// A number base not followed by any digits, like `16_`.
+    DanglingBase,
+}
+
+/// A syntax error located in the AST.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub struct Diagnostic {
+    /// The kind of error.
+    pub kind    : Kind,
+    /// The message describing the error to the user.
+    pub message : String,
+    /// The id of the erroneous node, if it has one.
+    pub id      : Option<Id>,
+    /// The location of the erroneous node, relative to the traversed AST.
+    pub crumbs  : Crumbs,
+    /// The span of the erroneous node in the code of the traversed AST, in characters.
+    pub span    : Span,
+}
+
+/// Describes the error represented by the given node, if it is an erroneous one.
+fn error_in(ast:&Ast) -> Option<(Kind,String)> {
+    match ast.shape() {
+        Shape::Unrecognized(unrecognized) =>
+            Some((Kind::Unrecognized, format!("Unrecognized token `{}`.",unrecognized.str))),
+        Shape::InvalidQuote(_) =>
+            Some((Kind::InvalidQuote, format!("Unexpected quote `{}`.",ast.repr()))),
+        Shape::InvalidSuffix(invalid) =>
+            Some((Kind::InvalidSuffix, format!("Invalid suffix `{}`.",invalid.suffix))),
+        Shape::TextUnclosed(_) =>
+            Some((Kind::UnclosedText, "Text literal is not closed.".to_string())),
+        Shape::DanglingBase(dangling) =>
+            Some((Kind::DanglingBase, format!("Number with base {} has no digits.",dangling.base))),
+        _ => None,
+    }
+}
+
+
+
+// ==================
+// === Collecting ===
+// ==================
+
+/// Collects the syntax errors from the whole given AST, in the order of their appearance in code.
+pub fn collect(ast:&Ast) -> Vec<Diagnostic> {
+    let mut found = Vec::new();
+    collect_in(ast,&mut default(),0,&mut found);
+    found.sort_by_key(|diagnostic| diagnostic.span.index);
+    found
+}
+
+fn collect_in(ast:&Ast, crumbs:&mut Crumbs, index:usize, found:&mut Vec<Diagnostic>) {
+    if let Some((kind,message)) = error_in(ast) {
+        let id     = ast.id;
+        let crumbs = crumbs.clone();
+        let span   = Span::new(Index::new(index),Size::new(ast.len()));
+        found.push(Diagnostic {kind,message,id,crumbs,span});
+    }
+    for crumb in ast.iter_subcrumbs() {
+        // NOTE Safe if the crumbs module is correct - children crumbs are always accessible.
+        let child = ast.get(&crumb).unwrap();
+        let span  = child_span(ast,&crumb).unwrap();
+        crumbs.push(crumb);
+        collect_in(child,crumbs,index + span.index.value,found);
+        crumbs.pop();
+    }
+}
+
+/// Span of the child under the given crumb, relative to the node's beginning, in characters.
+///
+/// The child is located by replacing it with a marker node, so it is found even if the same child
+/// appears more than once in the node, or if the crumbs are not ordered as the children in code.
+fn child_span(ast:&Ast, crumb:&Crumb) -> FallibleResult<Span> {
+    let child   = ast.get(crumb)?;
+    let marker  = Ast::new(child.shape().clone(),child.id);
+    let marked  = ast.set(crumb,marker.clone_ref())?;
+    let mut locator = ChildLocator::new(marker);
+    marked.shape().feed_to(&mut locator);
+    let index = locator.found.ok_or(MarkerNotFound)?;
+    Ok(Span::new(Index::new(index),Size::new(child.len())))
+}
+
+#[derive(Debug,Display,Fail,Clone,Copy)]
+struct MarkerNotFound;
+
+/// Finds the offset of the marker node among the tokens of its parent, in characters.
+#[derive(Clone,Debug)]
+struct ChildLocator {
+    marker : Ast,
+    offset : usize,
+    found  : Option<usize>,
+}
+
+impl ChildLocator {
+    fn new(marker:Ast) -> ChildLocator {
+        ChildLocator {marker,offset:0,found:None}
+    }
+}
+
+impl TokenConsumer for ChildLocator {
+    fn feed(&mut self, token:Token) {
+        if self.found.is_some() {
+            return
+        }
+        match token {
+            Token::Off(val) => self.offset += val,
+            Token::Chr(_)   => self.offset += 1,
+            Token::Str(val) => self.offset += val.chars().count(),
+            Token::Ast(val) => if Rc::ptr_eq(&val.wrapped,&self.marker.wrapped) {
+                self.found = Some(self.offset);
+            } else {
+                self.offset += val.len();
+            }
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Module;
+    use crate::crumbs::TraversableAst;
+
+    #[test]
+    fn collecting_diagnostics() {
+        let dangling = Ast::dangling_base("16");
+        let lines    = [
+            Some(Ast::prefix(Ast::var("foo"),dangling.clone_ref())),
+            Some(Ast::var("bar")),
+            Some(Ast::unrecognized("§")),
+        ];
+        let module      = Ast::new(Module::from_lines(&lines),None);
+        let diagnostics = collect(&module);
+        assert_eq!(module.repr(), "foo 16_\nbar\n§");
+        assert_eq!(diagnostics.len(), 2);
+
+        let first = &diagnostics[0];
+        assert_eq!(first.kind, Kind::DanglingBase);
+        assert_eq!(first.id, dangling.id);
+        assert_eq!(first.span, Span::new(Index::new(4),Size::new(3)));
+        assert_eq!(module.get_traversing(&first.crumbs).unwrap(), &dangling);
+
+        let second = &diagnostics[1];
+        assert_eq!(second.kind, Kind::Unrecognized);
+        assert_eq!(second.message, "Unrecognized token `§`.");
+        assert_eq!(second.span, Span::new(Index::new(12),Size::new(1)));
+    }
+
+    #[test]
+    fn no_diagnostics_in_valid_code() {
+        let ast = Ast::infix_var("a","+","b");
+        assert!(collect(&ast).is_empty());
+    }
+
+    #[test]
+    fn diagnostics_after_non_ascii_text() {
+        let unrecognized = Ast::unrecognized("§");
+        let line         = Ast::prefix(Ast::text_raw("ąę"),unrecognized.clone_ref());
+        let diagnostics  = collect(&line);
+        assert_eq!(line.repr(), "\"ąę\" §");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].span, Span::new(Index::new(5),Size::new(1)));
+    }
+
+    #[test]
+    fn diagnostics_in_shared_children() {
+        let dangling    = Ast::dangling_base("16");
+        let line        = Ast::infix(dangling.clone_ref(),"+",dangling.clone_ref());
+        let diagnostics = collect(&line);
+        assert_eq!(line.repr(), "16_ + 16_");
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].span, Span::new(Index::new(0),Size::new(3)));
+        assert_eq!(diagnostics[1].span, Span::new(Index::new(6),Size::new(3)));
+        assert_ne!(diagnostics[0].crumbs, diagnostics[1].crumbs);
+    }
+
+    #[test]
+    fn child_spans_follow_crumbs() {
+        let dangling = Ast::dangling_base("16");
+        let line     = Ast::infix(dangling.clone_ref(),"+",dangling.clone_ref());
+        for crumb in line.iter_subcrumbs() {
+            let span  = child_span(&line,&crumb).unwrap();
+            let child = line.get(&crumb).unwrap();
+            assert_eq!(&line.repr()[span], child.repr().as_str());
+        }
+        let left  = child_span(&line,&crate::crumbs::InfixCrumb::LeftOperand.into()).unwrap();
+        let right = child_span(&line,&crate::crumbs::InfixCrumb::RightOperand.into()).unwrap();
+        assert_eq!(left,  Span::new(Index::new(0),Size::new(3)));
+        assert_eq!(right, Span::new(Index::new(6),Size::new(3)));
+    }
+}
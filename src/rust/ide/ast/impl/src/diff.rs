@@ -0,0 +1,440 @@
+//! Structural comparison of two AST trees, producing text changes that transform the code of one
+//! tree into the code of another.
+//!
+//! The nodes are compared top-down. If two nodes have the same "skeleton" (i.e. all their tokens
+//! except child nodes are equal), only their children are compared. Otherwise the whole node is
+//! replaced. Lines of `Module` are additionally aligned using their ids, so inserting or removing
+//! a line does not make the following lines be replaced.
+//!
+//! All the indices of produced changes are character indices, just like AST lengths.
+
+use crate::prelude::*;
+
+use crate::BlockLine;
+use crate::HasLength;
+use crate::HasRepr;
+use crate::HasTokens;
+use crate::Id;
+use crate::Shape;
+use crate::Token;
+use crate::TokenConsumer;
+use crate::known;
+use crate::repr::NEWLINE;
+
+use data::text::Index;
+use data::text::TextChange;
+
+
+
+// ================
+// === Skeleton ===
+// ================
+
+/// A piece of node's representation: either some text or a placeholder for a child node.
+#[derive(Clone,Debug,Eq,PartialEq)]
+enum SkeletonPiece { Text(String), Child }
+
+/// The node's own tokens, with children replaced by placeholders. Children are stored separately
+/// together with their offset from the node beginning.
+#[derive(Clone,Debug,Default)]
+struct Skeleton {
+    pieces   : Vec<SkeletonPiece>,
+    children : Vec<(usize,Ast)>,
+    text     : String,
+    offset   : usize,
+}
+
+impl Skeleton {
+    fn new(ast:&Ast) -> Skeleton {
+        let mut skeleton = Skeleton::default();
+        ast.shape().feed_to(&mut skeleton);
+        skeleton.flush_text();
+        skeleton
+    }
+
+    fn flush_text(&mut self) {
+        if !self.text.is_empty() {
+            let text = std::mem::take(&mut self.text);
+            self.pieces.push(SkeletonPiece::Text(text));
+        }
+    }
+}
+
+impl TokenConsumer for Skeleton {
+    fn feed(&mut self, token:Token) {
+        match token {
+            Token::Off(val) => {
+                self.text.push_str(&" ".repeat(val));
+                self.offset += val;
+            }
+            Token::Chr(val) => {
+                self.text.push(val);
+                self.offset += 1;
+            }
+            Token::Str(val) => {
+                self.text.push_str(val);
+                self.offset += val.chars().count();
+            }
+            Token::Ast(val) => {
+                self.flush_text();
+                self.pieces.push(SkeletonPiece::Child);
+                self.children.push((self.offset,val.clone_ref()));
+                self.offset += val.len();
+            }
+        }
+    }
+}
+
+
+
+// ============
+// === Diff ===
+// ============
+
+/// Compares two modules and returns the text changes transforming the code of `old` into the code
+/// of `new`.
+///
+/// The changes use indices of the `old` code and are ordered by descending position, so they can
+/// be applied one after another.
+pub fn module(old:&known::Module, new:&known::Module) -> Vec<TextChange> {
+    diff(old.ast(),new.ast())
+}
+
+/// Compares two ASTs and returns the text changes transforming the code of `old` into the code of
+/// `new`. See `module` for the changes order.
+pub fn diff(old:&Ast, new:&Ast) -> Vec<TextChange> {
+    let mut changes = Vec::new();
+    diff_at(0,old,new,&mut changes);
+    changes.reverse();
+    changes
+}
+
+/// Merges the changes returned by `diff` into a single change, replacing the smallest fragment of
+/// old code that encloses all of them. Returns `None` if there are no changes.
+pub fn merge(changes:&[TextChange], new_code:&str) -> Option<TextChange> {
+    let start        = changes.iter().map(|change| change.replaced.start).min()?;
+    let end          = changes.iter().map(|change| change.replaced.end).max()?;
+    let size_diff    = |change:&TextChange| change.inserted.chars().count() as isize
+        - change.replaced_size().value as isize;
+    let total_diff   = changes.iter().map(size_diff).sum::<isize>();
+    let inserted_end = (end.value as isize + total_diff) as usize;
+    let inserted_len = inserted_end - start.value;
+    let inserted     = new_code.chars().skip(start.value).take(inserted_len).collect();
+    Some(TextChange::replace(start..end,inserted))
+}
+
+/// Checks if two ASTs have the same structure, i.e. the same shapes, tokens and children. Their ids
+/// are not compared.
+pub fn same_structure(left:&Ast, right:&Ast) -> bool {
+    let shape_kind     = |ast:&Ast| std::mem::discriminant(ast.shape());
+    let same_shape     = shape_kind(left) == shape_kind(right);
+    let left_skeleton  = Skeleton::new(left);
+    let right_skeleton = Skeleton::new(right);
+    let mut children   = left_skeleton.children.iter().zip(right_skeleton.children.iter());
+    let same_pieces    = left_skeleton.pieces == right_skeleton.pieces;
+    same_shape && same_pieces && children.all(|((_,left),(_,right))| same_structure(left,right))
+}
+
+/// Compares nodes, where `old` starts at `offset` in old code. Changes are pushed in ascending
+/// order.
+fn diff_at(offset:usize, old:&Ast, new:&Ast, changes:&mut Vec<TextChange>) {
+    if Rc::ptr_eq(&old.wrapped,&new.wrapped) || old == new {
+        return
+    }
+    if let (Shape::Module(old_module),Shape::Module(new_module)) = (old.shape(),new.shape()) {
+        diff_lines(offset,&old_module.lines,&new_module.lines,changes);
+        return
+    }
+    let old_skeleton = Skeleton::new(old);
+    let new_skeleton = Skeleton::new(new);
+    if old_skeleton.pieces == new_skeleton.pieces {
+        let children = old_skeleton.children.iter().zip(new_skeleton.children.iter());
+        for ((child_offset,old_child),(_,new_child)) in children {
+            diff_at(offset + child_offset,old_child,new_child,changes);
+        }
+    } else {
+        let replaced = Index::new(offset)..Index::new(offset + old.len());
+        changes.push(TextChange::replace(replaced,new.repr()));
+    }
+}
+
+
+
+// =============
+// === Lines ===
+// =============
+
+/// Key used to align lines of two modules.
+#[derive(Clone,Debug,Eq,PartialEq)]
+enum LineKey { Id(Id), Repr(String) }
+
+impl LineKey {
+    fn new(line:&BlockLine<Option<Ast>>) -> LineKey {
+        match &line.elem {
+            Some(ast) => ast.id.map(LineKey::Id).unwrap_or_else(|| LineKey::Repr(ast.repr())),
+            None      => LineKey::Repr(default()),
+        }
+    }
+}
+
+/// Returns pairs of indices of lines matched between `old` and `new`, in ascending order.
+///
+/// The lines are matched by the longest common subsequence of their keys. Common prefix and suffix
+/// are matched first, so the quadratic part only covers the actually changed fragment.
+fn align_lines(old:&[LineKey], new:&[LineKey]) -> Vec<(usize,usize)> {
+    let prefix     = old.iter().zip(new.iter()).take_while(|(o,n)| o == n).count();
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let suffix     = old.iter().rev().zip(new.iter().rev()).take(max_suffix)
+        .take_while(|(o,n)| o == n).count();
+    let old_mid    = &old[prefix..old.len()-suffix];
+    let new_mid    = &new[prefix..new.len()-suffix];
+
+    // lcs[i][j] is the LCS length of old_mid[i..] and new_mid[j..].
+    let mut lcs = vec![vec![0usize; new_mid.len()+1]; old_mid.len()+1];
+    for i in (0..old_mid.len()).rev() {
+        for j in (0..new_mid.len()).rev() {
+            lcs[i][j] = if old_mid[i] == new_mid[j] { lcs[i+1][j+1] + 1 }
+                        else                          { lcs[i+1][j].max(lcs[i][j+1]) };
+        }
+    }
+
+    let mut pairs = (0..prefix).map(|i| (i,i)).collect_vec();
+    let (mut i, mut j) = (0,0);
+    while i < old_mid.len() && j < new_mid.len() {
+        if old_mid[i] == new_mid[j] {
+            pairs.push((prefix+i,prefix+j));
+            i += 1;
+            j += 1;
+        } else if lcs[i+1][j] >= lcs[i][j+1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    let old_suffix_start = old.len() - suffix;
+    let new_suffix_start = new.len() - suffix;
+    pairs.extend((0..suffix).map(|k| (old_suffix_start+k,new_suffix_start+k)));
+    pairs
+}
+
+/// Code of given lines joined with newlines.
+fn lines_repr(lines:&[BlockLine<Option<Ast>>]) -> String {
+    lines.iter().map(|line| line.repr()).join(&NEWLINE.to_string())
+}
+
+/// Compares lines of two modules, where `old` lines start at `offset` in old code.
+fn diff_lines
+( offset  : usize
+, old     : &[BlockLine<Option<Ast>>]
+, new     : &[BlockLine<Option<Ast>>]
+, changes : &mut Vec<TextChange>) {
+    let old_keys = old.iter().map(LineKey::new).collect_vec();
+    let new_keys = new.iter().map(LineKey::new).collect_vec();
+    let pairs    = align_lines(&old_keys,&new_keys);
+
+    // Beginning of each old line, and one additional index past the end of code.
+    let mut starts  = Vec::with_capacity(old.len()+1);
+    let mut current = offset;
+    for line in old {
+        starts.push(current);
+        current += line.len() + 1;
+    }
+    starts.push(current);
+    let line_end = |index:usize| starts[index+1] - 1;
+
+    // Unmatched lines between consecutive matched pairs are replaced together, unless there is
+    // the same number of them on both sides. The sentinel pair at the end handles the lines after
+    // the last match.
+    let sentinel = std::iter::once((old.len(),new.len()));
+    let (mut old_next,mut new_next) = (0,0);
+    for (old_ix,new_ix) in pairs.into_iter().chain(sentinel) {
+        let old_run       = old_next..old_ix;
+        let new_run       = new_next..new_ix;
+        let old_run_empty = old_run.start == old_run.end;
+        let new_run_empty = new_run.start == new_run.end;
+        if old_run.len() == new_run.len() {
+            // Lines which could not be aligned by their keys (e.g. because their ids have changed)
+            // are compared positionally.
+            for (old_run_ix,new_run_ix) in old_run.zip(new_run) {
+                diff_line(starts[old_run_ix],&old[old_run_ix],&new[new_run_ix],changes);
+            }
+        } else {
+            let new_text = lines_repr(&new[new_run]);
+            let change   = if old_run_empty {
+                if old.is_empty() {
+                    TextChange::insert(Index::new(offset),new_text)
+                } else if old_run.start < old.len() {
+                    let at = Index::new(starts[old_run.start]);
+                    TextChange::insert(at,format!("{}{}",new_text,NEWLINE))
+                } else {
+                    let at = Index::new(line_end(old.len() - 1));
+                    TextChange::insert(at,format!("{}{}",NEWLINE,new_text))
+                }
+            } else if new_run_empty {
+                let (start,end) = if old_run.end < old.len() {
+                    (starts[old_run.start], starts[old_run.end])
+                } else if old_run.start > 0 {
+                    (line_end(old_run.start - 1), line_end(old_run.end - 1))
+                } else {
+                    (starts[0], line_end(old_run.end - 1))
+                };
+                TextChange::delete(Index::new(start)..Index::new(end))
+            } else {
+                let start = Index::new(starts[old_run.start]);
+                let end   = Index::new(line_end(old_run.end - 1));
+                TextChange::replace(start..end,new_text)
+            };
+            changes.push(change);
+        }
+        if old_ix < old.len() {
+            diff_line(starts[old_ix],&old[old_ix],&new[new_ix],changes);
+        }
+        old_next = old_ix + 1;
+        new_next = new_ix + 1;
+    }
+}
+
+/// Compares two matched lines, where `old` starts at `offset` in old code.
+fn diff_line
+(offset:usize, old:&BlockLine<Option<Ast>>, new:&BlockLine<Option<Ast>>
+, changes:&mut Vec<TextChange>) {
+    let elem_len = match (&old.elem,&new.elem) {
+        (Some(old_elem),Some(new_elem)) => {
+            diff_at(offset,old_elem,new_elem,changes);
+            old_elem.len()
+        }
+        (None,None) => 0,
+        _ => {
+            let replaced = Index::new(offset)..Index::new(offset + old.len());
+            changes.push(TextChange::replace(replaced,new.repr()));
+            return
+        }
+    };
+    if old.off != new.off {
+        let start = Index::new(offset + elem_len);
+        let end   = start + data::text::Size::new(old.off);
+        changes.push(TextChange::replace(start..end," ".repeat(new.off)));
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Module;
+
+    /// Applies the change to the code, treating its indices as character indices.
+    fn apply(code:&str, change:&TextChange) -> String {
+        let start  = change.replaced.start.value;
+        let end    = change.replaced.end.value;
+        let prefix = code.chars().take(start);
+        let suffix = code.chars().skip(end);
+        prefix.chain(change.inserted.chars()).chain(suffix).collect()
+    }
+
+    fn apply_all(code:&str, changes:&[TextChange]) -> String {
+        changes.iter().fold(code.to_string(), |code,change| apply(&code,change))
+    }
+
+    fn assert_transforms(old:&Ast, new:&Ast) -> Vec<TextChange> {
+        let changes = diff(old,new);
+        assert_eq!(apply_all(&old.repr(),&changes),new.repr());
+        let merged = merge(&changes,&new.repr());
+        if let Some(merged) = merged {
+            assert_eq!(apply(&old.repr(),&merged),new.repr());
+        }
+        changes
+    }
+
+    #[test]
+    fn no_changes_for_equal_asts() {
+        let ast = Ast::infix_var("a","+","b");
+        assert!(assert_transforms(&ast,&ast.clone()).is_empty());
+        assert!(merge(&[],"").is_none());
+    }
+
+    #[test]
+    fn replacing_single_child() {
+        let larg = Ast::var("a");
+        let old  = Ast::infix(larg.clone(),"+",Ast::var("b"));
+        let new  = Ast::infix(larg,"+",Ast::var("foo"));
+        let changes = assert_transforms(&old,&new);
+        let expected = TextChange::replace(Index::new(4)..Index::new(5),"foo".into());
+        assert_eq!(changes,vec![expected]);
+    }
+
+    #[test]
+    fn replacing_node_of_different_shape() {
+        let old = Ast::prefix(Ast::var("foo"),Ast::var("a"));
+        let new = Ast::prefix(Ast::var("foo"),Ast::infix_var("a","+","b"));
+        assert_eq!(assert_transforms(&old,&new).len(),1);
+    }
+
+    #[test]
+    fn diffing_module_lines() {
+        let line = |name:&str| Some(Ast::infix(Ast::var(name),"=",Ast::number(2)));
+        let (a,b,c,d) = (line("a"),line("b"),line("c"),line("d"));
+        let module    = |lines:&[Option<Ast>]| Ast::from(Module::from_lines(lines));
+        let cases = vec!
+            [ (vec![a.clone(),b.clone()]          , vec![a.clone(),c.clone(),b.clone()])
+            , (vec![a.clone(),b.clone()]          , vec![a.clone(),b.clone(),c.clone()])
+            , (vec![a.clone(),b.clone()]          , vec![c.clone(),a.clone(),b.clone()])
+            , (vec![a.clone(),b.clone(),c.clone()], vec![a.clone(),c.clone()])
+            , (vec![a.clone(),b.clone(),c.clone()], vec![a.clone(),b.clone()])
+            , (vec![a.clone(),b.clone(),c.clone()], vec![b.clone(),c.clone()])
+            , (vec![a.clone(),b.clone(),c.clone()], vec![d.clone()])
+            , (vec![a.clone(),None,b.clone()]     , vec![a.clone(),b.clone(),None])
+            , (vec![a.clone()]                    , vec![])
+            , (vec![]                             , vec![b.clone(),c.clone()])
+            ];
+        for (old,new) in cases {
+            assert_transforms(&module(&old),&module(&new));
+        }
+
+        // Inserted line must not cause other lines to be replaced.
+        let changes = assert_transforms(&module(&[a.clone(),b.clone()]),&module(&[a,c,b]));
+        let expected = TextChange::insert(Index::new(6),"c = 2\n".into());
+        assert_eq!(changes,vec![expected]);
+    }
+
+    #[test]
+    fn changes_are_in_descending_order() {
+        let old     = Ast::infix_var("a","+","b");
+        let new     = Ast::infix_var("foo","+","bar");
+        let changes = assert_transforms(&old,&new);
+        assert_eq!(changes.len(),2);
+        assert!(changes[0].replaced.start > changes[1].replaced.start);
+    }
+
+    #[test]
+    fn indices_are_in_characters() {
+        let text    = |text:&str| Ast::text_raw(text);
+        let old     = Ast::prefix(Ast::prefix(Ast::var("foo"),text("ąę")),Ast::var("a"));
+        let new     = Ast::prefix(Ast::prefix(Ast::var("foo"),text("ąę")),Ast::var("bar"));
+        let changes = assert_transforms(&old,&new);
+        let expected = TextChange::replace(Index::new(9)..Index::new(10),"bar".into());
+        assert_eq!(changes,vec![expected]);
+
+        let old     = Ast::prefix(Ast::var("a"),text("ąę"));
+        let new     = Ast::prefix(Ast::var("b"),text("żółw"));
+        let changes = assert_transforms(&old,&new);
+        let merged  = merge(&changes,&new.repr()).unwrap();
+        assert_eq!(merged.replaced,Index::new(0)..Index::new(6));
+        assert_eq!(merged.inserted,"b \"żółw\"");
+    }
+
+    #[test]
+    fn comparing_structure() {
+        let infix = Ast::infix_var("a","+","b");
+        assert!(same_structure(&infix,&infix.with_new_id()));
+        assert!(same_structure(&infix,&Ast::infix_var("a","+","b")));
+        assert!(!same_structure(&infix,&Ast::infix_var("a","+","c")));
+        assert!(!same_structure(&Ast::var("a"),&Ast::cons("a")));
+    }
+}
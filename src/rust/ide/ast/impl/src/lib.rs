@@ -10,6 +10,10 @@ pub mod assoc;
 #[warn(missing_docs)]
 pub mod crumbs;
 #[warn(missing_docs)]
+pub mod diagnostics;
+#[warn(missing_docs)]
+pub mod diff;
+#[warn(missing_docs)]
 pub mod identifier;
 #[warn(missing_docs)]
 pub mod internal;
@@ -893,7 +897,7 @@ impl TokenConsumer for LengthBuilder {
         match token {
             Token::Off(val) => self.length += val,
             Token::Chr( _ ) => self.length += 1,
-            Token::Str(val) => self.length += val.len(),
+            Token::Str(val) => self.length += val.chars().count(),
             Token::Ast(val) => val.shape().feed_to(self),
         }
     }
@@ -1122,6 +1126,92 @@ impl Infix<Ast> {
     }
 }
 
+impl Block<Ast> {
+    /// Creates a non-orphan block with given absolute indent, i.e. the block that is introduced
+    /// by the newline following e.g. the assignment operator. Empty lines are not allowed between
+    /// the block's introducer and its first line.
+    pub fn new(indent:usize, first_line:&Ast, tail_lines:&[Option<Ast>]) -> Block<Ast> {
+        let ty          = BlockType::Discontinuous {};
+        let empty_lines = Vec::new();
+        let first_line  = BlockLine::new(first_line.clone_ref());
+        let lines       = tail_lines.iter().cloned().map(BlockLine::new).collect();
+        let is_orphan   = false;
+        Block {ty,indent,empty_lines,first_line,lines,is_orphan}
+    }
+}
+
+impl TextLineRaw {
+    /// Creates a raw text line containing given text. Backslashes and double quotes are escaped.
+    ///
+    /// # Panics
+    /// Panics if the text contains newline characters, as raw text lines cannot express them. Use
+    /// `TextLineFmt::from_text`, which escapes them, for such texts.
+    pub fn from_text(text:&str) -> TextLineRaw {
+        let has_newline = text.contains(|char:char| char == '\n' || char == '\r');
+        assert!(!has_newline,"Raw text line cannot contain newline characters: {:?}.",text);
+        let mut segments = Vec::new();
+        let mut plain    = String::new();
+        let flush = |plain:&mut String, segments:&mut Vec<SegmentRaw>| if !plain.is_empty() {
+            segments.push(SegmentPlain {value:std::mem::take(plain)}.into())
+        };
+        for char in text.chars() {
+            let escape:Option<SegmentRaw> = match char {
+                repr::BACKSLASH => Some(Slash    {}.into()),
+                repr::RAW_QUOTE => Some(RawQuote {}.into()),
+                _               => None,
+            };
+            match escape {
+                Some(escape) => {
+                    flush(&mut plain,&mut segments);
+                    segments.push(escape);
+                }
+                None => plain.push(char),
+            }
+        }
+        flush(&mut plain,&mut segments);
+        TextLineRaw {text:segments}
+    }
+}
+
+impl<T> TextLineFmt<T> {
+    /// Creates a formatted text line containing given text. Backslashes, single quotes and
+    /// backticks are escaped, while newlines and tabs are replaced with their character escapes.
+    pub fn from_text(text:&str) -> TextLineFmt<T> {
+        let mut segments = Vec::new();
+        let mut plain    = String::new();
+        let flush = |plain:&mut String, segments:&mut Vec<SegmentFmt<T>>| if !plain.is_empty() {
+            segments.push(SegmentPlain {value:std::mem::take(plain)}.into())
+        };
+        for char in text.chars() {
+            let escape:Option<SegmentFmt<T>> = match char {
+                repr::BACKSLASH  => Some(Slash           {}         .into()),
+                repr::FMT_QUOTE  => Some(Quote           {}         .into()),
+                repr::EXPR_QUOTE => Some(Invalid         {str:char} .into()),
+                '\n'             => Some(EscapeCharacter {c:'n'}    .into()),
+                '\t'             => Some(EscapeCharacter {c:'t'}    .into()),
+                _                => None,
+            };
+            match escape {
+                Some(escape) => {
+                    flush(&mut plain,&mut segments);
+                    segments.push(escape);
+                }
+                None => plain.push(char),
+            }
+        }
+        flush(&mut plain,&mut segments);
+        TextLineFmt {text:segments}
+    }
+}
+
+impl<T> TextBlockLine<T> {
+    /// Creates a text block line with given segments that is not preceded by any empty lines.
+    pub fn new(text:Vec<T>) -> TextBlockLine<T> {
+        let empty_lines = Vec::new();
+        TextBlockLine {empty_lines,text}
+    }
+}
+
 impl Module<Ast> {
     /// Creates a `Module` Shape with lines storing given Asts and having 0 offset.
     pub fn from_lines(line_asts:&[Option<Ast>]) -> Module<Ast> {
@@ -1137,8 +1227,25 @@ impl Module<Ast> {
 // === AST ===
 
 impl Ast {
-    // TODO smart constructors for other cases
-    //  as part of https://github.com/luna/enso/issues/338
+    /// Creates an AST node with `Unrecognized` shape.
+    pub fn unrecognized(str:impl Str) -> Ast {
+        let unrecognized = Unrecognized {str:str.into()};
+        Ast::from(unrecognized)
+    }
+
+    /// Creates an AST node with `InvalidQuote` shape.
+    pub fn invalid_quote(quote:impl Str) -> Ast {
+        let quote         = Text {str:quote.into()}.into();
+        let invalid_quote = InvalidQuote {quote};
+        Ast::from(invalid_quote)
+    }
+
+    /// Creates an AST node with `InlineBlock` shape.
+    pub fn inline_block(quote:impl Str) -> Ast {
+        let quote        = Text {str:quote.into()}.into();
+        let inline_block = InlineBlock {quote};
+        Ast::from(inline_block)
+    }
 
     /// Creates Blank ast node (underscore).
     pub fn blank() -> Ast {
@@ -1151,6 +1258,18 @@ impl Ast {
         Ast::from(number)
     }
 
+    /// Creates an Ast node with Number in given base, e.g. `16_ff`.
+    pub fn number_with_base(base:impl Str, int:impl Str) -> Ast {
+        let number = Number {base:Some(base.into()),int:int.into()};
+        Ast::from(number)
+    }
+
+    /// Creates an AST node with `DanglingBase` shape, e.g. `16_`.
+    pub fn dangling_base(base:impl Str) -> Ast {
+        let dangling_base = DanglingBase {base:base.into()};
+        Ast::from(dangling_base)
+    }
+
     /// Creates an Ast node with Cons inside.
     pub fn cons(name:impl Str) -> Ast {
         let cons = Cons {name:name.into()};
@@ -1176,6 +1295,12 @@ impl Ast {
         Ast::from(opr)
     }
 
+    /// Creates an AST node with `Mod` shape, e.g. `+=` for operator `+`.
+    pub fn modifier(name:impl Str) -> Ast {
+        let modifier = Mod {name:name.into()};
+        Ast::from(modifier)
+    }
+
     /// Creates an AST node with `SectionLeft` shape.
     pub fn section_left<Arg:Into<Ast>>(arg:Arg, opr:impl Str) -> Ast {
         let off          = 1;
@@ -1192,6 +1317,22 @@ impl Ast {
         Ast::from(section_right)
     }
 
+    /// Creates an AST node with `SectionLeft` shape with given spacing before the operator.
+    pub fn section_left_with_offset(arg:impl Into<Ast>, off:usize, opr:impl Str) -> Ast {
+        let arg          = arg.into();
+        let opr          = Ast::opr(opr);
+        let section_left = SectionLeft {arg,off,opr};
+        Ast::from(section_left)
+    }
+
+    /// Creates an AST node with `SectionRight` shape with given spacing after the operator.
+    pub fn section_right_with_offset(opr:impl Str, off:usize, arg:impl Into<Ast>) -> Ast {
+        let arg           = arg.into();
+        let opr           = Ast::opr(opr);
+        let section_right = SectionRight {opr,off,arg};
+        Ast::from(section_right)
+    }
+
     /// Creates an AST node with `SectionSides` shape.
     pub fn section_sides(opr:impl Str) -> Ast {
         let opr           = Ast::opr(opr);
@@ -1206,6 +1347,21 @@ impl Ast {
         Ast::from(opr)
     }
 
+    /// Creates an AST node with `Prefix` shape with given spacing between function and argument.
+    pub fn prefix_with_offset(func:impl Into<Ast>, off:usize, arg:impl Into<Ast>) -> Ast {
+        let func   = func.into();
+        let arg    = arg.into();
+        let prefix = Prefix {func,off,arg};
+        Ast::from(prefix)
+    }
+
+    /// Creates a chain of `Prefix` applications of `func` to all `args`, e.g. `foo a b`.
+    ///
+    /// If there are no arguments, `func` is returned.
+    pub fn prefix_chain(func:impl Into<Ast>, args:impl IntoIterator<Item=Ast>) -> Ast {
+        args.into_iter().fold(func.into(), |func,arg| Ast::prefix(func,arg))
+    }
+
     /// Creates an AST node with `InvalidSuffix` shape.
     pub fn invalid_suffix(elem:impl Into<Ast>, suffix:impl Str) -> Ast {
         let elem           = elem.into();
@@ -1225,11 +1381,58 @@ impl Ast {
         Ast::from(infix)
     }
 
+    /// Creates an AST node with `Infix` shape with given spacing around the operator.
+    pub fn infix_with_offsets
+    (larg:impl Into<Ast>, loff:usize, opr:impl Str, roff:usize, rarg:impl Into<Ast>) -> Ast {
+        let larg  = larg.into();
+        let opr   = Ast::opr(opr);
+        let rarg  = rarg.into();
+        let infix = Infix {larg,loff,opr,roff,rarg};
+        Ast::from(infix)
+    }
+
+    /// Creates an AST node describing the definition binding, like `foo a b = body`.
+    ///
+    /// The left-hand side is a `Prefix` chain of the name and arguments, just like the one that
+    /// parser yields. If `body` is a `Block`, no space is placed after the `=` operator, as the
+    /// block starts with a newline.
+    pub fn definition(name:impl Into<Ast>, args:impl IntoIterator<Item=Ast>, body:impl Into<Ast>)
+    -> Ast {
+        let lhs  = Ast::prefix_chain(name,args);
+        let body = body.into();
+        let roff = if let Shape::Block(_) = body.shape() { 0 } else { 1 };
+        Ast::infix_with_offsets(lhs,1,opr::predefined::ASSIGNMENT,roff,body)
+    }
+
+    /// Creates an AST node with `Block` shape with given absolute indent and lines.
+    pub fn block(indent:usize, first_line:&Ast, tail_lines:&[Option<Ast>]) -> Ast {
+        let block = Block::new(indent,first_line,tail_lines);
+        Ast::from(block)
+    }
+
+    /// Creates AST node with `Module` shape with given lines.
+    pub fn module(lines:&[Option<Ast>]) -> Ast {
+        Ast::new_no_id(Module::from_lines(lines))
+    }
+
     /// Creates AST node with `Module` shape with one line.
     pub fn one_line_module(line_ast:impl Into<Ast>) -> Ast {
         Module::from_line(line_ast).into()
     }
 
+    /// Creates an AST node with `TextLineRaw` shape, escaping given text as needed.
+    ///
+    /// # Panics
+    /// Panics if the text contains newline characters, see `TextLineRaw::from_text`.
+    pub fn text_raw(text:&str) -> Ast {
+        Ast::from(TextLineRaw::from_text(text))
+    }
+
+    /// Creates an AST node with `TextLineFmt` shape, escaping given text as needed.
+    pub fn text_fmt(text:&str) -> Ast {
+        Ast::from(TextLineFmt::<Ast>::from_text(text))
+    }
+
     /// Creates an AST node with `TextLineFmt` shape.
     pub fn text_line_fmt(text:Vec<SegmentFmt<Ast>>) -> Ast {
         let text_line_fmt = TextLineFmt {text};
@@ -1242,6 +1445,13 @@ impl Ast {
         Ast::from(text_unclosed)
     }
 
+    /// Creates an AST node with `TextBlockRaw` shape.
+    pub fn text_block_raw(text:Vec<TextBlockLine<SegmentRaw>>, offset:usize) -> Ast {
+        let spaces = 0;
+        let text_block_raw = TextBlockRaw {text,spaces,offset};
+        Ast::from(text_block_raw)
+    }
+
     /// Creates an AST node with `TextBlockFmt` shape.
     pub fn text_block_fmt(text:Vec<TextBlockLine<SegmentFmt<Ast>>>, offset:usize) -> Ast {
         let spaces = 0;
@@ -1514,6 +1724,78 @@ mod tests {
         assert_eq!(abc.iter_recursive().count(), 5); // for 2 Apps and 3 Vars
     }
 
+    #[test]
+    fn smart_constructors_repr() {
+        let a = || Ast::var("a");
+        let b = || Ast::var("b");
+        let cases = vec!
+            [ (Ast::unrecognized("`")                        , "`")
+            , (Ast::invalid_quote("''")                      , "''")
+            , (Ast::inline_block("'''")                      , "'''")
+            , (Ast::modifier("+")                            , "+=")
+            , (Ast::number_with_base("16","ff")              , "16_ff")
+            , (Ast::dangling_base("16")                      , "16_")
+            , (Ast::section_left_with_offset(a(),2,"+")      , "a  +")
+            , (Ast::section_right_with_offset("+",0,b())     , "+b")
+            , (Ast::prefix_with_offset(a(),3,b())            , "a   b")
+            , (Ast::prefix_chain(a(),vec![b(),Ast::blank()]) , "a b _")
+            , (Ast::infix_with_offsets(a(),0,"+",2,b())      , "a+  b")
+            , (Ast::definition(a(),vec![b()],Ast::number(5)) , "a b = 5")
+            , (Ast::block(4,&a(),&[None,Some(b())])          , "\n    a\n\n    b")
+            , (Ast::module(&[Some(a()),None,Some(b())])      , "a\n\nb")
+            , (Ast::text_raw(r#"a"b\c"#)                    , r#""a\"b\\c""#)
+            , (Ast::text_fmt("a'b\n`c`")                    , r#"'a\'b\n\`c\`'"#)
+            , (macros::lambda(a(),b()).into()                , "a -> b")
+            , (macros::group(Some(a())).into()               , "(a)")
+            , (macros::group(None).into()                    , "()")
+            , (macros::import(vec!["Foo","Bar"]).into()      , "import Foo.Bar")
+            ];
+        for (ast,expected_repr) in cases {
+            assert_eq!(ast.repr(),expected_repr);
+            test_utils::validate_spans(&ast);
+        }
+    }
+
+    #[test]
+    fn text_constructors_round_trip() {
+        /// Reverts escaping done by the text constructors, given the repr without quotes.
+        fn unescape(repr:&str) -> String {
+            let mut result = String::new();
+            let mut chars  = repr.chars();
+            while let Some(char) = chars.next() {
+                match char {
+                    repr::BACKSLASH => match chars.next().unwrap() {
+                        'n'   => result.push('\n'),
+                        't'   => result.push('\t'),
+                        other => result.push(other),
+                    }
+                    other => result.push(other),
+                }
+            }
+            result
+        }
+        let samples = ["", "foo", "foo bar", "ąę ćż", "a\\b", "'\"`", "\\\\", "a`b`c"];
+        for sample in samples.iter() {
+            let raw      = Ast::text_raw(sample).repr();
+            let raw_body = &raw[1..raw.len()-1];
+            assert_eq!(unescape(raw_body),*sample);
+            test_utils::validate_spans(&Ast::text_raw(sample));
+
+            let with_newlines = format!("{}\n\t{}",sample,sample);
+            let fmt           = Ast::text_fmt(&with_newlines).repr();
+            let fmt_body      = &fmt[1..fmt.len()-1];
+            assert_eq!(unescape(fmt_body),with_newlines);
+            assert!(!fmt.contains('\n'));
+            test_utils::validate_spans(&Ast::text_fmt(&with_newlines));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot contain newline characters")]
+    fn raw_text_line_with_newline() {
+        TextLineRaw::from_text("foo\nbar");
+    }
+
     #[test]
     fn all_lines_of_block() {
         let ty          = BlockType::Discontinuous {};
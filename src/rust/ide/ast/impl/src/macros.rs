@@ -8,6 +8,200 @@ use crate::crumbs::AmbiguousCrumb;
 use crate::crumbs::Located;
 use crate::crumbs::MatchCrumb;
 use crate::known;
+use crate::opr::predefined::ACCESS;
+use crate::opr::predefined::ARROW;
+use crate::HasLength;
+use crate::MacroMatchSegment;
+use crate::MacroPattern;
+use crate::MacroPatternMatch;
+use crate::MacroPatternMatchRaw;
+use crate::MacroPatternMatchRawBuild;
+use crate::MacroPatternMatchRawNothing;
+use crate::MacroPatternRaw;
+use crate::MacroPatternRawBlank;
+use crate::MacroPatternRawBlock;
+use crate::MacroPatternRawBuild;
+use crate::MacroPatternRawCons;
+use crate::MacroPatternRawInvalid;
+use crate::MacroPatternRawMacro;
+use crate::MacroPatternRawMany;
+use crate::MacroPatternRawMod;
+use crate::MacroPatternRawNothing;
+use crate::MacroPatternRawNum;
+use crate::MacroPatternRawOpr;
+use crate::MacroPatternRawOr;
+use crate::MacroPatternRawSeq;
+use crate::MacroPatternRawText;
+use crate::MacroPatternRawVar;
+use crate::Opr;
+use crate::Shape;
+use crate::Shifted;
+use crate::ShiftedVec1;
+use crate::Unrecognized;
+use crate::Var;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// Operator opening the parenthesized group.
+pub const GROUP_OPEN:&str = "(";
+
+/// Operator closing the parenthesized group.
+pub const GROUP_CLOSE:&str = ")";
+
+/// Keyword introducing the import statement.
+pub const IMPORT_KEYWORD:&str = "import";
+
+/// Operator starting the comment.
+pub const COMMENT_MARKER:&str = "#";
+
+
+
+// ================
+// === Patterns ===
+// ================
+
+/// Pattern matching any single token, like the parser's builtin `Pattern.Any`.
+fn any_pattern() -> MacroPattern {
+    let spaced = None;
+    let tokens:Vec<MacroPatternRaw> = vec![
+        MacroPatternRaw::Blank  (MacroPatternRawBlank   {spaced}),
+        MacroPatternRaw::Var    (MacroPatternRawVar     {spaced}),
+        MacroPatternRaw::Cons   (MacroPatternRawCons    {spaced}),
+        MacroPatternRaw::Opr    (MacroPatternRawOpr     {spaced,max_prec:None}),
+        MacroPatternRaw::Mod    (MacroPatternRawMod     {spaced}),
+        MacroPatternRaw::Num    (MacroPatternRawNum     {spaced}),
+        MacroPatternRaw::Text   (MacroPatternRawText    {spaced}),
+        MacroPatternRaw::Macro  (MacroPatternRawMacro   {spaced}),
+        MacroPatternRaw::Invalid(MacroPatternRawInvalid {spaced}),
+        MacroPatternRaw::Block  (MacroPatternRawBlock   {spaced}),
+    ];
+    let mut tokens = tokens.into_iter().map(Rc::new);
+    let first      = tokens.next().expect("Token patterns list is not empty.");
+    tokens.fold(first, |pat1,pat2| Rc::new(MacroPatternRaw::Or(MacroPatternRawOr{pat1,pat2})))
+}
+
+/// Pattern matching a non-empty sequence of tokens built into a single AST, like the parser's
+/// builtin `Pattern.Expr`.
+fn expr_pattern() -> MacroPatternRawBuild {
+    let any  = any_pattern();
+    let many = Rc::new(MacroPatternRaw::Many(MacroPatternRawMany {pat:any.clone()}));
+    let seq  = Rc::new(MacroPatternRaw::Seq(MacroPatternRawSeq {pat1:any,pat2:many}));
+    MacroPatternRawBuild {pat:seq}
+}
+
+/// Creates a pattern match of `expr_pattern` that matched given AST preceded by `off` spaces.
+fn expr_match(off:usize, ast:Ast) -> MacroPatternMatch<Shifted<Ast>> {
+    let pat  = expr_pattern();
+    let elem = Shifted::new(off,ast);
+    Rc::new(MacroPatternMatchRaw::Build(MacroPatternMatchRawBuild {pat,elem}))
+}
+
+/// Creates a pattern match for a segment which has no body.
+fn nothing_match() -> MacroPatternMatch<Shifted<Ast>> {
+    let pat = MacroPatternRawNothing {};
+    Rc::new(MacroPatternMatchRaw::Nothing(MacroPatternMatchRawNothing {pat}))
+}
+
+/// Wraps the spaceless `resolved` shape into Ast. As spaceless AST cannot compute its length,
+/// the length of the whole macro match must be provided.
+fn resolved_ast(resolved:impl Into<Shape<Ast>>, len:usize) -> Ast {
+    Ast::from_ast_id_len(resolved.into(),None,len)
+}
+
+/// Creates the segment head operator. Segment heads cannot have ids, see `Ast::new_no_id`.
+fn opr_head(name:&str) -> Ast {
+    Ast::new_no_id(Opr {name:name.into()})
+}
+
+/// Creates the segment head keyword. Segment heads cannot have ids, see `Ast::new_no_id`.
+fn var_head(name:&str) -> Ast {
+    Ast::new_no_id(Var {name:name.into()})
+}
+
+
+
+// ========================
+// === Groups & Imports ===
+// ========================
+
+/// Creates a parenthesized expression `(body)` or `()`, as the builtin group macro match.
+pub fn group(body:Option<Ast>) -> known::Match {
+    let open_head  = opr_head(GROUP_OPEN);
+    let close_head = opr_head(GROUP_CLOSE);
+    let open_body  = match &body {
+        Some(body) => expr_match(0,body.clone_ref()),
+        None       => nothing_match(),
+    };
+    let open     = MacroMatchSegment {head:open_head , body:open_body};
+    let close    = MacroMatchSegment {head:close_head, body:nothing_match()};
+    let segs     = ShiftedVec1 {head:open, tail:vec![Shifted::new(0,close)]};
+    let len      = segs.len();
+    let resolved = resolved_ast(crate::Group {body},len);
+    known::Match::new(crate::Match {pfx:None,segs,resolved},None)
+}
+
+/// Creates an import statement, like `import Foo.Bar` for path segments `Foo` and `Bar`.
+pub fn import<S:Str>(path:impl IntoIterator<Item=S>) -> known::Match {
+    let path     = path.into_iter().map(Ast::cons).collect_vec();
+    let mut rest = path.iter().cloned();
+    let first    = rest.next().expect("Import path must not be empty.");
+    let target   = rest.fold(first, |larg,rarg| Ast::infix_with_offsets(larg,0,ACCESS,0,rarg));
+    let head     = var_head(IMPORT_KEYWORD);
+    let body     = expr_match(1,target);
+    let segs     = ShiftedVec1 {head:MacroMatchSegment {head,body}, tail:vec![]};
+    let len      = segs.len();
+    let resolved = resolved_ast(crate::Import {path},len);
+    known::Match::new(crate::Match {pfx:None,segs,resolved},None)
+}
+
+/// If this is the builtin import macro match, returns the imported path segments, like `Foo` and
+/// `Bar` for `import Foo.Bar`.
+pub fn as_import(ast:&Ast) -> Option<Vec<String>> {
+    let macro_match = known::Match::try_from(ast).ok()?;
+    match macro_match.resolved.shape() {
+        Shape::Import(import) => Some(import.path.iter().map(|segment| segment.repr()).collect()),
+        _                     => None,
+    }
+}
+
+
+
+// ================
+// === Comments ===
+// ================
+
+/// Creates a single-line comment `# text`, as the builtin comment macro match. The text is kept
+/// verbatim, so it must not contain newlines.
+pub fn comment(text:impl Str) -> known::Match {
+    let text     = text.as_ref();
+    let head     = opr_head(COMMENT_MARKER);
+    let body     = expr_match(1,Ast::new_no_id(Unrecognized {str:text.into()}));
+    let segs     = ShiftedVec1 {head:MacroMatchSegment {head,body}, tail:vec![]};
+    let len      = segs.len();
+    let resolved = resolved_ast(crate::Comment {lines:vec![text.into()]},len);
+    known::Match::new(crate::Match {pfx:None,segs,resolved},None)
+}
+
+/// Checks if the given Ast is the builtin comment macro match.
+pub fn is_comment(ast:&Ast) -> bool {
+    let macro_match = known::Match::try_from(ast).ok();
+    macro_match.contains_if(|m| crate::opr::is_opr_named(&m.segs.head.head,COMMENT_MARKER))
+}
+
+/// If this is the builtin comment macro match, returns the comment's text: everything after the
+/// `#` marker and the single space following it.
+pub fn as_comment(ast:&Ast) -> Option<String> {
+    is_comment(ast).as_some_from(|| {
+        let repr = ast.repr();
+        let text = &repr.trim_start()[COMMENT_MARKER.len()..];
+        let text = if text.starts_with(' ') {&text[1..]} else {text};
+        text.to_string()
+    })
+}
 
 
 
@@ -24,6 +218,20 @@ pub struct LambdaInfo<'a> {
     pub body : Located<&'a Ast>,
 }
 
+/// Creates a lambda expression `arg -> body`, as the builtin `->` macro match.
+///
+/// The `arg` and `body` keep their ids, both in the segments and in the resolved AST. The resolved
+/// `Infix` node is created by `Ast::infix` and gets a fresh id, and the macro match itself has none.
+pub fn lambda(arg:Ast, body:Ast) -> known::Match {
+    let off      = 1;
+    let pfx      = Some(expr_match(off,arg.clone_ref()));
+    let head     = opr_head(ARROW);
+    let body_pat = expr_match(off,body.clone_ref());
+    let segs     = ShiftedVec1 {head:MacroMatchSegment {head,body:body_pat}, tail:vec![]};
+    let resolved = Ast::infix(arg,ARROW,body);
+    known::Match::new(crate::Match {pfx,segs,resolved},None)
+}
+
 /// If this is the builtin macro for `->` (lambda expression), returns it as known `Match`.
 pub fn as_lambda_match(ast:&Ast) -> Option<known::Match> {
     let macro_match = known::Match::try_from(ast).ok()?;
@@ -77,3 +285,26 @@ impl crate::Ambiguous<Ast> {
         })
     }
 }
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comment_round_trip() {
+        let comment = comment("Sums the inputs.");
+        assert_eq!(comment.ast().repr(), "# Sums the inputs.");
+        assert_eq!(as_comment(comment.ast()), Some("Sums the inputs.".into()));
+
+        let empty = comment("");
+        assert_eq!(as_comment(empty.ast()), Some("".into()));
+        assert_eq!(as_comment(&Ast::var("foo")), None);
+        assert_eq!(as_comment(group(Some(Ast::var("foo"))).ast()), None);
+    }
+}
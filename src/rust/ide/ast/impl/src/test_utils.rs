@@ -40,3 +40,27 @@ pub fn validate_spans(ast:&Ast) {
                    , "`{}` part of `{}`", node.repr(), ast.repr());
     }
 }
+
+/// Serializes given AST to JSON, removing all the node ids. Allows comparing the AST structure
+/// when the ids are not relevant, e.g. between constructed and parsed AST.
+pub fn to_json_without_ids(ast:&Ast) -> serde_json::Value {
+    fn remove_ids(value:&mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                map.remove(crate::ast_schema::ID);
+                map.values_mut().for_each(remove_ids);
+            }
+            serde_json::Value::Array(vec) => vec.iter_mut().for_each(remove_ids),
+            _                             => {}
+        }
+    }
+    let mut json = serde_json::to_value(ast).expect("Ast is always serializable.");
+    remove_ids(&mut json);
+    json
+}
+
+/// Checks that given ASTs have the same structure, ignoring their ids.
+pub fn assert_same_structure(left:&Ast, right:&Ast) {
+    assert_eq!(to_json_without_ids(left), to_json_without_ids(right)
+              , "`{}` and `{}` differ in structure", left.repr(), right.repr());
+}
@@ -72,6 +72,43 @@ fn test_file_event_notification() {
     }
 }
 
+#[test]
+fn test_expression_values_computed_notification() {
+    let mut fixture = setup_language_server();
+    let mut events  = Box::pin(fixture.client.events());
+    events.expect_pending();
+
+    let context_id = uuid::Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap();
+    let id         = uuid::Uuid::parse_str("00000000-0000-0000-0000-000000000002").unwrap();
+    let update     = ExpressionValueUpdate {
+        id,
+        typename    : Some("Number".into()),
+        short_value : None,
+    };
+    let expected = ExpressionValuesComputed {context_id,updates:vec![update]};
+    let notification_text = r#"{
+            "jsonrpc": "2.0",
+            "method": "executionContext/expressionValuesComputed",
+            "params": {
+                "contextId" : "00000000-0000-0000-0000-000000000001",
+                "updates"   : [
+                    { "id"         : "00000000-0000-0000-0000-000000000002"
+                    , "type"       : "Number"
+                    , "shortValue" : null
+                    }
+                ]
+            }
+        }"#;
+    fixture.transport.mock_peer_text_message(notification_text);
+    fixture.executor.run_until_stalled();
+
+    if let Event::Notification(n) = events.expect_next() {
+        assert_eq!(n, Notification::ExpressionValuesComputed(expected));
+    } else {
+        panic!("expected notification event");
+    }
+}
+
 /// This function tests making a request using language server. It
 /// * creates FM client and uses `make_request` to make a request,
 /// * checks that request is made for `expected_method`,
@@ -117,7 +117,11 @@ pub enum Notification {
         /// to address this: https://github.com/luna/enso/issues/707
         // TODO [mwu] Update as the issue is resolved on way or another.
         event:FileEvent,
-    }
+    },
+    /// New information about the values of some expressions in the execution context, like their
+    /// types, became available.
+    #[serde(rename = "executionContext/expressionValuesComputed")]
+    ExpressionValuesComputed(ExpressionValuesComputed),
 }
 
 
@@ -327,6 +331,28 @@ pub type ContextId = Uuid;
 /// Execution context expression ID.
 pub type ExpressionId = Uuid;
 
+/// The `executionContext/expressionValuesComputed` notification parameters.
+#[derive(Clone,Debug,Deserialize,PartialEq,Serialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct ExpressionValuesComputed {
+    pub context_id : ContextId,
+    pub updates    : Vec<ExpressionValueUpdate>,
+}
+
+/// The information about the value of a single expression. The fields are missing if the
+/// information is not known.
+#[derive(Clone,Debug,Deserialize,Eq,PartialEq,Serialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct ExpressionValueUpdate {
+    pub id          : ExpressionId,
+    /// The qualified name of the value's type.
+    #[serde(rename = "type")]
+    pub typename    : Option<String>,
+    pub short_value : Option<String>,
+}
+
 /// A configuration object for properties of the visualisation.
 #[derive(Clone,Debug,Deserialize,Eq,Hash,PartialEq,Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -403,6 +429,14 @@ impl CapabilityRegistration {
         CapabilityRegistration {method,register_options}
     }
 
+    /// Create "receivesTreeUpdates" capability for path. The `file/event` notifications are sent
+    /// for all the changes of the file system objects in this directory.
+    pub fn create_receives_tree_updates(path:Path) -> Self {
+        let method           = "receivesTreeUpdates".to_string();
+        let register_options = RegisterOptions::Path {path};
+        CapabilityRegistration {method,register_options}
+    }
+
     /// Create "executionContext/canModify" capability for path
     pub fn create_can_modify_execution_context(context_id:Uuid) -> Self {
         let method = "executionContext/canModify".to_string();
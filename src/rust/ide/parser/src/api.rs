@@ -82,6 +82,12 @@ impl<M:Metadata> TryFrom<&ParsedSourceFile<M>> for String {
 
 const METADATA_TAG:&str = "\n\n\n#### METADATA ####\n";
 
+/// Returns the code part of serialized source file content, i.e. the content without the id map
+/// and metadata sections.
+pub fn code_without_metadata(content:&str) -> &str {
+    content.find(METADATA_TAG).map_or(content, |index| &content[..index])
+}
+
 fn to_json_single_line(val:&impl Serialize) -> std::result::Result<String,serde_json::Error> {
     let json = serde_json::to_string(val)?;
     let line = json.chars().filter(|c| *c != '\n' && *c != '\r').collect();
@@ -186,6 +192,8 @@ mod test {
         assert_eq!(serialized.content         , expected_content.to_string());
         assert_eq!(serialized.code_slice()    , "main = 2 + 2");
         assert_eq!(serialized.id_map_slice()  , expected_id_map.as_str());
-        assert_eq!(serialized.metadata_slice(), expected_metadata.as_str())
+        assert_eq!(serialized.metadata_slice(), expected_metadata.as_str());
+        assert_eq!(code_without_metadata(&serialized.content), "main = 2 + 2");
+        assert_eq!(code_without_metadata("main = 2 + 2"), "main = 2 + 2");
     }
 }
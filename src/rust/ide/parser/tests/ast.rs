@@ -12,8 +12,12 @@ use ast::HasRepr;
 use ast::opr;
 use ast::prefix;
 use wasm_bindgen_test::wasm_bindgen_test;
+use ast::test_utils::assert_same_structure;
 use ast::test_utils::expect_single_line;
 use ast::opr::GeneralizedInfix;
+use ast::Ast;
+use ast::HasLength;
+use ast::crumbs::Crumbable;
 
 wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
 
@@ -115,3 +119,231 @@ pub fn flatten_infix_test() {
     case("a,b,c",  "c",vec!["b","a"]);
     case("a+b*c+d","a",vec!["b*c","d"]);
 }
+
+#[wasm_bindgen_test]
+pub fn smart_constructors_match_parser() {
+    let parser = parser::Parser::new_or_panic();
+    let a      = || Ast::var("a");
+    let b      = || Ast::var("b");
+    let texts  = vec!["", "foo bar", "a\\b", "\"quoted\"", "it's", "`expr`", "ąęćż"];
+
+    let mut lines = vec!
+        [ Ast::unrecognized("`")
+        , Ast::modifier("+")
+        , Ast::number(127)
+        , Ast::number_with_base("16","ff")
+        , Ast::dangling_base("16")
+        , Ast::cons("Foo")
+        , Ast::blank()
+        , Ast::section_left_with_offset(a(),2,"+")
+        , Ast::section_right_with_offset("+",1,b())
+        , Ast::section_sides("+")
+        , Ast::prefix_chain(a(),vec![b(),Ast::number(4)])
+        , Ast::infix_with_offsets(a(),0,"+",2,b())
+        , Ast::definition(a(),vec![b()],Ast::infix_var("b","+","b"))
+        ];
+    for text in texts {
+        lines.push(Ast::text_raw(text));
+        lines.push(Ast::text_fmt(text));
+    }
+    for line in lines {
+        let parsed = parser.parse_line(line.repr()).unwrap();
+        assert_same_structure(&line,&parsed);
+    }
+
+    let block  = Ast::block(4,&a(),&[Some(b())]);
+    let module = Ast::one_line_module(Ast::definition(Ast::var("main"),vec![],block.clone()));
+    let parsed = parser.parse(module.repr(),default()).unwrap();
+    let parsed_def = ast::opr::to_assignment(expect_single_line(&parsed)).unwrap();
+    assert_same_structure(&parsed_def.rarg,&block);
+}
+
+/// Checks that the macro match built by smart constructor has the same structure as the one
+/// yielded by parser. The patterns of segments are not compared, as the exact patterns in the
+/// parser's builtin macro definitions are not part of the AST contract. The resolved AST and all
+/// the children (prefix and segment heads and bodies) must be the same, however.
+fn assert_same_match_structure(built:&ast::known::Match, parsed:&Ast) {
+    let parsed = ast::known::Match::try_from(parsed).unwrap();
+    assert_eq!(built.repr(),parsed.repr());
+    assert_eq!(built.ast().len(),parsed.ast().len());
+    assert_same_structure(&built.resolved,&parsed.resolved);
+    let built_children  = built.ast().iter_subcrumbs().collect_vec();
+    let parsed_children = parsed.ast().iter_subcrumbs().collect_vec();
+    assert_eq!(built_children,parsed_children);
+    for crumb in built_children {
+        let built_child  = built.ast().get_located(crumb.clone()).unwrap();
+        let parsed_child = parsed.ast().get_located(crumb).unwrap();
+        assert_same_structure(&built_child.item,&parsed_child.item);
+    }
+    ast::test_utils::validate_spans(built.ast());
+}
+
+#[wasm_bindgen_test]
+pub fn macro_constructors_match_parser() {
+    let parser = parser::Parser::new_or_panic();
+    let parse  = |code:String| parser.parse_line(code).unwrap();
+    let args   = vec!
+        [ Ast::var("a")
+        , Ast::number(5)
+        , Ast::prefix_chain(Ast::var("foo"),vec![Ast::var("bar")])
+        ];
+
+    for arg in &args {
+        for body in &args {
+            let lambda = ast::macros::lambda(arg.clone_ref(),body.clone_ref());
+            assert_same_match_structure(&lambda,&parse(lambda.repr()));
+            let info = ast::macros::as_lambda(lambda.ast()).unwrap();
+            assert_eq!(info.arg.item,arg);
+            assert_eq!(info.body.item,body);
+        }
+        let group = ast::macros::group(Some(arg.clone_ref()));
+        assert_same_match_structure(&group,&parse(group.repr()));
+    }
+    let empty_group = ast::macros::group(None);
+    assert_same_match_structure(&empty_group,&parse(empty_group.repr()));
+
+    let paths = vec![vec!["Foo"],vec!["Foo","Bar"],vec!["Standard","Base","Data"]];
+    for path in paths {
+        let import = ast::macros::import(path.clone());
+        let parsed = parse(import.repr());
+        assert_same_match_structure(&import,&parsed);
+        let segments = path.iter().map(|segment| segment.to_string()).collect();
+        assert_eq!(ast::macros::as_import(&parsed),Some(segments));
+    }
+    assert_eq!(ast::macros::as_import(&parse("foo.bar".into())),None);
+
+    let texts = vec!["Adds one.", "foo bar", "a # b", "ąę ćż", "`code` and \"quotes\""];
+    for text in texts {
+        let comment = ast::macros::comment(text);
+        let parsed  = parse(comment.repr());
+        assert_same_match_structure(&comment,&parsed);
+        assert_eq!(ast::macros::as_comment(&parsed).as_deref(),Some(text));
+    }
+}
+
+
+
+// ======================
+// === Property Tests ===
+// ======================
+
+/// A deterministic generator of random ASTs, built from identifiers, operators and literals with
+/// the smart constructors. The seed is part of the failure messages, so a failing case can be
+/// reproduced.
+#[derive(Clone,Copy,Debug)]
+struct AstGenerator {
+    state : u64,
+}
+
+impl AstGenerator {
+    /// The operators used in infix chains. All of them are left-associative, so a chain built from
+    /// a single one of them is parsed in the same order as it is built.
+    const OPERATORS : &[&str] = &["+","-","*","/","%","<","=="];
+
+    /// Characters of the text literals, including the ones that need escaping.
+    const TEXT_CHARS : &[char] = &['a','Z','7',' ','ą','ż','\\','\'','"','`'];
+
+    fn new(seed:u64) -> Self {
+        // The state of xorshift must not be zero.
+        let state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+        Self {state}
+    }
+
+    /// Next pseudo-random number, using the xorshift64 algorithm.
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Random number in range `0..bound`.
+    fn below(&mut self, bound:usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    fn pick<T:Copy>(&mut self, items:&[T]) -> T {
+        items[self.below(items.len())]
+    }
+
+    /// Identifier body following the first letter. The first letters are chosen so that the
+    /// identifiers never collide with the keywords.
+    fn identifier_tail(&mut self) -> String {
+        let chars = "abcxyz0189".chars().collect_vec();
+        (0..self.below(5)).map(|_| self.pick(&chars)).collect()
+    }
+
+    fn var(&mut self) -> Ast {
+        let head = self.pick(&['v','w','x','y','z']);
+        Ast::var(format!("{}{}",head,self.identifier_tail()))
+    }
+
+    fn cons(&mut self) -> Ast {
+        let head = self.pick(&['V','W','X','Y','Z']);
+        Ast::cons(format!("{}{}",head,self.identifier_tail()))
+    }
+
+    fn text(&mut self) -> String {
+        (0..self.below(6)).map(|_| self.pick(Self::TEXT_CHARS)).collect()
+    }
+
+    /// An identifier or a literal.
+    fn atom(&mut self) -> Ast {
+        match self.below(5) {
+            0 => self.var(),
+            1 => self.cons(),
+            2 => Ast::number(self.below(1000) as i64),
+            3 => Ast::text_raw(&self.text()),
+            _ => Ast::text_fmt(&self.text()),
+        }
+    }
+
+    /// An atom, or an application of a function to atoms.
+    fn operand(&mut self) -> Ast {
+        if self.below(3) == 0 {
+            let func = if self.below(2) == 0 { self.var() } else { self.cons() };
+            let args = (0..1 + self.below(3)).map(|_| self.atom()).collect_vec();
+            Ast::prefix_chain(func,args)
+        } else {
+            self.atom()
+        }
+    }
+
+    /// An operand, or a chain of operands joined with a single operator.
+    fn expression(&mut self) -> Ast {
+        let first = self.operand();
+        if self.below(2) == 0 {
+            first
+        } else {
+            let opr = self.pick(Self::OPERATORS);
+            (0..1 + self.below(3)).fold(first, |chain,_| Ast::infix(chain,opr,self.operand()))
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+pub fn generated_asts_round_trip() {
+    let parser = parser::Parser::new_or_panic();
+    for seed in 0..200 {
+        let ast    = AstGenerator::new(seed).expression();
+        let code   = ast.repr();
+        let parsed = parser.parse_line(code.clone()).unwrap();
+        assert_eq!(parsed.repr(),code,"Code of seed {} changed when parsed.",seed);
+        assert_same_structure(&ast,&parsed);
+        ast::test_utils::validate_spans(&parsed);
+    }
+}
+
+#[wasm_bindgen_test]
+pub fn generated_comments_round_trip() {
+    let parser = parser::Parser::new_or_panic();
+    for seed in 0..50 {
+        let mut generator = AstGenerator::new(seed);
+        let text          = format!("{} {}",generator.var().repr(),generator.text());
+        let text          = text.trim_end().to_string();
+        let comment       = ast::macros::comment(text.as_str());
+        let parsed        = parser.parse_line(comment.repr()).unwrap();
+        assert_same_match_structure(&comment,&parsed);
+        assert_eq!(ast::macros::as_comment(&parsed),Some(text),"Comment of seed {}.",seed);
+    }
+}
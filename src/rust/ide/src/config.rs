@@ -1,4 +1,12 @@
 //! This module provides constants used throughout the crate.
 
-/// Visualization folder where IDE can look for user-defined visualizations per project.
+/// Visualization folder where IDE can look for user-defined visualizations, both in the project
+/// and in each library.
 pub const PROJECT_VISUALIZATION_FOLDER:&str = "visualization";
+
+/// The package definition file, located in the root directory of each Enso library.
+pub const PACKAGE_DEFINITION_FILE:&str = "package.yaml";
+
+/// The user keymap file, located in the root directory of the project. The keymap applies only to
+/// the project containing it. See `ensogl::application::keymap` to learn about its format.
+pub const KEYMAP_FILE:&str = "keymap.json";
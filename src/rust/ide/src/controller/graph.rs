@@ -49,6 +49,13 @@ pub struct NoPatternOnNode {
     pub node : node::Id,
 }
 
+#[allow(missing_docs)]
+#[derive(Clone,Copy,Debug,Fail)]
+#[fail(display="Node {} has no visualization, so it cannot be a widget.",node)]
+pub struct NoVisualizationOnNode {
+    pub node : node::Id,
+}
+
 
 
 // ====================
@@ -75,6 +82,8 @@ pub struct Node {
     pub info : NodeInfo,
     /// Information about this node stored in the module's metadata.
     pub metadata : Option<NodeMetadata>,
+    /// The documentation of the node, written in the comment lines directly above it.
+    pub documentation : Option<String>,
 }
 
 
@@ -432,11 +441,15 @@ impl Handle {
         self.module.find_definition(&self.id)
     }
 
+    /// Retrieves double rep information about the graph.
+    pub fn graph_info(&self) -> FallibleResult<GraphInfo> {
+        let definition = self.graph_definition_info()?;
+        Ok(GraphInfo::from_definition(definition))
+    }
+
     /// Returns double rep information about all nodes in the graph.
     pub fn all_node_infos(&self) -> FallibleResult<Vec<NodeInfo>> {
-        let definition = self.graph_definition_info()?;
-        let graph      = double_representation::graph::GraphInfo::from_definition(definition);
-        Ok(graph.nodes())
+        Ok(self.graph_info()?.nodes())
     }
 
     /// Retrieves double rep information about node with given ID.
@@ -451,18 +464,29 @@ impl Handle {
     /// Note that it is more efficient to use `get_nodes` to obtain all information at once,
     /// rather then repeatedly call this method.
     pub fn node(&self, id:ast::Id) -> FallibleResult<Node> {
-        let info     = self.node_info(id)?;
-        let metadata = self.module.node_metadata(id).ok();
-        Ok(Node {info,metadata})
+        let info          = self.node_info(id)?;
+        let metadata      = self.module.node_metadata(id).ok();
+        let documentation = self.graph_info()?.node_documentation(id)?;
+        Ok(Node {info,metadata,documentation})
+    }
+
+    /// Syntax errors present in the node with given ID, e.g. to mark it as invalid.
+    ///
+    /// The crumbs and spans of the returned diagnostics are relative to the node's line AST.
+    pub fn node_diagnostics
+    (&self, id:ast::Id) -> FallibleResult<Vec<ast::diagnostics::Diagnostic>> {
+        let info = self.node_info(id)?;
+        Ok(ast::diagnostics::collect(info.ast()))
     }
 
     /// Returns information about all the nodes currently present in this graph.
     pub fn nodes(&self) -> FallibleResult<Vec<Node>> {
-        let node_infos = self.all_node_infos()?;
-        let mut nodes  = Vec::new();
-        for info in node_infos {
-            let metadata = self.module.node_metadata(info.id()).ok();
-            nodes.push(Node {info,metadata})
+        let graph     = self.graph_info()?;
+        let mut nodes = Vec::new();
+        for info in graph.nodes() {
+            let metadata      = self.module.node_metadata(info.id()).ok();
+            let documentation = graph.node_documentation(info.id())?;
+            nodes.push(Node {info,metadata,documentation})
         }
         Ok(nodes)
     }
@@ -701,6 +725,58 @@ impl Handle {
         Ok(())
     }
 
+    /// Enso literal of the data entered into a widget. Numbers and texts become the number and text
+    /// literals, while other JSON values become the text literals containing their JSON encoding.
+    pub fn widget_data_literal(&self, data:&serde_json::Value) -> FallibleResult<Ast> {
+        match data {
+            serde_json::Value::Number(number) => {
+                // The floating point numbers are printed without exponents, as Enso literals
+                // cannot contain them.
+                let code = match number.as_i64() {
+                    Some(integer) => integer.to_string(),
+                    None          => number.as_f64().unwrap_or_default().to_string(),
+                };
+                self.parser.parse_line(code)
+            }
+            serde_json::Value::String(text) => Ok(Ast::text_fmt(text)),
+            other                           => Ok(Ast::text_fmt(&other.to_string())),
+        }
+    }
+
+    /// Sets the expression of the widget node to the literal of the data entered by the user. The
+    /// node gets a name if it had none, so it always has the form of `name = data`. The node is
+    /// marked as a widget in its visualization metadata, so it fails if the node has no
+    /// visualization attached.
+    pub fn set_widget_data(&self, id:ast::Id, data:&serde_json::Value) -> FallibleResult<()> {
+        let metadata = self.module.node_metadata(id).ok();
+        if metadata.and_then(|metadata| metadata.visualization).is_none() {
+            return Err(NoVisualizationOnNode {node:id}.into())
+        }
+        let literal = self.widget_data_literal(data)?;
+        self.set_expression_ast(id,literal)?;
+        if self.node_info(id)?.pattern().is_none() {
+            self.introduce_name_on(id)?;
+        }
+        self.module.with_node_metadata(id, |metadata| {
+            if let Some(visualization) = &mut metadata.visualization {
+                visualization.widget = true;
+            }
+        });
+        Ok(())
+    }
+
+    /// Sets the documentation of the node, i.e. the comment lines directly above it. Passing `None`
+    /// or an empty text removes the documentation.
+    pub fn set_node_documentation
+    (&self, id:ast::Id, documentation:Option<&str>) -> FallibleResult<()> {
+        info!(self.logger, "Setting node {id} documentation to {documentation:?}");
+        self.update_definition_ast(|definition| {
+            let mut graph = GraphInfo::from_definition(definition);
+            graph.set_node_documentation(id,documentation)?;
+            Ok(graph.source)
+        })
+    }
+
     /// Updates the given node in the definition.
     ///
     /// The function `F` is called with the information with the state of the node so far and
@@ -744,6 +820,8 @@ mod tests {
     use ast::HasRepr;
     use ast::crumbs;
     use data::text::Index;
+    use data::text::Size;
+    use data::text::Span;
     use data::text::TextChange;
     use enso_protocol::language_server;
     use parser::Parser;
@@ -925,6 +1003,27 @@ main =
         })
     }
 
+    #[wasm_bindgen_test]
+    fn graph_controller_node_diagnostics() {
+        let mut test  = GraphControllerFixture::set_up();
+        const PROGRAM:&str = r"
+main =
+    foo = 16_
+    print foo";
+        test.run_graph_for_main(PROGRAM, "main", |module, graph| async move {
+            let nodes         = graph.nodes().unwrap();
+            let (node1,node2) = nodes.expect_tuple();
+            let diagnostics   = graph.node_diagnostics(node1.info.id()).unwrap();
+            let (diagnostic,) = diagnostics.expect_tuple();
+            assert_eq!(diagnostic.kind, ast::diagnostics::Kind::DanglingBase);
+            assert_eq!(diagnostic.span, Span::new(Index::new(6),Size::new(3)));
+            assert!(graph.node_diagnostics(node2.info.id()).unwrap().is_empty());
+
+            let (in_module,) = module.diagnostics().expect_tuple();
+            assert_eq!(in_module.id, diagnostic.id);
+        })
+    }
+
     #[wasm_bindgen_test]
     fn graph_controller_node_operations_node() {
         let mut test  = GraphControllerFixture::set_up();
@@ -943,7 +1042,7 @@ main =
             // === Add node ===
             let id       = ast::Id::new_v4();
             let position = Some(model::module::Position::new(10.0,20.0));
-            let metadata = NodeMetadata {position};
+            let metadata = NodeMetadata::at_position(position);
             let info     = NewNodeInfo {
                 expression    : "a+b".into(),
                 metadata      : Some(metadata),
@@ -1152,6 +1251,80 @@ main =
         })
     }
 
+    #[wasm_bindgen_test]
+    fn graph_controller_node_documentation() {
+        let mut test  = GraphControllerFixture::set_up();
+        const PROGRAM:&str = r"
+main =
+    # Reads the data.
+    foo = 2
+    bar = foo + 1";
+        test.run_graph_for_main(PROGRAM, "main", |module, graph| async move {
+            let (foo,bar) = graph.nodes().unwrap().expect_tuple();
+            assert_eq!(foo.documentation, Some("Reads the data.".to_string()));
+            assert_eq!(bar.documentation, None);
+
+            graph.set_node_documentation(bar.info.id(),Some("Adds one.")).unwrap();
+            graph.set_node_documentation(foo.info.id(),None).unwrap();
+            let expected_program = r"
+main =
+    foo = 2
+    # Adds one.
+    bar = foo + 1";
+            module.expect_code(expected_program);
+            let bar = graph.node(bar.info.id()).unwrap();
+            assert_eq!(bar.documentation, Some("Adds one.".to_string()));
+
+            graph.remove_node(bar.info.id()).unwrap();
+            module.expect_code("\nmain =\n    foo = 2");
+        })
+    }
+
+    #[wasm_bindgen_test]
+    fn graph_controller_widget_data() {
+        let mut test  = GraphControllerFixture::set_up();
+        const PROGRAM:&str = r"
+main =
+    points = 'old'
+    5";
+        test.run_graph_for_main(PROGRAM, "main", |module, graph| async move {
+            let (node0,node1) = graph.nodes().unwrap().expect_tuple();
+            let (id0,id1)     = (node0.info.id(),node1.info.id());
+            assert!(graph.set_widget_data(id0,&serde_json::json!(1)).is_err());
+            module.expect_code(PROGRAM);
+
+            let visualization = model::module::VisualizationMetadata {
+                library : "Builtin".into(),
+                name    : "Scatter Plot".into(),
+                ..default()
+            };
+            for id in &[id0,id1] {
+                graph.module.with_node_metadata(*id, |metadata| {
+                    metadata.visualization = Some(visualization.clone());
+                });
+            }
+            graph.set_widget_data(id0,&serde_json::json!([[1,2],[3.5,4]])).unwrap();
+            graph.set_widget_data(id1,&serde_json::json!(2.5)).unwrap();
+            let expected_program = r"
+main =
+    points = '[[1,2],[3.5,4]]'
+    number1 = 2.5";
+            module.expect_code(expected_program);
+
+            graph.set_widget_data(id0,&serde_json::json!("It's")).unwrap();
+            graph.set_widget_data(id1,&serde_json::json!(-3)).unwrap();
+            let expected_program = r"
+main =
+    points = 'It\'s'
+    number1 = -3";
+            module.expect_code(expected_program);
+
+            let is_widget = |id| module.node_metadata(id).unwrap().visualization.unwrap().widget;
+            assert!(is_widget(id0));
+            assert!(is_widget(id1));
+        })
+    }
+
     #[wasm_bindgen_test]
     fn suggested_names() {
         let parser = Parser::new_or_panic();
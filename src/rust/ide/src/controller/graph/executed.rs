@@ -5,9 +5,11 @@
 //! visualisations, retrieving types on ports, etc.
 use crate::prelude::*;
 
+use crate::model::execution_context::ComputedValueInfoRegistry;
 use crate::model::execution_context::Visualization;
 use crate::model::execution_context::VisualizationId;
 use crate::model::execution_context::VisualizationUpdateData;
+use crate::model::module::QualifiedName as ModuleQualifiedName;
 use crate::model::synchronized::ExecutionContext;
 
 /// Handle providing executed graph controller interface.
@@ -17,6 +19,8 @@ pub struct Handle {
     pub graph:controller::Graph,
     /// Execution Context handle, its call stack top contains `graph`'s definition.
     execution_ctx:Rc<ExecutionContext>,
+    /// The qualified name of the module containing the graph.
+    module_name:Rc<ModuleQualifiedName>,
 }
 
 impl Handle {
@@ -31,8 +35,16 @@ impl Handle {
     /// strong references to the execution context and it is expected that it will be dropped after
     /// the last copy of this controller is dropped.
     /// Then the context when being dropped shall remove itself from the Language Server.
-    pub fn new(graph:controller::Graph, execution_ctx:Rc<ExecutionContext>) -> Self {
-        Handle{graph,execution_ctx}
+    pub fn new
+    (graph:controller::Graph, execution_ctx:Rc<ExecutionContext>, module_name:ModuleQualifiedName)
+    -> Self {
+        let module_name = Rc::new(module_name);
+        Handle{graph,execution_ctx,module_name}
+    }
+
+    /// The qualified name of the module containing the graph.
+    pub fn module_qualified_name(&self) -> &ModuleQualifiedName {
+        &self.module_name
     }
 
     /// See `attach_visualization` in `ExecutionContext`.
@@ -42,12 +54,23 @@ impl Handle {
         self.execution_ctx.attach_visualization(visualization).await
     }
 
+    /// See `modify_visualization` in `ExecutionContext`.
+    pub async fn modify_visualization
+    (&self, id:&VisualizationId, expression:String, module:ModuleQualifiedName)
+    -> FallibleResult<()> {
+        self.execution_ctx.modify_visualization(id,expression,module).await
+    }
+
     /// See `detach_visualization` in `ExecutionContext`.
     pub async fn detach_visualization(&self, id:&VisualizationId) -> FallibleResult<Visualization> {
         self.execution_ctx.detach_visualization(id).await
     }
 
-    // TODO [mwu] Here goes the type/short_rep value access API
+    /// The information about the values computed in the graph's execution context, like the types
+    /// of the nodes' expressions.
+    pub fn computed_value_info_registry(&self) -> &ComputedValueInfoRegistry {
+        self.execution_ctx.computed_value_info_registry()
+    }
 }
 
 impl Deref for Handle {
@@ -90,6 +90,13 @@ impl Handle {
         self.model.ast().repr()
     }
 
+    /// Syntax errors present in the module's code.
+    ///
+    /// The crumbs and spans of the returned diagnostics are relative to the whole module.
+    pub fn diagnostics(&self) -> Vec<ast::diagnostics::Diagnostic> {
+        ast::diagnostics::collect(self.model.ast().ast())
+    }
+
     /// Check if current module state is synchronized with given code. If it's not, log error,
     /// and update module state to match the `code` passed as argument.
     pub fn check_code_sync(&self, code:String) -> FallibleResult<()> {
@@ -118,8 +125,9 @@ impl Handle {
         let definition_name = id.crumbs.last().cloned().ok_or_else(|| InvalidGraphId(id.clone()))?;
         let graph           = self.graph_controller_unchecked(id);
         let path            = self.path.clone_ref();
+        let module_name     = project.qualified_module_name(&path);
         let execution_ctx   = project.create_execution_context(path,definition_name).await?;
-        Ok(controller::ExecutedGraph::new(graph,execution_ctx))
+        Ok(controller::ExecutedGraph::new(graph,execution_ctx,module_name))
     }
 
     /// Returns a graph controller for graph in this module's subtree identified by `id` without
@@ -40,6 +40,11 @@ type ExecutionContextId = model::execution_context::Id;
 #[fail(display="No visualization with id {} was found in the registry.", _0)]
 pub struct NoSuchVisualization(VisualizationId);
 
+#[allow(missing_docs)]
+#[derive(Clone,Copy,Debug,Fail)]
+#[fail(display="No execution context with id {} was found in the registry.", _0)]
+pub struct NoSuchExecutionContext(ExecutionContextId);
+
 
 // === Aliases ===
 
@@ -49,7 +54,7 @@ type ExecutionContextWeakMap = WeakValueHashMap<ExecutionContextId,Weak<Executio
 // === Definition ===
 
 /// Stores the weak handles to the synchronized execution context models.
-/// Implements dispatching the visualization updates.
+/// Implements dispatching the visualization updates and the computed values information.
 #[derive(Clone,Debug,Default)]
 pub struct ExecutionContextsRegistry(RefCell<ExecutionContextWeakMap>);
 
@@ -67,8 +72,18 @@ impl ExecutionContextsRegistry {
         ctx.dispatch_visualization_update(visualization_id,data)
     }
 
+    /// Routes the computed values information into the appropriate execution context.
+    pub fn dispatch_expression_values
+    (&self, computed:language_server::ExpressionValuesComputed) -> FallibleResult<()> {
+        let context_id = computed.context_id;
+        let ctx        = self.0.borrow_mut().get(&context_id);
+        let ctx        = ctx.ok_or_else(|| NoSuchExecutionContext(context_id))?;
+        ctx.computed_value_info_registry().apply_updates(computed.updates);
+        Ok(())
+    }
+
     /// Registers a new ExecutionContext. It will be eligible for receiving future updates routed
-    /// through `dispatch_visualization_update`.
+    /// through `dispatch_visualization_update` and `dispatch_expression_values`.
     pub fn insert(&self, context:Rc<ExecutionContext>) {
         self.0.borrow_mut().insert(context.id(),context);
     }
@@ -96,9 +111,13 @@ pub struct Handle {
 
 impl Handle {
     /// Create a new project controller.
+    ///
+    /// The `language_server_events` should be the event stream of the Language Server client
+    /// wrapped by `language_server_client`.
     pub fn new
     ( parent                     : impl AnyLogger
     , language_server_client     : language_server::Connection
+    , language_server_events     : impl Stream<Item=language_server::Event> + 'static
     , mut language_server_binary : binary::Connection
     , project_name               : impl Str
     ) -> Self {
@@ -109,17 +128,20 @@ impl Handle {
         let language_server_rpc     = Rc::new(language_server_client);
         let language_server_bin     = Rc::new(language_server_binary);
         let language_server         = language_server_rpc.clone();
-        let visualization           = Visualization::new(language_server,embedded_visualizations);
         let project_name            = Rc::new(project_name.into());
+        let parser                  = Parser::new_or_panic();
+        let visualization           = Visualization::new(&logger,language_server
+            ,embedded_visualizations,project_name.as_str(),parser.clone_ref());
         let module_registry         = default();
         let execution_contexts      = default();
-        let parser                  = Parser::new_or_panic();
 
         let ret = Handle {project_name,module_registry,execution_contexts,parser,
             language_server_rpc,language_server_bin,logger,visualization};
 
         let binary_handler = ret.binary_event_handler();
         crate::executor::global::spawn(binary_protocol_events.for_each(binary_handler));
+        let json_handler = ret.json_event_handler();
+        crate::executor::global::spawn(language_server_events.for_each(json_handler));
         ret
     }
 
@@ -128,6 +150,41 @@ impl Handle {
         self.language_server_rpc.content_root()
     }
 
+    /// Returns a handling function capable of processing notifications from the Language Server
+    /// JSON-RPC protocol. Such function will be then typically used to process events stream from
+    /// the Language Server client.
+    pub fn json_event_handler
+    (&self) -> impl Fn(language_server::Event) -> futures::future::Ready<()> {
+        let logger                  = self.logger.clone_ref();
+        let visualization           = self.visualization.clone_ref();
+        let weak_execution_contexts = Rc::downgrade(&self.execution_contexts);
+        move |event| {
+            debug!(logger, "Received an event from the json-rpc protocol: {event:?}");
+            use json_rpc::handler::Event;
+            use language_server::Notification;
+            match event {
+                Event::Notification(Notification::FileEvent {event}) => {
+                    visualization.handle_file_event(&event);
+                }
+                Event::Notification(Notification::ExpressionValuesComputed(computed)) => {
+                    if let Some(execution_contexts) = weak_execution_contexts.upgrade() {
+                        let result = execution_contexts.dispatch_expression_values(computed);
+                        if let Err(error) = result {
+                            error!(logger,"Failed to handle the computed values: {error}.");
+                        }
+                    }
+                }
+                Event::Closed => {
+                    error!(logger,"Lost JSON-RPC connection with the Language Server!");
+                }
+                Event::Error(error) => {
+                    error!(logger,"Error emitted by the JSON-RPC connection: {error}.");
+                }
+            }
+            futures::future::ready(())
+        }
+    }
+
     /// Returns a handling function capable of processing updates from the binary protocol.
     /// Such function will be then typically used to process events stream from the binary
     /// connection handler.
@@ -282,7 +339,9 @@ mod test {
         let json_connection   = language_server::Connection::new_mock(json_client);
         let binary_connection = binary::Connection::new_mock(binary_client);
         let logger            = Logger::default();
-        controller::Project::new(logger,json_connection,binary_connection,DEFAULT_PROJECT_NAME)
+        let json_events       = futures::stream::empty();
+        controller::Project::new(logger,json_connection,json_events,binary_connection
+            ,DEFAULT_PROJECT_NAME)
     }
 
     #[wasm_bindgen_test]
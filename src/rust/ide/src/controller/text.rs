@@ -117,6 +117,16 @@ impl Handle {
         }
     }
 
+    /// Syntax errors present in the file's content, to be marked in the editor.
+    ///
+    /// Only module files are checked; for plain text files this is always empty.
+    pub fn diagnostics(&self) -> Vec<ast::diagnostics::Diagnostic> {
+        match &self.file {
+            FileHandle::PlainText{..}       => default(),
+            FileHandle::Module {controller} => controller.diagnostics(),
+        }
+    }
+
     /// Get a stream of text changes notifications.
     pub fn subscribe(&self) -> Pin<Box<dyn Stream<Item=Notification>>> {
         match &self.file {
@@ -1,17 +1,37 @@
 //! Visualization controller.
 //!
 //! Ths Visualization Controller is Responsible identifying all the available visualizations
-//! natively embedded in IDE and available within the project's `visualization` folder.
+//! natively embedded in IDE and available within the `visualization` folders of the project and
+//! of the libraries it imports. The folders are watched, so the visualizations are reloaded when
+//! their files change.
 
 use crate::prelude::*;
 
+use crate::config::PACKAGE_DEFINITION_FILE;
 use crate::config::PROJECT_VISUALIZATION_FOLDER;
+use crate::constants::LANGUAGE_FILE_EXTENSION;
+use crate::constants::SOURCE_DIRECTORY;
+use crate::notification;
 
 use enso_protocol::language_server;
-use graph_editor::data;
+use enso_protocol::language_server::CapabilityRegistration;
+use enso_protocol::language_server::FileSystemObject;
+use flo_stream::Subscriber;
+use graph_editor::data::LibraryName;
 use graph_editor::component::visualization::definition;
 use graph_editor::component::visualization;
+use parser::Parser;
 use std::rc::Rc;
+use uuid::Uuid;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The extension of the files defining the JavaScript visualizations.
+const JAVA_SCRIPT_EXTENSION:&str = "js";
 
 
 
@@ -27,9 +47,11 @@ pub enum VisualizationError {
     NotFound {
         identifier : VisualizationPath
     },
-    #[fail(display = "JavaScript visualization \"{}\" failed to be instantiated.", identifier)]
+    #[fail(display = "JavaScript visualization {} failed to be instantiated: {}", identifier,
+        reason)]
     InstantiationError {
-        identifier : VisualizationPath
+        identifier : VisualizationPath,
+        reason     : String,
     }
 }
 
@@ -39,13 +61,103 @@ pub enum VisualizationError {
 // === VisualizationPath ===
 // =========================
 
-/// This enum is used to provide a path to visualization either in the project folder or natively
-/// embedded in IDE.
-#[derive(Clone,Debug,Display,Eq,PartialEq)]
+/// This enum is used to provide a path to visualization either in the `visualization` folder of
+/// some library or natively embedded in IDE.
+#[derive(Clone,Debug,Eq,Hash,PartialEq)]
 #[allow(missing_docs)]
 pub enum VisualizationPath {
     Embedded(String),
-    File(language_server::Path)
+    File {
+        library : LibraryName,
+        path    : language_server::Path,
+    }
+}
+
+impl Display for VisualizationPath {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Embedded(name)     => write!(f,"\"{}\"",name),
+            Self::File{library,path} => write!(f,"\"{}\" of library {}",path,library.deref()),
+        }
+    }
+}
+
+
+
+// ===============
+// === Library ===
+// ===============
+
+/// A library providing visualizations. The project is the library too.
+#[derive(Clone,Debug,Eq,PartialEq)]
+#[allow(missing_docs)]
+pub struct Library {
+    pub name                 : LibraryName,
+    pub visualization_folder : language_server::Path,
+}
+
+impl Library {
+    /// Constructor. The visualizations are looked for in the `visualization` folder in the given
+    /// content root.
+    pub fn new(name:impl Into<LibraryName>, root_id:Uuid) -> Self {
+        let name                 = name.into();
+        let visualization_folder = language_server::Path::new(root_id,&[
+            PROJECT_VISUALIZATION_FOLDER
+        ]);
+        Self {name,visualization_folder}
+    }
+
+    /// Check if the file can define a visualization of this library.
+    pub fn contains_visualization_file(&self, path:&language_server::Path) -> bool {
+        let in_folder = path.parent().as_ref() == Some(&self.visualization_folder);
+        in_folder && path.extension() == Some(JAVA_SCRIPT_EXTENSION)
+    }
+
+    fn visualization_path(&self, path:language_server::Path) -> VisualizationPath {
+        let library = self.name.clone_ref();
+        VisualizationPath::File {library,path}
+    }
+}
+
+/// Read the library name from the package definition file contents: the value of the top-level
+/// `name` field, optionally quoted and followed by a comment. Returns `None` if there is no such
+/// field or it is empty.
+///
+/// Only this field is used by IDE, so the YAML document is not parsed as a whole.
+pub fn package_name(package_definition:&str) -> Option<LibraryName> {
+    const FIELD:&str = "name:";
+    let mut fields = package_definition.lines();
+    let field      = fields.find(|line| line.starts_with(FIELD))?;
+    let value      = field[FIELD.len()..].split(" #").next().unwrap_or_default().trim();
+    let quoted_in  = |quote:char| value.starts_with(quote) && value.ends_with(quote);
+    let quoted     = value.len() >= 2 && (quoted_in('"') || quoted_in('\''));
+    let name       = if quoted { value[1..value.len()-1].trim() } else { value };
+    (!name.is_empty()).as_some_from(|| name.into())
+}
+
+/// The names of the libraries imported by the module of the given code: the first segments of the
+/// imported paths. The module's id map and metadata are not needed, so they are read leniently.
+pub fn imported_libraries
+(logger:&Logger, parser:&Parser, module_code:String) -> FallibleResult<Vec<String>> {
+    let source = model::synchronized::Module::parse_leniently(logger,parser,module_code)?;
+    let lines  = source.ast.lines.iter().filter_map(|line| line.elem.as_ref());
+    let paths  = lines.filter_map(ast::macros::as_import);
+    Ok(paths.filter_map(|path| path.into_iter().next()).collect())
+}
+
+
+
+// ====================
+// === Notification ===
+// ====================
+
+/// A notification about changes of the visualizations defined in files.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub enum Notification {
+    /// The visualization file was added or modified, so it should be (re)loaded.
+    Changed(VisualizationPath),
+    /// The visualization file was removed.
+    Removed(VisualizationPath),
 }
 
 
@@ -72,35 +184,152 @@ pub struct EmbeddedVisualizations {
 // ==============
 
 /// Visualization Controller is responsible for listing and loading all the available
-/// visualizations on the project and the native ones embedded on IDE.
+/// visualizations of the project, the libraries it uses, and the native ones embedded on IDE.
 #[derive(Debug,Clone,CloneRef)]
 pub struct Handle {
+    logger                  : Logger,
     language_server_rpc     : Rc<language_server::Connection>,
-    embedded_visualizations : Rc<RefCell<EmbeddedVisualizations>>
+    embedded_visualizations : Rc<RefCell<EmbeddedVisualizations>>,
+    project_name            : LibraryName,
+    parser                  : Parser,
+    libraries               : Rc<RefCell<Vec<Library>>>,
+    notifications           : Rc<RefCell<notification::Publisher<Notification>>>,
 }
 
 impl Handle {
     /// Creates a new visualization controller.
     pub fn new
-    ( language_server_rpc     : Rc<language_server::Connection>
-    , embedded_visualizations : EmbeddedVisualizations) -> Self {
+    ( parent                  : impl AnyLogger
+    , language_server_rpc     : Rc<language_server::Connection>
+    , embedded_visualizations : EmbeddedVisualizations
+    , project_name            : impl Into<LibraryName>
+    , parser                  : Parser
+    ) -> Self {
+        let logger                  = Logger::sub(parent,"Visualization Controller");
         let embedded_visualizations = Rc::new(RefCell::new(embedded_visualizations));
-        Self {language_server_rpc,embedded_visualizations}
+        let project_name            = project_name.into();
+        let libraries               = default();
+        let notifications           = default();
+        Self {logger,language_server_rpc,embedded_visualizations,project_name,parser,libraries
+             ,notifications}
+    }
+
+    /// Subscribe for notifications about changes of the visualization files.
+    pub fn subscribe(&self) -> Subscriber<Notification> {
+        self.notifications.borrow_mut().subscribe()
+    }
+
+    /// Find the libraries providing visualizations: the project and the libraries imported by its
+    /// modules. The content roots are recognized by the names in their package definition files,
+    /// the ones without package definition are skipped. The project modules are scanned for
+    /// imports only if some other library has a visualization folder.
+    async fn find_libraries(&self) -> FallibleResult<Vec<Library>> {
+        let packages     = self.find_packages().await?;
+        let project_name = &self.project_name;
+        let project      = packages.iter().find(|library| &library.name == project_name).cloned();
+        let libraries    = match project {
+            Some(project) => {
+                let mut candidates = Vec::new();
+                for library in packages {
+                    let path = &library.visualization_folder;
+                    if &library.name != project_name
+                    && self.language_server_rpc.file_exists(path).await?.exists {
+                        candidates.push(library);
+                    }
+                }
+                let wanted : HashSet<String> = candidates.iter().map(|library| {
+                    library.name.deref().to_string()
+                }).collect();
+                let root_id  = project.visualization_folder.root_id;
+                let imports  = if wanted.is_empty() { default() } else {
+                    self.project_imports(root_id,&wanted).await?
+                };
+                let imported = candidates.into_iter().filter(|library| {
+                    imports.contains(library.name.deref())
+                });
+                std::iter::once(project).chain(imported).collect()
+            }
+            None => {
+                let project_name = project_name.deref();
+                warning!(self.logger,"No content root contains the project {project_name}, the \
+                    visualizations of the project and its libraries will not be loaded.");
+                default()
+            }
+        };
+        *self.libraries.borrow_mut() = libraries.clone();
+        Ok(libraries)
+    }
+
+    /// The libraries of all content roots with a package definition file.
+    async fn find_packages(&self) -> FallibleResult<Vec<Library>> {
+        let content_roots = self.language_server_rpc.content_roots().clone();
+        let mut libraries = Vec::new();
+        for root_id in content_roots {
+            let package = language_server::Path::new(root_id,&[PACKAGE_DEFINITION_FILE]);
+            if !self.language_server_rpc.file_exists(&package).await?.exists {
+                continue;
+            }
+            let contents = self.language_server_rpc.read_file(&package).await?.contents;
+            match package_name(&contents) {
+                Some(name) => libraries.push(Library::new(name,root_id)),
+                None       => warning!(self.logger,"Package definition {package} has no valid \
+                    name, its visualizations will not be loaded."),
+            }
+        }
+        Ok(libraries)
     }
 
-    async fn list_project_specific_visualizations
-    (&self) -> FallibleResult<Vec<VisualizationPath>> {
-        let root_id   = self.language_server_rpc.content_root();
-        let path      = language_server::Path::new(root_id,&[PROJECT_VISUALIZATION_FOLDER]);
-        let folder    = self.language_server_rpc.file_exists(&path).await?;
+    /// The names of the `wanted` libraries imported by the modules in the given project's content
+    /// root. The scan stops as soon as all of them are found.
+    async fn project_imports
+    (&self, root_id:Uuid, wanted:&HashSet<String>) -> FallibleResult<HashSet<String>> {
+        let mut imports = HashSet::new();
+        let sources     = language_server::Path::new(root_id,&[SOURCE_DIRECTORY]);
+        if !self.language_server_rpc.file_exists(&sources).await?.exists {
+            return Ok(imports)
+        }
+        let mut folders = vec![sources];
+        while let Some(folder) = folders.pop() {
+            for object in self.language_server_rpc.file_list(&folder).await?.paths {
+                if imports.len() == wanted.len() {
+                    return Ok(imports)
+                }
+                let path      = language_server::Path::from(&object);
+                let is_module = path.extension() == Some(LANGUAGE_FILE_EXTENSION);
+                match object {
+                    FileSystemObject::Directory {..}         => folders.push(path),
+                    FileSystemObject::File {..} if is_module => {
+                        let code = self.language_server_rpc.read_file(&path).await?.contents;
+                        match imported_libraries(&self.logger,&self.parser,code) {
+                            Ok(libraries) => imports.extend(libraries.into_iter().filter(|name| {
+                                wanted.contains(name)
+                            })),
+                            Err(err) => warning!(self.logger,"Failed to read the imports of \
+                                {path}: {err}"),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(imports)
+    }
+
+    async fn list_library_visualizations
+    (&self, library:&Library) -> FallibleResult<Vec<VisualizationPath>> {
+        let path      = &library.visualization_folder;
+        let folder    = self.language_server_rpc.file_exists(path).await?;
         let file_list = if folder.exists {
-            self.language_server_rpc.file_list(&path).await?.paths
+            self.language_server_rpc.file_list(path).await?.paths
         } else {
             default()
         };
         let result = file_list.iter().filter_map(|object| {
             if let language_server::FileSystemObject::File{..} = object {
-                Some(VisualizationPath::File(object.into()))
+                let path = language_server::Path::from(object);
+                library.contains_visualization_file(&path).as_some_from(|| {
+                    library.visualization_path(path)
+                })
             } else {
                 None
             }
@@ -118,10 +347,50 @@ impl Handle {
     /// Get a list of all available visualizations.
     pub async fn list_visualizations(&self) -> FallibleResult<Vec<VisualizationPath>> {
         let mut visualizations = self.list_embedded_visualizations();
-        visualizations.extend_from_slice(&self.list_project_specific_visualizations().await?);
+        for library in self.find_libraries().await? {
+            visualizations.extend(self.list_library_visualizations(&library).await?);
+        }
         Ok(visualizations)
     }
 
+    /// Start watching the `visualization` folders of the libraries found by the last
+    /// `list_visualizations` call. The changes of their files are reported by `subscribe` stream
+    /// once handled by `handle_file_event`.
+    pub async fn watch_visualization_folders(&self) -> FallibleResult<()> {
+        let libraries = self.libraries.borrow().clone();
+        for library in libraries {
+            let path = library.visualization_folder;
+            if self.language_server_rpc.file_exists(&path).await?.exists {
+                let capability = CapabilityRegistration::create_receives_tree_updates(path);
+                let method     = &capability.method;
+                let options    = &capability.register_options;
+                self.language_server_rpc.acquire_capability(method,options).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle the file system event reported by the Language Server. If it concerns some
+    /// visualization file, the subscribers are notified.
+    pub fn handle_file_event(&self, event:&language_server::FileEvent) {
+        let libraries = self.libraries.borrow();
+        let library   = libraries.iter().find(|lib| lib.contains_visualization_file(&event.path));
+        if let Some(library) = library {
+            let identifier   = library.visualization_path(event.path.clone());
+            let notification = match event.kind {
+                language_server::FileEventKind::Added    => Notification::Changed(identifier),
+                language_server::FileEventKind::Modified => Notification::Changed(identifier),
+                language_server::FileEventKind::Removed  => Notification::Removed(identifier),
+            };
+            self.notify(notification);
+        }
+    }
+
+    fn notify(&self, notification:Notification) {
+        let notify = self.notifications.borrow_mut().publish(notification);
+        executor::global::spawn(notify);
+    }
+
     /// Load the source code of the specified visualization.
     pub async fn load_visualization
     (&self, visualization:&VisualizationPath) -> FallibleResult<definition::Definition> {
@@ -133,14 +402,17 @@ impl Handle {
                 let error                   = || VisualizationError::NotFound{identifier}.into();
                 result.cloned().ok_or_else(error)
             },
-            VisualizationPath::File(path) => {
-                let js_code    = self.language_server_rpc.read_file(&path).await?.contents;
-                let identifier = visualization.clone();
-                let error      = |_| VisualizationError::InstantiationError {identifier}.into();
-                let module     = data::builtin_library(); // FIXME: provide real library name.
-                // TODO: this is wrong. This is translated to InstantiationError and it is preparation error :
-                let js_class   = visualization::java_script::Definition::new(module,&js_code).map_err(error);
-                js_class.map(|t| t.into())
+            VisualizationPath::File{library,path} => {
+                let error = |reason:String| {
+                    let identifier = visualization.clone();
+                    failure::Error::from(VisualizationError::InstantiationError {identifier,reason})
+                };
+                let read     = self.language_server_rpc.read_file(&path).await;
+                let js_code  = read.map_err(|err| error(err.to_string()))?.contents;
+                let library  = library.clone_ref();
+                let js_class = visualization::java_script::Definition::new(library,&js_code);
+                let js_class = js_class.map_err(|err| error(err.to_string()))?;
+                Ok(js_class.into())
             }
         }
     }
@@ -156,6 +428,10 @@ impl Handle {
 mod tests {
     use super::*;
 
+    use crate::executor::test_utils::TestWithLocalPoolExecutor;
+
+    use enso_protocol::language_server::FileEvent;
+    use enso_protocol::language_server::FileEventKind;
     use enso_protocol::language_server::FileSystemObject;
     use enso_protocol::language_server::Path;
     use graph_editor::builtin;
@@ -167,18 +443,74 @@ mod tests {
 
     wasm_bindgen_test_configure!(run_in_browser);
 
+    const PROJECT_NAME:&str = "TestProject";
+
+    /// Expect the calls reading the package definition of the project in the default content
+    /// root. There are no other libraries, so the project sources are not scanned for imports.
+    fn expect_project_package(mock_client:&language_server::MockClient) {
+        let package  = Path::new(default(),&[PACKAGE_DEFINITION_FILE]);
+        let exists   = language_server::response::FileExists{exists:true};
+        let contents = format!("name: {}\nversion: 0.0.1\n",PROJECT_NAME);
+        let read     = language_server::response::Read{contents};
+        expect_call!(mock_client.file_exists(path=package.clone()) => Ok(exists));
+        expect_call!(mock_client.read_file(path=package)           => Ok(read));
+    }
+
+    #[test]
+    fn reading_package_name() {
+        let package = "name: Base\nversion: 0.0.1\nlicense: APLv2\n";
+        assert_eq!(package_name(package).as_deref(),Some("Base"));
+        let package = "version: 0.0.1\nname: \"Table\"\n";
+        assert_eq!(package_name(package).as_deref(),Some("Table"));
+        assert_eq!(package_name("version: 0.0.1\n  name: Nested\n"),None);
+        assert_eq!(package_name("name:\n"),None);
+        assert_eq!(package_name("name: Base # The standard library.\n").as_deref(),Some("Base"));
+        assert_eq!(package_name("dependencies:\n  - name: Base\n"),None);
+        assert_eq!(package_name("name: 'Geo'\n").as_deref(),Some("Geo"));
+    }
+
+    #[wasm_bindgen_test]
+    fn reading_imported_libraries() {
+        let parser = Parser::new_or_panic();
+        let code   = "import Base.List\nimport Table\n\nmain =\n    import_count = 2\n";
+        let logger = Logger::new("Test");
+        let result = imported_libraries(&logger,&parser,code.to_string()).unwrap();
+        assert_eq!(result,vec!["Base".to_string(),"Table".to_string()]);
+
+        let code   = "import Base\n\n\n#### METADATA ####\n[broken id map\n{}";
+        let result = imported_libraries(&logger,&parser,code.to_string()).unwrap();
+        assert_eq!(result,vec!["Base".to_string()]);
+    }
+
+    #[test]
+    fn recognizing_visualization_files() {
+        let root_id = default();
+        let library = Library::new("Base",root_id);
+        let file    = |segments:&[&str]| Path::new(root_id,segments);
+        assert!( library.contains_visualization_file(&file(&["visualization","chart.js"])));
+        assert!(!library.contains_visualization_file(&file(&["visualization","notes.txt"])));
+        assert!(!library.contains_visualization_file(&file(&["visualization","a","chart.js"])));
+        assert!(!library.contains_visualization_file(&file(&["src","chart.js"])));
+        assert!(!library.contains_visualization_file(&Path::new(Uuid::new_v4(),&[
+            "visualization","chart.js"
+        ])));
+    }
+
     #[wasm_bindgen_test(async)]
     async fn list_and_load() {
         let mock_client = language_server::MockClient::default();
+        expect_project_package(&mock_client);
 
         let root_id = uuid::Uuid::default();
         let path  = Path::new(root_id,&["visualization"]);
         let path0 = Path::new(root_id,&["visualization","histogram.js"]);
         let path1 = Path::new(root_id,&["visualization","graph.js"]);
+        let path2 = Path::new(root_id,&["visualization","README.md"]);
 
         let paths   = vec![
             FileSystemObject::new_file(path0.clone()).unwrap(),
             FileSystemObject::new_file(path1.clone()).unwrap(),
+            FileSystemObject::new_file(path2).unwrap(),
         ];
         let file_list_result = language_server::response::FileList{paths};
         expect_call!(mock_client.file_list(path=path.clone()) => Ok(file_list_result));
@@ -212,18 +544,20 @@ mod tests {
         let mut embedded_visualizations = EmbeddedVisualizations::default();
         let embedded_visualization      = builtin::visualization::native::BubbleChart::definition();
         embedded_visualizations.insert("[Demo] Bubble Visualization".to_string(), embedded_visualization.clone());
-        let vis_controller              = Handle::new(language_server,embedded_visualizations);
+        let vis_controller              = Handle::new(Logger::default(),language_server
+            ,embedded_visualizations,PROJECT_NAME,Parser::new_or_panic());
 
         let visualizations = vis_controller.list_visualizations().await;
         let visualizations = visualizations.expect("Couldn't list visualizations.");
 
         assert_eq!(visualizations[0], VisualizationPath::Embedded("[Demo] Bubble Visualization".to_string()));
-        assert_eq!(visualizations[1], VisualizationPath::File(path0));
-        assert_eq!(visualizations[2], VisualizationPath::File(path1));
+        let library = LibraryName::from(PROJECT_NAME);
+        assert_eq!(visualizations[1], VisualizationPath::File{library:library.clone(),path:path0});
+        assert_eq!(visualizations[2], VisualizationPath::File{library,path:path1});
         assert_eq!(visualizations.len(),3);
 
-        let javascript_vis0 = visualization::java_script::Definition::new("builtin", &file_content0);
-        let javascript_vis1 = visualization::java_script::Definition::new("builtin", &file_content1);
+        let javascript_vis0 = visualization::java_script::Definition::new(PROJECT_NAME, &file_content0);
+        let javascript_vis1 = visualization::java_script::Definition::new(PROJECT_NAME, &file_content1);
         let javascript_vis0 = javascript_vis0.expect("Couldn't create visualization class.");
         let javascript_vis1 = javascript_vis1.expect("Couldn't create visualization class.");
         let javascript_vis0:visualization::Definition = javascript_vis0.into();
@@ -239,4 +573,42 @@ mod tests {
             assert_eq!(loaded_signature,expected_signature);
         }
     }
+
+    #[wasm_bindgen_test]
+    fn notifying_about_file_changes() {
+        let mut test = TestWithLocalPoolExecutor::set_up();
+        test.run_task(async move {
+            let mock_client = language_server::MockClient::default();
+            expect_project_package(&mock_client);
+            let folder      = Path::new(default(),&["visualization"]);
+            let exists      = || language_server::response::FileExists{exists:true};
+            let file_list   = language_server::response::FileList{paths:default()};
+            expect_call!(mock_client.file_exists(path=folder.clone()) => Ok(exists()));
+            expect_call!(mock_client.file_list(path=folder.clone())   => Ok(file_list));
+            expect_call!(mock_client.file_exists(path=folder.clone()) => Ok(exists()));
+            let capability       = CapabilityRegistration::create_receives_tree_updates(folder.clone());
+            let method           = capability.method;
+            let register_options = capability.register_options;
+            expect_call!(mock_client.acquire_capability(method,register_options) => Ok(()));
+
+            let language_server = language_server::Connection::new_mock_rc(mock_client);
+            let vis_controller  = Handle::new(Logger::default(),language_server,default()
+                ,PROJECT_NAME,Parser::new_or_panic());
+            vis_controller.list_visualizations().await.unwrap();
+            vis_controller.watch_visualization_folders().await.unwrap();
+
+            let mut notifications = vis_controller.subscribe();
+            let file              = folder.append_im("chart.js");
+            let event = |path:&Path, kind| FileEvent {path:path.clone(),kind};
+            vis_controller.handle_file_event(&event(&file,FileEventKind::Added));
+            vis_controller.handle_file_event(&event(&folder.append_im("notes.txt"),FileEventKind::Added));
+            vis_controller.handle_file_event(&event(&file,FileEventKind::Removed));
+
+            let library    = PROJECT_NAME.into();
+            let identifier = VisualizationPath::File {library,path:file};
+            let changed    = Notification::Changed(identifier.clone());
+            assert_eq!(notifications.next().await,Some(changed));
+            assert_eq!(notifications.next().await,Some(Notification::Removed(identifier)));
+        });
+    }
 }
@@ -90,7 +90,11 @@ impl GraphInfo {
             LocationHint::Start      => 0,
             LocationHint::End        => lines.len(),
             LocationHint::After(id)  => node::index_in_lines(&lines, id)? + 1,
-            LocationHint::Before(id) => node::index_in_lines(&lines, id)?
+            LocationHint::Before(id) => {
+                // The new line must not separate the node from its documentation.
+                let index = node::index_in_lines(&lines, id)?;
+                node::documentation_range(&lines,index).start
+            }
         };
         let elem = Some(line_ast);
         let off  = 0;
@@ -109,7 +113,25 @@ impl GraphInfo {
         Ast::cons("Nothing").with_new_id()
     }
 
-    /// Removes the node from graph.
+    /// Gets the documentation of the node, i.e. the text of comment lines directly above it.
+    pub fn node_documentation(&self, id:ast::Id) -> FallibleResult<Option<String>> {
+        let lines = self.source.block_lines()?;
+        let index = node::index_in_lines(&lines,id)?;
+        Ok(node::documentation_in_lines(&lines,index))
+    }
+
+    /// Replaces the documentation of the node. Passing `None` or an empty text removes it.
+    pub fn set_node_documentation
+    (&mut self, id:ast::Id, documentation:Option<&str>) -> FallibleResult<()> {
+        let mut lines = self.source.block_lines()?;
+        let index     = node::index_in_lines(&lines,id)?;
+        let range     = node::documentation_range(&lines,index);
+        let comments  = documentation.map(node::documentation_lines).unwrap_or_default();
+        lines.splice(range,comments);
+        self.source.set_block_lines(lines)
+    }
+
+    /// Removes the node from graph, together with its documentation.
     pub fn remove_node(&mut self, node_id:ast::Id) -> FallibleResult<()> {
         self.update_node(node_id, |_| None)
     }
@@ -133,7 +155,8 @@ impl GraphInfo {
             if let Some(updated_node) = f(node_info) {
                 lines[index].elem = Some(updated_node.ast().clone_ref());
             } else {
-                lines.remove(index);
+                let documentation = node::documentation_range(&lines,index);
+                lines.drain(documentation.start..=index);
             }
             if lines.is_empty() {
                 self.source.set_body_ast(Self::empty_graph_body());
@@ -381,6 +404,36 @@ main =
         graph.expect_code(expected_code);
     }
 
+    #[wasm_bindgen_test]
+    fn documenting_nodes_in_graph() {
+        let parser  = parser::Parser::new_or_panic();
+        let program = r"
+main =
+    # Adds numbers.
+    foo = 2 + 2
+    bar = 3 + 17";
+        let mut graph = main_graph(&parser, program);
+        let (foo,bar) = graph.nodes().expect_tuple();
+        let foo_doc   = graph.node_documentation(foo.id()).unwrap();
+        assert_eq!(foo_doc, Some("Adds numbers.".to_string()));
+        assert_eq!(graph.node_documentation(bar.id()).unwrap(), None);
+
+        graph.set_node_documentation(bar.id(),Some("Sums up.\nUsed twice.")).unwrap();
+        let (line_ast,_) = create_node_ast(&parser, "a + b");
+        graph.add_node(line_ast,LocationHint::Before(bar.id())).unwrap();
+        graph.expect_code(r"main =
+    # Adds numbers.
+    foo = 2 + 2
+    a + b
+    # Sums up.
+    # Used twice.
+    bar = 3 + 17");
+
+        graph.set_node_documentation(bar.id(),None).unwrap();
+        graph.remove_node(foo.id()).unwrap();
+        graph.expect_code("main =\n    a + b\n    bar = 3 + 17");
+    }
+
     #[wasm_bindgen_test]
     fn removing_last_node_from_graph() {
         let mut parser = parser::Parser::new_or_panic();
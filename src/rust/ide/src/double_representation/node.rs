@@ -69,9 +69,12 @@ impl NodeInfo {
         Some(NodeInfo::Expression {ast})
     }
 
-    /// Tries to interpret AST as node, treating whole AST as an expression.
+    /// Tries to interpret AST as node, treating whole AST as an expression. Comments are never
+    /// nodes.
     pub fn from_line_ast(ast:&Ast) -> Option<NodeInfo> {
-        if let Some(infix) = ast::opr::to_assignment(ast) {
+        if ast::macros::is_comment(ast) {
+            None
+        } else if let Some(infix) = ast::opr::to_assignment(ast) {
             Self::new_binding(infix)
         } else {
             Self::new_expression(ast.clone())
@@ -173,6 +176,43 @@ impl ast::HasTokens for NodeInfo {
 
 
 
+// =====================
+// === Documentation ===
+// =====================
+
+// The node's documentation are the comment lines directly above the node's line. Each comment line
+// holds a single line of the documentation text.
+
+/// Range of the documentation comment lines of the node in the line with given index.
+pub fn documentation_range(lines:&[ast::BlockLine<Option<Ast>>], index:usize) -> Range<usize> {
+    let is_comment = |line:&&ast::BlockLine<Option<Ast>>| {
+        line.elem.as_ref().contains_if(|ast| ast::macros::is_comment(ast))
+    };
+    let count = lines[..index].iter().rev().take_while(is_comment).count();
+    index - count .. index
+}
+
+/// Documentation of the node in the line with given index. Returns `None` if there are no comment
+/// lines directly above the node.
+pub fn documentation_in_lines
+(lines:&[ast::BlockLine<Option<Ast>>], index:usize) -> Option<String> {
+    let range    = documentation_range(lines,index);
+    let comments = lines[range].iter().filter_map(|line| line.elem.as_ref());
+    let text     = comments.filter_map(ast::macros::as_comment).join("\n");
+    (!text.is_empty()).as_some(text)
+}
+
+/// Comment lines storing the given documentation text.
+pub fn documentation_lines(documentation:&str) -> Vec<ast::BlockLine<Option<Ast>>> {
+    documentation.lines().map(|line| {
+        let elem = Some(ast::macros::comment(line.trim_end()).into());
+        let off  = 0;
+        ast::BlockLine {elem,off}
+    }).collect()
+}
+
+
+
 // =============
 // === Tests ===
 // =============
@@ -248,6 +288,22 @@ mod tests {
         assert_eq!(node.id(),id);
     }
 
+    #[test]
+    fn reading_and_writing_documentation() {
+        let node  = ast::BlockLine {elem:Some(Ast::var("foo").with_new_id()),off:0};
+        let empty = ast::BlockLine {elem:None,off:0};
+        let mut lines = documentation_lines("Old comment.");
+        lines.push(empty);
+        lines.extend(documentation_lines("Sums the inputs.\nUsed by the report."));
+        lines.push(node);
+        assert!(NodeInfo::from_block_line(&lines[0]).is_none());
+        assert_eq!(documentation_range(&lines,4), 2..4);
+        let expected = Some("Sums the inputs.\nUsed by the report.".to_string());
+        assert_eq!(documentation_in_lines(&lines,4), expected);
+        assert_eq!(documentation_in_lines(&lines,2), None);
+        assert_eq!(lines[3].elem.repr(), "# Used by the report.");
+    }
+
     #[test]
     fn setting_pattern_on_binding_node_test() {
         let id       = uuid::Uuid::new_v4();
@@ -2,9 +2,16 @@
 
 use crate::prelude::*;
 
+use ast::HasLength;
+use ast::HasRepr;
 use ast::IdMap;
+use ast::known;
+use data::text::Index;
 use data::text::Size;
 use data::text::Span;
+use data::text::TextChange;
+use parser::Parser;
+use std::ops::RangeInclusive;
 
 
 
@@ -12,7 +19,8 @@ use data::text::Span;
 // === Text API ===
 // ================
 
-/// Update IdMap to reflect the recent code change.
+/// Update IdMap to reflect the recent code change. The spans of the id map and the indices of the
+/// change are character indices.
 pub fn apply_code_change_to_id_map(id_map:&mut IdMap, change:&data::text::TextChange, code:&str) {
     // TODO [mwu]
     //   The initial provisional algorithm received some changes to better behave in our typical
@@ -38,23 +46,21 @@ pub fn apply_code_change_to_id_map(id_map:&mut IdMap, change:&data::text::TextCh
     // Remove all entries fully covered by the removed span.
     vector.drain_filter(|(span,_)| removed.contains_span(&span));
 
-    // If the edited section ends up being the trailing part of AST node, how many bytes should be
-    // trimmed from the id. Precalculated, as is constant in the loop below.
-    let to_trim_back = {
-        let last_non_white           = inserted.rfind(non_white);
-        let inserted_len             = || inserted.len();
-        let length_to_last_non_white = |index| inserted.len() - index - 1;
-        Size::new(last_non_white.map_or_else(inserted_len,length_to_last_non_white))
-    };
+    // If the edited section ends up being the trailing part of AST node, how many characters
+    // should be trimmed from the id. Precalculated, as is constant in the loop below.
+    let to_trim_back  = Size::new(inserted.chars().rev().take_while(|c| !non_white(*c)).count());
     // As above but for the front side.
-    let to_trim_front = {
-        let first_non_white = inserted.find(non_white);
-        let ret             = first_non_white.unwrap_or_else(|| inserted.len());
-        Size::new(ret)
-    };
+    let to_trim_front = Size::new(inserted.chars().take_while(|c| !non_white(*c)).count());
 
     let inserted_non_white = inserted.chars().any(non_white);
 
+    // The numbers of spaces directly before and after the removed span. Slicing the code by
+    // characters is linear, so they are precalculated instead of checking the code between every
+    // AST node and the edited region in the loop below.
+    let removed_bytes = removed.byte_range_in(code);
+    let spaces_before = spaces_size(code[..removed_bytes.start].chars().rev());
+    let spaces_after  = spaces_size(code[removed_bytes.end..].chars());
+
     // In case of collisions (when, after resizing spans, multiple ids for the same span are
     // present), the mappings from this map will be preferred over other ones.
     //
@@ -72,17 +78,17 @@ pub fn apply_code_change_to_id_map(id_map:&mut IdMap, change:&data::text::TextCh
         if span.index > removed.end() {
             debug!(logger,"Node after the edited region.");
             // AST node starts after edited region — it will be simply shifted.
-            let code_between = &code[Span::from(removed.end() .. span.index)];
+            let between = span.index - removed.end();
             span.move_left(removed.size);
             span.move_right(inserted_size);
 
             // If there are only spaces between current AST symbol and insertion, extend the symbol.
             // This is for cases like line with `foo ` being changed into `foo j`.
-            debug!(logger,"Between: `{code_between}`.");
-            if all_spaces(code_between) && inserted_non_white {
+            debug!(logger,"Between: {between} characters.");
+            if between <= spaces_after && inserted_non_white {
                 debug!(logger,"Will extend the node leftwards.");
                 span.extend_left(inserted_size);
-                span.extend_left(Size::from(code_between));
+                span.extend_left(between);
                 trim_front = true;
             }
         } else if span.index >= removed.index {
@@ -106,23 +112,23 @@ pub fn apply_code_change_to_id_map(id_map:&mut IdMap, change:&data::text::TextCh
             debug!(logger,"Node before the edited region.");
             // If there are only spaces between current AST symbol and insertion, extend the symbol.
             // This is for cases like line with `foo ` being changed into `foo j`.
-            let between = &code[Span::from(span.end() .. removed.index)];
-            if all_spaces(between) && inserted_non_white {
+            let between = removed.index - span.end();
+            if between <= spaces_before && inserted_non_white {
                 debug!(logger,"Will extend ");
-                span.size += Size::from(between) + inserted_size;
+                span.size += between + inserted_size;
                 trim_back = true;
             }
         }
 
         if trim_front && to_trim_front.non_empty() {
             span.shrink_left(to_trim_front);
-            debug!(logger,"Trimming front {to_trim_front} bytes.");
+            debug!(logger,"Trimming front {to_trim_front} characters.");
         }
 
         if trim_back {
             if to_trim_back.non_empty() {
                 span.shrink_right(to_trim_back);
-                debug!(logger, "Trimming back {to_trim_back} bytes.");
+                debug!(logger, "Trimming back {to_trim_back} characters.");
             }
             let new_repr = &new_code[*span];
             // Trim trailing spaces
@@ -152,18 +158,120 @@ pub fn apply_code_change_to_id_map(id_map:&mut IdMap, change:&data::text::TextCh
 
 
 
+// =========================
+// === Incremental Parse ===
+// =========================
+
+/// Applies the text change to the module, reparsing only the top-level lines it touches.
+///
+/// The touched lines are replaced with the lines obtained from parsing their new code, while all
+/// the other lines are reused as they are, keeping their ids. The `new_id_map` should describe
+/// the code after the change (see `apply_code_change_to_id_map`); only its entries lying within
+/// the reparsed fragment are used.
+///
+/// The line following the touched ones is parsed together with them, as the change might make it
+/// a part of the last touched line (e.g. by opening a text block). If its structure is different
+/// than before, or if the change cannot be localized, the whole module is parsed again.
+pub fn reparse_changed_lines
+(parser:&Parser, module:&known::Module, change:&TextChange, new_id_map:IdMap)
+-> FallibleResult<known::Module> {
+    let code        = module.repr();
+    let new_code    = change.applied(&code);
+    let spans       = module_line_spans(module);
+    let reparse_all = |code:String,id_map:IdMap| -> FallibleResult<known::Module> {
+        Ok(parser.parse_module(code,id_map)?)
+    };
+    let touched     = match lines_touched_by(module,&spans,change) {
+        Some(touched) => touched,
+        None          => return reparse_all(new_code,new_id_map),
+    };
+
+    let (first,last) = (*touched.start(),*touched.end());
+    let next_line    = module.lines.get(last+1);
+    let mut fragment = Span::from_indices(spans[first].index,spans[last].end());
+    fragment.shrink_right(change.replaced_size());
+    fragment.extend_right(Size::from(change.inserted.as_str()));
+    if let Some(next_span) = spans.get(last+1) {
+        fragment.extend_right(Size::new(1) + next_span.size);
+    }
+    let fragment_offset = Size::new(fragment.index.value);
+    let fragment_ids    = new_id_map.vec.iter().filter(|(span,_)| fragment.contains_span(span));
+    let fragment_ids    = fragment_ids.map(|(span,id)| {
+        let mut span = *span;
+        span.move_left(fragment_offset);
+        (span,*id)
+    }).collect();
+    let fragment_code  = &new_code[fragment];
+    let joins_previous = first > 0 && fragment_code.starts_with(' ');
+    let reparsed       = parser.parse_module(fragment_code,IdMap::new(fragment_ids))?;
+    let mut new_lines  = reparsed.lines.clone();
+    let next_line_kept = match next_line {
+        None            => true,
+        Some(next_line) => new_lines.pop().contains_if(|new_line| {
+            let same_off       = new_line.off == next_line.off;
+            let same_structure = match (&new_line.elem,&next_line.elem) {
+                (Some(new),Some(old)) => ast::diff::same_structure(new,old),
+                (None,None)           => true,
+                _                     => false,
+            };
+            same_off && same_structure
+        }),
+    };
+    if joins_previous || !next_line_kept {
+        return reparse_all(new_code,new_id_map)
+    }
+
+    let mut result = module.clone();
+    result.update_shape(|shape| {
+        let mut lines = shape.lines[..first].to_vec();
+        lines.extend(new_lines);
+        lines.extend_from_slice(&shape.lines[last+1..]);
+        shape.lines = lines;
+    });
+    Ok(result)
+}
+
+/// Spans of the module's top-level lines. A span does not include the newline character
+/// separating the line from the next one.
+fn module_line_spans(module:&known::Module) -> Vec<Span> {
+    let newline   = Size::new(1);
+    let mut index = Index::new(0);
+    module.lines.iter().map(|line| {
+        let size = Size::new(line.elem.as_ref().map_or(0, |elem| elem.len()) + line.off);
+        let span = Span::new(index,size);
+        index += size + newline;
+        span
+    }).collect()
+}
+
+/// Indices of the first and the last top-level line that need to be reparsed after the change.
+fn lines_touched_by
+(module:&known::Module, spans:&[Span], change:&TextChange) -> Option<RangeInclusive<usize>> {
+    let start     = change.replaced.start;
+    let end       = change.replaced.end;
+    let touches   = |span:&Span| span.index <= end && start <= span.end();
+    let mut first = spans.iter().position(touches)?;
+    let last      = spans.iter().rposition(touches)?;
+    // Edit at the very beginning of the line may indent it, making it a part of the block of the
+    // preceding non-empty line. Empty lines in between might be a part of that block as well.
+    if start == spans[first].index && first > 0 {
+        first -= 1;
+        while first > 0 && module.lines[first].elem.is_none() {
+            first -= 1;
+        }
+    }
+    Some(first..=last)
+}
+
+
+
 // ===============
 // === Helpers ===
 // ===============
 
-/// Returns the byte length of leading space characters sequence.
+/// Returns the length of leading space characters sequence.
 fn spaces_size(itr:impl Iterator<Item=char>) -> Size {
-    Size::new(itr.take_while(|c| *c == ' ').fold(0, |acc, c| acc + c.len_utf8()))
-}
-
-/// Checks if the given string slice contains only space charactesr.
-fn all_spaces(text:&str) -> bool {
-    text.chars().all(|c| c == ' ')
+    Size::new(itr.take_while(|c| *c == ' ').count())
 }
 
 
@@ -177,6 +285,7 @@ mod test {
     use super::*;
 
     use ast::HasIdMap;
+    use data::text::ByteIndex;
     use data::text::Index;
     use data::text::TextChange;
     use uuid::Uuid;
@@ -218,9 +327,12 @@ mod test {
                     let inserted_code = insertion.map_or("", |insertion|
                         &marked_code[insertion + INSERTION.len_utf8()..end]
                     );
+                    let char_index   = |byte| {
+                        Index::convert_byte_index(code.as_str(),ByteIndex::new(byte))
+                    };
                     let removed_span = Range {
-                        start : Index::new(start),
-                        end   : Index::new(erased_finish - START.len_utf8()),
+                        start : char_index(start),
+                        end   : char_index(erased_finish - START.len_utf8()),
                     };
                     let change = TextChange::replace(removed_span,inserted_code.to_string());
                     Case {code,change}
@@ -353,4 +465,74 @@ mod test {
             case.assert_edit_keeps_main_node_ids(&parser);
         }
     }
+
+    #[wasm_bindgen_test]
+    fn reparsing_changed_lines() {
+        let parser = Parser::new_or_panic();
+
+        // All the cases edit some lines after the first one, which should be kept intact.
+        let cases = [
+            "foo = 2\nmain =\n    a = 1«⎀2»\n    b = a\nbar = 3",
+            "foo = 2\nmain =\n    a = 1\n    b = a«⎀\n    c = b»\nbar = 3",
+            "foo = 2\nmain =\n    a = 1\n    b = a«⎀\nbaz = 4»\nbar = 3",
+            "foo = 2\nmain =\n    a = 1\n    b = a\n«⎀    »bar = 3",
+            "foo = 2\nmain =\n    a = 1\n«    »b = a\nbar = 3",
+            "foo = 2\nmain =\n    a = 1\n    b = a«\n»bar = 3",
+            "foo = 2\n\nmain = «1⎀\n    a = 1»\n\nbar = 3",
+            "foo = 2\nmain = 1\n«bar = 3⎀»",
+            "foo = 2\nmain = 1\nbar = 3«⎀\n\nbaz = 4»",
+        ];
+
+        for case in cases.iter() {
+            let case       = Case::from_markdown(case);
+            let module     = parser.parse_module(&case.code,default()).unwrap();
+            let mut id_map = module.id_map();
+            apply_code_change_to_id_map(&mut id_map,&case.change,&case.code);
+
+            let new_code  = case.resulting_code();
+            let reparsed  = reparse_changed_lines(&parser,&module,&case.change,id_map).unwrap();
+            let from_full = parser.parse_module(&new_code,default()).unwrap();
+            assert_eq!(reparsed.repr(),new_code);
+            ast::test_utils::assert_same_structure(reparsed.ast(),from_full.ast());
+
+            let first_line = |module:&known::Module| module.lines[0].elem.as_ref().unwrap().id;
+            assert_eq!(first_line(&reparsed),first_line(&module));
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn keeping_node_ids_after_non_ascii_code() {
+        let parser = Parser::new_or_panic();
+        let cases  = [
+            "b = a«⎀ + 1»",
+            "b = a «+ 1»",
+            "b = \"ą«⎀ę»\"",
+        ];
+
+        for case in cases.iter() {
+            let all_nodes  = ["a = \"żółw\"",case,"c = b"];
+            let case       = Case::from_markdown(to_main(all_nodes.iter()));
+            case.assert_edit_keeps_main_node_ids(&parser);
+
+            let module     = parser.parse_module(&case.code,default()).unwrap();
+            let mut id_map = module.id_map();
+            apply_code_change_to_id_map(&mut id_map,&case.change,&case.code);
+            let reparsed = reparse_changed_lines(&parser,&module,&case.change,id_map).unwrap();
+            assert_eq!(reparsed.repr(),case.resulting_code());
+            assert_same_node_ids(&module,&reparsed);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn reparsing_changed_lines_with_non_ascii_code() {
+        let parser   = Parser::new_or_panic();
+        let code     = "foo = \"ąę\"\nmain = \"żółw\"\nbar = 3";
+        let new_code = "foo = \"ąę\"\nmain = \"żółć\"\nbar = 3";
+        let module   = parser.parse_module(code,default()).unwrap();
+        let change   = TextChange::replace(Index::new(22)..Index::new(23),"ć".into());
+        let reparsed = reparse_changed_lines(&parser,&module,&change,default()).unwrap();
+        assert_eq!(reparsed.repr(),new_code);
+        let first_line = |module:&known::Module| module.lines[0].elem.as_ref().unwrap().id;
+        assert_eq!(first_line(&reparsed),first_line(&module));
+    }
 }
@@ -91,9 +91,6 @@ pub mod constants {
 
     /// The directory in the project that contains all the source files.
     pub const SOURCE_DIRECTORY:&str = "src";
-
-    /// An invocable language expression that serialize given input into JSON.
-    pub const SERIALIZE_TO_JSON_EXPRESSION:&str = "x -> x.json_serialize";
 }
 
 
@@ -177,11 +174,12 @@ pub async fn open_project
     let binary_ws     = new_opened_ws(logger.clone_ref(), binary_endpoint).await?;
     let client_json   = language_server::Client::new(json_ws);
     let client_binary = binary::Client::new(logger,binary_ws);
+    let events_json   = client_json.events();
     crate::executor::global::spawn(client_json.runner());
     crate::executor::global::spawn(client_binary.runner());
     let connection_json   = language_server::Connection::new(client_json,client_id).await?;
     let connection_binary = binary::Connection::new(client_binary,client_id).await?;
-    Ok(controller::Project::new(logger,connection_json,connection_binary,project_name))
+    Ok(controller::Project::new(logger,connection_json,events_json,connection_binary,project_name))
 }
 
 /// Creates a new project and returns its metadata, so the newly connected project can be opened.
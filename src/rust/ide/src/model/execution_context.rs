@@ -3,10 +3,14 @@
 use crate::prelude::*;
 
 use crate::model::module::QualifiedName as ModuleQualifiedName;
+use crate::notification;
 use crate::double_representation::definition::DefinitionName;
 
 use enso_protocol::language_server;
+use enso_protocol::language_server::ExpressionValueUpdate;
 use enso_protocol::language_server::VisualisationConfiguration;
+use flo_stream::MessagePublisher;
+use flo_stream::Subscriber;
 use std::collections::HashMap;
 use uuid::Uuid;
 
@@ -24,6 +28,62 @@ pub type ExpressionId = ast::Id;
 
 
 
+// =========================
+// === ComputedValueInfo ===
+// =========================
+
+/// Information about the computed value of some expression.
+#[derive(Clone,Debug,Default,Eq,PartialEq)]
+pub struct ComputedValueInfo {
+    /// The qualified name of the value's type, if known.
+    pub typename    : Option<String>,
+    /// A short textual representation of the value, if known.
+    pub short_value : Option<String>,
+}
+
+impl From<ExpressionValueUpdate> for ComputedValueInfo {
+    fn from(update:ExpressionValueUpdate) -> Self {
+        ComputedValueInfo {
+            typename    : update.typename,
+            short_value : update.short_value,
+        }
+    }
+}
+
+/// The information about the computed values of the expressions in an execution context, as
+/// reported by the Language Server. Every batch of updates is announced to the subscribers with
+/// the ids of the updated expressions.
+#[derive(Debug,Default)]
+pub struct ComputedValueInfoRegistry {
+    map     : RefCell<HashMap<ExpressionId,Rc<ComputedValueInfo>>>,
+    updates : RefCell<notification::Publisher<Vec<ExpressionId>>>,
+}
+
+impl ComputedValueInfoRegistry {
+    /// Store the information from the given updates and notify the subscribers.
+    pub fn apply_updates(&self, updates:Vec<ExpressionValueUpdate>) {
+        let ids = updates.iter().map(|update| update.id).collect_vec();
+        let mut map = self.map.borrow_mut();
+        for update in updates {
+            map.insert(update.id,Rc::new(update.into()));
+        }
+        let notify = self.updates.borrow_mut().publish(ids);
+        executor::global::spawn(notify);
+    }
+
+    /// Subscribe for the ids of the expressions whose information was updated.
+    pub fn subscribe(&self) -> Subscriber<Vec<ExpressionId>> {
+        self.updates.borrow_mut().subscribe()
+    }
+
+    /// The information about the value of the given expression, if any was received.
+    pub fn get(&self, id:&ExpressionId) -> Option<Rc<ComputedValueInfo>> {
+        self.map.borrow().get(id).cloned()
+    }
+}
+
+
+
 // ===============================
 // === VisualizationUpdateData ===
 // ===============================
@@ -159,8 +219,8 @@ pub struct AttachedVisualization {
 /// Execution Context Model.
 ///
 /// The execution context consists of the root call (which is a direct call of some function
-/// definition), stack of function calls (see `StackItem` definition and docs) and a list of
-/// active visualizations.
+/// definition), stack of function calls (see `StackItem` definition and docs), a list of
+/// active visualizations and the information about the computed values.
 ///
 /// It implements internal mutability pattern, so the state may be shared between different
 /// controllers.
@@ -173,6 +233,8 @@ pub struct ExecutionContext {
     stack:RefCell<Vec<LocalCall>>,
     /// Set of active visualizations.
     visualizations: RefCell<HashMap<VisualizationId,AttachedVisualization>>,
+    /// Information about the values computed in this context.
+    pub computed_value_info_registry:ComputedValueInfoRegistry,
 }
 
 impl ExecutionContext {
@@ -181,7 +243,8 @@ impl ExecutionContext {
         let logger         = logger.into();
         let stack          = default();
         let visualizations = default();
-        Self {logger,entry_point,stack,visualizations}
+        let computed_value_info_registry = default();
+        Self {logger,entry_point,stack,visualizations,computed_value_info_registry}
     }
 
     /// Push a new stack item to execution context.
@@ -209,6 +272,19 @@ impl ExecutionContext {
         receiver
     }
 
+    /// Modifies the preprocessor expression of the attached visualization and the module it is
+    /// evaluated in. Returns the visualization description after the change.
+    pub fn modify_visualization
+    (&self, id:&VisualizationId, expression:String, module:ModuleQualifiedName)
+    -> FallibleResult<Visualization> {
+        let err                = || InvalidVisualizationId(*id);
+        let mut visualizations = self.visualizations.borrow_mut();
+        let attached           = visualizations.get_mut(id).ok_or_else(err)?;
+        attached.visualization.expression           = expression;
+        attached.visualization.visualisation_module = module;
+        Ok(attached.visualization.clone())
+    }
+
     /// Detaches visualization from current execution context.
     pub fn detach_visualization(&self, id:&VisualizationId) -> FallibleResult<Visualization> {
         let err = || InvalidVisualizationId(*id);
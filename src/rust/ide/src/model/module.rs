@@ -5,6 +5,7 @@ use crate::constants::LANGUAGE_FILE_DOT_EXTENSION;
 use crate::constants::SOURCE_DIRECTORY;
 use crate::controller::FilePath;
 use crate::double_representation::definition::DefinitionInfo;
+use crate::double_representation::text::reparse_changed_lines;
 use crate::notification;
 
 use data::text::TextChange;
@@ -14,8 +15,10 @@ use flo_stream::Subscriber;
 use parser::api::SourceFile;
 use parser::api::ParsedSourceFile;
 use parser::Parser;
-use serde::Serialize;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
 
 
 
@@ -207,6 +210,18 @@ impl QualifiedName {
         let name             = all_segments.join(".");
         QualifiedName(name)
     }
+
+    /// Creates a qualified name from its textual form, e.g. `Project.Main`.
+    ///
+    /// ```
+    /// use ide::model::module::QualifiedName;
+    ///
+    /// let name = QualifiedName::from_text("Project.Main");
+    /// assert_eq!(name.to_string(), "Project.Main");
+    /// ```
+    pub fn from_text(text:impl Str) -> QualifiedName {
+        QualifiedName(text.into())
+    }
 }
 
 
@@ -237,11 +252,17 @@ pub enum Notification {
 // == Metadata ==
 // ==============
 
+/// The version of the IDE metadata schema written by this version of IDE.
+///
+/// The unversioned metadata are version 0, which had only the node positions. They are valid in
+/// version 1 as they are, because it only introduced more optional node data.
+pub const METADATA_VERSION:u32 = 1;
+
 /// Mapping between ID and metadata.
 #[derive(Debug,Clone,Deserialize,Serialize)]
 pub struct Metadata {
     /// Metadata used within ide.
-    #[serde(default="default")]
+    #[serde(default="default",deserialize_with="IdeMetadata::deserialize_lenient")]
     pub ide : IdeMetadata,
     #[serde(flatten)]
     /// Metadata of other users of ParsedSourceFile<Metadata> API.
@@ -263,17 +284,159 @@ impl Default for Metadata {
 }
 
 /// Metadata that belongs to ide.
-#[derive(Debug,Clone,Default,Deserialize,Serialize)]
+#[derive(Debug,Clone,Serialize)]
 pub struct IdeMetadata {
+    /// The version of the schema used by these metadata.
+    version : u32,
     /// Metadata that belongs to nodes.
-    node : HashMap<ast::Id,NodeMetadata>
+    node : HashMap<ast::Id,NodeMetadata>,
+    /// The fields unknown to this version of IDE, e.g. written by a newer one. They are written
+    /// back unchanged.
+    #[serde(flatten)]
+    rest : serde_json::Map<String,serde_json::Value>,
+}
+
+impl Default for IdeMetadata {
+    fn default() -> Self {
+        let version = METADATA_VERSION;
+        let node    = default();
+        let rest    = default();
+        IdeMetadata {version,node,rest}
+    }
 }
 
+
+// === Loading ===
+
+impl IdeMetadata {
+    /// Reads the IDE metadata from JSON.
+    ///
+    /// The loading is lenient: the entries which cannot be read are dropped with a warning,
+    /// instead of failing the whole module load. The unknown fields are kept, and the metadata
+    /// written by a newer IDE keep their version, so they are not downgraded when saved.
+    pub fn from_json(json:serde_json::Value, logger:&Logger) -> IdeMetadata {
+        let mut metadata = match json {
+            serde_json::Value::Object(metadata) => metadata,
+            other => {
+                warning!(logger,"Discarding IDE metadata which are not an object: {other}");
+                return default()
+            }
+        };
+        let version = metadata.remove("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+        if version > METADATA_VERSION {
+            warning!(logger,"IDE metadata have newer version {version}, reading only the known \
+                entries.");
+        }
+        let version = version.max(METADATA_VERSION);
+        let node    = Self::read_entries(&mut metadata,"node",logger,|key| key.parse().ok());
+        let rest    = metadata;
+        IdeMetadata {version,node,rest}
+    }
+
+    /// Reads the map stored under `field`, dropping the entries with invalid key or value.
+    fn read_entries<K:Eq+Hash,V:DeserializeOwned>
+    ( metadata  : &mut serde_json::Map<String,serde_json::Value>
+    , field     : &str
+    , logger    : &Logger
+    , parse_key : impl Fn(&str) -> Option<K>
+    ) -> HashMap<K,V> {
+        let entries = match metadata.remove(field) {
+            Some(serde_json::Value::Object(entries)) => entries,
+            None                                     => return default(),
+            Some(other)                              => {
+                warning!(logger,"Discarding IDE {field} metadata which are not an object: \
+                    {other}");
+                return default()
+            }
+        };
+        entries.into_iter().filter_map(|(key,value)| {
+            let entry = parse_key(&key).ok_or_else(|| iformat!("invalid key `{key}`"));
+            let entry = entry.and_then(|parsed_key| {
+                let value = serde_json::from_value(value).map_err(|e| e.to_string());
+                value.map(|value| (parsed_key,value))
+            });
+            entry.map_err(|error| {
+                warning!(logger,"Discarding IDE {field} metadata entry `{key}`: {error}.");
+            }).ok()
+        }).collect()
+    }
+
+    fn deserialize_lenient<'de,D:Deserializer<'de>>(deserializer:D) -> Result<Self,D::Error> {
+        let json   = serde_json::Value::deserialize(deserializer)?;
+        let logger = Logger::new("IdeMetadata");
+        Ok(Self::from_json(json,&logger))
+    }
+}
+
+
+// === Pruning ===
+
+impl IdeMetadata {
+    /// Removes the node metadata of ids not present in the given id map, returning the removed ids.
+    pub fn prune_nodes(&mut self, id_map:&ast::IdMap) -> Vec<ast::Id> {
+        let existing : HashSet<ast::Id> = id_map.vec.iter().map(|(_,id)| *id).collect();
+        let removed  = self.node.keys().filter(|id| !existing.contains(id)).copied().collect_vec();
+        for id in &removed {
+            self.node.remove(id);
+        }
+        removed
+    }
+}
+
+
+// === Node Metadata ===
+
 /// Metadata of specific node.
-#[derive(Debug,Clone,Copy,Default,Serialize,Deserialize,Shrinkwrap)]
+#[derive(Debug,Clone,Default,PartialEq,Serialize,Deserialize,Shrinkwrap)]
 pub struct NodeMetadata {
     /// Position in x,y coordinates.
-    pub position: Option<Position>
+    #[shrinkwrap(main_field)]
+    pub position : Option<Position>,
+    /// The visualization chosen for the node.
+    #[serde(default)]
+    pub visualization : Option<VisualizationMetadata>,
+    /// The fields unknown to this version of IDE, written back unchanged.
+    #[serde(flatten)]
+    rest : serde_json::Map<String,serde_json::Value>,
+}
+
+impl NodeMetadata {
+    /// Creates metadata with only the position set.
+    pub fn at_position(position:Option<Position>) -> NodeMetadata {
+        NodeMetadata {position,..default()}
+    }
+}
+
+/// Visualization chosen for a node.
+#[derive(Debug,Clone,Default,PartialEq,Serialize,Deserialize)]
+pub struct VisualizationMetadata {
+    /// The name of library defining the visualization.
+    pub library : String,
+    /// The name of the visualization in its library.
+    pub name : String,
+    /// Whether the visualization is shown.
+    #[serde(default)]
+    pub visible : bool,
+    /// The configuration specific to the visualization.
+    #[serde(default)]
+    pub config : serde_json::Value,
+    /// Whether the node is a widget: it displays the visualization instead of its expression,
+    /// and the expression is generated from the data the user enters into the visualization.
+    #[serde(default)]
+    pub widget : bool,
+    /// The layout of the panel the visualization is detached into, if it is not displayed under
+    /// the node.
+    #[serde(default)]
+    pub panel : Option<PanelLayout>,
+}
+
+/// Placement of a visualization detached from its node into a panel.
+#[derive(Copy,Clone,Debug,PartialEq,Serialize,Deserialize)]
+pub struct PanelLayout {
+    /// The position of the panel's center.
+    pub position : Position,
+    /// The size of the panel.
+    pub size : Vector2<f32>,
 }
 
 /// Used for storing node position.
@@ -382,21 +545,33 @@ impl Module {
     }
 
     /// Update ast in module controller.
+    ///
+    /// The old and new ASTs are compared, so the emitted `CodeChanged` notification describes only
+    /// the fragment of code which was actually changed. If the code has not changed, no
+    /// notification is emitted.
     pub fn update_ast(&self, ast:ast::known::Module) {
-        self.content.borrow_mut().ast  = ast;
-        self.notify(Notification::Invalidate);
+        let old_ast = self.ast();
+        let changes = ast::diff::module(&old_ast,&ast);
+        let change  = ast::diff::merge(&changes,&ast.repr());
+        self.content.borrow_mut().ast = ast;
+        if let Some(change) = change {
+            let replaced_location = TextLocation::convert_range(&old_ast.repr(),&change.replaced);
+            self.notify(Notification::CodeChanged {change,replaced_location});
+        }
     }
 
     /// Updates AST after code change.
     ///
+    /// Only the top-level lines touched by the change are reparsed, the other ones keep their
+    /// ASTs (see `double_representation::text::reparse_changed_lines`).
+    ///
     /// May return Error when new code causes parsing errors, or when parsed code does not produce
     /// Module ast.
     pub fn apply_code_change
     (&self, change:TextChange, parser:&Parser, new_id_map:ast::IdMap) -> FallibleResult<()> {
-        let mut code          = self.ast().repr();
-        let replaced_location = TextLocation::convert_range(&code,&change.replaced);
-        change.apply(&mut code);
-        let new_ast = parser.parse(code,new_id_map)?.try_into()?;
+        let old_ast           = self.ast();
+        let replaced_location = TextLocation::convert_range(&old_ast.repr(),&change.replaced);
+        let new_ast           = reparse_changed_lines(parser,&old_ast,&change,new_id_map)?;
         self.content.borrow_mut().ast = new_ast;
         self.notify(Notification::CodeChanged {change,replaced_location});
         Ok(())
@@ -447,6 +622,7 @@ mod test {
 
     use crate::executor::test_utils::TestWithLocalPoolExecutor;
 
+    use ast::HasIdMap;
     use data::text;
     use uuid::Uuid;
     use wasm_bindgen_test::wasm_bindgen_test;
@@ -477,7 +653,10 @@ mod test {
             let new_module_ast = Ast::one_line_module(new_line);
             let new_module_ast = ast::known::Module::try_new(new_module_ast).unwrap();
             module.update_ast(new_module_ast.clone_ref());
-            assert_eq!(Some(Notification::Invalidate), subscription.next().await);
+            let change            = TextChange::insert(text::Index::new(0),"a + b".to_string());
+            let replaced_location = TextLocation{line:0, column:0}..TextLocation{line:0, column:0};
+            let notification      = Notification::CodeChanged {change,replaced_location};
+            assert_eq!(Some(notification), subscription.next().await);
 
             // Code change
             let change = TextChange {
@@ -491,7 +670,7 @@ mod test {
 
             // Metadata update
             let id            = Uuid::new_v4();
-            let node_metadata = NodeMetadata {position:Some(Position::new(1.0, 2.0))};
+            let node_metadata = NodeMetadata::at_position(Some(Position::new(1.0, 2.0)));
             module.set_node_metadata(id.clone(),node_metadata.clone());
             assert_eq!(Some(Notification::MetadataChanged), subscription.next().await);
             module.remove_node_metadata(id.clone()).unwrap();
@@ -511,6 +690,30 @@ mod test {
         });
     }
 
+    #[test]
+    fn updating_ast_notifies_about_changed_fragment() {
+        let mut test = TestWithLocalPoolExecutor::set_up();
+        test.run_task(async {
+            let line   = |name:&str| Some(Ast::infix(Ast::var(name),"=",Ast::number(2)));
+            let (a,b)  = (line("a"),line("b"));
+            let module = |lines:&[Option<Ast>]| {
+                let ast = Ast::new_no_id(ast::Module::from_lines(lines));
+                ast::known::Module::try_new(ast).unwrap()
+            };
+            let model            = Module::new(module(&[a.clone(),b.clone()]),default());
+            let mut subscription = model.subscribe();
+
+            let new_line = Ast::infix(Ast::var("foo"),"=",Ast::var("bar"));
+            model.update_ast(module(&[a,Some(new_line),b]));
+            assert_eq!(model.ast().repr(),"a = 2\nfoo = bar\nb = 2");
+            let change            = TextChange::insert(text::Index::new(6),"foo = bar\n".into());
+            let location          = TextLocation{line:1, column:0};
+            let replaced_location = location..location;
+            let notification      = Notification::CodeChanged {change,replaced_location};
+            assert_eq!(Some(notification), subscription.next().await);
+        });
+    }
+
     #[test]
     fn handling_metadata() {
         let mut test = TestWithLocalPoolExecutor::set_up();
@@ -521,7 +724,7 @@ mod test {
             let initial_md = module.node_metadata(id.clone());
             assert!(initial_md.is_err());
 
-            let md_to_set = NodeMetadata {position:Some(Position::new(1.0, 2.0))};
+            let md_to_set = NodeMetadata::at_position(Some(Position::new(1.0, 2.0)));
             module.set_node_metadata(id.clone(),md_to_set.clone());
             assert_eq!(md_to_set.position, module.node_metadata(id.clone()).unwrap().position);
 
@@ -534,6 +737,96 @@ mod test {
         });
     }
 
+    #[test]
+    fn loading_unversioned_metadata() {
+        let id   = Uuid::new_v4();
+        let json = serde_json::json!({
+            "ide"   : {"node" : {id.to_string() : {"position" : {"vector" : [1.0,2.0]}}}},
+            "other" : {"foo" : 1},
+        });
+        let metadata : Metadata = serde_json::from_value(json).unwrap();
+        assert_eq!(metadata.ide.version, METADATA_VERSION);
+        let node = metadata.ide.node.get(&id).unwrap();
+        assert_eq!(node.position, Some(Position::new(1.0,2.0)));
+        assert_eq!(node.visualization, None);
+        assert_eq!(metadata.rest["other"]["foo"], 1);
+    }
+
+    #[test]
+    fn discarding_invalid_metadata_entries() {
+        let id   = Uuid::new_v4();
+        let json = serde_json::json!({
+            "version" : 1,
+            "node"    : {
+                id.to_string()  : {"position" : {"vector" : [1.0,2.0]}},
+                "not an uuid"   : {"position" : null},
+                Uuid::new_v4().to_string() : {"position" : "somewhere"},
+            },
+        });
+        let metadata = IdeMetadata::from_json(json,&Logger::new("Test"));
+        assert_eq!(metadata.node.len(), 1);
+        assert_eq!(metadata.node[&id].position, Some(Position::new(1.0,2.0)));
+
+        let metadata = IdeMetadata::from_json(serde_json::json!([1,2,3]),&Logger::new("Test"));
+        assert!(metadata.node.is_empty());
+    }
+
+    #[test]
+    fn keeping_unknown_metadata_fields() {
+        let id   = Uuid::new_v4();
+        let json = serde_json::json!({
+            "version" : 2,
+            "node"    : {id.to_string() : {"position" : {"vector" : [1.0,2.0]}, "color" : "red"}},
+            "groups"  : [[id.to_string()]],
+        });
+        let metadata = IdeMetadata::from_json(json.clone(),&Logger::new("Test"));
+        assert_eq!(metadata.version, 2);
+        assert_eq!(metadata.node[&id].position, Some(Position::new(1.0,2.0)));
+        let saved = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(saved["version"], 2);
+        assert_eq!(saved["groups"], json["groups"]);
+        assert_eq!(saved["node"][id.to_string()]["color"], "red");
+    }
+
+    #[test]
+    fn metadata_round_trip() {
+        let id       = Uuid::new_v4();
+        let mut data = Metadata::default();
+        let node     = NodeMetadata {
+            position      : Some(Position::new(1.0,2.0)),
+            visualization : Some(VisualizationMetadata {
+                library : "Builtin".into(),
+                name    : "Scatter Plot".into(),
+                visible : true,
+                config  : serde_json::json!({"points" : 100}),
+                widget  : true,
+                panel   : Some(PanelLayout {
+                    position : Position::new(300.0,-50.0),
+                    size     : Vector2::new(400.0,250.0),
+                }),
+            }),
+            ..default()
+        };
+        data.ide.node.insert(id,node.clone());
+        let json   = serde_json::to_value(&data).unwrap();
+        let loaded : Metadata = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.ide.node[&id], node);
+    }
+
+    #[wasm_bindgen_test]
+    fn pruning_metadata() {
+        let module   = Module::from_code_or_panic("main = 2 + 2",default(),default());
+        let existing = module.ast().id_map().vec[0].1;
+        let removed  = Uuid::new_v4();
+        let mut data = IdeMetadata::default();
+        data.node.insert(existing,default());
+        data.node.insert(removed,default());
+
+        assert_eq!(data.prune_nodes(&module.ast().id_map()), vec![removed]);
+        assert!(data.node.contains_key(&existing));
+        assert!(!data.node.contains_key(&removed));
+    }
+
     #[test]
     fn module_path_conversion() {
         let path = FilePath::new(default(), &["src","Main.enso"]);
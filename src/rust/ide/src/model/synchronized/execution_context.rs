@@ -3,10 +3,12 @@
 use crate::prelude::*;
 
 use crate::double_representation::definition::DefinitionName;
+use crate::model::execution_context::ComputedValueInfoRegistry;
 use crate::model::execution_context::LocalCall;
 use crate::model::execution_context::Visualization;
 use crate::model::execution_context::VisualizationUpdateData;
 use crate::model::execution_context::VisualizationId;
+use crate::model::module::QualifiedName as ModuleQualifiedName;
 
 use enso_protocol::language_server;
 use json_rpc::error::RpcError;
@@ -112,6 +114,18 @@ impl ExecutionContext {
         }
     }
 
+    /// Modify the preprocessor expression of the attached visualization and the module it is
+    /// evaluated in.
+    pub async fn modify_visualization
+    (&self, id:&VisualizationId, expression:String, module:ModuleQualifiedName)
+    -> FallibleResult<()> {
+        info!(self.logger,"Modifying visualization {id}.");
+        let vis    = self.model.modify_visualization(id,expression,module)?;
+        let config = vis.config(self.id);
+        self.language_server.modify_visualisation(id,&config).await?;
+        Ok(())
+    }
+
     /// Detach visualization from current execution context.
     pub async fn detach_visualization(&self, id:&VisualizationId) -> FallibleResult<Visualization> {
         info!(self.logger,"Scheduling detaching visualization by id: {id}.");
@@ -139,6 +153,11 @@ impl ExecutionContext {
         self.model.dispatch_visualization_update(visualization_id,data)
     }
 
+    /// The information about the values computed in this context.
+    pub fn computed_value_info_registry(&self) -> &ComputedValueInfoRegistry {
+        &self.model.computed_value_info_registry
+    }
+
     /// Create a mock which does no call on `language_server` during construction.
     #[cfg(test)]
     pub fn new_mock
@@ -179,7 +198,6 @@ mod test {
     use super::*;
 
     use crate::executor::test_utils::TestWithLocalPoolExecutor;
-    use crate::model::module::QualifiedName as ModuleQualifiedName;
 
     use enso_protocol::language_server::CapabilityRegistration;
     use json_rpc::expect_call;
@@ -292,12 +310,19 @@ mod test {
             expression           : "".to_string(),
             visualisation_module : ModuleQualifiedName::from_path(&path,"PPPP"),
         };
-        let vis_id = vis.id;
-        let ast_id = vis.ast_id;
-        let config = vis.config(exe_id);
+        let vis_id   = vis.id;
+        let ast_id   = vis.ast_id;
+        let config   = vis.config(exe_id);
+        let modified = Visualization {
+            expression           : "x -> x.to_text".to_string(),
+            visualisation_module : ModuleQualifiedName::from_module_segments(&["Vis"],"PPPP"),
+            ..vis.clone()
+        };
+        let modified_config = modified.config(exe_id);
 
-        expect_call!(ls.attach_visualisation(vis_id,ast_id,config) => Ok(()));
-        expect_call!(ls.detach_visualisation(exe_id,vis_id,ast_id) => Ok(()));
+        expect_call!(ls.attach_visualisation(vis_id,ast_id,config)    => Ok(()));
+        expect_call!(ls.modify_visualisation(vis_id,modified_config) => Ok(()));
+        expect_call!(ls.detach_visualisation(exe_id,vis_id,ast_id)    => Ok(()));
         expect_call!(ls.destroy_execution_context(exe_id)          => Ok(()));
 
         let context = ExecutionContext::new_mock(exe_id,path,model,ls);
@@ -319,7 +344,15 @@ mod test {
             events.expect_pending();
             assert!(context.detach_visualization(&wrong_id).await.is_err());
             events.expect_pending();
-            assert!(context.detach_visualization(&vis.id).await.is_ok());
+            let expression = modified.expression.clone();
+            let module     = modified.visualisation_module.clone();
+            context.modify_visualization(&vis.id,expression,module).await.unwrap();
+            let expression = modified.expression.clone();
+            let module     = modified.visualisation_module.clone();
+            assert!(context.modify_visualization(&wrong_id,expression,module).await.is_err());
+            events.expect_pending();
+            let detached = context.detach_visualization(&vis.id).await.unwrap();
+            assert_eq!(detached.expression, modified.expression);
             events.expect_terminated();
             assert!(context.detach_visualization(&vis.id).await.is_err());
             context.dispatch_visualization_update(vis.id,update.clone()).unwrap_err();
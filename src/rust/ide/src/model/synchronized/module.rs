@@ -6,6 +6,7 @@ use crate::model::module::Notification;
 
 use enso_protocol::types::Sha3_224;
 use enso_protocol::language_server;
+use ast::HasIdMap;
 use data::text::TextLocation;
 use parser::api::SourceFile;
 use parser::Parser;
@@ -113,9 +114,12 @@ impl Module {
         let opened = language_server.client.open_text_file(&file_path).await?;
         info!(logger, "Read content of the module {path}, digest is {opened.current_version:?}");
         let end_of_file = TextLocation::at_document_end(&opened.content);
-        // TODO[ao] We should not fail here when metadata are malformed, but discard them and set
-        //  default instead.
-        let source  = parser.parse_with_metadata(opened.content)?;
+        let mut source  = Self::parse_leniently(&logger,&parser,opened.content)?;
+        let id_map      = source.ast.id_map();
+        let pruned      = source.metadata.ide.prune_nodes(&id_map);
+        if !pruned.is_empty() {
+            warning!(logger,"Discarded metadata of nodes not present in the module: {pruned:?}.");
+        }
         let digest  = opened.current_version;
         let summary = ContentSummary {digest,end_of_file};
         let model   = model::Module::new(source.ast,source.metadata);
@@ -124,6 +128,23 @@ impl Module {
         Ok(this)
     }
 
+    /// Parse the module file content. If the id map or metadata are malformed, they are discarded
+    /// and only the code is parsed.
+    pub fn parse_leniently
+    (logger:&Logger, parser:&Parser, content:String) -> FallibleResult<model::module::Content> {
+        match parser.parse_with_metadata(content.clone()) {
+            Ok(source) => Ok(source),
+            Err(err)   => {
+                warning!(logger,"Failed to read module's id map and metadata, discarding them: \
+                    {err}");
+                let code     = parser::api::code_without_metadata(&content);
+                let ast      = parser.parse_module(code,default())?;
+                let metadata = default();
+                Ok(model::module::Content {ast,metadata})
+            }
+        }
+    }
+
     /// Create a module mock.
     #[cfg(test)]
     pub fn mock(path:model::module::Path, model:model::Module) -> Rc<Self> {
@@ -367,7 +388,15 @@ pub mod test {
 
         let setup           = LsClientSetup::new(path.file_path().clone(),initial_content);
         setup.expect_invalidate(Ok(()));
-        setup.expect_invalidate(Ok(()));
+        setup.expect_edit(Ok(()), |edits| {
+            // Check that only the changed text literal is replaced:
+            let code_edit = &edits[1];
+            let start     = language_server::types::Position {line:1, character:12};
+            let end       = language_server::types::Position {line:1, character:26};
+            assert_eq!(code_edit.range, language_server::types::TextRange {start,end});
+            assert_eq!(code_edit.text, "\"Test\"");
+            "main =\n    println \"Test\"".to_string()
+        });
         setup.expect_edit(Ok(()), |edits| {
             // Check that it's not invalidate:
             assert_ne!(edits[0].range.start, TextLocation::at_document_begin().into());
@@ -430,4 +459,23 @@ pub mod test {
         });
         test.when_stalled(move || *module.borrow_mut() = None);
     }
+
+    #[wasm_bindgen_test]
+    fn opening_module_with_malformed_metadata() {
+        let path            = model::module::Path::from_mock_module_name("TestModule");
+        let initial_content = "main = 2\n\n\n#### METADATA ####\n[]\n{\"ide\":";
+
+        let setup           = LsClientSetup::new(path.file_path().clone(),initial_content);
+        setup.expect_invalidate(Ok(()));
+        let connection                             = setup.finish();
+        let mut test                               = TestWithLocalPoolExecutor::set_up();
+        let module:Rc<RefCell<Option<Rc<Module>>>> = default();
+        let module_ref                             = module.clone();
+        test.run_task(async move {
+            let module = Module::open(path,connection,Parser::new_or_panic()).await.unwrap();
+            assert_eq!(module.ast().repr(), "main = 2");
+            *module_ref.borrow_mut() = Some(module);
+        });
+        test.when_stalled(move || *module.borrow_mut() = None);
+    }
 }
@@ -1,5 +1,6 @@
 //! A module containing view components.
 
+pub mod command_palette;
 pub mod node_editor;
 pub mod layout;
 pub mod node_searcher;
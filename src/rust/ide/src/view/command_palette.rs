@@ -0,0 +1,334 @@
+//! This module contains the CommandPalette, a view listing the commands of application views
+//! matching the typed query, and running the chosen one.
+
+use crate::prelude::*;
+
+use ensogl::application::Application;
+use ensogl::application::palette;
+use ensogl::application::shortcut;
+use ensogl::application;
+use ensogl::data::color;
+use ensogl::display::shape::text::glyph::font;
+use ensogl::display::shape::text::text_field::TextField;
+use ensogl::display::shape::text::text_field::TextFieldProperties;
+use ensogl::display::world::World;
+use ensogl::display;
+use ensogl::traits::*;
+use enso_frp as frp;
+use enso_frp::io::keyboard::Key;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The maximum number of commands listed at once.
+const MAX_LISTED_ITEMS : usize = 10;
+
+/// The size of the query and of the listed commands text.
+const TEXT_SIZE : f32 = 16.0;
+
+/// The width of the palette.
+const WIDTH : f32 = 600.0;
+
+
+
+// ===========
+// === Frp ===
+// ===========
+
+ensogl::def_command_api! { Commands
+    /// Show the command palette if it is hidden, hide it otherwise.
+    toggle,
+    /// Select the next listed command.
+    select_next,
+    /// Select the previous listed command.
+    select_previous,
+    /// Hide the command palette.
+    hide,
+}
+
+impl Commands {
+    fn new(network:&frp::Network) -> Self {
+        frp::extend! { network
+            toggle          <- source();
+            select_next     <- source();
+            select_previous <- source();
+            hide            <- source();
+        }
+        Self {toggle,select_next,select_previous,hide}
+    }
+}
+
+ensogl::def_status_api! { FrpStatus
+    /// Checks whether the command palette is visible.
+    is_visible,
+}
+
+
+
+// =============
+// === Model ===
+// =============
+
+/// Internal representation of `CommandPalette`.
+#[derive(Debug)]
+pub struct Model {
+    logger             : Logger,
+    display_object     : display::object::Instance,
+    application        : Application,
+    query_field        : TextField,
+    list_field         : TextField,
+    matches            : RefCell<Vec<palette::Item>>,
+    selected           : Cell<usize>,
+    visible            : Cell<bool>,
+    visibility_changed : frp::Source<bool>,
+}
+
+impl Model {
+    /// Checks whether the palette is shown.
+    pub fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
+    /// Show the palette if it is invisible, listing all the commands.
+    pub fn show(&self) {
+        if !self.is_visible() {
+            self.visible.set(true);
+            self.display_object.add_child(&self.query_field.display_object());
+            self.display_object.add_child(&self.list_field.display_object());
+            self.query_field.clear_content();
+            self.query_field.set_focus();
+            self.selected.set(0);
+            self.update("");
+            self.visibility_changed.emit(true);
+        }
+    }
+
+    /// Hide the palette if it is visible.
+    pub fn hide(&self) {
+        if self.is_visible() {
+            self.visible.set(false);
+            self.query_field.clear_content();
+            self.list_field.clear_content();
+            self.matches.borrow_mut().clear();
+            self.display_object.remove_child(&self.query_field.display_object());
+            self.display_object.remove_child(&self.list_field.display_object());
+            self.visibility_changed.emit(false);
+        }
+    }
+
+    /// Show the palette if it is hidden, hide it otherwise.
+    pub fn toggle(&self) {
+        if self.is_visible() { self.hide() } else { self.show() }
+    }
+
+    /// Select the next listed command.
+    pub fn select_next(&self) {
+        let count = self.matches.borrow().len();
+        if count > 0 {
+            self.selected.set((self.selected.get() + 1) % count);
+            self.render();
+        }
+    }
+
+    /// Select the previous listed command.
+    pub fn select_previous(&self) {
+        let count = self.matches.borrow().len();
+        if count > 0 {
+            self.selected.set((self.selected.get() + count - 1) % count);
+            self.render();
+        }
+    }
+
+    /// Hide the palette and run the selected command. Commands which can not be used now are not
+    /// run.
+    pub fn run_selected(&self) {
+        let selected = self.matches.borrow().get(self.selected.get()).cloned();
+        self.hide();
+        match selected {
+            Some(item) if item.enabled => item.run(),
+            Some(item)                 => warning!(self.logger,
+                "The command {item.target}.{item.command} can not be used now."),
+            None                       => {},
+        }
+    }
+
+    fn update(&self, query:&str) {
+        let items   = palette::items(&self.application.commands,&self.application.views);
+        let matches = palette::search::search(query,&items);
+        let matches = matches.into_iter().take(MAX_LISTED_ITEMS).cloned().collect();
+        *self.matches.borrow_mut() = matches;
+        self.render();
+    }
+
+    fn render(&self) {
+        let matches  = self.matches.borrow();
+        let selected = self.selected.get();
+        let lines    = matches.iter().enumerate().map(|(index,item)| {
+            let marker    = if index == selected { ">" } else { " " };
+            let shortcuts = item.shortcuts.join(", ");
+            let shortcuts = (!shortcuts.is_empty()).as_some_from(|| format!(" [{}]",shortcuts));
+            let shortcuts = shortcuts.unwrap_or_default();
+            let status    = if item.enabled { "" } else { " (unavailable)" };
+            format!("{} {}{}{}",marker,item.caption,shortcuts,status)
+        });
+        self.list_field.set_content(&lines.join("\n"));
+    }
+}
+
+
+
+// ======================
+// === CommandPalette ===
+// ======================
+
+/// A view listing the commands of all live command providers registered in the application, with
+/// their captions, shortcuts and whether they can be used now. The list is filtered and ranked by
+/// the typed query, see `ensogl::application::palette::search`. Enter runs the selected command.
+///
+/// The palette is a command provider itself, so its shortcuts are listed and can be changed by
+/// the user keymap. It should be registered in the view registry of the application before being
+/// created.
+#[derive(Clone,CloneRef,Debug)]
+#[allow(missing_docs)]
+pub struct CommandPalette {
+    model        : Rc<Model>,
+    network      : frp::Network,
+    pub commands : Commands,
+    pub status   : FrpStatus,
+}
+
+impl Deref for CommandPalette {
+    type Target = Model;
+    fn deref(&self) -> &Self::Target {
+        &self.model
+    }
+}
+
+impl CommandPalette {
+    /// Constructor. The palette is hidden initially.
+    pub fn new
+    (logger:impl AnyLogger, application:&Application, fonts:&mut font::Registry) -> Self {
+        let logger         = Logger::sub(logger,"CommandPalette");
+        let application    = application.clone_ref();
+        let world          = &application.display;
+        let display_object = display::object::Instance::new(&logger);
+        let query_field    = Self::text_field(world,fonts,1,color::Rgba::new(1.0,1.0,1.0,0.7));
+        let list_field     = Self::text_field(world,fonts,MAX_LISTED_ITEMS,
+            color::Rgba::new(1.0,1.0,1.0,0.5));
+        list_field.set_position(Vector3::new(0.0,-TEXT_SIZE * 1.5,0.0));
+        let network  = frp::Network::new();
+        let commands = Commands::new(&network);
+        frp::extend! { network
+            def visibility_changed = source::<bool>();
+            def is_visible         = visibility_changed.sampler();
+        }
+        let matches  = default();
+        let selected = default();
+        let visible  = default();
+        let model    = Model {logger,display_object,application,query_field,list_field,matches
+                             ,selected,visible,visibility_changed};
+        let model    = Rc::new(model);
+        frp::extend! { network
+            eval_ commands.toggle          (model.toggle());
+            eval_ commands.select_next     (model.select_next());
+            eval_ commands.select_previous (model.select_previous());
+            eval_ commands.hide            (model.hide());
+        }
+        let status  = FrpStatus {is_visible};
+        let palette = Self {model,network,commands,status};
+        palette.application.commands.register_instance(&palette);
+        palette.initialize()
+    }
+
+    fn text_field
+    (world:&World, fonts:&mut font::Registry, lines:usize, base_color:color::Rgba) -> TextField {
+        let properties = TextFieldProperties {
+            font       : fonts.get_or_load_embedded_font("DejaVuSansMono").unwrap(),
+            text_size  : TEXT_SIZE,
+            base_color,
+            size       : Vector2::new(WIDTH,TEXT_SIZE * lines as f32),
+        };
+        TextField::new(world,properties)
+    }
+
+    fn initialize(self) -> Self {
+        // The text field is owned by the model, so the callback can not keep the model alive.
+        let model = Rc::downgrade(&self.model);
+        self.query_field.set_text_edit_callback(move |change| {
+            if let Some(model) = model.upgrade() {
+                let field_content = model.query_field.get_content();
+                let query         = field_content.split('\n').next().unwrap();
+                if change.inserted == "\n" {
+                    model.run_selected();
+                } else {
+                    // Keep only one line.
+                    model.query_field.set_content(query);
+                    model.selected.set(0);
+                    model.update(query);
+                }
+            }
+        });
+        self
+    }
+}
+
+impl display::Object for CommandPalette {
+    fn display_object(&self) -> &display::object::Instance {
+        &self.display_object
+    }
+}
+
+
+
+// ========================
+// === Command Provider ===
+// ========================
+
+impl application::command::FrpNetworkProvider for CommandPalette {
+    fn network(&self) -> &frp::Network {
+        &self.network
+    }
+}
+
+impl application::command::CommandApi for CommandPalette {
+    fn command_api_docs() -> Vec<application::command::EndpointDocs> {
+        Commands::command_api_docs()
+    }
+
+    fn command_api(&self) -> Vec<application::command::CommandEndpoint> {
+        self.commands.command_api()
+    }
+}
+
+impl application::command::StatusApi for CommandPalette {
+    fn status_api_docs() -> Vec<application::command::EndpointDocs> {
+        FrpStatus::status_api_docs()
+    }
+
+    fn status_api(&self) -> Vec<application::command::StatusEndpoint> {
+        self.status.status_api()
+    }
+}
+
+impl application::command::Provider for CommandPalette {
+    fn label() -> &'static str {
+        "CommandPalette"
+    }
+}
+
+impl application::shortcut::DefaultShortcutProvider for CommandPalette {
+    fn default_shortcuts() -> Vec<shortcut::Shortcut> {
+        use shortcut::Action;
+        use shortcut::Condition;
+        let visible = || Condition::Simple("is_visible".into());
+        let p       = || Key::Character("p".into());
+        vec! [ Self::self_shortcut(Action::press           (&[Key::Control,Key::Shift,p()]) , "toggle")
+             , Self::self_shortcut_when(Action::press      (&[Key::ArrowDown])              , "select_next"     , visible())
+             , Self::self_shortcut_when(Action::press      (&[Key::ArrowUp])                , "select_previous" , visible())
+             , Self::self_shortcut_when(Action::press      (&[Key::Escape])                 , "hide"            , visible())
+             ]
+    }
+}
@@ -3,6 +3,7 @@
 
 use crate::prelude::*;
 
+use crate::view::command_palette::CommandPalette;
 use crate::view::temporary_panel::TemporaryPadding;
 use crate::view::temporary_panel::TemporaryPanel;
 use crate::view::text_editor::TextEditor;
@@ -37,9 +38,10 @@ pub struct ViewLayoutData {
     text_editor               : TextEditor,
     node_editor               : NodeEditor,
     node_searcher             : NodeSearcher,
+    command_palette           : CommandPalette,
     size                      : Vector2<f32>,
     logger                    : Logger,
-    node_searcher_show_action : Option<callback::Handle>
+    node_searcher_show_action : Option<callback::Handle>,
 }
 
 impl {
@@ -60,6 +62,7 @@ impl ViewLayoutData {
         self.update_text_editor();
         self.update_graph_editor();
         self.update_node_searcher();
+        self.update_command_palette();
     }
 
     fn update_text_editor(&mut self) {
@@ -88,6 +91,12 @@ impl ViewLayoutData {
         let position    = Vector3::new(screen_size.x*2.0/3.0, screen_size.y - 10.0, 0.0);
         self.node_searcher.set_position(position);
     }
+
+    fn update_command_palette(&mut self) {
+        let screen_size = self.size;
+        let position    = Vector3::new(screen_size.x / 4.0, screen_size.y - 40.0, 0.0);
+        self.command_palette.set_position(position);
+    }
 }
 
 impl ViewLayout {
@@ -108,16 +117,24 @@ impl ViewLayout {
         let node_editor   = NodeEditor::new
             (&logger,application,graph_controller,visualization_controller).await?;
         let node_searcher = NodeSearcher::new(world,&logger,node_editor.clone_ref(),graph,fonts);
+        let command_palette = CommandPalette::new(&logger,application,fonts);
         world.add_child(&text_editor.display_object());
         world.add_child(&node_editor);
         world.add_child(&node_searcher);
+        world.add_child(&command_palette);
         let size  = zero();
         let scene = world.scene();
         let mouse = &scene.mouse.frp;
-        frp::new_network! { network def mouse_position_sampler = mouse.position.sampler(); }
+        let graph_editor    = node_editor.graph.graph_editor();
+        let palette_visible = &command_palette.status.is_visible;
+        frp::new_network! { network
+            def mouse_position_sampler = mouse.position.sampler();
+            eval palette_visible ((visible)
+                graph_editor.frp.set_command_palette_visibility.emit(visible));
+        }
         let node_searcher_show_action = None;
-        let data = ViewLayoutData{network,text_editor,node_editor,node_searcher,size,logger,
-            node_searcher_show_action,mouse_position_sampler};
+        let data = ViewLayoutData{network,text_editor,node_editor,node_searcher,command_palette,
+            size,logger,node_searcher_show_action,mouse_position_sampler};
         let rc   = Rc::new(RefCell::new(data));
         Ok(Self {rc}.init(world,kb_actions))
     }
@@ -3,9 +3,13 @@
 use crate::prelude::*;
 
 use crate::controller::graph::NodeTrees;
+use crate::controller::visualization::VisualizationPath;
 use crate::model::execution_context::Visualization;
 use crate::model::execution_context::VisualizationId;
 use crate::model::execution_context::VisualizationUpdateData;
+use crate::model::module::PanelLayout;
+use crate::model::module::QualifiedName;
+use crate::model::module::VisualizationMetadata;
 
 use bimap::BiMap;
 use enso_frp as frp;
@@ -105,10 +109,6 @@ impl<Parameter:frp::Data> FencedAction<Parameter> {
 // === GraphEditorIntegration ===
 // ==============================
 
-/// The gap between nodes in pixels on default node layout (when user did not set any position of
-/// node - possible when node was added by editing text).
-const DEFAULT_GAP_BETWEEN_NODES:f32 = 44.0;
-
 /// A structure which handles integration between controller and graph_editor EnsoGl control.
 /// All changes made by user in view are reflected in controller, and all controller notifications
 /// update view accordingly.
@@ -116,8 +116,11 @@ const DEFAULT_GAP_BETWEEN_NODES:f32 = 44.0;
 #[allow(missing_docs)]
 #[derive(Clone,CloneRef,Debug)]
 pub struct GraphEditorIntegratedWithController {
-    model   : Rc<GraphEditorIntegratedWithControllerModel>,
-    network : frp::Network,
+    model                  : Rc<GraphEditorIntegratedWithControllerModel>,
+    network                : frp::Network,
+    /// Restores the visualizations of all displayed nodes, as stored in their metadata. It should
+    /// be emitted once all the visualizations are loaded.
+    restore_visualizations : frp::Source<()>,
 }
 
 impl GraphEditorIntegratedWithController {
@@ -136,6 +139,9 @@ struct GraphEditorIntegratedWithControllerModel {
     expression_views : RefCell<HashMap<graph_editor::NodeId,String>>,
     connection_views : RefCell<BiMap<controller::graph::Connection,graph_editor::EdgeId>>,
     visualizations   : SharedHashMap<graph_editor::NodeId,VisualizationId>,
+    /// Whether the visualizations stored in metadata were restored. Until then, the new nodes do
+    /// not restore their visualizations, as these might be not loaded yet.
+    restored         : Cell<bool>,
 }
 
 
@@ -154,6 +160,12 @@ impl GraphEditorIntegratedWithController {
                     error!(model.logger,"Error while invalidating graph: {err}");
                 }
             }));
+            let restore = FencedAction::fence(&network,f!([model](()) {
+                let result = model.restore_visualizations();
+                if let Err(err) = result {
+                    error!(model.logger,"Error while restoring visualizations: {err}");
+                }
+            }));
         }
         let node_removed = Self::ui_action(&model,
             GraphEditorIntegratedWithControllerModel::node_removed_in_ui,&invalidate.trigger);
@@ -169,6 +181,21 @@ impl GraphEditorIntegratedWithController {
         let visualization_disabled = Self::ui_action(&model,
             GraphEditorIntegratedWithControllerModel::visualization_disabled_in_ui,
             &invalidate.trigger);
+        let visualization_changed = Self::ui_action(&model,
+            GraphEditorIntegratedWithControllerModel::visualization_changed_in_ui,
+            &invalidate.trigger);
+        let widget_data_changed = Self::ui_action(&model,
+            GraphEditorIntegratedWithControllerModel::widget_data_changed_in_ui,
+            &invalidate.trigger);
+        let node_kind_changed = Self::ui_action(&model,
+            GraphEditorIntegratedWithControllerModel::node_kind_changed_in_ui,
+            &invalidate.trigger);
+        let visualization_panel_changed = Self::ui_action(&model,
+            GraphEditorIntegratedWithControllerModel::visualization_panel_changed_in_ui,
+            &invalidate.trigger);
+        let node_comment_changed = Self::ui_action(&model,
+            GraphEditorIntegratedWithControllerModel::node_comment_changed_in_ui,
+            &invalidate.trigger);
         frp::extend! {network
             // Notifications from controller
             let handle_notification = FencedAction::fence(&network,
@@ -178,16 +205,37 @@ impl GraphEditorIntegratedWithController {
 
             // Changes in Graph Editor
             let is_handling_notification = handle_notification.is_running;
-            def is_hold = is_handling_notification.all_with(&invalidate.is_running, |l,r| *l || *r);
+            def is_hold = is_handling_notification.all_with3(&invalidate.is_running,
+                &restore.is_running, |a,b,c| *a || *b || *c);
             def _action = editor_outs.node_removed             .map2(&is_hold,node_removed);
             def _action = editor_outs.connection_added         .map2(&is_hold,connection_created);
             def _action = editor_outs.visualization_enabled    .map2(&is_hold,visualization_enabled);
             def _action = editor_outs.visualization_disabled   .map2(&is_hold,visualization_disabled);
+            def _action = editor_outs.visualization_changed    .map2(&is_hold,visualization_changed);
+            def _action = editor_outs.widget_data_changed      .map2(&is_hold,widget_data_changed);
+            def _action = editor_outs.node_kind_changed        .map2(&is_hold,node_kind_changed);
+            def _action = editor_outs.visualization_panel_changed
+                .map2(&is_hold,visualization_panel_changed);
+            def _action = editor_outs.node_comment_changed     .map2(&is_hold,node_comment_changed);
             def _action = editor_outs.connection_removed       .map2(&is_hold,connection_removed);
             def _action = editor_outs.node_position_set_batched.map2(&is_hold,node_moved);
+
+            // The nodes without position are placed once the graph editor events are passed to
+            // the controllers again, so their positions are stored in metadata by `node_moved`.
+            def hold_released = is_hold.filter(|is_hold| !is_hold);
+            eval_ hold_released ([model] {
+                if let Err(err) = model.place_nodes_without_position() {
+                    error!(model.logger,"Error while placing nodes: {err}");
+                }
+            });
         }
         Self::connect_frp_to_controller_notifications(&model,handle_notification.trigger);
-        Self {model,network}
+        Self::connect_to_computed_values(&model);
+        if let Err(err) = model.place_nodes_without_position() {
+            error!(model.logger,"Error while placing nodes: {err}");
+        }
+        let restore_visualizations = restore.trigger;
+        Self {model,network,restore_visualizations}
     }
 
     fn connect_frp_to_controller_notifications
@@ -203,6 +251,16 @@ impl GraphEditorIntegratedWithController {
         executor::global::spawn(handler);
     }
 
+    fn connect_to_computed_values(model:&Rc<GraphEditorIntegratedWithControllerModel>) {
+        let stream  = model.controller.computed_value_info_registry().subscribe();
+        let weak    = Rc::downgrade(model);
+        let handler = process_stream_with_handle(stream,weak,move |ids,model| {
+            model.update_node_types(&ids);
+            futures::future::ready(())
+        });
+        executor::global::spawn(handler);
+    }
+
     /// Convert a function being a method of GraphEditorIntegratedWithControllerModel to a closure
     /// suitable for connecting to GraphEditor frp network. Returned lambda takes `Parameter` and a
     /// bool, which indicates if this action is currently on hold (e.g. due to performing
@@ -234,8 +292,9 @@ impl GraphEditorIntegratedWithControllerModel {
         let connection_views = default();
         let expression_views = default();
         let visualizations   = default();
+        let restored         = default();
         let this = GraphEditorIntegratedWithControllerModel {editor,controller,node_views,
-            expression_views,connection_views,logger,visualizations};
+            expression_views,connection_views,logger,visualizations,restored};
 
         if let Err(err) = this.update_graph_view() {
             error!(this.logger,"Error while initializing graph editor: {err}");
@@ -262,14 +321,51 @@ impl GraphEditorIntegratedWithControllerModel {
         let nodes = self.controller.graph.nodes()?;
         let ids   = nodes.iter().map(|node| node.info.id() ).collect();
         self.retain_node_views(&ids);
-        for (i,node_info) in nodes.iter().enumerate() {
-            let id          = node_info.info.id();
-            let node_trees  = trees.remove(&id).unwrap_or_else(default);
-            let default_pos = Vector2(0.0, i as f32 * -DEFAULT_GAP_BETWEEN_NODES);
-            let displayed   = self.node_views.borrow_mut().get_by_left(&id).cloned();
+        for node_info in &nodes {
+            let id         = node_info.info.id();
+            let node_trees = trees.remove(&id).unwrap_or_else(default);
+            let displayed  = self.node_views.borrow_mut().get_by_left(&id).cloned();
             match displayed {
                 Some(displayed) => self.update_node_view(displayed,node_info,node_trees),
-                None            => self.create_node_view(node_info,node_trees,default_pos),
+                None            => self.create_node_view(node_info,node_trees),
+            }
+        }
+        Ok(())
+    }
+
+    /// Places the nodes which have no position in metadata (e.g. added by editing text) using the
+    /// automatic layout. The positions are set in the graph editor, which passes them to the
+    /// controllers just like the positions of nodes moved by the user, so this should not be
+    /// called while the graph editor events are on hold.
+    fn place_nodes_without_position(&self) -> FallibleResult<()> {
+        use controller::graph::Connections;
+        use graph_editor::layout;
+        let nodes        = self.controller.graph.nodes()?;
+        let has_position = |node:&controller::graph::Node| {
+            node.metadata.as_ref().and_then(|md| md.position).is_some()
+        };
+        if nodes.iter().all(has_position) {
+            return Ok(())
+        }
+        let Connections{connections,..} = self.controller.graph.connections()?;
+        let node_views   = self.node_views.borrow();
+        let size_of      = |id| node_views.get_by_left(id).and_then(|displayed| {
+            let node = self.editor.nodes.get_cloned_ref(displayed)?;
+            Some(Vector2(node.width(),node.height()))
+        }).unwrap_or_else(|| Vector2(0.0,graph_editor::component::node::NODE_HEIGHT));
+        let layout_nodes = nodes.iter().map(|node| {
+            let id       = node.info.id();
+            let position = node.metadata.as_ref().and_then(|md| md.position);
+            match position {
+                Some(position) => layout::Node::fixed(id,size_of(&id),position.vector),
+                None           => layout::Node::new(id,size_of(&id)),
+            }
+        }).collect();
+        let edges  = connections.iter().map(|con| (con.source.node,con.destination.node));
+        let layout = layout::Layout::new(layout_nodes,edges.collect());
+        for (id,position) in layout.arrange() {
+            if let Some(displayed) = node_views.get_by_left(&id) {
+                self.editor.frp.inputs.set_node_position.emit_event(&(*displayed,position));
             }
         }
         Ok(())
@@ -288,52 +384,97 @@ impl GraphEditorIntegratedWithControllerModel {
         }
     }
 
-    fn create_node_view
-    (&self, info:&controller::graph::Node, trees:NodeTrees, default_pos:Vector2) {
+    fn create_node_view(&self, info:&controller::graph::Node, trees:NodeTrees) {
         let id           = info.info.id();
         let displayed_id = self.editor.add_node();
         self.update_node_view(displayed_id,info,trees);
-        // If position wasn't present in metadata, we must initialize it.
-        if info.metadata.and_then(|md| md.position).is_none() {
-            self.editor.frp.inputs.set_node_position.emit_event(&(displayed_id,default_pos));
-        }
         self.node_views.borrow_mut().insert(id, displayed_id);
+        if self.restored.get() {
+            self.restore_visualization(displayed_id,info);
+        }
+    }
+
+    /// Restore the visualizations of all displayed nodes, as stored in their metadata. The nodes
+    /// created later restore their visualizations on their own.
+    fn restore_visualizations(&self) -> FallibleResult<()> {
+        self.restored.set(true);
+        for node in self.controller.graph.nodes()? {
+            if let Ok(displayed_id) = self.get_displayed_node_id(node.info.id()) {
+                self.restore_visualization(displayed_id,&node);
+            }
+        }
+        Ok(())
+    }
+
+    /// Restore the visualization chosen for the node, as stored in its metadata.
+    fn restore_visualization
+    (&self, displayed_id:graph_editor::NodeId, info:&controller::graph::Node) {
+        let stored = info.metadata.as_ref().and_then(|md| md.visualization.clone());
+        if let Some(stored) = stored {
+            let path   = visualization::Path::new(stored.library.as_str(),stored.name.as_str());
+            let inputs = &self.editor.frp.inputs;
+            inputs.set_visualization.emit_event(&(displayed_id,Some(path)));
+            if stored.widget {
+                let kind = graph_editor::component::node::Kind::Widget;
+                inputs.set_node_kind.emit_event(&(displayed_id,kind));
+            }
+            let attached = |this:&Self| this.get_controller_visualization_id(displayed_id).is_ok();
+            if stored.visible && !attached(self) {
+                inputs.enable_visualization.emit_event(&displayed_id);
+                // The editor events are not passed to the controllers when we are handling
+                // controller's notifications, so we may need to attach the visualization here.
+                if !attached(self) {
+                    if let Err(err) = self.visualization_enabled_in_ui(&displayed_id) {
+                        error!(self.logger,"Failed to restore visualization: {err}");
+                    }
+                }
+            }
+            let panel = stored.panel.map(|panel| {
+                visualization::PanelLayout::new(panel.position.vector,panel.size)
+            });
+            inputs.set_visualization_panel.emit_event(&(displayed_id,panel));
+        }
     }
 
     /// Return an asynchronous event processor that routes visualization update to the given's
-    /// visualization respective FRP endpoint.
+    /// visualization respective FRP endpoint. The update is decoded according to the input format
+    /// of the visualization currently chosen for the node. If the decoding fails, the error is
+    /// passed to the visualization instead. The error is cleared by the first successful update
+    /// after it.
     fn visualization_update_handler
     ( &self
     , endpoint : frp::Source<(graph_editor::NodeId,visualization::Data)>
     , node_id  : graph_editor::NodeId
     ) -> impl FnMut(VisualizationUpdateData) -> futures::future::Ready<()> {
-        // TODO [mwu]
-        //  For now only JSON visualizations are supported, so we can just assume JSON data in the
-        //  binary package.
-        let logger = self.logger.clone_ref();
+        let logger         = self.logger.clone_ref();
+        let nodes          = self.editor.nodes.clone_ref();
+        let error_endpoint = self.editor.frp.inputs.set_visualization_data_error.clone_ref();
+        let mut has_error  = false;
         move |update| {
-            match Self::deserialize_visualization_data(update) {
-                Ok (data)  => endpoint.emit((node_id,data)),
-                Err(error) =>
-                    // TODO [mwu]
-                    //  We should consider having the visualization also accept error input.
-                    error!(logger, "Failed to deserialize visualization update. {error}"),
+            let node      = nodes.get_cloned_ref(&node_id);
+            let signature = node.and_then(|node| node.visualization_signature());
+            let format    = signature.map(|signature| *signature.input_format).unwrap_or_default();
+            match visualization::Data::decode(format,update.as_ref()) {
+                Ok (data)  => {
+                    if has_error {
+                        has_error = false;
+                        error_endpoint.emit((node_id,None));
+                    }
+                    endpoint.emit((node_id,data));
+                }
+                Err(error) => {
+                    error!(logger, "Failed to decode visualization update. {error}");
+                    has_error = true;
+                    error_endpoint.emit((node_id,Some(error.into())));
+                }
             }
             futures::future::ready(())
         }
     }
 
-    fn deserialize_visualization_data
-    (data:VisualizationUpdateData) -> FallibleResult<visualization::Data> {
-        let binary  = data.as_ref();
-        let as_text = std::str::from_utf8(binary)?;
-        let as_json : serde_json::Value = serde_json::from_str(as_text)?;
-        Ok(visualization::Data::from(as_json))
-    }
-
     fn update_node_view
     (&self, node:graph_editor::NodeId, info:&controller::graph::Node, trees:NodeTrees) {
-        let position = info.metadata.and_then(|md| md.position);
+        let position = info.metadata.as_ref().and_then(|md| md.position);
         if let Some(pos) = position {
             self.editor.frp.inputs.set_node_position.emit_event(&(node,pos.vector));
         }
@@ -347,6 +488,26 @@ impl GraphEditorIntegratedWithControllerModel {
             self.editor.frp.inputs.set_node_expression.emit_event(&(node,code_and_trees));
             self.expression_views.borrow_mut().insert(node, expression);
         }
+        let comment = info.documentation.clone().unwrap_or_default();
+        self.editor.frp.inputs.set_node_comment.emit_event(&(node,comment));
+        self.update_node_type(node,&info.info.id());
+    }
+
+    /// Update the types of the displayed nodes of the given expressions, if there are any.
+    fn update_node_types(&self, ids:&[ast::Id]) {
+        for id in ids {
+            let displayed = self.node_views.borrow().get_by_left(id).cloned();
+            if let Some(displayed) = displayed {
+                self.update_node_type(displayed,id);
+            }
+        }
+    }
+
+    fn update_node_type(&self, node:graph_editor::NodeId, id:&ast::Id) {
+        let info     = self.controller.computed_value_info_registry().get(id);
+        let typename = info.and_then(|info| info.typename.clone());
+        let tp       = typename.map(graph_editor::data::EnsoType::from);
+        self.editor.frp.inputs.set_node_type.emit_event(&(node,tp));
     }
 
     fn update_connection_views
@@ -444,6 +605,42 @@ impl GraphEditorIntegratedWithControllerModel {
         Ok(())
     }
 
+    fn widget_data_changed_in_ui
+    (&self, param:&(graph_editor::NodeId,serde_json::Value)) -> FallibleResult<()> {
+        let (displayed_id,data) = param;
+        let id                  = self.get_controller_node_id(*displayed_id)?;
+        self.controller.graph.set_widget_data(id,data)
+    }
+
+    fn node_kind_changed_in_ui
+    (&self, param:&(graph_editor::NodeId,graph_editor::component::node::Kind))
+    -> FallibleResult<()> {
+        let (displayed_id,kind) = param;
+        let widget              = *kind == graph_editor::component::node::Kind::Widget;
+        self.with_visualization_metadata(*displayed_id, |visualization| {
+            visualization.widget = widget
+        })
+    }
+
+    fn visualization_panel_changed_in_ui
+    (&self, param:&(graph_editor::NodeId,Option<visualization::PanelLayout>))
+    -> FallibleResult<()> {
+        let (displayed_id,layout) = param;
+        let panel                 = layout.map(|layout| {
+            let position = model::module::Position::new(layout.position.x,layout.position.y);
+            PanelLayout {position,size:layout.size}
+        });
+        self.with_visualization_metadata(*displayed_id, |visualization| visualization.panel = panel)
+    }
+
+    fn node_comment_changed_in_ui
+    (&self, param:&(graph_editor::NodeId,String)) -> FallibleResult<()> {
+        let (displayed_id,comment) = param;
+        let id                     = self.get_controller_node_id(*displayed_id)?;
+        let documentation          = (!comment.is_empty()).as_some(comment.as_str());
+        self.controller.graph.set_node_documentation(id,documentation)
+    }
+
     fn connection_removed_in_ui(&self, edge_id:&graph_editor::EdgeId) -> FallibleResult<()> {
         let connection = self.get_controller_connection(*edge_id)?;
         self.connection_views.borrow_mut().remove_by_left(&connection);
@@ -455,26 +652,61 @@ impl GraphEditorIntegratedWithControllerModel {
     /// from the graph editor endpoints.
     fn prepare_visualization
     (&self, node_id:&graph_editor::NodeId) -> FallibleResult<Visualization> {
-        use crate::model::module::QualifiedName;
-
-        // TODO [mwu]
-        //   Currently it is not possible to:
-        //    * use other project name than the default;
-        //    * enter other module than the initial (namely, "Main")
-        //    * describe that visualization's expression wishes to be evaluated in any other
-        //      context.
-        //   Because of that for now we will just hardcode the `visualization_module` using
-        //   fixed defaults. In future this will be changed, then the editor will also get access
-        //   to the customised values.
-        let project_name         = crate::DEFAULT_PROJECT_NAME;
-        let module_name          = crate::view::project::INITIAL_MODULE_NAME;
-        let visualisation_module = QualifiedName::from_module_segments(&[module_name],project_name);
-        let id                   = VisualizationId::new_v4();
-        let expression           = crate::constants::SERIALIZE_TO_JSON_EXPRESSION.into();
-        let ast_id               = self.get_controller_node_id(*node_id)?;
+        let (expression,visualisation_module) = self.visualization_preprocessor(*node_id);
+        let id                                = VisualizationId::new_v4();
+        let ast_id                            = self.get_controller_node_id(*node_id)?;
         Ok(Visualization{ast_id,expression,id,visualisation_module})
     }
 
+    /// The preprocessor expression and the module it should be evaluated in, as declared by the
+    /// visualization chosen for the node. If none was chosen, the default preprocessor is used.
+    fn visualization_preprocessor
+    (&self, node_id:graph_editor::NodeId) -> (String,QualifiedName) {
+        let node         = self.editor.nodes.get_cloned_ref(&node_id);
+        let signature    = node.and_then(|node| node.visualization_signature());
+        let preprocessor = signature.map(|signature| signature.preprocessor).unwrap_or_default();
+        let expression   = preprocessor.code.to_string();
+        let module       = match preprocessor.module {
+            Some(module) => QualifiedName::from_text(module.to_string()),
+            None         => self.controller.module_qualified_name().clone(),
+        };
+        (expression,module)
+    }
+
+    /// The metadata of the visualization currently chosen for the node in the graph editor, e.g.
+    /// the default one if the user has not chosen any.
+    fn displayed_visualization_metadata
+    (&self, node_id:graph_editor::NodeId) -> Option<VisualizationMetadata> {
+        let node      = self.editor.nodes.get_cloned_ref(&node_id)?;
+        let signature = node.visualization_signature()?;
+        let library   = signature.path.library.to_string();
+        let name      = signature.path.name.to_string();
+        Some(VisualizationMetadata {library,name,..default()})
+    }
+
+    /// Updates the visualization metadata of the node, creating them first for the displayed
+    /// visualization if they are missing.
+    fn with_visualization_metadata
+    (&self, node_id:graph_editor::NodeId, f:impl FnOnce(&mut VisualizationMetadata))
+    -> FallibleResult<()> {
+        let id        = self.get_controller_node_id(node_id)?;
+        let displayed = self.displayed_visualization_metadata(node_id);
+        self.controller.graph.module.with_node_metadata(id, |md| {
+            if md.visualization.is_none() {
+                md.visualization = displayed;
+            }
+            if let Some(visualization) = &mut md.visualization {
+                f(visualization);
+            }
+        });
+        Ok(())
+    }
+
+    fn set_visualization_visibility_in_metadata
+    (&self, node_id:graph_editor::NodeId, visible:bool) -> FallibleResult<()> {
+        self.with_visualization_metadata(node_id, |visualization| visualization.visible = visible)
+    }
+
     fn visualization_enabled_in_ui(&self, node_id:&graph_editor::NodeId) -> FallibleResult<()> {
         // Do nothing if there is already a visualization attached.
         let err = || VisualizationAlreadyAttached(*node_id);
@@ -482,6 +714,7 @@ impl GraphEditorIntegratedWithControllerModel {
 
         debug!(self.logger, "Attaching visualization on {node_id}.");
         let visualization  = self.prepare_visualization(node_id)?;
+        self.set_visualization_visibility_in_metadata(*node_id,true)?;
         let id             = visualization.id;
         let node_id        = *node_id;
         let controller     = self.controller.clone_ref();
@@ -517,6 +750,7 @@ impl GraphEditorIntegratedWithControllerModel {
 
         // We first detach to allow re-attaching even before server confirms the operation.
         visualizations.remove(&node_id);
+        self.set_visualization_visibility_in_metadata(node_id,false)?;
 
         let detach_action = async move {
             if graph.detach_visualization(&id).await.is_ok() {
@@ -537,6 +771,42 @@ impl GraphEditorIntegratedWithControllerModel {
         crate::executor::global::spawn(detach_action);
         Ok(())
     }
+
+    fn visualization_changed_in_ui
+    (&self, param:&(graph_editor::NodeId,Option<visualization::Signature>)) -> FallibleResult<()> {
+        let (node_id,signature) = param;
+        let id                  = self.get_controller_node_id(*node_id)?;
+        let node                = self.editor.nodes.get_cloned_ref(node_id);
+        let visible             = node.map_or(false, |node| node.visualization.is_visible());
+        self.controller.graph.module.with_node_metadata(id, |md| {
+            let previous     = md.visualization.take();
+            let widget       = previous.as_ref().map_or(false, |old| old.widget);
+            let panel        = previous.as_ref().and_then(|old| old.panel);
+            md.visualization = signature.as_ref().map(|signature| {
+                let library = signature.path.library.to_string();
+                let name    = signature.path.name.to_string();
+                let is_same = |old:&VisualizationMetadata| old.library == library
+                    && old.name == name;
+                let config  = previous.filter(is_same).map(|old| old.config).unwrap_or_default();
+                VisualizationMetadata {library,name,visible,config,widget,panel}
+            });
+        });
+
+        if let Ok(vis_id) = self.get_controller_visualization_id(*node_id) {
+            debug!(self.logger,"Modifying visualization {vis_id} of node {node_id}.");
+            let (expression,module) = self.visualization_preprocessor(*node_id);
+            let controller          = self.controller.clone_ref();
+            let logger              = self.logger.clone_ref();
+            let modify_action       = async move {
+                let result = controller.modify_visualization(&vis_id,expression,module).await;
+                if let Err(err) = result {
+                    error!(logger,"Failed to modify visualization {vis_id}: {err}");
+                }
+            };
+            executor::global::spawn(modify_action);
+        }
+        Ok(())
+    }
 }
 
 
@@ -583,6 +853,90 @@ impl GraphEditorIntegratedWithControllerModel {
 
 
 
+// ===========================
+// === VisualizationLoader ===
+// ===========================
+
+/// Loads the visualizations listed by the visualization controller into the graph editor, and
+/// keeps them up to date with the changes of their files.
+#[derive(Clone,CloneRef,Debug)]
+struct VisualizationLoader {
+    logger     : Logger,
+    controller : controller::Visualization,
+    /// The paths the loaded visualizations are registered with in the graph editor.
+    loaded     : SharedHashMap<VisualizationPath,visualization::Path>,
+}
+
+impl VisualizationLoader {
+    fn new(logger:impl AnyLogger, controller:controller::Visualization) -> Self {
+        let logger = Logger::sub(logger,"VisualizationLoader");
+        let loaded = default();
+        Self {logger,controller,loaded}
+    }
+
+    /// Load all the available visualizations. The failures are logged, so a single broken
+    /// visualization file does not prevent the others from being loaded.
+    async fn load_all(&self, editor:&GraphEditor) {
+        match self.controller.list_visualizations().await {
+            Ok(identifiers) => for identifier in identifiers {
+                self.load(editor,identifier).await;
+            },
+            Err(err) => error!(self.logger,"Failed to list the visualizations: {err}"),
+        }
+    }
+
+    async fn load(&self, editor:&GraphEditor, identifier:VisualizationPath) {
+        match self.controller.load_visualization(&identifier).await {
+            Ok(definition) => {
+                let path = definition.signature.path.clone_ref();
+                info!(self.logger,"Registering visualization {path:?} from {identifier}.");
+                // The file could have changed the visualization name.
+                if let Some(old_path) = self.loaded.insert(identifier,path.clone_ref()) {
+                    if old_path != path {
+                        editor.frp.unregister_visualization.emit(Some(old_path));
+                    }
+                }
+                editor.frp.register_visualization.emit(Some(definition));
+            }
+            Err(err) => error!(self.logger,"{err}"),
+        }
+    }
+
+    fn unload(&self, editor:&GraphEditor, identifier:&VisualizationPath) {
+        if let Some(path) = self.loaded.remove(identifier) {
+            info!(self.logger,"Unregistering visualization {path:?} from {identifier}.");
+            editor.frp.unregister_visualization.emit(Some(path));
+        }
+    }
+
+    /// Reload the visualizations when their files change, as long as the graph is displayed.
+    async fn watch(&self, graph:&Rc<GraphEditorIntegratedWithController>) {
+        // We subscribe first, so the changes reported while acquiring the capabilities are not
+        // lost.
+        let stream  = self.controller.subscribe();
+        let weak    = Rc::downgrade(graph);
+        let this    = self.clone_ref();
+        let handler = process_stream_with_handle(stream,weak,move |notification,graph| {
+            let this = this.clone_ref();
+            async move {
+                let editor = graph.graph_editor();
+                match notification {
+                    controller::visualization::Notification::Changed(identifier) =>
+                        this.load(&editor,identifier).await,
+                    controller::visualization::Notification::Removed(identifier) =>
+                        this.unload(&editor,&identifier),
+                }
+            }
+        });
+        executor::global::spawn(handler);
+        if let Err(err) = self.controller.watch_visualization_folders().await {
+            warning!(self.logger,"Visualizations will not be reloaded on file changes: {err}");
+        }
+    }
+}
+
+
+
 // ==================
 // === NodeEditor ===
 // ==================
@@ -594,7 +948,7 @@ pub struct NodeEditor {
     #[allow(missing_docs)]
     pub graph     : Rc<GraphEditorIntegratedWithController>,
     controller    : controller::ExecutedGraph,
-    visualization : controller::Visualization
+    visualization : VisualizationLoader,
 }
 
 impl NodeEditor {
@@ -606,6 +960,7 @@ impl NodeEditor {
     , visualization : controller::Visualization) -> FallibleResult<Self> {
         let logger         = Logger::sub(logger,"NodeEditor");
         let display_object = display::object::Instance::new(&logger);
+        let visualization  = VisualizationLoader::new(&logger,visualization);
         let graph          = GraphEditorIntegratedWithController::new(logger,app,controller.clone_ref());
         let graph          = Rc::new(graph);
         display_object.add_child(&graph.model.editor);
@@ -613,16 +968,10 @@ impl NodeEditor {
     }
 
     async fn init(self) -> FallibleResult<Self> {
-        let graph_editor = self.graph.graph_editor();
-        let identifiers  = self.visualization.list_visualizations().await;
-        let identifiers  = identifiers.unwrap_or_default();
-        for identifier in identifiers {
-            let visualization = self.visualization.load_visualization(&identifier).await;
-            let visualization = visualization.map(|visualization| {
-                graph_editor.frp.register_visualization.emit(Some(visualization));
-            });
-            visualization?;
-        }
+        self.visualization.load_all(&self.graph.graph_editor()).await;
+        // Visualizations chosen for nodes could not be restored before all of them were loaded.
+        self.graph.restore_visualizations.emit(());
+        self.visualization.watch(&self.graph).await;
         Ok(self)
     }
 }
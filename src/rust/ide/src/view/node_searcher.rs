@@ -62,7 +62,7 @@ impl NodeSearcher {
                 let position      = node_searcher.display_object.position();
                 let position      = position - node_searcher.node_editor.position();
                 let position      = Some(Position{vector:Vector2::new(position.x,position.y)});
-                let metadata      = Some(NodeMetadata{position});
+                let metadata      = Some(NodeMetadata::at_position(position));
                 let id            = None;
                 let location_hint = LocationHint::End;
                 let expression    = expression.to_string();
@@ -3,11 +3,15 @@
 
 use crate::prelude::*;
 
+use crate::config::KEYMAP_FILE;
 use crate::double_representation::definition::DefinitionName;
 use crate::model::module::Path as ModulePath;
+use crate::view::command_palette::CommandPalette;
 use crate::view::layout::ViewLayout;
 
 use ensogl::application::Application;
+use ensogl::application::keymap::Keymap;
+use ensogl::application::keymap;
 use ensogl::control::callback;
 use ensogl::control::io::keyboard::listener::KeyboardFrpBindings;
 use ensogl::data::color;
@@ -16,6 +20,7 @@ use ensogl::display::style::theme;
 use ensogl::system::web;
 use enso_frp::io::keyboard::Keyboard;
 use enso_frp::io::keyboard;
+use enso_protocol::language_server;
 use nalgebra::Vector2;
 use shapely::shared;
 
@@ -88,11 +93,12 @@ impl ProjectView {
         let graph_controller     = module_controller.executed_graph_controller_unchecked(graph_id,&controller);
         let graph_controller     = graph_controller.await?;
         let application          = Application::new(&web::get_html_element_by_id("root").unwrap());
+        let logger               = Logger::sub(logger,"ProjectView");
         Self::setup_components(&application);
         Self::setup_theme(&application);
+        Self::setup_keymap(&logger,&application,&controller).await;
         let _world               = &application.display;
         // graph::register_shapes(&world);
-        let logger                   = Logger::sub(logger,"ProjectView");
         let keyboard                 = Keyboard::default();
         let keyboard_bindings        = KeyboardFrpBindings::new(&logger,&keyboard);
         let mut keyboard_actions     = keyboard::Actions::new(&keyboard);
@@ -122,6 +128,36 @@ impl ProjectView {
 
     fn setup_components(app:&Application) {
         app.views.register::<graph_editor::GraphEditor>();
+        app.views.register::<CommandPalette>();
+    }
+
+    /// Loads the user keymap from the `KEYMAP_FILE` in the project root, if it exists. The keymap
+    /// is stored per project, as the IDE has no access to user configuration outside of the
+    /// project, so it needs to be copied to every project it should apply to. The keymap errors
+    /// are logged, and the default shortcuts are used in place of the invalid ones.
+    async fn setup_keymap(logger:&Logger, app:&Application, project:&controller::Project) {
+        match Self::read_keymap(project).await {
+            Ok(Some((keymap,errors))) => {
+                for error in errors {
+                    warning!(logger,"{error} The shortcut will be ignored.");
+                }
+                app.views.load_keymap(keymap);
+            },
+            Ok(None) => {},
+            Err(err) => error!(logger,"Failed to load the keymap: {err}"),
+        }
+    }
+
+    async fn read_keymap
+    (project:&controller::Project) -> FallibleResult<Option<(Keymap,Vec<keymap::Error>)>> {
+        let path = language_server::Path::new(project.content_root_id(),&[KEYMAP_FILE]);
+        let rpc  = &project.language_server_rpc;
+        if rpc.file_exists(&path).await?.exists {
+            let contents = rpc.read_file(&path).await?.contents;
+            Ok(Some(Keymap::from_json(&contents)?))
+        } else {
+            Ok(None)
+        }
     }
 
     fn setup_theme(app:&Application) {
@@ -145,3 +181,32 @@ impl ProjectView {
         std::mem::forget(self)
     }
 }
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ensogl::application::shortcut::DefaultShortcutProvider;
+    use ensogl::application::shortcut::docs_table;
+    use graph_editor::GraphEditor;
+
+    /// The shortcut tables of `docs/shortcuts.md` are generated, so they must be regenerated when
+    /// the default shortcuts change.
+    #[test]
+    fn shortcut_docs_are_up_to_date() {
+        let docs   = include_str!("../../../../../docs/shortcuts.md");
+        let tables = vec!
+            [ docs_table(&GraphEditor::default_shortcut_docs())
+            , docs_table(&CommandPalette::default_shortcut_docs())
+            ];
+        for table in tables {
+            assert!(docs.contains(&table),"Outdated docs/shortcuts.md, expected:\n{}",table);
+        }
+    }
+}
@@ -1,7 +1,11 @@
 //! The common structures for text location and manipulation.
+//!
+//! The `Index`, `Size` and `Span` structures, and thus the `TextChange`, count characters, not
+//! bytes. Use `ByteIndex` for the byte offsets.
 
 use enso_prelude::*;
 
+use std::iter;
 use std::ops::Add;
 use std::ops::AddAssign;
 use std::ops::Range;
@@ -18,7 +22,7 @@ use serde::Deserialize;
 
 // === Index ===
 
-/// Strongly typed index into container.
+/// Strongly typed index of character in String.
 #[allow(missing_docs)]
 #[derive(Clone,Copy,Debug,Default,Hash,PartialEq,Eq,PartialOrd,Ord,Serialize,Deserialize)]
 pub struct Index { pub value:usize }
@@ -52,12 +56,20 @@ impl ByteIndex {
     pub fn new(value:usize) -> Self {
         ByteIndex {value}
     }
+
+    /// Create byte index from the char index. It must traverse the content to find the char. The
+    /// index past the last character is converted to the content length.
+    pub fn convert_index(content:impl Str, index:Index) -> Self {
+        let content = content.as_ref();
+        let byte    = content.char_indices().nth(index.value).map(|(byte,_)| byte);
+        Self::new(byte.unwrap_or_else(|| content.len()))
+    }
 }
 
 
 // === Size ===
 
-/// Strongly typed size of container.
+/// Strongly typed size of container, in characters in case of text.
 #[allow(missing_docs)]
 #[derive(Clone,Copy,Debug,Default,Hash,PartialEq,Eq,PartialOrd,Ord,Serialize,Deserialize)]
 pub struct Size { pub value:usize }
@@ -113,7 +125,7 @@ impl Display for Size {
 
 impl From<&str> for Size {
     fn from(text:&str) -> Self {
-        Size::new(text.len())
+        Size::new(text.chars().count())
     }
 }
 
@@ -214,6 +226,22 @@ impl Span {
     pub fn set_right(&mut self, new_right:Index) {
         self.size = new_right - self.index;
     }
+
+    /// Converts the span of characters in the text to the range of bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the span is out of the text bounds.
+    pub fn byte_range_in(&self, text:&str) -> Range<usize> {
+        let mut bytes     = text.char_indices().map(|(byte,_)| byte).chain(iter::once(text.len()));
+        let out_of_bounds = || panic!("The span {} is out of the text bounds.",self);
+        let start         = bytes.nth(self.index.value).unwrap_or_else(out_of_bounds);
+        let end           = match self.size.value {
+            0    => start,
+            size => bytes.nth(size - 1).unwrap_or_else(out_of_bounds),
+        };
+        start..end
+    }
 }
 
 impls! { From + &From <Range<usize>> for Span { |range|
@@ -240,7 +268,7 @@ impl std::ops::Index<Span> for str {
     type Output = str;
 
     fn index(&self, index:Span) -> &Self::Output {
-        &self[index.range()]
+        &self[index.byte_range_in(self)]
     }
 }
 
@@ -436,10 +464,8 @@ impl<Content> TextChangeTemplate<Index,Content> {
     ///
     /// Panics if the replaced span is out of the string value bounds.
     pub fn apply(&self, target:&mut String) where Content:AsRef<str> {
-        //debug!(logger, "change: {change:?}, my code: \n```\n{code}\n```");
-        let replaced_indices  = self.replaced.start.value..self.replaced.end.value;
-        //debug!(logger, "replacing range {replaced_indices:?} with {change.inserted}");
-        target.replace_range(replaced_indices,self.inserted.as_ref());
+        let replaced_bytes = self.replaced_span().byte_range_in(target);
+        target.replace_range(replaced_bytes,self.inserted.as_ref());
     }
 
     /// Applies the text edit on string and returns the result.
@@ -522,4 +548,16 @@ mod test {
         assert_eq!(TextLocation::at_document_end("")  , TextLocation {line:0, column:0});
         assert_eq!(TextLocation::at_document_end("\n"), TextLocation {line:1, column:0});
     }
+
+    #[test]
+    fn indexing_non_ascii_text_by_characters() {
+        let str = "zażółć gęślą";
+        assert_eq!(&str[Span::from(2..6)],"żółć");
+        assert_eq!(&str[Span::from(12..12)],"");
+        assert_eq!(Size::from("gęślą"),Size::new(5));
+        assert_eq!(ByteIndex::convert_index(str,Index::new(7)),ByteIndex::new(11));
+
+        let change = TextChange::replace(Index::new(7)..Index::new(12),"jaźń".into());
+        assert_eq!(change.applied(str),"zażółć jaźń");
+    }
 }
@@ -0,0 +1,227 @@
+//! Clocks driving the time-based FRP nodes, like `delay` or `debounce`.
+//!
+//! The nodes do not use the browser timers directly. Instead, they ask a `Clock` for the current
+//! time and to schedule callbacks. The `Clock::web` uses the browser timers, while the
+//! `VirtualClock` allows stepping the time manually, which makes the time-based networks
+//! deterministic and testable without a browser.
+
+use crate::prelude::*;
+
+use crate::web;
+
+
+
+// ============
+// === Time ===
+// ============
+
+/// Time in milliseconds.
+pub type Time = f64;
+
+
+
+// ==================
+// === TimeSource ===
+// ==================
+
+/// Implementors of this trait know the current time and are able to call a function after the
+/// given amount of time has passed.
+pub trait TimeSource : Debug {
+    /// The current time.
+    fn now(&self) -> Time;
+    /// Call the callback once, after `delay` has passed.
+    fn schedule(&self, delay:Time, callback:Box<dyn FnOnce()>);
+}
+
+
+
+// =============
+// === Clock ===
+// =============
+
+/// A cheap, cloneable handle to a `TimeSource`. This is the type consumed by time-based nodes.
+#[derive(Clone,CloneRef,Debug)]
+pub struct Clock {
+    source : Rc<dyn TimeSource>,
+}
+
+impl Clock {
+    /// Constructor.
+    pub fn new<T:TimeSource+'static>(source:T) -> Self {
+        let source = Rc::new(source);
+        Self {source}
+    }
+
+    /// Clock using the browser timers.
+    pub fn web() -> Self {
+        Self::new(WebTimeSource)
+    }
+
+    /// The current time.
+    pub fn now(&self) -> Time {
+        self.source.now()
+    }
+
+    /// Call the callback once, after `delay` has passed.
+    pub fn schedule(&self, delay:Time, callback:impl FnOnce() + 'static) {
+        self.source.schedule(delay,Box::new(callback))
+    }
+}
+
+
+
+// =====================
+// === WebTimeSource ===
+// =====================
+
+/// Time source using `performance.now()` and the browser timers.
+#[derive(Clone,Copy,Debug,Default)]
+pub struct WebTimeSource;
+
+impl TimeSource for WebTimeSource {
+    fn now(&self) -> Time {
+        web::performance().now()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn schedule(&self, delay:Time, callback:Box<dyn FnOnce()>) {
+        let duration = web::Duration::from_secs_f64(delay.max(0.0) / 1000.0);
+        web::set_timeout(duration,callback)
+    }
+
+    /// The native timers call the callbacks on other threads, which the FRP networks cannot be
+    /// used from. Use the `VirtualClock` outside the browser instead.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn schedule(&self, _delay:Time, _callback:Box<dyn FnOnce()>) {
+        panic!("The browser timers are not available outside the browser.")
+    }
+}
+
+
+
+// ====================
+// === VirtualClock ===
+// ====================
+
+/// Scheduled callback of the `VirtualClock`.
+#[derive(Derivative)]
+#[derivative(Debug)]
+struct Timer {
+    time     : Time,
+    #[derivative(Debug="ignore")]
+    callback : Box<dyn FnOnce()>,
+}
+
+/// Internal data of `VirtualClock`.
+#[derive(Debug,Default)]
+pub struct VirtualClockData {
+    time   : Cell<Time>,
+    timers : RefCell<Vec<Timer>>,
+}
+
+/// Deterministic clock whose time passes only when `advance` is called. The callbacks are called in
+/// the order of their scheduled time, and in the order of scheduling if the time is equal.
+#[derive(Clone,CloneRef,Debug,Default)]
+pub struct VirtualClock {
+    data : Rc<VirtualClockData>,
+}
+
+impl VirtualClock {
+    /// Constructor. The clock starts at time zero.
+    pub fn new() -> Self {
+        default()
+    }
+
+    /// A `Clock` handle driven by this virtual clock.
+    pub fn clock(&self) -> Clock {
+        Clock::new(self.clone_ref())
+    }
+
+    /// Move the time forward, calling all the callbacks scheduled in the meantime. Callbacks
+    /// scheduled by other callbacks are called as well, if their time has come.
+    pub fn advance(&self, delta:Time) {
+        let target = self.data.time.get() + delta;
+        while let Some(timer) = self.take_timer_before(target) {
+            self.data.time.set(timer.time);
+            (timer.callback)();
+        }
+        self.data.time.set(target);
+    }
+
+    /// Number of callbacks waiting for their time.
+    pub fn pending_timers(&self) -> usize {
+        self.data.timers.borrow().len()
+    }
+
+    fn take_timer_before(&self, target:Time) -> Option<Timer> {
+        let mut timers = self.data.timers.borrow_mut();
+        let mut first  = None;
+        for (index,timer) in timers.iter().enumerate() {
+            let is_earliest = first.map(|(_,time)| timer.time < time).unwrap_or(true);
+            if timer.time <= target && is_earliest {
+                first = Some((index,timer.time));
+            }
+        }
+        first.map(|(index,_)| timers.remove(index))
+    }
+}
+
+impl TimeSource for VirtualClock {
+    fn now(&self) -> Time {
+        self.data.time.get()
+    }
+
+    fn schedule(&self, delay:Time, callback:Box<dyn FnOnce()>) {
+        let time = self.data.time.get() + delay.max(0.0);
+        self.data.timers.borrow_mut().push(Timer {time,callback});
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn virtual_clock_calls_timers_scheduled_by_timers() {
+        let log           = Rc::new(RefCell::new(vec![]));
+        let virtual_clock = VirtualClock::new();
+        assert_eq!(virtual_clock.now(),0.0);
+
+        let clock = virtual_clock.clock();
+        let log1  = log.clone_ref();
+        let inner = clock.clone_ref();
+        clock.schedule(5.0, move || {
+            let log2 = log1.clone_ref();
+            log1.borrow_mut().push(10);
+            inner.schedule(5.0, move || log2.borrow_mut().push(20));
+        });
+        virtual_clock.advance(9.0);
+        assert_eq!(*log.borrow(),vec![10]);
+        assert_eq!(virtual_clock.pending_timers(),1);
+        virtual_clock.advance(1.0);
+        assert_eq!(*log.borrow(),vec![10,20]);
+        assert_eq!(virtual_clock.now(),10.0);
+    }
+
+    #[test]
+    fn virtual_clock_orders_equal_times_by_scheduling() {
+        let virtual_clock = VirtualClock::new();
+        let clock         = virtual_clock.clock();
+        let log           = Rc::new(RefCell::new(vec![]));
+        for (delay,value) in vec![(20.0,2),(10.0,1),(20.0,3)] {
+            let log = log.clone_ref();
+            clock.schedule(delay, move || log.borrow_mut().push(value));
+        }
+        virtual_clock.advance(15.0);
+        assert_eq!(*log.borrow(),vec![1]);
+        virtual_clock.advance(5.0);
+        assert_eq!(*log.borrow(),vec![1,2,3]);
+        assert_eq!(virtual_clock.pending_timers(),0);
+    }
+}
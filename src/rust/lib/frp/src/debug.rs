@@ -55,6 +55,7 @@ impl VizLink {
 pub struct Graphviz {
     nodes  : HashMap<usize,VizNode>,
     labels : HashMap<usize,String>,
+    values : HashMap<usize,String>,
     links  : Vec<VizLink>,
 }
 
@@ -67,6 +68,11 @@ impl Graphviz {
         self.nodes.insert(id,VizNode::new(tp,label.clone()));
         self.labels.insert(id,label);
     }
+
+    /// Annotates the node with the debug representation of its value.
+    pub fn set_value<Value:Str>(&mut self, id:usize, value:Value) {
+        self.values.insert(id,value.into());
+    }
 //
 //    /// Defines a new link between nodes.
 //    pub fn add_link<S:Str>
@@ -115,7 +121,11 @@ impl Graphviz {
             let fill    = iformat!("[fillcolor=\"#{color}\"]");
             let spacing = "<br/><FONT POINT-SIZE=\"5\"> </FONT><br/>";
             let variant = iformat!("<FONT POINT-SIZE=\"9\">{node.variant}</FONT>");
-            let label   = iformat!("[label=< {node.label} {spacing} {variant} >]");
+            let value   = self.values.get(idx).map(|value| {
+                let value = escape_html(value);
+                iformat!("<br/><FONT POINT-SIZE=\"9\">= {value}</FONT>")
+            }).unwrap_or_default();
+            let label   = iformat!("[label=< {node.label} {spacing} {variant} {value} >]");
             let line    = iformat!("\n{idx} {fill} {label}");
             code.push_str(&line);
         }
@@ -149,6 +159,11 @@ impl Graphviz {
     }
 }
 
+/// Escapes the text to be used in Graphviz HTML-like labels.
+fn escape_html(text:&str) -> String {
+    text.replace('&',"&amp;").replace('<',"&lt;").replace('>',"&gt;").replace('"',"&quot;")
+}
+
 impl From<Graphviz> for String {
     fn from(cfg:Graphviz) -> String {
         cfg.to_code()
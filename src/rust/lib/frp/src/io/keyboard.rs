@@ -6,6 +6,7 @@ use crate as frp;
 use crate::data::bitfield::BitField256;
 use crate::data::bitfield::BitField;
 use enso_callback as callback;
+use std::collections::BTreeSet;
 
 
 
@@ -13,22 +14,120 @@ use enso_callback as callback;
 // === Key ===
 // ===========
 
-/// A key representation.
+/// A logical key representation, the meaning of the key in the current keyboard layout.
 pub use keyboard_types::Key;
 
+/// A physical key representation, the location of the key on the keyboard.
+pub use keyboard_types::Code;
+
+/// The normalized name of a logical key. Characters are lowercased, so the name does not change
+/// when Shift or Caps Lock is held. Dead keys and keys used during text composition do not have a
+/// name, as they do not mean anything until the composition ends.
+pub fn key_name(key:&Key) -> Option<String> {
+    match key {
+        Key::Dead | Key::Process | Key::Unidentified => None,
+        Key::Character(chars)                        => Some(chars.to_lowercase()),
+        other                                        => Some(other.to_string()),
+    }
+}
+
+
+
+// ============
+// === Code ===
+// ============
+
+/// Names of all physical key codes defined by the W3C UI Events spec. The position of the name is
+/// the index of the `CodeMask` bit of the key, so new codes should be appended at the end.
+pub const CODES : &[&str] =
+    // Alphanumeric section.
+    &[ "Backquote", "Backslash", "BracketLeft", "BracketRight", "Comma", "Digit0", "Digit1"
+     , "Digit2", "Digit3", "Digit4", "Digit5", "Digit6", "Digit7", "Digit8", "Digit9", "Equal"
+     , "IntlBackslash", "IntlRo", "IntlYen", "KeyA", "KeyB", "KeyC", "KeyD", "KeyE", "KeyF"
+     , "KeyG", "KeyH", "KeyI", "KeyJ", "KeyK", "KeyL", "KeyM", "KeyN", "KeyO", "KeyP", "KeyQ"
+     , "KeyR", "KeyS", "KeyT", "KeyU", "KeyV", "KeyW", "KeyX", "KeyY", "KeyZ", "Minus", "Period"
+     , "Quote", "Semicolon", "Slash"
+     // Functional keys of the alphanumeric section.
+     , "AltLeft", "AltRight", "Backspace", "CapsLock", "ContextMenu", "ControlLeft", "ControlRight"
+     , "Enter", "MetaLeft", "MetaRight", "ShiftLeft", "ShiftRight", "Space", "Tab", "Convert"
+     , "KanaMode", "Lang1", "Lang2", "Lang3", "Lang4", "Lang5", "NonConvert"
+     // Control pad and arrow pad sections.
+     , "Delete", "End", "Help", "Home", "Insert", "PageDown", "PageUp", "ArrowDown", "ArrowLeft"
+     , "ArrowRight", "ArrowUp"
+     // Numpad section.
+     , "NumLock", "Numpad0", "Numpad1", "Numpad2", "Numpad3", "Numpad4", "Numpad5", "Numpad6"
+     , "Numpad7", "Numpad8", "Numpad9", "NumpadAdd", "NumpadBackspace", "NumpadClear"
+     , "NumpadClearEntry", "NumpadComma", "NumpadDecimal", "NumpadDivide", "NumpadEnter"
+     , "NumpadEqual", "NumpadHash", "NumpadMemoryAdd", "NumpadMemoryClear", "NumpadMemoryRecall"
+     , "NumpadMemoryStore", "NumpadMemorySubtract", "NumpadMultiply", "NumpadParenLeft"
+     , "NumpadParenRight", "NumpadStar", "NumpadSubtract"
+     // Function section.
+     , "Escape", "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12", "Fn"
+     , "FnLock", "PrintScreen", "ScrollLock", "Pause"
+     // Media keys.
+     , "BrowserBack", "BrowserFavorites", "BrowserForward", "BrowserHome", "BrowserRefresh"
+     , "BrowserSearch", "BrowserStop", "Eject", "LaunchApp1", "LaunchApp2", "LaunchMail"
+     , "MediaPlayPause", "MediaSelect", "MediaStop", "MediaTrackNext", "MediaTrackPrevious"
+     , "Power", "Sleep", "AudioVolumeDown", "AudioVolumeMute", "AudioVolumeUp", "WakeUp"
+     // Legacy, non-standard and special keys.
+     , "Hyper", "Super", "Turbo", "Abort", "Resume", "Suspend", "Again", "Copy", "Cut", "Find"
+     , "Open", "Paste", "Props", "Select", "Undo", "Hiragana", "Katakana"
+     ];
+
+/// The index of the `CodeMask` bit of the physical key, if the key is known.
+pub fn code_index(code:&Code) -> Option<usize> {
+    let name = code.to_string();
+    CODES.iter().position(|t| *t == name)
+}
+
+/// The name of the logical key produced by the physical key in the US layout, see `key_name`. The
+/// keys present on both sides of the keyboard, like `ShiftLeft`, are named after their meaning,
+/// like `Shift`. Returns `None` for the keys without an unambiguous meaning, like the numpad ones.
+pub fn us_layout_key_name(code_name:&str) -> Option<String> {
+    let is_letter   = code_name.len() == 4 && code_name.starts_with("Key");
+    let is_digit    = code_name.len() == 6 && code_name.starts_with("Digit");
+    let is_function = code_name.starts_with('F') && code_name[1..].parse::<u8>().is_ok();
+    let name = match code_name {
+        "Backquote"    => "`",
+        "Backslash"    => "\\",
+        "BracketLeft"  => "[",
+        "BracketRight" => "]",
+        "Comma"        => ",",
+        "Equal"        => "=",
+        "Minus"        => "-",
+        "Period"       => ".",
+        "Quote"        => "'",
+        "Semicolon"    => ";",
+        "Slash"        => "/",
+        "Space"        => " ",
+        "AltLeft"     | "AltRight"     => "Alt",
+        "ControlLeft" | "ControlRight" => "Control",
+        "MetaLeft"    | "MetaRight"    => "Meta",
+        "ShiftLeft"   | "ShiftRight"   => "Shift",
+        "Backspace" | "CapsLock" | "ContextMenu" | "Enter" | "Tab" | "Delete" | "End" | "Help"
+        | "Home" | "Insert" | "PageDown" | "PageUp" | "ArrowDown" | "ArrowLeft" | "ArrowRight"
+        | "ArrowUp" | "Escape" => code_name,
+        _ if is_letter   => return Some(code_name[3..].to_lowercase()),
+        _ if is_digit    => &code_name[5..],
+        _ if is_function => code_name,
+        _                => return None,
+    };
+    Some(name.into())
+}
+
 
 
 // ===============
 // === KeyMask ===
 // ===============
 
-// FIXME: The follwoing implementation uses `key.legacy_keycode` which reports key codes for a very
-//        small amount of keys. We need a better mechanism here.
-
-/// The key bitmask (each bit represents one key). Used for matching key combinations.
-#[derive(Clone,Copy,Debug,Default,Eq,Hash,PartialEq,Shrinkwrap)]
-#[shrinkwrap(mutable)]
-pub struct KeyMask(pub BitField256);
+/// A set of logical keys held at the same time, like `Control` + `s`. Used for matching key
+/// combinations which depend on the keyboard layout. See `key_name` to learn how the keys are
+/// compared.
+#[derive(Clone,Debug,Default,Eq,Hash,PartialEq)]
+pub struct KeyMask {
+    keys : BTreeSet<String>,
+}
 
 impl KeyMask {
     /// Creates Key::Meta + Key::Character.
@@ -51,16 +150,32 @@ impl KeyMask {
         keys.iter().collect()
     }
 
-    /// Check if key bit is on.
+    /// Check if key is in the mask.
     pub fn contains(&self, key:&Key) -> bool {
-        let KeyMask(bit_set) = self;
-        bit_set.get_bit(key.legacy_keycode() as usize)
+        key_name(key).map(|name| self.keys.contains(&name)).unwrap_or(false)
     }
 
-    /// Set the `key` bit for new state.
+    /// Set the `key` state. Keys without a name (see `key_name`) are ignored.
     pub fn set(&mut self, key:&Key, state:bool) {
-        let KeyMask(ref mut bit_set) = self;
-        bit_set.set_bit(key.legacy_keycode() as usize,state);
+        if let Some(name) = key_name(key) {
+            if state { self.keys.insert(name);  }
+            else     { self.keys.remove(&name); }
+        }
+    }
+
+    /// Check if no key is in the mask.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Normalized names of the keys in the mask.
+    pub fn key_names(&self) -> impl Iterator<Item=&str> {
+        self.keys.iter().map(|name| name.as_str())
+    }
+
+    /// Check if all keys of this mask are in the other one.
+    pub fn is_subset(&self, other:&Self) -> bool {
+        self.keys.is_subset(&other.keys)
     }
 }
 
@@ -68,8 +183,7 @@ impl<'a> FromIterator<&'a Key> for KeyMask {
     fn from_iter<T: IntoIterator<Item=&'a Key>>(iter:T) -> Self {
         let mut key_mask = KeyMask::default();
         for key in iter {
-            let bit = key.legacy_keycode() as usize;
-            key_mask.set_bit(bit,true);
+            key_mask.set(key,true);
         }
         key_mask
     }
@@ -84,18 +198,219 @@ impl From<&[Key;5]> for KeyMask { fn from(keys:&[Key;5]) -> Self { KeyMask::from
 
 
 
+// ================
+// === CodeMask ===
+// ================
+
+/// The physical key bitmask (each bit represents one key, see `CODES`). Used for matching key
+/// combinations which do not depend on the keyboard layout.
+#[derive(Clone,Copy,Debug,Default,Eq,Hash,PartialEq,Shrinkwrap)]
+#[shrinkwrap(mutable)]
+pub struct CodeMask(pub BitField256);
+
+impl CodeMask {
+    /// Check if key bit is on.
+    pub fn contains(&self, code:&Code) -> bool {
+        let CodeMask(bit_set) = self;
+        code_index(code).map(|bit| bit_set.get_bit(bit)).unwrap_or(false)
+    }
+
+    /// Set the `code` bit for new state. Unknown codes are ignored.
+    pub fn set(&mut self, code:&Code, state:bool) {
+        let CodeMask(ref mut bit_set) = self;
+        if let Some(bit) = code_index(code) {
+            bit_set.set_bit(bit,state);
+        }
+    }
+
+    /// Check if no bit is on.
+    pub fn is_empty(&self) -> bool {
+        *self == default()
+    }
+
+    /// Check if all keys of this mask are in the other one.
+    pub fn is_subset(&self, other:&Self) -> bool {
+        (0..CODES.len()).all(|bit| !self.get_bit(bit) || other.get_bit(bit))
+    }
+
+    /// Names of the keys in the mask, in the order of `CODES`.
+    pub fn code_names(&self) -> impl Iterator<Item=&'static str> + '_ {
+        let CodeMask(bit_set) = self;
+        CODES.iter().enumerate().filter(move |(bit,_)| bit_set.get_bit(*bit)).map(|(_,name)| *name)
+    }
+
+    /// The logical keys produced by these physical keys in the US layout, if all of them have a
+    /// meaning there. See `us_layout_key_name`.
+    pub fn us_layout_key_mask(&self) -> Option<KeyMask> {
+        let keys = self.code_names().map(us_layout_key_name).collect::<Option<_>>()?;
+        Some(KeyMask {keys})
+    }
+}
+
+impl<'a> FromIterator<&'a Code> for CodeMask {
+    fn from_iter<T: IntoIterator<Item=&'a Code>>(iter:T) -> Self {
+        let mut code_mask = CodeMask::default();
+        for code in iter {
+            code_mask.set(code,true);
+        }
+        code_mask
+    }
+}
+
+impl From<&[Code]>   for CodeMask { fn from(keys:&[Code])   -> Self { CodeMask::from_iter(keys) } }
+impl From<&[Code;1]> for CodeMask { fn from(keys:&[Code;1]) -> Self { CodeMask::from_iter(keys) } }
+impl From<&[Code;2]> for CodeMask { fn from(keys:&[Code;2]) -> Self { CodeMask::from_iter(keys) } }
+impl From<&[Code;3]> for CodeMask { fn from(keys:&[Code;3]) -> Self { CodeMask::from_iter(keys) } }
+impl From<&[Code;4]> for CodeMask { fn from(keys:&[Code;4]) -> Self { CodeMask::from_iter(keys) } }
+impl From<&[Code;5]> for CodeMask { fn from(keys:&[Code;5]) -> Self { CodeMask::from_iter(keys) } }
+
+
+
+// ================
+// === KeyEvent ===
+// ================
+
+/// A key down or key up event.
+#[derive(Clone,Debug)]
+#[allow(missing_docs)]
+pub struct KeyEvent {
+    pub key    : Key,
+    pub code   : Code,
+    /// Whether the event was generated by holding the key down for a long time.
+    pub repeat : bool,
+}
+
+impl KeyEvent {
+    /// Constructor.
+    pub fn new(key:Key, code:Code, repeat:bool) -> Self {
+        Self {key,code,repeat}
+    }
+}
+
+impl From<Key> for KeyEvent {
+    /// An event of the key at an unknown location. Such events do not change the `CodeMask`.
+    fn from(key:Key) -> Self {
+        Self::new(key,Code::Unidentified,false)
+    }
+}
+
+impl Default for KeyEvent {
+    fn default() -> Self {
+        Key::Unidentified.into()
+    }
+}
+
+
+
 // =====================
-// === KeyMaskChange ===
+// === KeyboardState ===
 // =====================
 
-/// A helper structure used for describing KeyMask changes.
+/// A key being held down. The logical key is remembered from the key down event, because the key
+/// up event may report a different one, for example, `a` instead of `A` when Shift was released
+/// first.
+#[derive(Clone,Debug)]
+struct HeldKey {
+    key  : Key,
+    code : Code,
+}
+
+impl HeldKey {
+    /// Checks whether the event concerns this key. The keys are compared by their location if it
+    /// is known, and by their names otherwise.
+    fn is_target_of(&self, event:&KeyEvent) -> bool {
+        if event.code != Code::Unidentified { self.code == event.code }
+        else                                { key_name(&self.key) == key_name(&event.key) }
+    }
+}
+
+/// The set of currently held keys, together with their logical and physical masks.
+#[derive(Clone,Debug,Default)]
+pub struct KeyboardState {
+    held          : Vec<HeldKey>,
+    key_mask      : KeyMask,
+    code_mask     : CodeMask,
+    keys_changed  : bool,
+    codes_changed : bool,
+}
+
+impl KeyboardState {
+    /// The logical keys being held.
+    pub fn key_mask(&self) -> &KeyMask {
+        &self.key_mask
+    }
+
+    /// The physical keys being held.
+    pub fn code_mask(&self) -> &CodeMask {
+        &self.code_mask
+    }
+
+    /// The logical keys being held, if the last change modified them.
+    pub fn changed_key_mask(&self) -> Option<KeyMask> {
+        self.keys_changed.as_some_from(|| self.key_mask.clone())
+    }
+
+    /// The physical keys being held, if the last change modified them.
+    pub fn changed_code_mask(&self) -> Option<CodeMask> {
+        self.codes_changed.as_some(self.code_mask)
+    }
+
+    /// Marks the key as held. Repeated key down events of a key which is already held do not
+    /// change anything.
+    pub fn press(&mut self, event:&KeyEvent) {
+        if !self.held.iter().any(|held| held.is_target_of(event)) {
+            let key  = event.key.clone();
+            let code = event.code;
+            self.held.push(HeldKey {key,code});
+        }
+        self.update_masks()
+    }
+
+    /// Marks the key as released.
+    pub fn release(&mut self, event:&KeyEvent) {
+        match event.key {
+            // The very special case: pressing CMD on MacOS makes all the keyup events for letters
+            // lost. Therefore for CMD releasing we must clear all keys.
+            Key::Meta => self.held.clear(),
+            _         => self.held.retain(|held| !held.is_target_of(event)),
+        }
+        self.update_masks()
+    }
+
+    /// Releases all keys.
+    pub fn clear(&mut self) {
+        self.held.clear();
+        self.update_masks()
+    }
+
+    fn update_masks(&mut self) {
+        let key_mask       = self.held.iter().map(|held| &held.key).collect::<KeyMask>();
+        let code_mask      = self.held.iter().map(|held| &held.code).collect::<CodeMask>();
+        self.keys_changed  = key_mask  != self.key_mask;
+        self.codes_changed = code_mask != self.code_mask;
+        self.key_mask      = key_mask;
+        self.code_mask     = code_mask;
+    }
+}
+
+
+
+// ======================
+// === KeyboardChange ===
+// ======================
+
+/// A helper structure used for describing KeyboardState changes.
 #[derive(Clone,Debug)]
 #[allow(missing_docs)]
-enum KeyMaskChange { Set(Key), Unset(Key), Clear }
+enum KeyboardChange { Press(KeyEvent), Release(KeyEvent), Clear }
+
+impl KeyboardChange {
+    fn on_key_down(event:&KeyEvent) -> Self {
+        Self::Press(event.clone())
+    }
 
-impl KeyMaskChange {
-    fn on_pressed(key:&Key) -> Self {
-        Self::Set(key.clone())
+    fn on_key_up(event:&KeyEvent) -> Self {
+        Self::Release(event.clone())
     }
 
     /// When we're losing focus we should clear keymask, because we are not sure what keys were
@@ -104,28 +419,19 @@ impl KeyMaskChange {
         Self::Clear
     }
 
-    fn on_released (key:&Key) -> Self {
-        match key {
-            // The very special case: pressing CMD on MacOS makes all the keyup events for letters
-            // lost. Therefore for CMD releasing we must clear keymask.
-            Key::Meta => Self::Clear,
-            other     => Self::Unset(other.clone())
-        }
-    }
-
-    /// Returns copy of given KeyMask with applied change
-    fn updated_mask(&self, mask:&KeyMask) -> KeyMask {
-        let mut mask = *mask;
+    /// Returns copy of given KeyboardState with applied change
+    fn updated_state(&self, state:&KeyboardState) -> KeyboardState {
+        let mut state = state.clone();
         match self {
-            Self::Set   (ref key) => mask.set(key,true),
-            Self::Unset (ref key) => mask.set(key,false),
-            Self::Clear           => mask = default()
+            Self::Press   (event) => state.press(event),
+            Self::Release (event) => state.release(event),
+            Self::Clear           => state.clear(),
         }
-        mask
+        state
     }
 }
 
-impl Default for KeyMaskChange {
+impl Default for KeyboardChange {
     fn default() -> Self {
         Self::Clear
     }
@@ -137,36 +443,52 @@ impl Default for KeyMaskChange {
 // === Keyboard ===
 // ================
 
-/// Keyboard FRP bindings.
+/// Keyboard FRP bindings. The key events should be emitted to `on_key_down` and `on_key_up`. The
+/// state and masks are emitted only when they change, so repeated key down events are visible only
+/// in `on_pressed` and `on_repeat`.
 #[derive(Clone,CloneRef,Debug)]
 #[allow(missing_docs)]
 pub struct Keyboard {
-    pub network           : frp::Network,
-    pub on_pressed        : frp::Source<Key>,
-    pub on_released       : frp::Source<Key>,
-    pub on_defocus        : frp::Source,
-    pub key_mask          : frp::Stream<KeyMask>,
-    pub previous_key_mask : frp::Stream<KeyMask>,
+    pub network            : frp::Network,
+    pub on_key_down        : frp::Source<KeyEvent>,
+    pub on_key_up          : frp::Source<KeyEvent>,
+    pub on_defocus         : frp::Source,
+    pub on_pressed         : frp::Stream<Key>,
+    pub on_released        : frp::Stream<Key>,
+    pub on_repeat          : frp::Stream<KeyEvent>,
+    pub state              : frp::Stream<KeyboardState>,
+    pub key_mask           : frp::Stream<KeyMask>,
+    pub previous_key_mask  : frp::Stream<KeyMask>,
+    pub code_mask          : frp::Stream<CodeMask>,
+    pub previous_code_mask : frp::Stream<CodeMask>,
 }
 
 impl Default for Keyboard {
     fn default() -> Self {
         frp::new_network! { keyboard
-            on_pressed        <- source();
-            on_released       <- source();
-            on_defocus        <- source();
-            change_set        <- on_pressed  . map(KeyMaskChange::on_pressed);
-            change_unset      <- on_released . map(KeyMaskChange::on_released);
-            change_clear      <- on_defocus  . map(|_| KeyMaskChange::on_defocus());
-            change_set_unset  <- any (change_set,change_unset);
-            change            <- any (change_set_unset,change_clear);
-            prev_key_mask     <- any_mut::<KeyMask>();
-            key_mask          <- change.map2(&prev_key_mask,KeyMaskChange::updated_mask);
-            prev_key_mask     <+ key_mask;
-            previous_key_mask <- key_mask.previous();
+            on_key_down        <- source();
+            on_key_up          <- source();
+            on_defocus         <- source();
+            change_press       <- on_key_down . map(KeyboardChange::on_key_down);
+            change_release     <- on_key_up   . map(KeyboardChange::on_key_up);
+            change_clear       <- on_defocus  . map(|_| KeyboardChange::on_defocus());
+            change             <- any (change_press,change_release,change_clear);
+            prev_state         <- any_mut::<KeyboardState>();
+            state              <- change.map2(&prev_state,KeyboardChange::updated_state);
+            prev_state         <+ state;
+            changed_state      <- state.filter(|s| s.keys_changed || s.codes_changed);
+            key_mask           <- state.filter_map(KeyboardState::changed_key_mask);
+            code_mask          <- state.filter_map(KeyboardState::changed_code_mask);
+            previous_key_mask  <- key_mask.previous();
+            previous_code_mask <- code_mask.previous();
+            on_pressed         <- on_key_down.map(|event| event.key.clone());
+            on_released        <- on_key_up.map(|event| event.key.clone());
+            on_repeat          <- on_key_down.filter(|event| event.repeat);
         }
         let network = keyboard;
-        Keyboard {network,on_pressed,on_released,on_defocus,key_mask,previous_key_mask}
+        let state   = changed_state;
+        Keyboard {network,on_key_down,on_key_up,on_defocus,on_pressed,on_released,on_repeat,state
+                 ,key_mask,previous_key_mask,code_mask,previous_code_mask}
     }
 }
 
@@ -198,7 +520,9 @@ impl Actions {
     pub fn new(keyboard:&Keyboard) -> Self {
         let action_map = Rc::new(RefCell::new(HashMap::new()));
         frp::new_network! { keyboard_actions
-            def _action = keyboard.key_mask.map(Self::perform_action_fn(action_map.clone_ref()));
+            def repeated = keyboard.key_mask.sample(&keyboard.on_repeat);
+            def key_mask = keyboard.key_mask.any(&repeated);
+            def _action  = key_mask.map(Self::perform_action_fn(action_map.clone_ref()));
         }
         let network = keyboard_actions;
         Actions{action_map,network}
@@ -244,6 +568,18 @@ impl Debug for Actions {
 mod test {
     use super::*;
 
+    fn press(key:Key, code:Code) -> KeyEvent {
+        KeyEvent::new(key,code,false)
+    }
+
+    fn repeat(key:Key, code:Code) -> KeyEvent {
+        KeyEvent::new(key,code,true)
+    }
+
+    fn character(c:&str) -> Key {
+        Key::Character(c.into())
+    }
+
     #[test]
     fn key_mask() {
         let keyboard                  = Keyboard::default();
@@ -255,16 +591,196 @@ mod test {
         let key1 = Key::Character("x".to_string());
         let key2 = Key::Control;
 
-        keyboard.on_pressed.emit(key1.clone());
+        keyboard.on_key_down.emit(KeyEvent::from(key1.clone()));
         let expected_key_mask:KeyMask = std::iter::once(&key1).collect();
         assert_eq!(expected_key_mask, sampler.value());
 
-        keyboard.on_pressed.emit(key2.clone());
+        keyboard.on_key_down.emit(KeyEvent::from(key2.clone()));
         let expected_key_mask:KeyMask = [&key1,&key2].iter().cloned().collect();
         assert_eq!(expected_key_mask, sampler.value());
 
-        keyboard.on_released.emit(key1);
+        keyboard.on_key_up.emit(KeyEvent::from(key1));
         let expected_key_mask:KeyMask = std::iter::once(&key2).collect();
         assert_eq!(expected_key_mask, sampler.value());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn code_table() {
+        assert!(CODES.len() <= BitField256::BIT_LENGTH);
+        assert_eq!(CODES.iter().unique().count(),CODES.len());
+        for (index,name) in CODES.iter().enumerate() {
+            let code = name.parse::<Code>().unwrap();
+            assert_eq!(code_index(&code),Some(index),"Wrong index of {}.",name);
+            assert_eq!(code.to_string(),*name);
+        }
+        assert_eq!(code_index(&Code::Unidentified),None);
+    }
+
+    #[test]
+    fn key_names() {
+        let cases = vec!
+            [ (character("a")          , Some("a"))
+            , (character("A")          , Some("a"))
+            , (character("Ä")          , Some("ä"))
+            , (character(";")          , Some(";"))
+            , (character(" ")          , Some(" "))
+            , (Key::Control       , Some("Control"))
+            , (Key::ArrowLeft     , Some("ArrowLeft"))
+            , (Key::F12           , Some("F12"))
+            , (Key::Dead          , None)
+            , (Key::Process       , None)
+            , (Key::Unidentified  , None)
+            ];
+        for (key,expected) in cases {
+            assert_eq!(key_name(&key).as_deref(),expected,"Wrong name of {:?}.",key);
+        }
+    }
+
+    #[test]
+    fn us_layout_key_names() {
+        let cases = vec!
+            [ ("KeyZ"         , Some("z"))
+            , ("Digit7"       , Some("7"))
+            , ("Semicolon"    , Some(";"))
+            , ("Space"        , Some(" "))
+            , ("ControlRight" , Some("Control"))
+            , ("F12"          , Some("F12"))
+            , ("Escape"       , Some("Escape"))
+            , ("Fn"           , None)
+            , ("Numpad1"      , None)
+            , ("IntlBackslash", None)
+            ];
+        for (code,expected) in cases {
+            assert_eq!(us_layout_key_name(code).as_deref(),expected,"Wrong name of {}.",code);
+        }
+        let codes : CodeMask = [Code::ControlLeft,Code::KeyS].iter().collect();
+        let keys  : KeyMask  = [Key::Control,character("s")].iter().collect();
+        assert_eq!(codes.us_layout_key_mask(),Some(keys));
+        let codes : CodeMask = [Code::ControlLeft,Code::Numpad1].iter().collect();
+        assert_eq!(codes.us_layout_key_mask(),None);
+    }
+
+    #[test]
+    fn code_mask() {
+        let mask : CodeMask = [Code::ControlLeft,Code::NumpadAdd,Code::Semicolon].iter().collect();
+        assert!(mask.contains(&Code::ControlLeft));
+        assert!(mask.contains(&Code::NumpadAdd));
+        assert!(!mask.contains(&Code::ControlRight));
+        assert!(CodeMask::from(&[Code::Semicolon]).is_subset(&mask));
+        assert!(!CodeMask::from(&[Code::Semicolon,Code::KeyA]).is_subset(&mask));
+        assert_eq!(mask.code_names().collect_vec(),vec!["Semicolon","ControlLeft","NumpadAdd"]);
+        let mut mask = mask;
+        mask.set(&Code::Unidentified,true);
+        mask.set(&Code::NumpadAdd,false);
+        assert_eq!(mask.code_names().collect_vec(),vec!["Semicolon","ControlLeft"]);
+    }
+
+    /// Events applied to the `KeyboardState` and the expected key names and codes afterwards.
+    type StateCase = (&'static str, Vec<(bool,KeyEvent)>, Vec<&'static str>, Vec<&'static str>);
+
+    #[test]
+    fn keyboard_state() {
+        let cases : Vec<StateCase> = vec!
+            [ ( "Letters are case insensitive."
+              , vec![ (true  , press(Key::Shift,Code::ShiftLeft))
+                    , (true  , press(character("S"),Code::KeyS))
+                    ]
+              , vec!["Shift","s"]
+              , vec!["KeyS","ShiftLeft"]
+              )
+            , ( "Keys are released by their location."
+              , vec![ (true  , press(Key::Shift,Code::ShiftLeft))
+                    , (true  , press(character("!"),Code::Digit1))
+                    , (false , press(Key::Shift,Code::ShiftLeft))
+                    , (false , press(character("1"),Code::Digit1))
+                    ]
+              , vec![]
+              , vec![]
+              )
+            , ( "AZERTY layout."
+              , vec![ (true  , press(Key::Control,Code::ControlLeft))
+                    , (true  , press(character("a"),Code::KeyQ))
+                    ]
+              , vec!["Control","a"]
+              , vec!["KeyQ","ControlLeft"]
+              )
+            , ( "Dead keys do not change the logical keys."
+              , vec![ (true  , press(Key::Dead,Code::Quote))
+                    ]
+              , vec![]
+              , vec!["Quote"]
+              )
+            , ( "Repeated keys are held once."
+              , vec![ (true  , press(character("x"),Code::KeyX))
+                    , (true  , repeat(character("x"),Code::KeyX))
+                    , (true  , repeat(character("x"),Code::KeyX))
+                    , (false , press(character("x"),Code::KeyX))
+                    ]
+              , vec![]
+              , vec![]
+              )
+            , ( "Numpad and punctuation."
+              , vec![ (true  , press(character("+"),Code::NumpadAdd))
+                    , (true  , press(character("/"),Code::Slash))
+                    ]
+              , vec!["+","/"]
+              , vec!["Slash","NumpadAdd"]
+              )
+            , ( "Keys with unknown location are matched by name."
+              , vec![ (true  , KeyEvent::from(character("A")))
+                    , (true  , KeyEvent::from(Key::Alt))
+                    , (false , KeyEvent::from(character("a")))
+                    ]
+              , vec!["Alt"]
+              , vec![]
+              )
+            , ( "Releasing Meta releases all keys."
+              , vec![ (true  , press(Key::Meta,Code::MetaLeft))
+                    , (true  , press(character("z"),Code::KeyZ))
+                    , (false , press(Key::Meta,Code::MetaLeft))
+                    ]
+              , vec![]
+              , vec![]
+              )
+            ];
+        for (description,events,key_names,code_names) in cases {
+            let mut state = KeyboardState::default();
+            for (is_down,event) in &events {
+                if *is_down { state.press(event)   }
+                else        { state.release(event) }
+            }
+            assert_eq!(state.key_mask().key_names().collect_vec(),key_names,"{}",description);
+            assert_eq!(state.code_mask().code_names().collect_vec(),code_names,"{}",description);
+        }
+    }
+
+    #[test]
+    fn masks_change_only_on_change() {
+        let mut state = KeyboardState::default();
+        state.press(&press(character("x"),Code::KeyX));
+        assert!(state.changed_key_mask().is_some());
+        assert!(state.changed_code_mask().is_some());
+        state.press(&repeat(character("x"),Code::KeyX));
+        assert!(state.changed_key_mask().is_none());
+        assert!(state.changed_code_mask().is_none());
+        state.press(&press(Key::Dead,Code::Backquote));
+        assert!(state.changed_key_mask().is_none());
+        assert!(state.changed_code_mask().is_some());
+    }
+
+    #[test]
+    fn repeated_key_down() {
+        let keyboard = Keyboard::default();
+        frp::new_network! { network
+            def mask_count   = keyboard.key_mask.count().sampler();
+            def press_count  = keyboard.on_pressed.count().sampler();
+            def repeat_count = keyboard.on_repeat.count().sampler();
+        }
+        keyboard.on_key_down.emit(press(character("x"),Code::KeyX));
+        keyboard.on_key_down.emit(repeat(character("x"),Code::KeyX));
+        keyboard.on_key_down.emit(repeat(character("x"),Code::KeyX));
+        assert_eq!(mask_count.value(),1);
+        assert_eq!(press_count.value(),3);
+        assert_eq!(repeat_count.value(),2);
+    }
+}
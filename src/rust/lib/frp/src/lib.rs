@@ -44,7 +44,20 @@
 //! Please note, that although there are valid use cases when this mode is useful, you should always
 //! default to network mode instead. In this mode the `Network` is not used and you are using strong
 //! references to nodes instead (types prefixed with "Owned" in the API, like `frp::OwnedSource`).
-//! Nodes are dropped as soon as no references exist to them anymore.
+//! Nodes are dropped as soon as no references exist to them anymore. If a group of nodes should
+//! share a lifetime, for example, the FRP logic of a graph node created when the node appears,
+//! create them with `DynamicNetwork::scoped` and `frp::extend!`. The nodes will be kept alive
+//! until the scoped network is dropped.
+//!
+//!
+//! ## Transactional propagation.
+//! By default, events are propagated depth-first, so in diamond-shaped networks a node can fire
+//! several times for a single event and observe a mix of old and new values of its inputs. Calling
+//! `frp::transaction::set_propagation_mode(PropagationMode::Transactional)` makes every external
+//! emit open a transaction in which nodes are evaluated in topological order. Nodes combining the
+//! values of their inputs, like `all_with`, fire at most once per transaction, while other nodes
+//! receive the latest event of every input. Several emits can be grouped in a single transaction with `frp::transaction`. See the
+//! `transaction` module docs to learn more.
 //!
 //!
 //!
@@ -159,6 +172,7 @@
 #![feature(unboxed_closures)]
 #![feature(weak_into_raw)]
 
+pub mod clock;
 pub mod debug;
 pub mod data;
 pub mod io;
@@ -166,15 +180,23 @@ pub mod macros;
 pub mod network;
 pub mod node;
 pub mod nodes;
+pub mod recorder;
 pub mod stream;
+pub mod test;
+pub mod transaction;
 
+pub use clock::Clock;
+pub use clock::VirtualClock;
 pub use network::*;
 pub use node::*;
 pub use nodes::*;
+pub use recorder::Recorder;
+pub use recorder::Replay;
 
 pub use enso_prelude as prelude;
 pub use ensogl_system_web as web;
 pub use stream::Stream;
+pub use transaction::transaction;
 
 
 
@@ -203,6 +225,134 @@ mod network_mode_tests {
         source.emit(());
         assert_eq!(sampler.value(),0);
     }
+
+    #[test]
+    fn filter_and_filter_map() {
+        frp::new_network! { network
+            def source      = source::<usize>();
+            def evens       = source.filter(|n| n % 2 == 0);
+            def evens_count = evens.count().sampler();
+            def halves      = source.filter_map(|n| if n % 2 == 0 { Some(n / 2) } else { None });
+            def last_half   = halves.sampler();
+        }
+        source.emit(4);
+        source.emit(5);
+        assert_eq!(evens_count.value(),1);
+        assert_eq!(last_half.value(),2);
+    }
+
+    #[test]
+    fn partition() {
+        frp::new_network! { network
+            def source   = source::<usize>();
+            (small,big) <- source.partition(|n| *n < 10);
+            def small_count = small.count().sampler();
+            def last_big    = big.sampler();
+        }
+        source.emit(1);
+        source.emit(20);
+        source.emit(2);
+        assert_eq!(small_count.value(),2);
+        assert_eq!(last_big.value(),20);
+    }
+
+    #[test]
+    fn switch() {
+        frp::new_network! { network
+            def first    = source::<usize>();
+            def second   = source::<usize>();
+            def selector = source::<bool>();
+        }
+        let first_stream  = frp::Stream::from(&first);
+        let second_stream = frp::Stream::from(&second);
+        frp::extend! { network
+            def selected = selector.map(move |is_second| {
+                if *is_second { second_stream.clone() } else { first_stream.clone() }
+            });
+            def switched = selected.switch();
+            def count    = switched.count().sampler();
+            def last     = switched.sampler();
+        }
+        first.emit(1);
+        assert_eq!(count.value(),0);
+        selector.emit(false);
+        first.emit(2);
+        second.emit(3);
+        assert_eq!((count.value(),last.value()),(1,2));
+        selector.emit(true);
+        first.emit(4);
+        second.emit(5);
+        assert_eq!((count.value(),last.value()),(2,5));
+    }
+
+    #[test]
+    fn delay_and_debounce() {
+        let virtual_clock = frp::VirtualClock::new();
+        let clock         = virtual_clock.clock();
+        frp::new_network! { network
+            def source          = source::<usize>();
+            def delayed         = source.delay(&clock,100.0);
+            def delayed_count   = delayed.count().sampler();
+            def debounced       = source.debounce(&clock,100.0);
+            def debounced_count = debounced.count().sampler();
+            def last_debounced  = debounced.sampler();
+        }
+        source.emit(1);
+        virtual_clock.advance(50.0);
+        source.emit(2);
+        virtual_clock.advance(60.0);
+        assert_eq!(delayed_count.value(),1);
+        assert_eq!(debounced_count.value(),0);
+        virtual_clock.advance(40.0);
+        assert_eq!(delayed_count.value(),2);
+        assert_eq!(debounced_count.value(),1);
+        assert_eq!(last_debounced.value(),2);
+    }
+
+    #[test]
+    fn throttle() {
+        let virtual_clock = frp::VirtualClock::new();
+        let clock         = virtual_clock.clock();
+        frp::new_network! { network
+            def source    = source::<usize>();
+            def throttled = source.throttle(&clock,100.0);
+            def count     = throttled.count().sampler();
+            def last      = throttled.sampler();
+        }
+        source.emit(1);
+        assert_eq!((count.value(),last.value()),(1,1));
+        source.emit(2);
+        source.emit(3);
+        assert_eq!((count.value(),last.value()),(1,1));
+        virtual_clock.advance(100.0);
+        assert_eq!((count.value(),last.value()),(2,3));
+        virtual_clock.advance(100.0);
+        source.emit(4);
+        assert_eq!((count.value(),last.value()),(3,4));
+    }
+
+    #[test]
+    fn sample_every_and_timeout() {
+        let virtual_clock = frp::VirtualClock::new();
+        let clock         = virtual_clock.clock();
+        frp::new_network! { network
+            def source    = source::<usize>();
+            def behavior  = source.sampler();
+            def sampled   = behavior.sample_every(&clock,10.0);
+            def count     = sampled.count().sampler();
+            def last      = sampled.sampler();
+            def timed_out = source.timeout(&clock,25.0);
+            def is_idle   = timed_out.sampler();
+        }
+        source.emit(5);
+        assert_eq!(is_idle.value(),false);
+        virtual_clock.advance(30.0);
+        assert_eq!((count.value(),last.value()),(3,5));
+        assert_eq!(is_idle.value(),true);
+        drop(network);
+        virtual_clock.advance(30.0);
+        assert_eq!(virtual_clock.pending_timers(),0);
+    }
 }
 
 #[cfg(test)]
@@ -255,6 +405,52 @@ mod dynamic_mode_tests {
         assert!(weak_source.upgrade().is_none());
     }
 
+    #[test]
+    fn debounce() {
+        let virtual_clock = frp::VirtualClock::new();
+        let clock         = virtual_clock.clock();
+        frp::new_dynamic_network! {
+            def source    = source::<usize>();
+            def debounced = source.debounce(&clock,100.0);
+            def sampler   = debounced.sampler();
+        }
+        source.emit(1);
+        virtual_clock.advance(50.0);
+        source.emit(2);
+        virtual_clock.advance(100.0);
+        assert_eq!(sampler.value(),2);
+    }
+
+    #[test]
+    fn map_keeps_its_definition() {
+        frp::new_dynamic_network! {
+            def source  = source::<usize>();
+            def double  = source.map(|n| n * 2);
+            def sampler = double.sampler();
+        }
+        source.emit(2);
+        assert_eq!(sampler.value(),4);
+    }
+
+    #[test]
+    fn scoped_network() {
+        frp::new_dynamic_network! {
+            def source = source::<usize>();
+        }
+        let scope = frp::DynamicNetwork::scoped();
+        frp::extend! { scope
+            def double  = source.map(|n| n * 2);
+            def sampler = double.sampler();
+        }
+        let sampler = sampler.downgrade();
+        drop(double);
+        source.emit(2);
+        assert_eq!(sampler.value(),4);
+        drop(scope);
+        assert!(sampler.upgrade().is_none());
+        source.emit(3);
+    }
+
     #[test]
     #[ignore] // Issue #427
     fn test_toggle_true() {
@@ -279,3 +475,163 @@ mod dynamic_mode_tests {
         map_src.emit(());
     }
 }
+
+#[cfg(test)]
+mod transactional_mode_tests {
+    use crate as frp;
+    use frp::transaction::PropagationMode;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn enable_transactions() {
+        frp::transaction::set_propagation_mode(PropagationMode::Transactional);
+    }
+
+    #[test]
+    fn diamond() {
+        enable_transactions();
+        frp::new_network! { network
+            def source = source::<i32>();
+            def double = source.map(|t| t * 2);
+            def triple = source.map(|t| t * 3);
+            def sum    = double.all_with(&triple,|a,b| a + b);
+            def count  = sum.count().sampler();
+            def last   = sum.sampler();
+        }
+        source.emit(1);
+        assert_eq!((count.value(),last.value()),(1,5));
+        source.emit(2);
+        assert_eq!((count.value(),last.value()),(2,10));
+    }
+
+    #[test]
+    fn recovering_from_panics() {
+        enable_transactions();
+        frp::new_network! { network
+            def source = source::<i32>();
+            def _check = source.map(|t| if *t < 0 { panic!("Negative value.") });
+            def last   = source.sampler();
+        }
+        let emit_negative = std::panic::AssertUnwindSafe(|| source.emit(-1));
+        assert!(std::panic::catch_unwind(emit_negative).is_err());
+        source.emit(1);
+        assert_eq!(last.value(),1);
+    }
+
+    #[test]
+    fn diamond_with_watched_behavior() {
+        enable_transactions();
+        frp::new_network! { network
+            def source = source::<i32>();
+            def double = source.map(|t| t * 2);
+            def triple = source.map(|t| t * 3);
+            def sum    = double.map2(&triple,|a,b| a + b);
+            def count  = sum.count().sampler();
+            def last   = sum.sampler();
+        }
+        source.emit(1);
+        assert_eq!((count.value(),last.value()),(1,5));
+    }
+
+    #[test]
+    fn watched_behavior_ranked_later() {
+        enable_transactions();
+        frp::new_network! { network
+            def source   = source::<i32>();
+            def behavior = any_mut::<i32>();
+            def sum      = source.map2(&behavior,|a,b| a + b);
+            def last     = sum.sampler();
+            def double   = source.map(|t| t * 2);
+        }
+        // Attaching raises the rank of the behavior above the rank of `sum`, which watches it.
+        behavior.attach(&double);
+        source.emit(1);
+        assert_eq!(last.value(),3);
+    }
+
+    #[test]
+    fn depth_first_diamond_fires_twice() {
+        frp::new_network! { network
+            def source = source::<i32>();
+            def double = source.map(|t| t * 2);
+            def triple = source.map(|t| t * 3);
+            def sum    = double.all_with(&triple,|a,b| a + b);
+            def count  = sum.count().sampler();
+        }
+        source.emit(1);
+        assert_eq!(count.value(),2);
+    }
+
+    #[test]
+    fn nested_networks() {
+        enable_transactions();
+        frp::new_network! { network1
+            def source   = source::<i32>();
+            def inc      = source.map(|t| t + 1);
+            def last_inc = inc.sampler();
+        }
+        frp::new_network! { network2
+            def pair  = source.all_with(&inc,|a,b| (*a,*b));
+            def count = pair.count().sampler();
+            def last  = pair.sampler();
+        }
+        source.emit(1);
+        assert_eq!((count.value(),last.value()),(1,(1,2)));
+        drop(network2);
+        source.emit(2);
+        assert_eq!(last_inc.value(),3);
+    }
+
+    #[test]
+    fn diamond_with_merge() {
+        enable_transactions();
+        let values = Rc::new(RefCell::new(Vec::new()));
+        let log    = values.clone();
+        frp::new_network! { network
+            def source = source::<i32>();
+            def double = source.map(|t| t * 2);
+            def triple = source.map(|t| t * 3);
+            def merged = any2(&double,&triple);
+            def count  = merged.count().sampler();
+            def _log   = merged.map(move |t| log.borrow_mut().push(*t));
+        }
+        source.emit(1);
+        assert_eq!(count.value(),2);
+        source.emit(2);
+        assert_eq!(count.value(),4);
+        assert_eq!(*values.borrow(),vec![2,3,4,6]);
+    }
+
+    #[test]
+    fn diamond_with_merge_and_combine() {
+        enable_transactions();
+        frp::new_network! { network
+            def source   = source::<i32>();
+            def double   = source.map(|t| t * 2);
+            def triple   = source.map(|t| t * 3);
+            def merged   = any2(&double,&triple);
+            def combined = merged.all_with(&source,|a,b| a + b);
+            def count    = combined.count().sampler();
+            def last     = combined.sampler();
+        }
+        source.emit(1);
+        assert_eq!((count.value(),last.value()),(1,4));
+    }
+
+    #[test]
+    fn explicit_transaction() {
+        enable_transactions();
+        frp::new_network! { network
+            def source = source::<i32>();
+            def double = source.map(|t| t * 2);
+            def count  = double.count().sampler();
+            def last   = double.sampler();
+        }
+        frp::transaction(|| {
+            source.emit(1);
+            source.emit(2);
+            assert_eq!(count.value(),0);
+        });
+        assert_eq!((count.value(),last.value()),(1,4));
+    }
+}
@@ -99,6 +99,16 @@
 ///   ```
 ///
 ///
+/// - Definition of two streams at once, for nodes returning a pair of streams.
+///   ```compile_fail
+///   (evens,odds) <- numbers.partition(|n| n % 2 == 0);
+///   ```
+///   Desugars to:
+///   ```compile_fail
+///   let (evens,odds) = network.partition("network.evens,odds",&numbers,|n| n % 2 == 0);
+///   ```
+///
+///
 /// - Stream iteration.
 ///   ```compile_fail
 ///   each_node <= all_nodes;
@@ -252,6 +262,10 @@ macro_rules! extend_line2 {
     ([] $net:ident $name:ident <- all_ ( $($arg1:ident).+ , $($arg2:ident).+ , $($arg3:ident).+ , $($arg4:ident).+ ) $($ts:tt)* ) => {$crate::extend_line2! { [] $net def $name = all4_(&$($arg1).+,&$($arg2).+,&$($arg3).+,&$($arg4).+) $($ts)* } };
 
 
+    ([] $net:ident def ($name1:ident,$name2:ident) = $tgt1:ident               . $base:ident($($arg:tt)*)) => { let ($name1,$name2) = $net.$base(concat!(stringify!($net),".",stringify!($name1),",",stringify!($name2)),&$tgt1,$($arg)*);        };
+    ([] $net:ident def ($name1:ident,$name2:ident) = $tgt1:ident . $tgt2:ident . $base:ident($($arg:tt)*)) => { let ($name1,$name2) = $net.$base(concat!(stringify!($net),".",stringify!($name1),",",stringify!($name2)),&$tgt1.$tgt2,$($arg)*); };
+    ([] $net:ident ($name1:ident,$name2:ident) <- $($toks:tt)*) => {$crate::extend_line2! { [] $net def ($name1,$name2) = $($toks)* } };
+
     ([] $net:ident $name:ident <= $($toks:tt)*) => {$crate::extend_line2! { [] $net def $name = $($toks)* . iter()} };
     ([] $net:ident $name:ident <- $($toks:tt)*) => {$crate::extend_line2! { [] $net def $name = $($toks)* } };
     ([] $net:ident $($tgt:ident).+ <+ $($src:ident).+) => { $($tgt).+.attach(&$($src).+); };
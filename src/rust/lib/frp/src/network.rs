@@ -5,6 +5,8 @@ use crate::stream;
 use crate::stream::Stream;
 use crate::node::*;
 use crate::debug;
+use crate::recorder;
+use crate::recorder::Recorder;
 
 
 
@@ -97,12 +99,36 @@ impl Network {
         self.data.bridges.borrow_mut().push(sub_network.clone_ref())
     }
 
-    /// Draw the network using GraphViz.
-    pub fn draw(&self) {
+    /// Starts recording all events emitted by the nodes of this network. The recording stops when
+    /// the returned recorder is dropped. Nodes added to the network later are not recorded.
+    pub fn record(&self) -> Recorder {
+        let nodes = self.data.nodes.borrow().iter().map(|node| {
+            let label      = node.label();
+            let type_label = node.output_type_label();
+            (node.id(),recorder::NodeInfo {label,type_label})
+        }).collect();
+        Recorder::new(nodes)
+    }
+
+    /// The GraphViz diagram of the network.
+    pub fn graphviz(&self) -> debug::Graphviz {
         let mut viz = debug::Graphviz::default();
         self.data.nodes.borrow().iter().for_each(|node| {
             viz.add_node(node.id().into(),node.output_type_label(),node.label());
         });
+        viz
+    }
+
+    /// Draw the network using GraphViz.
+    pub fn draw(&self) {
+        debug::display_graphviz(self.graphviz());
+    }
+
+    /// Draw the network using GraphViz, annotating the nodes with the last values recorded by the
+    /// recorder.
+    pub fn draw_with_values(&self, recorder:&Recorder) {
+        let mut viz = self.graphviz();
+        recorder.annotate(&mut viz);
         debug::display_graphviz(viz);
     }
 }
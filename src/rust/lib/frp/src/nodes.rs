@@ -9,6 +9,8 @@
 
 use crate::prelude::*;
 
+use crate::clock::Clock;
+use crate::clock::Time;
 use crate::data::watch;
 use crate::network::*;
 use crate::node::*;
@@ -17,133 +19,217 @@ use crate::stream::ValueProvider;
 use crate::stream::Stream;
 use crate::stream::OwnedStream;
 use crate::stream;
+use crate::transaction;
 use enso_generics as generics;
 use enso_generics::traits::*;
 
 
 
-// ========================
-// === Network Node API ===
-// ========================
+// =======================
+// === Dynamic Network ===
+// =======================
 
-impl Network {
+/// Network of nodes owned by the user, used by macros to create dynamic FRP graphs. It exposes the
+/// same API as `Network` in order to reuse macro code for both network and dynamic modes, but all
+/// of its methods return strong references to nodes (types with the "Owned" prefix).
+///
+/// By default, the nodes are dropped as soon as no references exist to them anymore. A dynamic
+/// network created with `scoped` additionally keeps all of its nodes alive until the network (and
+/// all of its clones) is dropped, which allows creating and tearing down groups of nodes at
+/// runtime, like the FRP logic of a graph node which appears and disappears from the scene.
+#[derive(Clone,Debug,Default)]
+pub struct DynamicNetwork {
+    scope : Option<Network>,
+}
+
+impl DynamicNetwork {
+    /// Constructor.
+    pub fn new() -> Self {
+        default()
+    }
+
+    /// Constructor of a dynamic network keeping all of its nodes alive until it is dropped.
+    pub fn scoped() -> Self {
+        let scope = Some(Network::new());
+        Self {scope}
+    }
+
+    /// The network keeping the nodes of a scoped dynamic network alive. It can be used for
+    /// debugging, for example, to record or draw the nodes.
+    pub fn scope(&self) -> Option<&Network> {
+        self.scope.as_ref()
+    }
+
+    /// Registers the node in the scope, if any.
+    fn keep<Def:HasOutputStatic>(&self, node:stream::Node<Def>) -> stream::Node<Def> {
+        if let Some(scope) = &self.scope {
+            scope.register_raw(node.clone_ref());
+        }
+        node
+    }
+}
+
+
+
+// ================
+// === Node API ===
+// ================
+
+/// Defines the node API of both `Network` and `DynamicNetwork` from a single list of entries, so
+/// the two APIs cannot diverge. Every entry provides the generics, the arguments, the output type
+/// of the `Network` method, the output type of the `DynamicNetwork` method (after the `as`
+/// keyword), the optional bounds, and the body. The entry kind defines the meaning of the body:
+///
+/// - `stream` entries construct an owned node, which `Network` registers and returns as a
+///   `Stream`.
+/// - `raw` entries construct an owned node, which `Network` registers and returns as a weak node
+///   reference, like `Source`.
+/// - `derived` entries are defined in terms of other methods of the network, which is accessible
+///   in the body under the name provided as the first argument of the macro.
+///
+/// `DynamicNetwork` returns the owned nodes, keeping them alive in its scope, if any.
+macro_rules! define_node_api {
+    ($network:ident
+        $(
+            $(#[$($meta:tt)*])*
+            $kind:ident fn $name:ident [$($param:tt)*] ($($arg:ident : $arg_tp:ty),*)
+            -> $out:ty as $owned:ty $(where [$($bounds:tt)*])?
+            $body:block
+        )*
+    ) => {
+        impl Network {$(
+            $(#[$($meta)*])*
+            pub fn $name<$($param)*>(&self, $($arg:$arg_tp),*) -> $out
+            where $($($bounds)*)? {
+                define_node_api! {@network $kind self $network $body}
+            }
+        )*}
+
+        /// See docs of `Network` to learn about the methods.
+        impl DynamicNetwork {$(
+            pub fn $name<$($param)*>(&self, $($arg:$arg_tp),*) -> $owned
+            where $($($bounds)*)? {
+                define_node_api! {@dynamic $kind self $network $body}
+            }
+        )*}
+    };
+
+    (@network stream  $this:ident $network:ident $body:block) => { $this.register($body) };
+    (@network raw     $this:ident $network:ident $body:block) => { $this.register_raw($body) };
+    (@network derived $this:ident $network:ident $body:block) => {{ let $network = $this; $body }};
+    (@dynamic stream  $this:ident $network:ident $body:block) => { $this.keep($body) };
+    (@dynamic raw     $this:ident $network:ident $body:block) => { $this.keep($body) };
+    (@dynamic derived $this:ident $network:ident $body:block) => {{ let $network = $this; $body }};
+}
+
+define_node_api! { network
     /// Begin point in the FRP network. It does not accept inputs, but it is able to emit events.
     /// Often it is used to indicate that something happened, like a button was pressed. In such
     /// case its type parameter is set to an empty tuple.
-    pub fn source<T:Data>(&self, label:Label) -> Source<T> {
-        self.register_raw(OwnedSource::new(label))
-    }
+    raw fn source [T:Data] (label:Label) -> Source<T> as OwnedSource<T>
+    { OwnedSource::new(label) }
 
     /// Starting point in the FRP network. Specialized version of `source`.
-    pub fn source_(&self, label:Label) -> Source {
-        self.register_raw(OwnedSource::new(label))
-    }
+    raw fn source_ [] (label:Label) -> Source as OwnedSource
+    { OwnedSource::new(label) }
 
     /// Remember the last event value and allow sampling it anytime.
-    pub fn sampler<T,Out>(&self, label:Label, src:&T) -> Sampler<Out>
-    where T:EventOutput<Output=Out>, Out:Data {
-        self.register_raw(OwnedSampler::new(label,src))
-    }
+    raw fn sampler [T,Out] (label:Label, src:&T) -> Sampler<Out> as OwnedSampler<Out>
+    where [T:EventOutput<Output=Out>, Out:Data]
+    { OwnedSampler::new(label,src) }
 
     /// Print the incoming events to console and pass them to output.
-    pub fn trace<T:EventOutput>(&self, label:Label, src:&T) -> Stream<Output<T>> {
-        self.register(OwnedTrace::new(label,src))
-    }
+    stream fn trace [T:EventOutput] (label:Label, src:&T) -> Stream<Output<T>> as OwnedTrace<T>
+    { OwnedTrace::new(label,src) }
 
     /// Emits `true`, `false`, `true`, `false`, ... on every incoming event. Initialized with false
     /// value.
-    pub fn toggle<T:EventOutput>(&self, label:Label, src:&T) -> Stream<bool> {
-        self.register(OwnedToggle::new(label,src))
-    }
+    stream fn toggle [T:EventOutput] (label:Label, src:&T) -> Stream<bool> as OwnedToggle<T>
+    { OwnedToggle::new(label,src) }
 
     /// Emits `false`, `true`, `false`, `true`, ... on every incoming event. Initialized with true
     /// value.
-    pub fn toggle_true<T:EventOutput>(&self, label:Label, src:&T) -> Stream<bool> {
-        self.register(OwnedToggle::new_with(label,src,true))
-    }
+    stream fn toggle_true [T:EventOutput] (label:Label, src:&T) -> Stream<bool> as OwnedToggle<T>
+    { OwnedToggle::new_with(label,src,true) }
 
     /// Count the incoming events.
-    pub fn count<T:EventOutput>(&self, label:Label, src:&T) -> Stream<usize> {
-        self.register(OwnedCount::new(label,src))
-    }
+    stream fn count [T:EventOutput] (label:Label, src:&T) -> Stream<usize> as OwnedCount<T>
+    { OwnedCount::new(label,src) }
 
     /// Replace the incoming event with the predefined value.
-    pub fn constant<X:Data,T:EventOutput> (&self, label:Label, src:&T, value:X) -> Stream<X> {
-        self.register(OwnedConstant::new(label,src,value))
-    }
+    stream fn constant [X:Data,T:EventOutput] (label:Label, src:&T, value:X)
+    -> Stream<X> as OwnedConstant<T,X>
+    { OwnedConstant::new(label,src,value) }
 
     /// Replace the incoming event with `true`.
-    pub fn to_true<T:EventOutput> (&self, label:Label, src:&T) -> Stream<bool> {
-        self.constant(label,src,true)
-    }
+    derived fn to_true [T:EventOutput] (label:Label, src:&T)
+    -> Stream<bool> as OwnedConstant<T,bool>
+    { network.constant(label,src,true) }
 
     /// Replace the incoming event with `false`.
-    pub fn to_false<T:EventOutput> (&self, label:Label, src:&T) -> Stream<bool> {
-        self.constant(label,src,false)
-    }
+    derived fn to_false [T:EventOutput] (label:Label, src:&T)
+    -> Stream<bool> as OwnedConstant<T,bool>
+    { network.constant(label,src,false) }
 
     /// Replace the incoming event from first input with `false` and from second with `true`.
-    pub fn bool<T1,T2> (&self, label:Label, src1:&T1, src2:&T2) -> Stream<bool>
-    where T1:EventOutput, T2:EventOutput {
-        let false_ = self.to_false (label,src1);
-        let true_  = self.to_true  (label,src2);
-        self.any(label,&false_,&true_)
+    derived fn bool [T1,T2] (label:Label, src1:&T1, src2:&T2) -> Stream<bool> as OwnedAny<bool>
+    where [T1:EventOutput, T2:EventOutput]
+    {
+        let false_ = network.to_false (label,src1);
+        let true_  = network.to_true  (label,src2);
+        network.any(label,&false_,&true_)
     }
 
     /// Remembers the value of the input stream and outputs the previously received one.
-    pub fn previous<T:EventOutput> (&self, label:Label, src:&T) -> Stream<Output<T>> {
-        self.register(OwnedPrevious::new(label,src))
-    }
+    stream fn previous [T:EventOutput] (label:Label, src:&T)
+    -> Stream<Output<T>> as OwnedPrevious<T>
+    { OwnedPrevious::new(label,src) }
 
     /// Samples the first stream (behavior) on every incoming event of the second stream. The
     /// incoming event is dropped and a new event with the behavior's value is emitted.
-    pub fn sample<T1:EventOutput,T2:EventOutput>
-    (&self, label:Label, behavior:&T1, event:&T2) -> Stream<Output<T1>> {
-        self.register(OwnedSample::new(label,behavior,event))
-    }
+    stream fn sample [T1:EventOutput,T2:EventOutput] (label:Label, behavior:&T1, event:&T2)
+    -> Stream<Output<T1>> as OwnedSample<T1,T2>
+    { OwnedSample::new(label,behavior,event) }
 
     /// Passes the incoming event of the fisrt stream only if the value of the second stream is
     /// true.
-    pub fn gate<T1,T2>(&self, label:Label, event:&T1, behavior:&T2) -> Stream<Output<T1>>
-        where T1:EventOutput, T2:EventOutput<Output=bool> {
-        self.register(OwnedGate::new(label,event,behavior))
-    }
+    stream fn gate [T1,T2] (label:Label, event:&T1, behavior:&T2)
+    -> Stream<Output<T1>> as OwnedGate<T1,T2>
+    where [T1:EventOutput, T2:EventOutput<Output=bool>]
+    { OwnedGate::new(label,event,behavior) }
 
     /// Like `gate` but passes the value when the condition is `false`.
-    pub fn gate_not<T1,T2>(&self, label:Label, event:&T1, behavior:&T2) -> Stream<Output<T1>>
-        where T1:EventOutput, T2:EventOutput<Output=bool> {
-        self.register(OwnedGateNot::new(label,event,behavior))
-    }
+    stream fn gate_not [T1,T2] (label:Label, event:&T1, behavior:&T2)
+    -> Stream<Output<T1>> as OwnedGateNot<T1,T2>
+    where [T1:EventOutput, T2:EventOutput<Output=bool>]
+    { OwnedGateNot::new(label,event,behavior) }
 
-    pub fn unwrap<T,S>(&self, label:Label, event:&T) -> Stream<S>
-        where T:EventOutput<Output=Option<S>>, S:Data {
-        self.register(OwnedUnwrap::new(label,event))
-    }
+    stream fn unwrap [T,S] (label:Label, event:&T) -> Stream<S> as OwnedUnwrap<T>
+    where [T:EventOutput<Output=Option<S>>, S:Data]
+    { OwnedUnwrap::new(label,event) }
 
-    pub fn iter<T1,X>(&self, label:Label, event:&T1) -> Stream<X>
-        where T1:EventOutput, for<'t> &'t T1::Output:IntoIterator<Item=&'t X>, X:Data {
-        self.register(OwnedIter::new(label,event))
-    }
+    stream fn iter [T1,X] (label:Label, event:&T1) -> Stream<X> as OwnedIter<T1>
+    where [T1:EventOutput, for<'t> &'t T1::Output:IntoIterator<Item=&'t X>, X:Data]
+    { OwnedIter::new(label,event) }
 
-    pub fn fold<T1,X>(&self, label:Label, event:&T1) -> Stream<X>
-        where T1:EventOutput, for<'t> &'t T1::Output:IntoIterator<Item=&'t X>, X:Data+Monoid {
-        self.register(OwnedFold::new(label,event))
-    }
+    stream fn fold [T1,X] (label:Label, event:&T1) -> Stream<X> as OwnedFold<T1>
+    where [T1:EventOutput, for<'t> &'t T1::Output:IntoIterator<Item=&'t X>, X:Data+Monoid]
+    { OwnedFold::new(label,event) }
 
-    pub fn _0<T1>(&self, label:Label, event:&T1) -> Stream<generics::ItemAt0<Output<T1>>>
-        where T1:EventOutput, T1::Output:generics::GetItemAt0, generics::ItemAt0<T1::Output>:Data {
-        self.register(OwnedGet0::new(label,event))
-    }
+    stream fn _0 [T1] (label:Label, event:&T1)
+    -> Stream<generics::ItemAt0<Output<T1>>> as OwnedGet0<T1>
+    where [T1:EventOutput, T1::Output:generics::GetItemAt0, generics::ItemAt0<T1::Output>:Data]
+    { OwnedGet0::new(label,event) }
 
-    pub fn _1<T1>(&self, label:Label, event:&T1) -> Stream<generics::ItemAt1<Output<T1>>>
-        where T1:EventOutput, T1::Output:generics::GetItemAt1, generics::ItemAt1<T1::Output>:Data {
-        self.register(OwnedGet1::new(label,event))
-    }
+    stream fn _1 [T1] (label:Label, event:&T1)
+    -> Stream<generics::ItemAt1<Output<T1>>> as OwnedGet1<T1>
+    where [T1:EventOutput, T1::Output:generics::GetItemAt1, generics::ItemAt1<T1::Output>:Data]
+    { OwnedGet1::new(label,event) }
 
-    pub fn _2<T1>(&self, label:Label, event:&T1) -> Stream<generics::ItemAt2<Output<T1>>>
-        where T1:EventOutput, T1::Output:generics::GetItemAt2, generics::ItemAt2<T1::Output>:Data {
-        self.register(OwnedGet2::new(label,event))
-    }
+    stream fn _2 [T1] (label:Label, event:&T1)
+    -> Stream<generics::ItemAt2<Output<T1>>> as OwnedGet2<T1>
+    where [T1:EventOutput, T1::Output:generics::GetItemAt2, generics::ItemAt2<T1::Output>:Data]
+    { OwnedGet2::new(label,event) }
 
 
     // === Any ===
@@ -153,154 +239,125 @@ impl Network {
     /// networks by creating an empty `any` and using the `attach` method to attach new streams to
     /// it. When a recursive network is created, `any_mut` breaks the cycle. After passing the first
     /// event, no more events will be passed till the end of the current FRP network resolution.
-    pub fn any_mut<T:Data>(&self, label:Label) -> Any<T> {
-        self.register_raw(OwnedAny::new(label))
-    }
+    raw fn any_mut [T:Data] (label:Label) -> Any<T> as OwnedAny<T>
+    { OwnedAny::new(label) }
 
     /// Merges multiple input streams into a single output stream. All input streams have to share
     /// the same output data type.
-    pub fn any<T1,T2,T:Data>(&self, label:Label, t1:&T1, t2:&T2) -> Stream<T>
-    where T1:EventOutput<Output=T>, T2:EventOutput<Output=T> {
-        self.register(OwnedAny::new2(label,t1,t2))
-    }
+    stream fn any [T1,T2,T:Data] (label:Label, t1:&T1, t2:&T2) -> Stream<T> as OwnedAny<T>
+    where [T1:EventOutput<Output=T>, T2:EventOutput<Output=T>]
+    { OwnedAny::new2(label,t1,t2) }
 
     /// Specialized version of `any`.
-    pub fn any2<T1,T2,T:Data>(&self, label:Label, t1:&T1, t2:&T2) -> Stream<T>
-    where T1:EventOutput<Output=T>, T2:EventOutput<Output=T> {
-        self.register(OwnedAny::new2(label,t1,t2))
-    }
+    stream fn any2 [T1,T2,T:Data] (label:Label, t1:&T1, t2:&T2) -> Stream<T> as OwnedAny<T>
+    where [T1:EventOutput<Output=T>, T2:EventOutput<Output=T>]
+    { OwnedAny::new2(label,t1,t2) }
 
     /// Specialized version of `any`.
-    pub fn any3<T1,T2,T3,T:Data>(&self, label:Label, t1:&T1, t2:&T2, t3:&T3) -> Stream<T>
-    where T1:EventOutput<Output=T>, T2:EventOutput<Output=T>, T3:EventOutput<Output=T> {
-        self.register(OwnedAny::new3(label,t1,t2,t3))
-    }
+    stream fn any3 [T1,T2,T3,T:Data] (label:Label, t1:&T1, t2:&T2, t3:&T3)
+    -> Stream<T> as OwnedAny<T>
+    where [T1:EventOutput<Output=T>, T2:EventOutput<Output=T>, T3:EventOutput<Output=T>]
+    { OwnedAny::new3(label,t1,t2,t3) }
 
     /// Specialized version of `any`.
-    pub fn any4<T1,T2,T3,T4,T:Data>
-    (&self, label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4) -> Stream<T>
-    where T1:EventOutput<Output=T>,
-          T2:EventOutput<Output=T>,
-          T3:EventOutput<Output=T>,
-          T4:EventOutput<Output=T> {
-        self.register(OwnedAny::new4(label,t1,t2,t3,t4))
-    }
+    stream fn any4 [T1,T2,T3,T4,T:Data] (label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4)
+    -> Stream<T> as OwnedAny<T>
+    where [T1:EventOutput<Output=T>, T2:EventOutput<Output=T>, T3:EventOutput<Output=T>,
+           T4:EventOutput<Output=T>]
+    { OwnedAny::new4(label,t1,t2,t3,t4) }
 
 
     // === Any_ ===
 
     /// Like `any_mut` but drops the incoming data. You can attach streams of different types.
-    pub fn any_mut_(&self, label:Label) -> Any_ {
-        self.register_raw(OwnedAny_::new(label))
-    }
+    raw fn any_mut_ [] (label:Label) -> Any_ as OwnedAny_
+    { OwnedAny_::new(label) }
 
     /// Like `any` but drops the incoming data. You can attach streams of different types.
-    pub fn any_<T1,T2>(&self, label:Label, t1:&T1, t2:&T2) -> Stream<()>
-    where T1:EventOutput, T2:EventOutput {
-        self.register(OwnedAny_::new2(label,t1,t2))
-    }
+    stream fn any_ [T1,T2] (label:Label, t1:&T1, t2:&T2) -> Stream<()> as OwnedAny_
+    where [T1:EventOutput, T2:EventOutput]
+    { OwnedAny_::new2(label,t1,t2) }
 
     /// Specialized version of `any_`.
-    pub fn any2_<T1,T2>(&self, label:Label, t1:&T1, t2:&T2) -> Stream<()>
-    where T1:EventOutput, T2:EventOutput {
-        self.register(OwnedAny_::new2(label,t1,t2))
-    }
+    stream fn any2_ [T1,T2] (label:Label, t1:&T1, t2:&T2) -> Stream<()> as OwnedAny_
+    where [T1:EventOutput, T2:EventOutput]
+    { OwnedAny_::new2(label,t1,t2) }
 
     /// Specialized version of `any_`.
-    pub fn any3_<T1,T2,T3>(&self, label:Label, t1:&T1, t2:&T2, t3:&T3) -> Stream<()>
-    where T1:EventOutput, T2:EventOutput, T3:EventOutput {
-        self.register(OwnedAny_::new3(label,t1,t2,t3))
-    }
+    stream fn any3_ [T1,T2,T3] (label:Label, t1:&T1, t2:&T2, t3:&T3) -> Stream<()> as OwnedAny_
+    where [T1:EventOutput, T2:EventOutput, T3:EventOutput]
+    { OwnedAny_::new3(label,t1,t2,t3) }
 
     /// Specialized version of `any_`.
-    pub fn any4_<T1,T2,T3,T4>
-    (&self, label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4) -> Stream<()>
-    where T1:EventOutput, T2:EventOutput, T3:EventOutput, T4:EventOutput {
-        self.register(OwnedAny_::new4(label,t1,t2,t3,t4))
-    }
+    stream fn any4_ [T1,T2,T3,T4] (label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4)
+    -> Stream<()> as OwnedAny_
+    where [T1:EventOutput, T2:EventOutput, T3:EventOutput, T4:EventOutput]
+    { OwnedAny_::new4(label,t1,t2,t3,t4) }
 
 
     // === All ===
 
     /// Merges input streams into a stream containing values from all of them. On event from any of
     /// the input streams, all streams are sampled and the final event is produced.
-    pub fn all_mut<T:Data>(&self, label:Label) -> AllMut<T> {
-        self.register_raw(OwnedAllMut::new(label))
-    }
-
-    pub fn all_vec2<Out,T1,T2>(&self, label:Label, t1:&T1, t2:&T2) -> Stream<Vec<Out>>
-    where Out:Data, T1:EventOutput<Output=Out>, T2:EventOutput<Output=Out> {
-        self.register(OwnedAllMut::new(label).with(t1).with(t2))
-    }
-
-    pub fn all_vec3<Out,T1,T2,T3>(&self, label:Label, t1:&T1, t2:&T2, t3:&T3) -> Stream<Vec<Out>>
-    where Out : Data,
-          T1  : EventOutput<Output=Out>,
-          T2  : EventOutput<Output=Out>,
-          T3  : EventOutput<Output=Out> {
-        self.register(OwnedAllMut::new(label).with(t1).with(t2).with(t3))
-    }
-
-    pub fn all_vec4<Out,T1,T2,T3,T4>
-    (&self, label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4) -> Stream<Vec<Out>>
-    where Out : Data,
-          T1  : EventOutput<Output=Out>,
-          T2  : EventOutput<Output=Out>,
-          T3  : EventOutput<Output=Out>,
-          T4  : EventOutput<Output=Out> {
-        self.register(OwnedAllMut::new(label).with(t1).with(t2).with(t3).with(t4))
-    }
-
-    pub fn all_vec5<Out,T1,T2,T3,T4,T5>
-    (&self, label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4, t5:&T5) -> Stream<Vec<Out>>
-        where Out : Data,
-              T1  : EventOutput<Output=Out>,
-              T2  : EventOutput<Output=Out>,
-              T3  : EventOutput<Output=Out>,
-              T4  : EventOutput<Output=Out>,
-              T5  : EventOutput<Output=Out> {
-        self.register(OwnedAllMut::new(label).with(t1).with(t2).with(t3).with(t4).with(t5))
-    }
-
-    pub fn all_vec6<Out,T1,T2,T3,T4,T5,T6>
-    (&self, label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4, t5:&T5, t6:&T6) -> Stream<Vec<Out>>
-        where Out : Data,
-              T1  : EventOutput<Output=Out>,
-              T2  : EventOutput<Output=Out>,
-              T3  : EventOutput<Output=Out>,
-              T4  : EventOutput<Output=Out>,
-              T5  : EventOutput<Output=Out>,
-              T6  : EventOutput<Output=Out> {
-        self.register(OwnedAllMut::new(label).with(t1).with(t2).with(t3).with(t4).with(t5).with(t6))
-    }
+    raw fn all_mut [T:Data] (label:Label) -> AllMut<T> as OwnedAllMut<T>
+    { OwnedAllMut::new(label) }
+
+    stream fn all_vec2 [Out,T1,T2] (label:Label, t1:&T1, t2:&T2)
+    -> Stream<Vec<Out>> as OwnedAllMut<Out>
+    where [Out:Data, T1:EventOutput<Output=Out>, T2:EventOutput<Output=Out>]
+    { OwnedAllMut::new(label).with(t1).with(t2) }
+
+    stream fn all_vec3 [Out,T1,T2,T3] (label:Label, t1:&T1, t2:&T2, t3:&T3)
+    -> Stream<Vec<Out>> as OwnedAllMut<Out>
+    where [Out:Data, T1:EventOutput<Output=Out>, T2:EventOutput<Output=Out>,
+           T3:EventOutput<Output=Out>]
+    { OwnedAllMut::new(label).with(t1).with(t2).with(t3) }
+
+    stream fn all_vec4 [Out,T1,T2,T3,T4] (label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4)
+    -> Stream<Vec<Out>> as OwnedAllMut<Out>
+    where [Out:Data, T1:EventOutput<Output=Out>, T2:EventOutput<Output=Out>,
+           T3:EventOutput<Output=Out>, T4:EventOutput<Output=Out>]
+    { OwnedAllMut::new(label).with(t1).with(t2).with(t3).with(t4) }
+
+    stream fn all_vec5 [Out,T1,T2,T3,T4,T5] (label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4, t5:&T5)
+    -> Stream<Vec<Out>> as OwnedAllMut<Out>
+    where [Out:Data, T1:EventOutput<Output=Out>, T2:EventOutput<Output=Out>,
+           T3:EventOutput<Output=Out>, T4:EventOutput<Output=Out>,
+           T5:EventOutput<Output=Out>]
+    { OwnedAllMut::new(label).with(t1).with(t2).with(t3).with(t4).with(t5) }
+
+    stream fn all_vec6 [Out,T1,T2,T3,T4,T5,T6]
+    (label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4, t5:&T5, t6:&T6)
+    -> Stream<Vec<Out>> as OwnedAllMut<Out>
+    where [Out:Data, T1:EventOutput<Output=Out>, T2:EventOutput<Output=Out>,
+           T3:EventOutput<Output=Out>, T4:EventOutput<Output=Out>,
+           T5:EventOutput<Output=Out>, T6:EventOutput<Output=Out>]
+    { OwnedAllMut::new(label).with(t1).with(t2).with(t3).with(t4).with(t5).with(t6) }
 
     /// Merges input streams into a stream containing values from all of them. On event from any of
     /// the input streams, all streams are sampled and the final event is produced.
-    pub fn all<T1,T2>(&self, label:Label, t1:&T1, t2:&T2) -> Stream<(Output<T1>,Output<T2>)>
-    where T1:EventOutput, T2:EventOutput {
-        self.register(OwnedAll2::new(label,t1,t2))
-    }
+    stream fn all [T1,T2] (label:Label, t1:&T1, t2:&T2)
+    -> Stream<(Output<T1>,Output<T2>)> as OwnedAll2<T1,T2>
+    where [T1:EventOutput, T2:EventOutput]
+    { OwnedAll2::new(label,t1,t2) }
 
     /// Specialized version of `all`.
-    pub fn all2<T1,T2>(&self, label:Label, t1:&T1, t2:&T2) -> Stream<(Output<T1>,Output<T2>)>
-    where T1:EventOutput, T2:EventOutput {
-        self.register(OwnedAll2::new(label,t1,t2))
-    }
+    stream fn all2 [T1,T2] (label:Label, t1:&T1, t2:&T2)
+    -> Stream<(Output<T1>,Output<T2>)> as OwnedAll2<T1,T2>
+    where [T1:EventOutput, T2:EventOutput]
+    { OwnedAll2::new(label,t1,t2) }
 
     /// Specialized version of `all`.
-    pub fn all3<T1,T2,T3>
-    (&self, label:Label, t1:&T1, t2:&T2, t3:&T3) -> Stream<(Output<T1>,Output<T2>,Output<T3>)>
-    where T1:EventOutput, T2:EventOutput, T3:EventOutput {
-        self.register(OwnedAll3::new(label,t1,t2,t3))
-    }
+    stream fn all3 [T1,T2,T3] (label:Label, t1:&T1, t2:&T2, t3:&T3)
+    -> Stream<(Output<T1>,Output<T2>,Output<T3>)> as OwnedAll3<T1,T2,T3>
+    where [T1:EventOutput, T2:EventOutput, T3:EventOutput]
+    { OwnedAll3::new(label,t1,t2,t3) }
 
     /// Specialized version of `all`.
-    pub fn all4<T1,T2,T3,T4>
-    (&self, label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4)
-    -> Stream<(Output<T1>,Output<T2>,Output<T3>,Output<T4>)>
-    where T1:EventOutput, T2:EventOutput, T3:EventOutput, T4:EventOutput {
-        self.register(OwnedAll4::new(label,t1,t2,t3,t4))
-    }
+    stream fn all4 [T1,T2,T3,T4] (label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4)
+    -> Stream<(Output<T1>,Output<T2>,Output<T3>,Output<T4>)> as OwnedAll4<T1,T2,T3,T4>
+    where [T1:EventOutput, T2:EventOutput, T3:EventOutput, T4:EventOutput]
+    { OwnedAll4::new(label,t1,t2,t3,t4) }
 
 
     // === Map ===
@@ -308,32 +365,28 @@ impl Network {
     /// On every event from the first input stream, sample all other input streams and run the
     /// provided function on all gathered values. If you want to run the function on event from any
     /// input stream, use the `all_with` function family instead.
-    pub fn map<T,F,Out>(&self, label:Label, src:&T, f:F) -> Stream<Out>
-    where T:EventOutput, Out:Data, F:'static+Fn(&Output<T>)->Out {
-        self.register(OwnedMap::new(label,src,f))
-    }
+    stream fn map [T,F,Out] (label:Label, src:&T, f:F) -> Stream<Out> as OwnedMap<T,F>
+    where [T:EventOutput, Out:Data, F:'static+Fn(&Output<T>)->Out]
+    { OwnedMap::new(label,src,f) }
 
     /// Specialized version of `map`.
-    pub fn map2<T1,T2,F,T>(&self, label:Label, t1:&T1, t2:&T2, f:F) -> Stream<T>
-    where T1:EventOutput, T2:EventOutput, T:Data, F:'static+Fn(&Output<T1>,&Output<T2>)->T {
-        self.register(OwnedMap2::new(label,t1,t2,f))
-    }
+    stream fn map2 [T1,T2,F,T] (label:Label, t1:&T1, t2:&T2, f:F) -> Stream<T> as OwnedMap2<T1,T2,F>
+    where [T1:EventOutput, T2:EventOutput, T:Data, F:'static+Fn(&Output<T1>,&Output<T2>)->T]
+    { OwnedMap2::new(label,t1,t2,f) }
 
     /// Specialized version of `map`.
-    pub fn map3<T1,T2,T3,F,T>
-    (&self, label:Label, t1:&T1, t2:&T2, t3:&T3, f:F) -> Stream<T>
-    where T1:EventOutput, T2:EventOutput, T3:EventOutput, T:Data,
-          F:'static+Fn(&Output<T1>,&Output<T2>,&Output<T3>)->T {
-        self.register(OwnedMap3::new(label,t1,t2,t3,f))
-    }
+    stream fn map3 [T1,T2,T3,F,T] (label:Label, t1:&T1, t2:&T2, t3:&T3, f:F)
+    -> Stream<T> as OwnedMap3<T1,T2,T3,F>
+    where [T1:EventOutput, T2:EventOutput, T3:EventOutput, T:Data,
+           F:'static+Fn(&Output<T1>,&Output<T2>,&Output<T3>)->T]
+    { OwnedMap3::new(label,t1,t2,t3,f) }
 
     /// Specialized version of `map`.
-    pub fn map4<T1,T2,T3,T4,F,T>
-    (&self, label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4, f:F) -> Stream<T>
-    where T1:EventOutput, T2:EventOutput, T3:EventOutput, T4:EventOutput, T:Data,
-          F:'static+Fn(&Output<T1>,&Output<T2>,&Output<T3>,&Output<T4>)->T {
-        self.register(OwnedMap4::new(label,t1,t2,t3,t4,f))
-    }
+    stream fn map4 [T1,T2,T3,T4,F,T] (label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4, f:F)
+    -> Stream<T> as OwnedMap4<T1,T2,T3,T4,F>
+    where [T1:EventOutput, T2:EventOutput, T3:EventOutput, T4:EventOutput, T:Data,
+           F:'static+Fn(&Output<T1>,&Output<T2>,&Output<T3>,&Output<T4>)->T]
+    { OwnedMap4::new(label,t1,t2,t3,t4,f) }
 
 
     // === AllWith ===
@@ -341,254 +394,112 @@ impl Network {
     /// On every input event sample all input streams and run the provided function on all gathered
     /// values. If you want to run the function only on event on the first input, use the `map`
     /// function family instead.
-    pub fn all_with<T1,T2,F,T>(&self, label:Label, t1:&T1, t2:&T2, f:F) -> Stream<T>
-    where T1:EventOutput, T2:EventOutput, T:Data, F:'static+Fn(&Output<T1>,&Output<T2>)->T {
-        self.register(OwnedAllWith2::new(label,t1,t2,f))
-    }
+    stream fn all_with [T1,T2,F,T] (label:Label, t1:&T1, t2:&T2, f:F)
+    -> Stream<T> as OwnedAllWith2<T1,T2,F>
+    where [T1:EventOutput, T2:EventOutput, T:Data, F:'static+Fn(&Output<T1>,&Output<T2>)->T]
+    { OwnedAllWith2::new(label,t1,t2,f) }
 
     /// Specialized version `all_with`.
-    pub fn all_with3<T1,T2,T3,F,T>
-    (&self, label:Label, t1:&T1, t2:&T2, t3:&T3, f:F) -> Stream<T>
-    where T1:EventOutput, T2:EventOutput, T3:EventOutput, T:Data,
-          F:'static+Fn(&Output<T1>,&Output<T2>,&Output<T3>)->T {
-        self.register(OwnedAllWith3::new(label,t1,t2,t3,f))
-    }
+    stream fn all_with3 [T1,T2,T3,F,T] (label:Label, t1:&T1, t2:&T2, t3:&T3, f:F)
+    -> Stream<T> as OwnedAllWith3<T1,T2,T3,F>
+    where [T1:EventOutput, T2:EventOutput, T3:EventOutput, T:Data,
+           F:'static+Fn(&Output<T1>,&Output<T2>,&Output<T3>)->T]
+    { OwnedAllWith3::new(label,t1,t2,t3,f) }
 
     /// Specialized version `all_with`.
-    pub fn all_with4<T1,T2,T3,T4,F,T>
-    (&self, label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4, f:F) -> Stream<T>
-    where T1:EventOutput, T2:EventOutput, T3:EventOutput, T4:EventOutput, T:Data,
-          F:'static+Fn(&Output<T1>,&Output<T2>,&Output<T3>,&Output<T4>)->T {
-        self.register(OwnedAllWith4::new(label,t1,t2,t3,t4,f))
-    }
-}
-
-
-
-// ========================
-// === Dynamic Node API ===
-// ========================
-
-/// This is a phantom structure used by macros to create dynamic FRP graphs. It exposes the same
-/// API as `Network` in order to reuse macro code for both network and dynamic modes.
-#[derive(Clone,Copy,Debug,Default)]
-pub struct DynamicNetwork {}
-
-impl DynamicNetwork {
-    /// Constructor.
-    pub fn new() -> Self {
-        default()
-    }
-}
-
-/// See docs of `Network` to learn about the methods.
-impl DynamicNetwork {
-    pub fn source<T:Data>(self, label:Label) -> OwnedSource<T> {
-        OwnedSource::new(label)
-    }
-
-    pub fn source_(self, label:Label) -> OwnedSource {
-        OwnedSource::new(label)
-    }
-
-    pub fn sampler<T,Out>(self, label:Label, src:&T) -> OwnedSampler<Out>
-        where T:EventOutput<Output=Out>, Out:Data {
-        OwnedSampler::new(label,src)
-    }
-
-    pub fn trace<T:EventOutput>(self, label:Label, src:&T) -> OwnedStream<Output<T>> {
-        OwnedTrace::new(label,src).into()
-    }
-
-    pub fn toggle<T:EventOutput>(self, label:Label, src:&T) -> OwnedStream<bool> {
-        OwnedToggle::new(label,src).into()
-    }
-
-    pub fn count<T:EventOutput>(self, label:Label, src:&T) -> OwnedCount<T> {
-        OwnedCount::new(label,src)
-    }
-
-    pub fn constant<X:Data,T:EventOutput> (self, label:Label, src:&T, value:X) -> OwnedStream<X> {
-        OwnedConstant::new(label,src,value).into()
-    }
-
-    pub fn previous<T:EventOutput> (self, label:Label, src:&T) -> OwnedStream<Output<T>> {
-        OwnedPrevious::new(label,src).into()
-    }
-
-    pub fn sample<T1:EventOutput,T2:EventOutput>
-    (self, label:Label, behavior:&T1, event:&T2) -> OwnedStream<Output<T1>> {
-        OwnedSample::new(label,behavior,event).into()
-    }
-
-    pub fn gate<T1,T2>(self, label:Label, event:&T1, behavior:&T2) -> OwnedStream<Output<T1>>
-    where T1:EventOutput, T2:EventOutput<Output=bool> {
-        OwnedGate::new(label,event,behavior).into()
-    }
-
-    pub fn gate_not<T1,T2>(self, label:Label, event:&T1, behavior:&T2) -> OwnedStream<Output<T1>>
-    where T1:EventOutput, T2:EventOutput<Output=bool> {
-        OwnedGateNot::new(label,event,behavior).into()
-    }
-
-    pub fn iter<T1,X>(self, label:Label, event:&T1) -> OwnedStream<X>
-    where T1:EventOutput, for<'t> &'t T1::Output:IntoIterator<Item=&'t X>, X:Data {
-        OwnedIter::new(label,event).into()
-    }
-
-    pub fn fold<T1,X>(self, label:Label, event:&T1) -> OwnedStream<X>
-    where T1:EventOutput, for<'t> &'t T1::Output:IntoIterator<Item=&'t X>, X:Data+Monoid {
-        OwnedFold::new(label,event).into()
-    }
-
-    pub fn _0<T1>(self, label:Label, event:&T1) -> OwnedStream<generics::ItemAt0<Output<T1>>>
-    where T1:EventOutput, T1::Output:generics::GetItemAt0, generics::ItemAt0<T1::Output>:Data {
-        OwnedGet0::new(label,event).into()
-    }
-
-    pub fn _1<T1>(self, label:Label, event:&T1) -> OwnedStream<generics::ItemAt1<Output<T1>>>
-    where T1:EventOutput, T1::Output:generics::GetItemAt1, generics::ItemAt1<T1::Output>:Data {
-        OwnedGet1::new(label,event).into()
-    }
-
-    pub fn _2<T1>(self, label:Label, event:&T1) -> OwnedStream<generics::ItemAt2<Output<T1>>>
-    where T1:EventOutput, T1::Output:generics::GetItemAt2, generics::ItemAt2<T1::Output>:Data {
-        OwnedGet2::new(label,event).into()
-    }
-
-
-    // === Any ===
-
-    pub fn any_mut<T:Data>(self, label:Label) -> OwnedAny<T> {
-        OwnedAny::new(label)
-    }
-
-    pub fn any<T1,T2,T:Data>(self, label:Label, t1:&T1, t2:&T2) -> OwnedStream<T>
-    where T1:EventOutput<Output=T>, T2:EventOutput<Output=T> {
-        OwnedAny::new2(label,t1,t2).into()
-    }
-
-    pub fn any2<T1,T2,T:Data>(self, label:Label, t1:&T1, t2:&T2) -> OwnedStream<T>
-    where T1:EventOutput<Output=T>, T2:EventOutput<Output=T> {
-        OwnedAny::new2(label,t1,t2).into()
-    }
-
-    pub fn any3<T1,T2,T3,T:Data>(self, label:Label, t1:&T1, t2:&T2, t3:&T3) -> OwnedStream<T>
-    where T1:EventOutput<Output=T>, T2:EventOutput<Output=T>, T3:EventOutput<Output=T> {
-        OwnedAny::new3(label,t1,t2,t3).into()
-    }
-
-    pub fn any4<T1,T2,T3,T4,T:Data>
-    (self, label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4) -> OwnedStream<T>
-    where T1:EventOutput<Output=T>,
-              T2:EventOutput<Output=T>,
-              T3:EventOutput<Output=T>,
-              T4:EventOutput<Output=T> {
-        OwnedAny::new4(label,t1,t2,t3,t4).into()
-    }
-
-
-    // === Any_ ===
-
-    pub fn any_mut_(self, label:Label) -> OwnedAny_ {
-        OwnedAny_::new(label)
-    }
-
-    pub fn any_<T1,T2>(self, label:Label, t1:&T1, t2:&T2) -> OwnedStream<()>
-    where T1:EventOutput, T2:EventOutput {
-        OwnedAny_::new2(label,t1,t2).into()
-    }
-
-    pub fn any2_<T1,T2>(self, label:Label, t1:&T1, t2:&T2) -> OwnedStream<()>
-    where T1:EventOutput, T2:EventOutput {
-        OwnedAny_::new2(label,t1,t2).into()
-    }
-
-    pub fn any3_<T1,T2,T3>(self, label:Label, t1:&T1, t2:&T2, t3:&T3) -> OwnedStream<()>
-    where T1:EventOutput, T2:EventOutput, T3:EventOutput {
-        OwnedAny_::new3(label,t1,t2,t3).into()
-    }
-
-    pub fn any4_<T1,T2,T3,T4>
-    (self, label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4) -> OwnedStream<()>
-    where T1:EventOutput, T2:EventOutput, T3:EventOutput, T4:EventOutput {
-        OwnedAny_::new4(label,t1,t2,t3,t4).into()
-    }
-
-
-    // === All ===
-
-    pub fn all<T1,T2>(self, label:Label, t1:&T1, t2:&T2) -> OwnedStream<(Output<T1>,Output<T2>)>
-    where T1:EventOutput, T2:EventOutput {
-        OwnedAll2::new(label,t1,t2).into()
-    }
-
-    pub fn all2<T1,T2>(self, label:Label, t1:&T1, t2:&T2) -> OwnedStream<(Output<T1>,Output<T2>)>
-    where T1:EventOutput, T2:EventOutput {
-        OwnedAll2::new(label,t1,t2).into()
-    }
-
-    pub fn all3<T1,T2,T3>
-    (self, label:Label, t1:&T1, t2:&T2, t3:&T3) -> OwnedStream<(Output<T1>,Output<T2>,Output<T3>)>
-    where T1:EventOutput, T2:EventOutput, T3:EventOutput {
-        OwnedAll3::new(label,t1,t2,t3).into()
-    }
-
-    pub fn all4<T1,T2,T3,T4>
-    (self, label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4)
-     -> OwnedStream<(Output<T1>,Output<T2>,Output<T3>,Output<T4>)>
-    where T1:EventOutput, T2:EventOutput, T3:EventOutput, T4:EventOutput {
-        OwnedAll4::new(label,t1,t2,t3,t4).into()
-    }
-
-
-    // === Map ===
-
-    pub fn map<T,F,Out>(self, label:Label, src:&T, f:F) -> OwnedStream<Out>
-    where T:EventOutput, Out:Data, F:'static+Fn(&Output<T>)->Out {
-        OwnedMap::new(label,src,f).into()
-    }
-
-    pub fn map2<T1,T2,F,T>(self, label:Label, t1:&T1, t2:&T2, f:F) -> OwnedStream<T>
-    where T1:EventOutput, T2:EventOutput, T:Data, F:'static+Fn(&Output<T1>,&Output<T2>)->T {
-        OwnedMap2::new(label,t1,t2,f).into()
-    }
-
-    pub fn map3<T1,T2,T3,F,T>
-    (self, label:Label, t1:&T1, t2:&T2, t3:&T3, f:F) -> OwnedStream<T>
-    where T1:EventOutput, T2:EventOutput, T3:EventOutput, T:Data,
-          F:'static+Fn(&Output<T1>,&Output<T2>,&Output<T3>)->T {
-        OwnedMap3::new(label,t1,t2,t3,f).into()
-    }
-
-    pub fn map4<T1,T2,T3,T4,F,T>
-    (self, label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4, f:F) -> OwnedStream<T>
-    where T1:EventOutput, T2:EventOutput, T3:EventOutput, T4:EventOutput, T:Data,
-          F:'static+Fn(&Output<T1>,&Output<T2>,&Output<T3>,&Output<T4>)->T {
-        OwnedMap4::new(label,t1,t2,t3,t4,f).into()
-    }
-
-
-    // === AllWith ===
-
-    pub fn apply2<T1,T2,F,T>(self, label:Label, t1:&T1, t2:&T2, f:F) -> OwnedStream<T>
-    where T1:EventOutput, T2:EventOutput, T:Data, F:'static+Fn(&Output<T1>,&Output<T2>)->T {
-        OwnedAllWith2::new(label,t1,t2,f).into()
-    }
-
-    pub fn apply3<T1,T2,T3,F,T>
-    (self, label:Label, t1:&T1, t2:&T2, t3:&T3, f:F) -> OwnedStream<T>
-    where T1:EventOutput, T2:EventOutput, T3:EventOutput, T:Data,
-          F:'static+Fn(&Output<T1>,&Output<T2>,&Output<T3>)->T {
-        OwnedAllWith3::new(label,t1,t2,t3,f).into()
-    }
-
-    pub fn apply4<T1,T2,T3,T4,F,T>
-    (self, label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4, f:F) -> OwnedStream<T>
-    where T1:EventOutput, T2:EventOutput, T3:EventOutput, T4:EventOutput, T:Data,
-          F:'static+Fn(&Output<T1>,&Output<T2>,&Output<T3>,&Output<T4>)->T {
-        OwnedAllWith4::new(label,t1,t2,t3,t4,f).into()
-    }
+    stream fn all_with4 [T1,T2,T3,T4,F,T] (label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4, f:F)
+    -> Stream<T> as OwnedAllWith4<T1,T2,T3,T4,F>
+    where [T1:EventOutput, T2:EventOutput, T3:EventOutput, T4:EventOutput, T:Data,
+           F:'static+Fn(&Output<T1>,&Output<T2>,&Output<T3>,&Output<T4>)->T]
+    { OwnedAllWith4::new(label,t1,t2,t3,t4,f) }
+
+
+    /// Alias of `all_with`.
+    derived fn apply2 [T1,T2,F,T] (label:Label, t1:&T1, t2:&T2, f:F)
+    -> Stream<T> as OwnedAllWith2<T1,T2,F>
+    where [T1:EventOutput, T2:EventOutput, T:Data, F:'static+Fn(&Output<T1>,&Output<T2>)->T]
+    { network.all_with(label,t1,t2,f) }
+
+    /// Alias of `all_with3`.
+    derived fn apply3 [T1,T2,T3,F,T] (label:Label, t1:&T1, t2:&T2, t3:&T3, f:F)
+    -> Stream<T> as OwnedAllWith3<T1,T2,T3,F>
+    where [T1:EventOutput, T2:EventOutput, T3:EventOutput, T:Data,
+           F:'static+Fn(&Output<T1>,&Output<T2>,&Output<T3>)->T]
+    { network.all_with3(label,t1,t2,t3,f) }
+
+    /// Alias of `all_with4`.
+    derived fn apply4 [T1,T2,T3,T4,F,T] (label:Label, t1:&T1, t2:&T2, t3:&T3, t4:&T4, f:F)
+    -> Stream<T> as OwnedAllWith4<T1,T2,T3,T4,F>
+    where [T1:EventOutput, T2:EventOutput, T3:EventOutput, T4:EventOutput, T:Data,
+           F:'static+Fn(&Output<T1>,&Output<T2>,&Output<T3>,&Output<T4>)->T]
+    { network.all_with4(label,t1,t2,t3,t4,f) }
+
+
+    // === Filter ===
+
+    /// Passes only the events for which the predicate returns `true`.
+    stream fn filter [T,P] (label:Label, src:&T, predicate:P)
+    -> Stream<Output<T>> as OwnedFilter<T,P>
+    where [T:EventOutput, P:'static+Fn(&Output<T>)->bool]
+    { OwnedFilter::new(label,src,predicate) }
+
+    /// Runs the function on every incoming event and emits the result if it is `Some`. This is a
+    /// shorter and cheaper version of `map` followed by `unwrap`.
+    stream fn filter_map [T,F,Out] (label:Label, src:&T, f:F) -> Stream<Out> as OwnedFilterMap<T,F>
+    where [T:EventOutput, Out:Data, F:'static+Fn(&Output<T>)->Option<Out>]
+    { OwnedFilterMap::new(label,src,f) }
+
+    /// Splits the stream in two. The first output stream gets the events for which the predicate
+    /// returns `true`, the second one gets the rest of them.
+    derived fn partition [T,P] (label:Label, src:&T, predicate:P)
+    -> (Stream<Output<T>>,Stream<Output<T>>)
+    as (OwnedFilter<T,Predicate<Output<T>>>,OwnedFilter<T,Predicate<Output<T>>>)
+    where [T:EventOutput, P:'static+Fn(&Output<T>)->bool]
+    {
+        let (matching,rest) = partition_predicates(predicate);
+        let (label1,label2) = partition_labels(label);
+        let matching        = network.filter(label1,src,matching);
+        let rest            = network.filter(label2,src,rest);
+        (matching,rest)
+    }
+
+    /// Higher-order node. Every incoming event is a stream, and the output follows the events of
+    /// the most recently received one. The previously received streams are disconnected.
+    stream fn switch [T,Out] (label:Label, src:&T) -> Stream<Out> as OwnedSwitch<T>
+    where [T:EventOutput<Output=Stream<Out>>, Out:Data]
+    { OwnedSwitch::new(label,src) }
+
+
+    // === Time ===
+
+    /// Emits every incoming event after `time` has passed, according to the provided clock.
+    stream fn delay [T:EventOutput] (label:Label, src:&T, clock:&Clock, time:Time)
+    -> Stream<Output<T>> as OwnedDelay<T>
+    { OwnedDelay::new(label,src,clock,time) }
+
+    /// Emits the last incoming event after no new events arrived for `time`. Useful for reacting
+    /// to the end of a burst of events, like typing.
+    stream fn debounce [T:EventOutput] (label:Label, src:&T, clock:&Clock, time:Time)
+    -> Stream<Output<T>> as OwnedDebounce<T>
+    { OwnedDebounce::new(label,src,clock,time) }
+
+    /// Emits at most one event per `time`. The first event is passed immediately. The events
+    /// arriving in the meantime are dropped, except the last one, which is emitted when the time
+    /// passes.
+    stream fn throttle [T:EventOutput] (label:Label, src:&T, clock:&Clock, time:Time)
+    -> Stream<Output<T>> as OwnedThrottle<T>
+    { OwnedThrottle::new(label,src,clock,time) }
+
+    /// Samples the behavior every `period` and emits its value. The period has to be positive.
+    stream fn sample_every [T:EventOutput] (label:Label, behavior:&T, clock:&Clock, period:Time)
+    -> Stream<Output<T>> as OwnedSampleEvery<T>
+    { OwnedSampleEvery::new(label,behavior,clock,period) }
+
+    /// Emits `false` on every incoming event and `true` when no new event arrived for `time`
+    /// afterwards. Nothing is emitted before the first incoming event.
+    stream fn timeout [T:EventOutput] (label:Label, src:&T, clock:&Clock, time:Time)
+    -> Stream<bool> as OwnedTimeout<T>
+    { OwnedTimeout::new(label,src,clock,time) }
 }
 
 
@@ -870,9 +781,9 @@ impl<T1:EventOutput,T2:EventOutput> OwnedSample<T1,T2> {
     /// Constructor.
     pub fn new(label:Label, behavior:&T1, src:&T2) -> Self {
         let event      = src.clone_ref();
-        let behavior   = watch_stream(behavior);
-        let definition = SampleData {behavior,event};
-        Self::construct_and_connect(label,src,definition)
+        let watch      = watch_stream(behavior);
+        let definition = SampleData {behavior:watch,event};
+        Self::construct_and_connect(label,src,definition).rank_above(behavior)
     }
 }
 
@@ -908,9 +819,9 @@ where T1:EventOutput,T2:EventOutput<Output=bool> {
     /// Constructor.
     pub fn new(label:Label, src:&T1, behavior:&T2) -> Self {
         let event      = src.clone_ref();
-        let behavior   = watch_stream(behavior);
-        let definition = GateData {event,behavior};
-        Self::construct_and_connect(label,src,definition)
+        let watch      = watch_stream(behavior);
+        let definition = GateData {event,behavior:watch};
+        Self::construct_and_connect(label,src,definition).rank_above(behavior)
     }
 }
 
@@ -950,9 +861,9 @@ impl<T1,T2> OwnedGateNot<T1,T2>
     /// Constructor.
     pub fn new(label:Label, src:&T1, behavior:&T2) -> Self {
         let event      = src.clone_ref();
-        let behavior   = watch_stream(behavior);
-        let definition = GateNotData {event,behavior};
-        Self::construct_and_connect(label,src,definition)
+        let watch      = watch_stream(behavior);
+        let definition = GateNotData {event,behavior:watch};
+        Self::construct_and_connect(label,src,definition).rank_above(behavior)
     }
 }
 
@@ -1399,7 +1310,7 @@ impl<Out:Data> OwnedAllMut<Out> {
         let watches = default();
         let phantom = default();
         let def     = AllMutData {srcs,watches,phantom};
-        Self::construct(label,def)
+        Self::construct(label,def).combining_inputs()
     }
 
     /// Takes ownership of self and returns it with a new stream attached.
@@ -1492,7 +1403,7 @@ where T1:EventOutput, T2:EventOutput {
         let src1 = watch_stream(t1);
         let src2 = watch_stream(t2);
         let def   = All2Data {src1,src2};
-        let this  = Self::construct(label,def);
+        let this  = Self::construct(label,def).combining_inputs();
         let weak  = this.downgrade();
         t1.register_target(weak.clone_ref().into());
         t2.register_target(weak.into());
@@ -1541,7 +1452,7 @@ where T1:EventOutput, T2:EventOutput, T3:EventOutput {
         let src2 = watch_stream(t2);
         let src3 = watch_stream(t3);
         let def   = All3Data {src1,src2,src3};
-        let this  = Self::construct(label,def);
+        let this  = Self::construct(label,def).combining_inputs();
         let weak  = this.downgrade();
         t1.register_target(weak.clone_ref().into());
         t2.register_target(weak.clone_ref().into());
@@ -1593,7 +1504,7 @@ where T1:EventOutput, T2:EventOutput, T3:EventOutput, T4:EventOutput {
         let src3 = watch_stream(t3);
         let src4 = watch_stream(t4);
         let def   = All4Data {src1,src2,src3,src4};
-        let this  = Self::construct(label,def);
+        let this  = Self::construct(label,def).combining_inputs();
         let weak  = this.downgrade();
         t1.register_target(weak.clone_ref().into());
         t2.register_target(weak.clone_ref().into());
@@ -1686,7 +1597,7 @@ where T1:EventOutput, T2:EventOutput, Out:Data, F:'static+Fn(&Output<T1>,&Output
         let _src1 = t1.clone_ref();
         let src2  = watch_stream(t2);
         let def      = Map2Data {_src1,src2,function};
-        let this     = Self::construct(label,def);
+        let this     = Self::construct(label,def).rank_above(t2);
         let weak     = this.downgrade();
         t1.register_target(weak.into());
         this
@@ -1741,7 +1652,7 @@ where T1:EventOutput, T2:EventOutput, T3:EventOutput, Out:Data,
         let src2  = watch_stream(t2);
         let src3  = watch_stream(t3);
         let def      = Map3Data {_src1,src2,src3,function};
-        let this     = Self::construct(label,def);
+        let this     = Self::construct(label,def).rank_above(t2).rank_above(t3);
         let weak     = this.downgrade();
         t1.register_target(weak.into());
         this
@@ -1800,7 +1711,7 @@ impl<T1,T2,T3,T4,F,Out> OwnedMap4<T1,T2,T3,T4,F>
         let src3  = watch_stream(t3);
         let src4  = watch_stream(t4);
         let def      = Map4Data {_src1,src2,src3,src4,function};
-        let this     = Self::construct(label,def);
+        let this     = Self::construct(label,def).rank_above(t2).rank_above(t3).rank_above(t4);
         let weak     = this.downgrade();
         t1.register_target(weak.into());
         this
@@ -1857,7 +1768,7 @@ where T1:EventOutput, T2:EventOutput, Out:Data, F:'static+Fn(&Output<T1>,&Output
         let src1 = watch_stream(t1);
         let src2 = watch_stream(t2);
         let def     = AllWith2Data {src1,src2,function};
-        let this    = Self::construct(label,def);
+        let this    = Self::construct(label,def).combining_inputs();
         let weak    = this.downgrade();
         t1.register_target(weak.clone_ref().into());
         t2.register_target(weak.into());
@@ -1907,7 +1818,7 @@ where T1:EventOutput, T2:EventOutput, T3:EventOutput, Out:Data,
         let src2 = watch_stream(t2);
         let src3 = watch_stream(t3);
         let def     = AllWith3Data {src1,src2,src3,function};
-        let this    = Self::construct(label,def);
+        let this    = Self::construct(label,def).combining_inputs();
         let weak    = this.downgrade();
         t1.register_target(weak.clone_ref().into());
         t2.register_target(weak.clone_ref().into());
@@ -1962,7 +1873,7 @@ impl<T1,T2,T3,T4,F,Out> OwnedAllWith4<T1,T2,T3,T4,F>
         let src3 = watch_stream(t3);
         let src4 = watch_stream(t4);
         let def     = AllWith4Data {src1,src2,src3,src4,function};
-        let this    = Self::construct(label,def);
+        let this    = Self::construct(label,def).combining_inputs();
         let weak    = this.downgrade();
         t1.register_target(weak.clone_ref().into());
         t2.register_target(weak.clone_ref().into());
@@ -1990,3 +1901,403 @@ impl<T1,T2,T3,T4,F> Debug for AllWith4Data<T1,T2,T3,T4,F> {
         write!(f,"AllWith4Data")
     }
 }
+
+
+
+// ==============
+// === Filter ===
+// ==============
+
+/// Boxed predicate, used when the predicate type has to be named.
+pub type Predicate<T> = Box<dyn Fn(&T)->bool>;
+
+/// Two predicates sharing the provided one. The first one accepts what it accepts, the second one
+/// accepts the rest.
+fn partition_predicates<T,P>(predicate:P) -> (Predicate<T>,Predicate<T>)
+where P:'static+Fn(&T)->bool {
+    let predicate = Rc::new(predicate);
+    let negated   = predicate.clone_ref();
+    (Box::new(move |t:&T| predicate(t)), Box::new(move |t:&T| !negated(t)))
+}
+
+thread_local! {
+    static PARTITION_LABELS : RefCell<HashMap<Label,(Label,Label)>> = default();
+}
+
+/// The labels of the filters created by `partition`, suffixed with `.true` and `.false`, so the
+/// nodes can be told apart, for example in recordings. Labels are static, so the suffixed ones are
+/// leaked, once per partition label.
+fn partition_labels(label:Label) -> (Label,Label) {
+    PARTITION_LABELS.with(|labels| {
+        *labels.borrow_mut().entry(label).or_insert_with(|| {
+            let leak = |suffix:&str| -> Label {
+                Box::leak(format!("{}.{}",label,suffix).into_boxed_str())
+            };
+            (leak("true"),leak("false"))
+        })
+    })
+}
+
+pub struct FilterData  <T,P> { src:T, predicate:P }
+pub type   OwnedFilter <T,P> = stream::Node     <FilterData<T,P>>;
+pub type   Filter      <T,P> = stream::WeakNode <FilterData<T,P>>;
+
+impl<T,P> HasOutput for FilterData<T,P>
+where T:EventOutput, P:'static+Fn(&Output<T>)->bool {
+    type Output = Output<T>;
+}
+
+impl<T,P> OwnedFilter<T,P>
+where T:EventOutput, P:'static+Fn(&Output<T>)->bool {
+    /// Constructor.
+    pub fn new(label:Label, src1:&T, predicate:P) -> Self {
+        let src = src1.clone_ref();
+        let def = FilterData {src,predicate};
+        Self::construct_and_connect(label,src1,def)
+    }
+}
+
+impl<T,P> stream::EventConsumer<Output<T>> for OwnedFilter<T,P>
+where T:EventOutput, P:'static+Fn(&Output<T>)->bool {
+    fn on_event(&self, event:&Output<T>) {
+        if (self.predicate)(event) {
+            self.emit_event(event)
+        }
+    }
+}
+
+impl<T,P> Debug for FilterData<T,P> {
+    fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f,"FilterData")
+    }
+}
+
+
+
+// =================
+// === FilterMap ===
+// =================
+
+pub struct FilterMapData  <T,F> { src:T, function:F }
+pub type   OwnedFilterMap <T,F> = stream::Node     <FilterMapData<T,F>>;
+pub type   FilterMap      <T,F> = stream::WeakNode <FilterMapData<T,F>>;
+
+impl<T,F,Out> HasOutput for FilterMapData<T,F>
+where T:EventOutput, Out:Data, F:'static+Fn(&Output<T>)->Option<Out> {
+    type Output = Out;
+}
+
+impl<T,F,Out> OwnedFilterMap<T,F>
+where T:EventOutput, Out:Data, F:'static+Fn(&Output<T>)->Option<Out> {
+    /// Constructor.
+    pub fn new(label:Label, src1:&T, function:F) -> Self {
+        let src = src1.clone_ref();
+        let def = FilterMapData {src,function};
+        Self::construct_and_connect(label,src1,def)
+    }
+}
+
+impl<T,F,Out> stream::EventConsumer<Output<T>> for OwnedFilterMap<T,F>
+where T:EventOutput, Out:Data, F:'static+Fn(&Output<T>)->Option<Out> {
+    fn on_event(&self, event:&Output<T>) {
+        if let Some(out) = (self.function)(event) {
+            self.emit_event(&out)
+        }
+    }
+}
+
+impl<T,F> Debug for FilterMapData<T,F> {
+    fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f,"FilterMapData")
+    }
+}
+
+
+
+// ==============
+// === Switch ===
+// ==============
+
+#[derive(Debug)]
+pub struct SwitchData  <T> { src:T, generation:Cell<usize> }
+pub type   OwnedSwitch <T> = stream::Node     <SwitchData<T>>;
+pub type   Switch      <T> = stream::WeakNode <SwitchData<T>>;
+
+impl<T,Out> HasOutput for SwitchData<T>
+where T:EventOutput<Output=Stream<Out>>, Out:Data {
+    type Output = Out;
+}
+
+impl<T,Out> OwnedSwitch<T>
+where T:EventOutput<Output=Stream<Out>>, Out:Data {
+    /// Constructor.
+    pub fn new(label:Label, src1:&T) -> Self {
+        let src        = src1.clone_ref();
+        let generation = default();
+        let def        = SwitchData {src,generation};
+        Self::construct_and_connect(label,src1,def)
+    }
+}
+
+impl<T,Out> stream::EventConsumer<Stream<Out>> for OwnedSwitch<T>
+where T:EventOutput<Output=Stream<Out>>, Out:Data {
+    fn on_event(&self, inner:&Stream<Out>) {
+        let generation = self.generation.get() + 1;
+        let node       = self.downgrade();
+        self.generation.set(generation);
+        inner.register_target(stream::EventInput::new(SwitchTarget {node,generation}));
+    }
+}
+
+/// Connection between the inner stream and the `Switch` node. It is dropped by the inner stream
+/// as soon as the node switches to another stream.
+#[derive(Debug)]
+struct SwitchTarget<Def:HasOutputStatic> {
+    node       : stream::WeakNode<Def>,
+    generation : usize,
+}
+
+impl<T,Out> stream::WeakEventConsumer<Out> for SwitchTarget<SwitchData<T>>
+where T:EventOutput<Output=Stream<Out>>, Out:Data {
+    fn on_event_if_exists(&self, event:&Out) -> bool {
+        self.node.upgrade().map(|node| {
+            let is_current = node.generation.get() == self.generation;
+            if is_current {
+                node.emit_event(event)
+            }
+            is_current
+        }).unwrap_or(false)
+    }
+
+    fn target(&self) -> Option<transaction::Target> {
+        self.node.upgrade().and_then(|node| {
+            let is_current = node.generation.get() == self.generation;
+            is_current.as_some_from(|| {
+                let id              = node.id();
+                let rank            = node.rank();
+                let combines_inputs = false;
+                transaction::Target {id,rank,combines_inputs}
+            })
+        })
+    }
+
+    fn raise_rank(&self, min_rank:usize) {
+        self.node.upgrade().for_each(|node| node.raise_rank(min_rank))
+    }
+}
+
+
+
+// =============
+// === Delay ===
+// =============
+
+#[derive(Debug)]
+pub struct DelayData  <T> { src:T, clock:Clock, time:Time }
+pub type   OwnedDelay <T> = stream::Node     <DelayData<T>>;
+pub type   Delay      <T> = stream::WeakNode <DelayData<T>>;
+
+impl<T:EventOutput> HasOutput for DelayData<T> {
+    type Output = Output<T>;
+}
+
+impl<T:EventOutput> OwnedDelay<T> {
+    /// Constructor.
+    pub fn new(label:Label, src1:&T, clock:&Clock, time:Time) -> Self {
+        let src   = src1.clone_ref();
+        let clock = clock.clone_ref();
+        let def   = DelayData {src,clock,time};
+        Self::construct_and_connect(label,src1,def)
+    }
+}
+
+impl<T:EventOutput> stream::EventConsumer<Output<T>> for OwnedDelay<T> {
+    fn on_event(&self, event:&Output<T>) {
+        let this  = self.downgrade();
+        let event = event.clone();
+        self.clock.schedule(self.time, move || this.emit_event(&event));
+    }
+}
+
+
+
+// ================
+// === Debounce ===
+// ================
+
+#[derive(Debug)]
+pub struct DebounceData  <T> { src:T, clock:Clock, time:Time, generation:Cell<usize> }
+pub type   OwnedDebounce <T> = stream::Node     <DebounceData<T>>;
+pub type   Debounce      <T> = stream::WeakNode <DebounceData<T>>;
+
+impl<T:EventOutput> HasOutput for DebounceData<T> {
+    type Output = Output<T>;
+}
+
+impl<T:EventOutput> OwnedDebounce<T> {
+    /// Constructor.
+    pub fn new(label:Label, src1:&T, clock:&Clock, time:Time) -> Self {
+        let src        = src1.clone_ref();
+        let clock      = clock.clone_ref();
+        let generation = default();
+        let def        = DebounceData {src,clock,time,generation};
+        Self::construct_and_connect(label,src1,def)
+    }
+}
+
+impl<T:EventOutput> stream::EventConsumer<Output<T>> for OwnedDebounce<T> {
+    fn on_event(&self, event:&Output<T>) {
+        // Every event invalidates the emissions scheduled by the previous ones.
+        let generation = self.generation.get() + 1;
+        let this       = self.downgrade();
+        let event      = event.clone();
+        self.generation.set(generation);
+        self.clock.schedule(self.time, move || {
+            if let Some(this) = this.upgrade() {
+                if this.generation.get() == generation {
+                    this.emit_event(&event)
+                }
+            }
+        });
+    }
+}
+
+
+
+// ================
+// === Throttle ===
+// ================
+
+#[derive(Debug)]
+pub struct ThrottleData <T:EventOutput>
+    { src:T, clock:Clock, time:Time, is_blocked:Cell<bool>, pending:RefCell<Option<Output<T>>> }
+pub type   OwnedThrottle <T> = stream::Node     <ThrottleData<T>>;
+pub type   Throttle      <T> = stream::WeakNode <ThrottleData<T>>;
+
+impl<T:EventOutput> HasOutput for ThrottleData<T> {
+    type Output = Output<T>;
+}
+
+impl<T:EventOutput> OwnedThrottle<T> {
+    /// Constructor.
+    pub fn new(label:Label, src1:&T, clock:&Clock, time:Time) -> Self {
+        let src        = src1.clone_ref();
+        let clock      = clock.clone_ref();
+        let is_blocked = default();
+        let pending    = default();
+        let def        = ThrottleData {src,clock,time,is_blocked,pending};
+        Self::construct_and_connect(label,src1,def)
+    }
+
+    fn emit_and_block(&self, event:&Output<T>) {
+        let this = self.downgrade();
+        self.is_blocked.set(true);
+        self.emit_event(event);
+        self.clock.schedule(self.time, move || {
+            if let Some(this) = this.upgrade() {
+                this.is_blocked.set(false);
+                let pending = this.pending.borrow_mut().take();
+                if let Some(event) = pending {
+                    this.emit_and_block(&event)
+                }
+            }
+        });
+    }
+}
+
+impl<T:EventOutput> stream::EventConsumer<Output<T>> for OwnedThrottle<T> {
+    fn on_event(&self, event:&Output<T>) {
+        if self.is_blocked.get() {
+            *self.pending.borrow_mut() = Some(event.clone());
+        } else {
+            self.emit_and_block(event)
+        }
+    }
+}
+
+
+
+// ===================
+// === SampleEvery ===
+// ===================
+
+#[derive(Debug)]
+pub struct SampleEveryData  <T> { behavior:watch::Ref<T>, clock:Clock, period:Time }
+pub type   OwnedSampleEvery <T> = stream::Node     <SampleEveryData<T>>;
+pub type   SampleEvery      <T> = stream::WeakNode <SampleEveryData<T>>;
+
+impl<T:EventOutput> HasOutput for SampleEveryData<T> {
+    type Output = Output<T>;
+}
+
+impl<T:EventOutput> OwnedSampleEvery<T> {
+    /// Constructor.
+    pub fn new(label:Label, behavior:&T, clock:&Clock, period:Time) -> Self {
+        debug_assert!(period > 0.0, "The sampling period has to be positive.");
+        let watch    = watch_stream(behavior);
+        let clock    = clock.clone_ref();
+        let def      = SampleEveryData {behavior:watch,clock,period};
+        let this     = Self::construct(label,def).rank_above(behavior);
+        this.schedule_tick();
+        this
+    }
+
+    /// The ticks stop as soon as the node is dropped.
+    fn schedule_tick(&self) {
+        let this = self.downgrade();
+        self.clock.schedule(self.period, move || {
+            if let Some(this) = this.upgrade() {
+                this.emit_event(&this.behavior.value());
+                this.schedule_tick();
+            }
+        });
+    }
+}
+
+impl<T:EventOutput> stream::InputBehaviors for SampleEveryData<T> {
+    fn input_behaviors(&self) -> Vec<Link> {
+        vec![Link::behavior(&self.behavior)]
+    }
+}
+
+
+
+// ===============
+// === Timeout ===
+// ===============
+
+#[derive(Debug)]
+pub struct TimeoutData  <T> { src:T, clock:Clock, time:Time, generation:Cell<usize> }
+pub type   OwnedTimeout <T> = stream::Node     <TimeoutData<T>>;
+pub type   Timeout      <T> = stream::WeakNode <TimeoutData<T>>;
+
+impl<T> HasOutput for TimeoutData<T> {
+    type Output = bool;
+}
+
+impl<T:EventOutput> OwnedTimeout<T> {
+    /// Constructor.
+    pub fn new(label:Label, src1:&T, clock:&Clock, time:Time) -> Self {
+        let src        = src1.clone_ref();
+        let clock      = clock.clone_ref();
+        let generation = default();
+        let def        = TimeoutData {src,clock,time,generation};
+        Self::construct_and_connect(label,src1,def)
+    }
+}
+
+impl<T:EventOutput> stream::EventConsumer<Output<T>> for OwnedTimeout<T> {
+    fn on_event(&self, _:&Output<T>) {
+        let generation = self.generation.get() + 1;
+        let this       = self.downgrade();
+        self.generation.set(generation);
+        self.emit_event(&false);
+        self.clock.schedule(self.time, move || {
+            if let Some(this) = this.upgrade() {
+                if this.generation.get() == generation {
+                    this.emit_event(&true)
+                }
+            }
+        });
+    }
+}
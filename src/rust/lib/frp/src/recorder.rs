@@ -0,0 +1,365 @@
+//! Recording of events flowing trough FRP networks.
+//!
+//! A `Recorder` created by `Network::record` logs every event emitted by the nodes of the network,
+//! together with the event which caused it. The recording can be exported to JSON, used to
+//! annotate the Graphviz diagram of the network with node values at any point of time, and its
+//! external events (the ones without a cause, usually emitted to sources) can be replayed into a
+//! fresh network with `Replay`. This allows reproducing UI bugs in unit tests.
+
+use crate::prelude::*;
+
+use crate::debug;
+use crate::node::*;
+use crate::stream::EventOutput;
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::any::Any;
+
+
+
+// =============
+// === Event ===
+// =============
+
+/// Identifier of a recorded event. Events are numbered in the order of their emission, globally
+/// for all recorders.
+pub type EventId = usize;
+
+/// A single recorded emission of an FRP node.
+#[derive(Clone,Debug,Deserialize,Serialize)]
+pub struct Event {
+    /// Identifier of this event.
+    pub id         : EventId,
+    /// Identifier of the emitting node. Valid only during the recording session.
+    pub node       : usize,
+    /// Label of the emitting node, like `network.source`.
+    pub label      : String,
+    /// Output type label of the emitting node, like `Source`.
+    pub type_label : String,
+    /// Debug representation of the emitted value.
+    pub value      : String,
+    /// The event which caused this one. External events, like the ones emitted to sources, do not
+    /// have a cause.
+    pub cause      : Option<EventId>,
+    /// The emitted value itself. Available only for external events and not exported to JSON.
+    #[serde(skip)]
+    pub payload    : Option<Rc<dyn Any>>,
+}
+
+impl Event {
+    /// Checks whether the event was emitted from outside of the FRP networks.
+    pub fn is_external(&self) -> bool {
+        self.cause.is_none()
+    }
+}
+
+
+
+// ================
+// === Recorder ===
+// ================
+
+/// Label and output type label of a recorded node.
+#[derive(Clone,Debug)]
+#[allow(missing_docs)]
+pub struct NodeInfo {
+    pub label      : Label,
+    pub type_label : String,
+}
+
+/// Internal data of `Recorder`.
+#[derive(Debug)]
+pub struct RecorderData {
+    nodes  : HashMap<Id,NodeInfo>,
+    events : RefCell<Vec<Event>>,
+}
+
+/// Records all events emitted by a fixed set of nodes. The recording starts as soon as the recorder
+/// is created and stops when it is dropped.
+#[derive(Clone,CloneRef,Debug)]
+pub struct Recorder {
+    data : Rc<RecorderData>,
+}
+
+impl Recorder {
+    /// Constructor. Records the events of the provided nodes. See also `Network::record`.
+    pub fn new(nodes:HashMap<Id,NodeInfo>) -> Self {
+        let events = default();
+        let data   = Rc::new(RecorderData {nodes,events});
+        RECORDERS.with(|recorders| recorders.borrow_mut().push(Rc::downgrade(&data)));
+        Self {data}
+    }
+
+    /// All events recorded so far.
+    pub fn events(&self) -> Vec<Event> {
+        self.data.events.borrow().clone()
+    }
+
+    /// Forgets all events recorded so far.
+    pub fn clear(&self) {
+        self.data.events.borrow_mut().clear()
+    }
+
+    /// Exports the recorded events to JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&*self.data.events.borrow()).unwrap()
+    }
+
+    /// The replay of the external events recorded so far.
+    pub fn replay(&self) -> Replay {
+        Replay::new(&self.data.events.borrow())
+    }
+
+    /// Debug representation of the last value of every node after the first `step` events were
+    /// emitted. Allows inspecting the state of the network at any point of the recording.
+    pub fn values_at(&self, step:usize) -> HashMap<Id,String> {
+        let events = self.data.events.borrow();
+        let events = events.iter().take(step);
+        events.map(|event| (event.node.into(),event.value.clone())).collect()
+    }
+
+    /// Annotates the Graphviz diagram nodes with their values after the first `step` events.
+    pub fn annotate_at(&self, viz:&mut debug::Graphviz, step:usize) {
+        for (id,value) in self.values_at(step) {
+            viz.set_value(id.into(),value)
+        }
+    }
+
+    /// Annotates the Graphviz diagram nodes with their last recorded values.
+    pub fn annotate(&self, viz:&mut debug::Graphviz) {
+        let step = self.data.events.borrow().len();
+        self.annotate_at(viz,step)
+    }
+}
+
+
+
+// =================
+// === Recording ===
+// =================
+
+thread_local! {
+    static RECORDERS  : RefCell<Vec<Weak<RecorderData>>> = default();
+    static CAUSE      : Cell<Option<EventId>>            = default();
+    static NEXT_EVENT : Cell<EventId>                    = default();
+}
+
+/// Records the emission of a node in all recorders watching it. Returns the id of the new event,
+/// or the current cause if the node is not recorded, so the causal chain is preserved trough nodes
+/// which are not recorded. This is called by every node emitting an event, so it returns as soon
+/// as possible if there are no recorders.
+pub(crate) fn record<T:Data>(node:Id, value:&T) -> Option<EventId> {
+    let cause = CAUSE.with(|t| t.get());
+    RECORDERS.with(|recorders| {
+        let mut recorders = recorders.borrow_mut();
+        if recorders.is_empty() {
+            return cause
+        }
+        recorders.retain(|recorder| recorder.upgrade().is_some());
+        let mut event : Option<Event> = None;
+        for recorder in recorders.iter().filter_map(|recorder| recorder.upgrade()) {
+            if let Some(info) = recorder.nodes.get(&node) {
+                let event = event.get_or_insert_with(|| {
+                    let id         = NEXT_EVENT.with(|t| t.replace(t.get() + 1));
+                    let node       = node.into();
+                    let label      = info.label.into();
+                    let type_label = info.type_label.clone();
+                    let payload    = cause.is_none().as_some_from(|| {
+                        Rc::new(value.clone()) as Rc<dyn Any>
+                    });
+                    let value      = format!("{:?}",value);
+                    Event {id,node,label,type_label,value,cause,payload}
+                });
+                recorder.events.borrow_mut().push(event.clone());
+            }
+        }
+        event.map(|event| event.id).or(cause)
+    })
+}
+
+/// Runs the function with the provided event set as the cause of all events emitted by it.
+pub(crate) fn with_cause<T>(cause:Option<EventId>, f:impl FnOnce()->T) -> T {
+    let parent = CAUSE.with(|t| t.replace(cause));
+    let out    = f();
+    CAUSE.with(|t| t.set(parent));
+    out
+}
+
+
+
+// ==============
+// === Replay ===
+// ==============
+
+/// Emits the recorded value into the bound node.
+type Binding = Box<dyn Fn(&Event)>;
+
+/// External events of a recording which can be emitted again into a fresh network. The nodes of
+/// the new network have to be bound to labels of recorded nodes first, using the `bind` method.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct Replay {
+    events   : Vec<Event>,
+    #[derivative(Debug="ignore")]
+    bindings : HashMap<String,Binding>,
+}
+
+impl Replay {
+    /// Constructor. Only the external events of the recording are replayed, as all the other ones
+    /// are expected to be caused by them again.
+    pub fn new(events:&[Event]) -> Self {
+        let events   = events.iter().filter(|event| event.is_external()).cloned().collect();
+        let bindings = default();
+        Self {events,bindings}
+    }
+
+    /// Constructor of a replay of events exported by `Recorder::to_json`.
+    pub fn from_json(json:&str) -> serde_json::Result<Self> {
+        let events : Vec<Event> = serde_json::from_str(json)?;
+        Ok(Self::new(&events))
+    }
+
+    /// The events which will be replayed.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Binds the node to events recorded with the given label. The recorded values are emitted
+    /// directly if they were recorded in this process, otherwise the `parse` function is used to
+    /// restore them from their debug representation. Events which can't be parsed are skipped.
+    pub fn bind<S,F>(&mut self, label:impl Str, node:&S, parse:F)
+    where S:EventOutput, F:'static+Fn(&str)->Option<Output<S>> {
+        let node    = node.clone_ref();
+        let binding = move |event:&Event| {
+            let payload = event.payload.as_ref().and_then(|t| t.downcast_ref::<Output<S>>());
+            let value   = payload.cloned().or_else(|| parse(&event.value));
+            value.for_each(|value| node.emit_event(&value))
+        };
+        self.bindings.insert(label.into(),Box::new(binding));
+    }
+
+    /// Emits all the recorded events to the bound nodes, in the order of their recording. Returns
+    /// the number of events which were not bound to any node.
+    pub fn run(&self) -> usize {
+        let mut unbound = 0;
+        for event in &self.events {
+            match self.bindings.get(&event.label) {
+                Some(binding) => binding(event),
+                None          => unbound += 1,
+            }
+        }
+        unbound
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate as frp;
+
+    #[test]
+    fn recording_causes() {
+        frp::new_network! { network
+            def source  = source::<usize>();
+            def double  = source.map(|t| t * 2);
+            def sampler = double.sampler();
+        }
+        let recorder = network.record();
+        source.emit(3);
+        let events = recorder.events();
+        let labels = events.iter().map(|event| event.label.as_str()).collect_vec();
+        let values = events.iter().map(|event| event.value.as_str()).collect_vec();
+        assert_eq!(labels,vec!["network.source","network.double","network.sampler"]);
+        assert_eq!(values,vec!["3","6","6"]);
+        assert_eq!(events[0].cause,None);
+        assert_eq!(events[1].cause,Some(events[0].id));
+        assert_eq!(events[2].cause,Some(events[1].id));
+        assert_eq!(events[0].type_label,"Source");
+        drop(recorder);
+        source.emit(4);
+        assert_eq!(sampler.value(),8);
+    }
+
+    #[test]
+    fn recording_partitions() {
+        frp::new_network! { network
+            def source   = source::<usize>();
+            (small,big) <- source.partition(|n| *n < 10);
+            def _small   = small.sampler();
+            def _big     = big.sampler();
+        }
+        let recorder = network.record();
+        source.emit(3);
+        source.emit(30);
+        let events = recorder.events();
+        let labels = events.iter().map(|event| event.label.as_str());
+        let labels = labels.filter(|label| label.starts_with("network.small,big")).collect_vec();
+        assert_eq!(labels,vec!["network.small,big.true","network.small,big.false"]);
+    }
+
+    fn counter_network() -> (frp::Network,frp::Source,frp::Stream<usize>,frp::Sampler<usize>) {
+        frp::new_network! { network
+            def source  = source::<()>();
+            def count   = source.count();
+            def sampler = count.sampler();
+        }
+        (network,source,count,sampler)
+    }
+
+    #[test]
+    fn replaying_json() {
+        let (network,source,_,_) = counter_network();
+        let recorder = network.record();
+        source.emit(());
+        source.emit(());
+        let json = recorder.to_json();
+        drop(network);
+
+        let (_network,source,_,sampler) = counter_network();
+        let mut replay = Replay::from_json(&json).unwrap();
+        assert_eq!(replay.events().len(),2);
+        assert_eq!(replay.run(),2);
+        replay.bind("network.source",&source,|value| (value == "()").as_some(()));
+        assert_eq!(replay.run(),0);
+        assert_eq!(sampler.value(),2);
+    }
+
+    #[test]
+    fn replaying_in_memory_values() {
+        frp::new_network! { network
+            def source = source::<String>();
+        }
+        let recorder = network.record();
+        source.emit("a \"quoted\" text".to_string());
+        let mut replay = recorder.replay();
+        drop(network);
+
+        frp::new_network! { network
+            def source  = source::<String>();
+            def sampler = source.sampler();
+        }
+        replay.bind("network.source",&source,|_| None);
+        replay.run();
+        assert_eq!(sampler.value(),"a \"quoted\" text");
+    }
+
+    #[test]
+    fn inspecting_values_in_time() {
+        let (network,source,count,_) = counter_network();
+        let recorder = network.record();
+        source.emit(());
+        source.emit(());
+        assert_eq!(recorder.values_at(3).get(&count.id()),Some(&"1".to_string()));
+        assert_eq!(recorder.values_at(6).get(&count.id()),Some(&"2".to_string()));
+        let mut viz = network.graphviz();
+        recorder.annotate(&mut viz);
+        assert!(viz.to_code().contains("= 2"));
+    }
+}
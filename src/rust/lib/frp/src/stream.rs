@@ -4,6 +4,8 @@ use crate::prelude::*;
 use crate::network::*;
 use crate::node::*;
 use crate::data::watch;
+use crate::recorder;
+use crate::transaction;
 
 
 
@@ -55,6 +57,24 @@ pub trait EventEmitter : HasOutput {
     fn register_target(&self , target:EventInput<Output<Self>>);
     /// Register that someone is watching value of this node.
     fn register_watch(&self) -> watch::Handle;
+    /// Rank of this node. See the docs of `NodeData` to learn more.
+    fn rank(&self) -> usize;
+    /// Register a node watching the value of this node, so its rank is raised together with the
+    /// rank of this node.
+    fn register_rank_watcher(&self, watcher:Weak<dyn RaiseRank>);
+}
+
+
+
+// =================
+// === RaiseRank ===
+// =================
+
+/// Nodes whose rank can be raised. Used to keep the ranks of nodes watching behaviors above the
+/// ranks of the watched nodes. See the docs of `NodeData` to learn more.
+pub trait RaiseRank {
+    /// Makes sure that the rank of the node is not lower than the provided one.
+    fn raise_rank(&self, min_rank:usize);
 }
 
 
@@ -75,6 +95,11 @@ pub trait WeakEventConsumer<T> {
     /// Callback for a new incoming event. Returns true if the event was consumed or false if it was
     /// not. Not consuming an event means that the event receiver was already dropped.
     fn on_event_if_exists(&self, value:&T) -> bool;
+    /// Information needed to schedule the event delivery in a transaction. Returns `None` if the
+    /// event receiver was already dropped.
+    fn target(&self) -> Option<transaction::Target>;
+    /// Makes sure that the rank of the event receiver is not lower than the provided one.
+    fn raise_rank(&self, min_rank:usize);
 }
 
 
@@ -103,6 +128,13 @@ pub struct EventInput<Input> {
     data : Rc<dyn WeakEventConsumer<Input>>
 }
 
+impl<Input> EventInput<Input> {
+    /// Constructor of a custom event consumer. Nodes are usually converted with `into` instead.
+    pub fn new<T:WeakEventConsumer<Input>+'static>(consumer:T) -> Self {
+        Self {data:Rc::new(consumer)}
+    }
+}
+
 impl<Def,Input> From<WeakNode<Def>> for EventInput<Input>
 where Def:HasOutputStatic, Node<Def>:EventConsumer<Input> {
     fn from(node:WeakNode<Def>) -> Self {
@@ -139,28 +171,98 @@ impl<Input> Debug for EventInput<Input> {
 /// value any time. If the number of such nodes is zero, the value propagated trough this node does
 /// not need to be cached, and it will not be cloned. This minimizes the amount of clones in FRP
 /// networks drastically.
-#[derive(Debug)]
+///
+/// The `rank` field is used by the transactional propagation mode (see the `transaction` module).
+/// The rank of every node is greater than the rank of all nodes it receives events from or
+/// watches, so evaluating the nodes in the order of their ranks guarantees that every node is
+/// evaluated after all of its inputs were updated. Ranks are only raised, never lowered, and the
+/// `during_ranking` field guards the raising from looping in recursive networks. The raised rank
+/// is propagated to the targets and to the `rank_watchers`, the nodes watching this node. The
+/// `combines_inputs` field is set for nodes which are evaluated once per transaction, even if
+/// several of their inputs fired, see `Node::combining_inputs`.
+#[derive(Derivative)]
+#[derivative(Debug)]
 pub struct NodeData<Out=()> {
-    label         : Label,
-    targets       : RefCell<Vec<EventInput<Out>>>,
-    value_cache   : RefCell<Out>,
-    during_call   : Cell<bool>,
-    watch_counter : watch::Counter,
+    label           : Label,
+    targets         : RefCell<Vec<EventInput<Out>>>,
+    value_cache     : RefCell<Out>,
+    during_call     : Cell<bool>,
+    watch_counter   : watch::Counter,
+    rank            : Cell<usize>,
+    during_ranking  : Cell<bool>,
+    combines_inputs : Cell<bool>,
+    #[derivative(Debug="ignore")]
+    rank_watchers   : RefCell<Vec<Weak<dyn RaiseRank>>>,
 }
 
 impl<Out:Default> NodeData<Out> {
     /// Constructor.
     pub fn new(label:Label) -> Self {
-        let targets       = default();
-        let value_cache   = default();
-        let during_call   = default();
-        let watch_counter = default();
-        Self {label,targets,value_cache,during_call,watch_counter}
+        let targets         = default();
+        let value_cache     = default();
+        let during_call     = default();
+        let watch_counter   = default();
+        let rank            = default();
+        let during_ranking  = default();
+        let combines_inputs = default();
+        let rank_watchers   = default();
+        Self {label,targets,value_cache,during_call,watch_counter,rank,during_ranking
+             ,combines_inputs,rank_watchers}
     }
 
     fn use_caching(&self) -> bool {
         !self.watch_counter.is_zero()
     }
+
+    /// Identifier of the node. The same as the one returned by `HasId` implementations of streams.
+    fn id(&self) -> Id {
+        let raw = self as *const Self as *const() as usize;
+        raw.into()
+    }
+
+    /// Makes sure that the rank of this node is not lower than the provided one. All targets and
+    /// rank watchers of this node are updated accordingly.
+    pub fn raise_rank(&self, min_rank:usize) {
+        if self.rank.get() < min_rank && !self.during_ranking.get() {
+            self.during_ranking.set(true);
+            self.rank.set(min_rank);
+            for target in self.targets.borrow().iter() {
+                target.data.raise_rank(min_rank + 1)
+            }
+            self.rank_watchers.borrow_mut().retain(|watcher| {
+                watcher.upgrade().map(|watcher| watcher.raise_rank(min_rank + 1)).is_some()
+            });
+            self.during_ranking.set(false);
+        }
+    }
+}
+
+impl<Out:Default> RaiseRank for NodeData<Out> {
+    fn raise_rank(&self, min_rank:usize) {
+        NodeData::raise_rank(self,min_rank)
+    }
+}
+
+impl<Out:Data> NodeData<Out> {
+    /// Queues the event for all targets in the current transaction, opening a new one if needed.
+    fn schedule_event(&self, value:&Out) {
+        if self.use_caching() {
+            *self.value_cache.borrow_mut() = value.clone();
+        }
+        let cause   = recorder::record(self.id(),value);
+        let emitter = transaction::Emitter {id:self.id(), rank:self.rank.get()};
+        transaction::transaction(|| {
+            self.targets.borrow_mut().retain(|target| {
+                target.data.target().map(|info| {
+                    let target = target.clone();
+                    let value  = value.clone();
+                    transaction::schedule(emitter,info, move || {
+                        recorder::with_cause(cause, || target.data.on_event_if_exists(&value));
+                    })
+                }).is_some()
+            })
+        })
+    }
 }
 
 impl<Out:Data> HasOutput for NodeData<Out> {
@@ -169,23 +271,40 @@ impl<Out:Data> HasOutput for NodeData<Out> {
 
 impl<Out:Data> EventEmitter for NodeData<Out> {
     fn emit_event(&self, value:&Out) {
-        if !self.during_call.get() {
+        if transaction::is_transactional() {
+            self.schedule_event(value)
+        } else if !self.during_call.get() {
             self.during_call.set(true);
             if self.use_caching() {
                 *self.value_cache.borrow_mut() = value.clone();
             }
-            self.targets.borrow_mut().retain(|target| target.data.on_event_if_exists(value));
+            let cause = recorder::record(self.id(),value);
+            recorder::with_cause(cause, || {
+                self.targets.borrow_mut().retain(|target| target.data.on_event_if_exists(value))
+            });
             self.during_call.set(false);
         }
     }
 
     fn register_target(&self,target:EventInput<Out>) {
+        target.data.raise_rank(self.rank.get() + 1);
         self.targets.borrow_mut().push(target)
     }
 
     fn register_watch(&self) -> watch::Handle {
         self.watch_counter.new_watch()
     }
+
+    fn rank(&self) -> usize {
+        self.rank.get()
+    }
+
+    fn register_rank_watcher(&self, watcher:Weak<dyn RaiseRank>) {
+        if let Some(watcher) = watcher.upgrade() {
+            watcher.raise_rank(self.rank.get() + 1)
+        }
+        self.rank_watchers.borrow_mut().push(watcher)
+    }
 }
 
 impl<Out:Data> ValueProvider for NodeData<Out> {
@@ -295,6 +414,30 @@ impl<Def:HasOutputStatic> Node<Def> {
         this
     }
 
+    /// Makes sure that the rank of this node is not lower than the provided one. See the docs of
+    /// `NodeData` to learn more.
+    pub fn raise_rank(&self, min_rank:usize) {
+        self.stream.data.raise_rank(min_rank)
+    }
+
+    /// Marks the node as combining the current values of its inputs instead of using the values of
+    /// the received events. In the transactional propagation mode, such a node is evaluated once
+    /// per transaction, even if several of its inputs fired.
+    pub fn combining_inputs(self) -> Self {
+        self.stream.data.combines_inputs.set(true);
+        self
+    }
+
+    /// Makes sure the node is evaluated after the provided one in the transactional propagation
+    /// mode, also when the rank of the provided one is raised later. Used by nodes watching
+    /// behaviors without being their event targets.
+    pub fn rank_above<S:EventEmitter>(self, src:&S) -> Self {
+        let data    = Rc::downgrade(&self.stream.data);
+        let watcher : Weak<dyn RaiseRank> = data;
+        src.register_rank_watcher(watcher);
+        self
+    }
+
     /// Downgrades to the weak version.
     pub fn downgrade(&self) -> WeakNode<Def> {
         let stream     = self.stream.downgrade();
@@ -385,6 +528,14 @@ impl<Out:Data> EventEmitter for OwnedStream<Out> {
     fn register_watch(&self) -> watch::Handle {
         self.data.register_watch()
     }
+
+    fn rank(&self) -> usize {
+        self.data.rank()
+    }
+
+    fn register_rank_watcher(&self, watcher:Weak<dyn RaiseRank>) {
+        self.data.register_rank_watcher(watcher)
+    }
 }
 
 impl<Out:Data> EventEmitter for Stream<Out> {
@@ -399,18 +550,34 @@ impl<Out:Data> EventEmitter for Stream<Out> {
     fn register_watch(&self) -> watch::Handle {
         self.upgrade().map(|t| t.register_watch()).unwrap() // FIXME
     }
+
+    fn rank(&self) -> usize {
+        self.upgrade().map(|t| t.rank()).unwrap_or_default()
+    }
+
+    fn register_rank_watcher(&self, watcher:Weak<dyn RaiseRank>) {
+        self.upgrade().for_each(|t| t.register_rank_watcher(watcher))
+    }
 }
 
 impl<Def:HasOutputStatic> EventEmitter for Node<Def>  {
     fn emit_event      (&self, value:&Output<Def>)          { self.stream.emit_event(value) }
     fn register_target (&self,tgt:EventInput<Output<Self>>) { self.stream.register_target(tgt) }
     fn register_watch  (&self) -> watch::Handle             { self.stream.register_watch() }
+    fn rank            (&self) -> usize                     { self.stream.rank() }
+    fn register_rank_watcher(&self, watcher:Weak<dyn RaiseRank>) {
+        self.stream.register_rank_watcher(watcher)
+    }
 }
 
 impl<Def:HasOutputStatic> EventEmitter for WeakNode<Def> {
     fn emit_event      (&self, value:&Output<Def>)          { self.stream.emit_event(value) }
     fn register_target (&self,tgt:EventInput<Output<Self>>) { self.stream.register_target(tgt) }
     fn register_watch  (&self) -> watch::Handle             { self.stream.register_watch() }
+    fn rank            (&self) -> usize                     { self.stream.rank() }
+    fn register_rank_watcher(&self, watcher:Weak<dyn RaiseRank>) {
+        self.stream.register_rank_watcher(watcher)
+    }
 }
 
 
@@ -421,6 +588,19 @@ impl<Def,T> WeakEventConsumer<T> for WeakNode<Def>
     fn on_event_if_exists(&self, value:&T) -> bool {
         self.upgrade().map(|node| {node.on_event(value);}).is_some()
     }
+
+    fn target(&self) -> Option<transaction::Target> {
+        self.stream.data.upgrade().map(|data| {
+            let id              = self.id();
+            let rank            = data.rank.get();
+            let combines_inputs = data.combines_inputs.get();
+            transaction::Target {id,rank,combines_inputs}
+        })
+    }
+
+    fn raise_rank(&self, min_rank:usize) {
+        self.stream.data.upgrade().for_each(|data| data.raise_rank(min_rank))
+    }
 }
 
 
@@ -518,6 +698,16 @@ impl<Def:HasOutputStatic> InputBehaviors for WeakNode<Def>
 }
 
 
+// === Default ===
+
+/// The default stream is already dropped. It never emits events and ignores new targets.
+impl<Out> Default for Stream<Out> {
+    fn default() -> Self {
+        Self {data:Weak::new()}
+    }
+}
+
+
 // === Debug ===
 
 impl<Out> Debug for Stream<Out> {
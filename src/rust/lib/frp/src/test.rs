@@ -0,0 +1,266 @@
+//! Harness for testing FRP networks without a browser.
+//!
+//! The `Harness` allows injecting events into named sources, stepping a virtual clock driving all
+//! time-based nodes, and checking the sequences of values emitted by named streams. It also checks
+//! whether the networks under test were dropped, which catches leaks caused by reference cycles,
+//! for example, closures capturing the network.
+//!
+//! ```compile_fail
+//! let mut harness = frp::test::Harness::new();
+//! let component   = Component::new(&harness.clock());
+//! harness.source("press",&component.frp.press);
+//! harness.probe("count",&component.frp.count);
+//! harness.probe("idle",&component.frp.idle);
+//! harness.watch_network("component",&component.network);
+//! harness.emit("press",());
+//! harness.emit("press",());
+//! harness.advance(100.0);
+//! frp::expect_events!(harness, count => [1,2], idle => [true]);
+//! drop(component);
+//! harness.assert_no_leaks();
+//! ```
+
+use crate::prelude::*;
+
+use crate::clock::Clock;
+use crate::clock::Time;
+use crate::clock::VirtualClock;
+use crate::network::Network;
+use crate::network::WeakNetwork;
+use crate::node::*;
+use crate::stream::EventOutput;
+
+use std::any::Any;
+use std::any::type_name;
+
+
+
+// =============
+// === Probe ===
+// =============
+
+/// Debug representations of values emitted by a stream since the last check.
+type Probe = Rc<RefCell<Vec<String>>>;
+
+/// Emits the value to the source, if it has the right type. Returns `false` otherwise.
+type Injector = Box<dyn Fn(&dyn Any)->bool>;
+
+
+
+// ===============
+// === Harness ===
+// ===============
+
+/// Test harness for FRP networks. See the module docs to learn more.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct Harness {
+    network  : Network,
+    clock    : VirtualClock,
+    #[derivative(Debug="ignore")]
+    sources  : HashMap<String,Injector>,
+    probes   : HashMap<String,Probe>,
+    networks : Vec<(String,WeakNetwork)>,
+}
+
+impl Harness {
+    /// Constructor.
+    pub fn new() -> Self {
+        let network  = Network::new();
+        let clock    = VirtualClock::new();
+        let sources  = default();
+        let probes   = default();
+        let networks = default();
+        Self {network,clock,sources,probes,networks}
+    }
+
+    /// The clock which should be used by the time-based nodes of the tested networks.
+    pub fn clock(&self) -> Clock {
+        self.clock.clock()
+    }
+
+    /// Moves the time of the harness clock forward.
+    pub fn advance(&self, time:Time) {
+        self.clock.advance(time)
+    }
+
+    /// Registers the stream as a named source of events. The harness does not keep the stream
+    /// alive if it was defined in a network.
+    pub fn source<S:EventOutput>(&mut self, name:impl Str, stream:&S) {
+        let stream   = stream.clone_ref();
+        let injector = move |value:&dyn Any| {
+            value.downcast_ref::<Output<S>>().map(|value| stream.emit_event(value)).is_some()
+        };
+        self.sources.insert(name.into(),Box::new(injector));
+    }
+
+    /// Emits the value to the named source.
+    pub fn emit<T:'static>(&self, name:&str, value:T) {
+        let injector = self.sources.get(name);
+        let injector = injector.unwrap_or_else(|| panic!("Unknown source `{}`.",name));
+        if !injector(&value) {
+            panic!("Source `{}` does not accept values of type `{}`.",name,type_name::<T>())
+        }
+    }
+
+    /// Starts recording the values emitted by the stream under the given name.
+    pub fn probe<S:EventOutput>(&mut self, name:impl Str, stream:&S) {
+        let probe : Probe = default();
+        let log           = probe.clone_ref();
+        self.network.map("harness.probe",stream,move |value| {
+            log.borrow_mut().push(format!("{:?}",value))
+        });
+        self.probes.insert(name.into(),probe);
+    }
+
+    /// Debug representations of the values emitted by the named stream since the last check.
+    pub fn emitted(&self, name:&str) -> Vec<String> {
+        self.probe_log(name).borrow().clone()
+    }
+
+    /// Checks that the named stream emitted exactly the provided values since the last check. The
+    /// values are compared by their debug representations.
+    pub fn expect<T:Debug>(&self, name:&str, expected:&[T]) {
+        let expected = expected.iter().map(|value| format!("{:?}",value)).collect_vec();
+        self.expect_debug(name,&expected)
+    }
+
+    /// Checks that the named stream did not emit anything since the last check.
+    pub fn expect_none(&self, name:&str) {
+        self.expect_debug(name,&[])
+    }
+
+    /// Like `expect`, but takes the debug representations of the values. Used by the
+    /// `expect_events` macro.
+    pub fn expect_debug(&self, name:&str, expected:&[String]) {
+        let emitted = mem::take(&mut *self.probe_log(name).borrow_mut());
+        if emitted != expected {
+            panic!("Stream `{}` emitted {:?}, but {:?} was expected.",name,emitted,expected)
+        }
+    }
+
+    /// Registers the network to be checked by `assert_no_leaks`.
+    pub fn watch_network(&mut self, name:impl Str, network:&Network) {
+        self.networks.push((name.into(),network.downgrade()))
+    }
+
+    /// Checks that all the watched networks were dropped.
+    pub fn assert_no_leaks(&self) {
+        let leaked = self.networks.iter().filter(|(_,network)| network.upgrade().is_some());
+        let leaked = leaked.map(|(name,_)| name.as_str()).collect_vec();
+        if !leaked.is_empty() {
+            panic!("Networks {:?} are still alive.",leaked)
+        }
+    }
+
+    fn probe_log(&self, name:&str) -> &Probe {
+        self.probes.get(name).unwrap_or_else(|| panic!("Unknown probe `{}`.",name))
+    }
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Checks the values emitted by the probes of the harness since the last check.
+///
+/// ```compile_fail
+/// frp::expect_events!(harness, count => [1,2,3], hover => []);
+/// ```
+/// Desugars to:
+/// ```compile_fail
+/// harness.expect_debug("count",&[format!("{:?}",1),format!("{:?}",2),format!("{:?}",3)]);
+/// harness.expect_debug("hover",&[]);
+/// ```
+#[macro_export]
+macro_rules! expect_events {
+    ($harness:expr, $($name:ident => [$($value:expr),* $(,)?]),* $(,)?) => {
+        $($harness.expect_debug(stringify!($name),&[$(format!("{:?}",$value)),*]);)*
+    };
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate as frp;
+
+    #[test]
+    fn injecting_and_expecting_events() {
+        let mut harness = Harness::new();
+        let clock       = harness.clock();
+        frp::new_network! { network
+            def source  = source::<usize>();
+            def evens   = source.filter(|n| n % 2 == 0);
+            def delayed = source.delay(&clock,10.0);
+        }
+        harness.source("source",&source);
+        harness.probe("evens",&evens);
+        harness.probe("delayed",&delayed);
+        harness.emit("source",1_usize);
+        harness.emit("source",2_usize);
+        frp::expect_events!(harness, evens => [2], delayed => []);
+        harness.advance(10.0);
+        frp::expect_events!(harness, evens => [], delayed => [1,2]);
+        harness.expect_none("delayed");
+    }
+
+    #[test]
+    #[should_panic(expected = "does not accept values")]
+    fn emitting_value_of_wrong_type() {
+        let mut harness = Harness::new();
+        frp::new_network! { network
+            def source = source::<usize>();
+        }
+        harness.source("source",&source);
+        harness.emit("source","text");
+    }
+
+    #[test]
+    #[should_panic(expected = "emitted [\"1\"], but [] was expected")]
+    fn unexpected_emission() {
+        let mut harness = Harness::new();
+        frp::new_network! { network
+            def source = source::<usize>();
+        }
+        harness.source("source",&source);
+        harness.probe("source",&source);
+        harness.emit("source",1_usize);
+        harness.expect_none("source");
+    }
+
+    #[test]
+    fn detecting_leaks() {
+        let mut harness = Harness::new();
+        frp::new_network! { network
+            def source = source::<()>();
+        }
+        harness.watch_network("network",&network);
+        harness.source("source",&source);
+        drop(network);
+        harness.assert_no_leaks();
+    }
+
+    #[test]
+    #[should_panic(expected = "are still alive")]
+    fn detecting_leaked_network() {
+        let mut harness = Harness::new();
+        frp::new_network! { network
+            def source = source::<()>();
+        }
+        let cycle = network.clone_ref();
+        frp::extend! { network
+            def _eval = source.map(move |_| drop(cycle.clone_ref()));
+        }
+        harness.watch_network("network",&network);
+        drop(network);
+        harness.assert_no_leaks();
+    }
+}
@@ -0,0 +1,213 @@
+//! Transactional, glitch-free propagation of events.
+//!
+//! By default, events are propagated depth-first: every node passes the event to its targets as
+//! soon as it receives it. This is fast, but in diamond-shaped networks a node can fire several
+//! times per a single external event and it can observe a mix of old and new values of its inputs
+//! (a "glitch"). In the `Transactional` mode, every external emit opens a transaction instead. The
+//! events are queued and the nodes are evaluated in the order of their rank, which is always
+//! greater than the rank of every node they are connected to.
+//!
+//! Nodes combining the current values of their inputs, like `all` or `all_with`, fire at most once
+//! per transaction, no matter how many of their inputs fired. Other nodes receive the latest event
+//! of every input, so nodes merging several streams, like `any`, pass events of all their inputs.
+//! If an input fires several times in one step, for example if a source is emitted several times
+//! in an explicit `transaction`, only the latest of these events is delivered.
+//!
+//! The mode is global and opt-in. See `set_propagation_mode` and `transaction`.
+
+use crate::prelude::*;
+
+use crate::node::Id;
+
+
+
+// =======================
+// === PropagationMode ===
+// =======================
+
+/// The way events are propagated trough FRP networks.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+pub enum PropagationMode {
+    /// Events are passed to node targets immediately, depth-first. This is the default mode.
+    DepthFirst,
+    /// Events are queued in transactions and every node fires at most once per transaction.
+    Transactional,
+}
+
+impl Default for PropagationMode {
+    fn default() -> Self {
+        Self::DepthFirst
+    }
+}
+
+thread_local! {
+    static MODE    : Cell<PropagationMode>        = default();
+    static CURRENT : RefCell<Option<Transaction>> = default();
+}
+
+/// Sets the propagation mode of all FRP networks in this thread.
+pub fn set_propagation_mode(mode:PropagationMode) {
+    MODE.with(|t| t.set(mode))
+}
+
+/// The propagation mode of all FRP networks in this thread.
+pub fn propagation_mode() -> PropagationMode {
+    MODE.with(|t| t.get())
+}
+
+/// Checks whether the events should be propagated in transactions.
+pub fn is_transactional() -> bool {
+    propagation_mode() == PropagationMode::Transactional
+}
+
+
+
+// ==============
+// === Target ===
+// ==============
+
+/// Information about an event target needed to schedule its evaluation.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+pub struct Target {
+    /// Identifier of the target node.
+    pub id              : Id,
+    /// Rank of the target node. See the docs of `NodeData` to learn more.
+    pub rank            : usize,
+    /// Whether the target node combines the current values of its inputs, so it needs to be
+    /// evaluated only once, even if several of its inputs fired.
+    pub combines_inputs : bool,
+}
+
+
+
+// ===============
+// === Emitter ===
+// ===============
+
+/// Information about the node emitting an event, needed to schedule its delivery.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+pub struct Emitter {
+    /// Identifier of the emitting node.
+    pub id   : Id,
+    /// Rank of the emitting node.
+    pub rank : usize,
+}
+
+
+
+// ===================
+// === Transaction ===
+// ===================
+
+/// Callback delivering an event to its target.
+type Callback = Box<dyn FnOnce()>;
+
+/// Identifies the pending events replacing each other. Events of a target combining its inputs
+/// always replace each other. Events of other targets replace each other only if they come from the
+/// same emitter in the same step, that is, during the same callback or outside of any callback.
+#[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
+enum PendingKey {
+    Combined (Id),
+    Input    {target:Id, emitter:Id, step:usize},
+}
+
+impl PendingKey {
+    fn target(&self) -> Id {
+        match self {
+            Self::Combined(target)  => *target,
+            Self::Input {target,..} => *target,
+        }
+    }
+}
+
+/// Queue of events waiting for delivery. The events are sorted by the rank of their targets and
+/// then by the order of scheduling.
+#[derive(Derivative,Default)]
+#[derivative(Debug)]
+struct Transaction {
+    #[derivative(Debug="ignore")]
+    queue    : BTreeMap<(usize,usize),(PendingKey,Callback)>,
+    pending  : HashMap<PendingKey,(usize,usize)>,
+    fired    : HashSet<Id>,
+    step     : usize,
+    next_seq : usize,
+}
+
+impl Transaction {
+    /// Queues the callback, replacing the pending event with the same `PendingKey`, so the target
+    /// receives only the latest one. Events sent back to a target which already fired, which
+    /// happens only in recursive networks, are skipped.
+    fn schedule(&mut self, emitter:Emitter, target:Target, callback:Callback) {
+        let is_back_edge = target.rank <= emitter.rank;
+        if !(is_back_edge && self.fired.contains(&target.id)) {
+            let pending_key = if target.combines_inputs {
+                PendingKey::Combined(target.id)
+            } else {
+                PendingKey::Input {target:target.id, emitter:emitter.id, step:self.step}
+            };
+            if let Some(key) = self.pending.remove(&pending_key) {
+                self.queue.remove(&key);
+            }
+            let key = (target.rank,self.next_seq);
+            self.next_seq += 1;
+            self.queue.insert(key,(pending_key,callback));
+            self.pending.insert(pending_key,key);
+        }
+    }
+
+    /// Takes the callback of the lowest rank, marks its target as fired and starts a new step.
+    fn next(&mut self) -> Option<Callback> {
+        let key                    = *self.queue.keys().next()?;
+        let (pending_key,callback) = self.queue.remove(&key)?;
+        self.pending.remove(&pending_key);
+        self.fired.insert(pending_key.target());
+        self.step += 1;
+        Some(callback)
+    }
+}
+
+/// Runs the function in a transaction. All events emitted by the function are delivered after it
+/// finishes, in the order of ranks of their targets. Nested calls join the outermost transaction.
+/// Works in both propagation modes, however, in the `DepthFirst` mode the events emitted by the
+/// function are delivered immediately, so it just runs the function.
+pub fn transaction<T>(f:impl FnOnce()->T) -> T {
+    let is_outermost = CURRENT.with(|current| {
+        let mut current  = current.borrow_mut();
+        let is_outermost = current.is_none();
+        if is_outermost { *current = Some(default()) }
+        is_outermost
+    });
+    let guard = is_outermost.as_some(OutermostTransactionGuard);
+    let out   = f();
+    if guard.is_some() {
+        while let Some(callback) = next_callback() { callback() }
+    }
+    out
+}
+
+/// Closes the outermost transaction when dropped. It is dropped also when a callback panics, so
+/// the events emitted afterwards do not join a transaction which would never be finished.
+struct OutermostTransactionGuard;
+
+impl Drop for OutermostTransactionGuard {
+    fn drop(&mut self) {
+        CURRENT.with(|current| *current.borrow_mut() = None);
+    }
+}
+
+/// Takes the next callback of the current transaction. The transaction is not borrowed while the
+/// callback runs, so it can schedule new events.
+fn next_callback() -> Option<Callback> {
+    CURRENT.with(|current| current.borrow_mut().as_mut().and_then(|t| t.next()))
+}
+
+/// Queues the event delivery in the current transaction. Should be called only inside of the
+/// `transaction` function.
+pub(crate) fn schedule(emitter:Emitter, target:Target, callback:impl FnOnce() + 'static) {
+    CURRENT.with(|current| {
+        match current.borrow_mut().as_mut() {
+            Some(transaction) => transaction.schedule(emitter,target,Box::new(callback)),
+            None              => panic!("Scheduling an event outside of a transaction."),
+        }
+    })
+}
@@ -57,14 +57,8 @@ pub struct BubbleChartModel {
 impl BubbleChartModel {
     fn receive_data(&self, data:&Data) -> Result<(),DataError> {
         let data_inner = match data {
-            Data::Json {content} => content,
-            _ => todo!() // FIXME
-        };
-        let data_inner:&serde_json::Value = data_inner;
-        let data_inner: Rc<Vec<Vector3<f32>>> = if let Ok(result) = serde_json::from_value(data_inner.clone()) {
-            result
-        } else {
-            return Err(DataError::InvalidDataType)
+            Data::Json   {content} => Self::bubbles_from_json(content)?,
+            Data::Binary {content} => Self::bubbles_from_binary(content)?,
         };
 
         // Avoid re-creating views, if we have already created some before.
@@ -83,6 +77,36 @@ impl BubbleChartModel {
         });
         Ok(())
     }
+
+    fn bubbles_from_json(json:&Json) -> Result<Vec<Vector3<f32>>,DataError> {
+        let json:&serde_json::Value = json;
+        serde_json::from_value(json.clone()).map_err(|_| DataError::InvalidDataType)
+    }
+
+    /// Reads the bubbles either from a flat array of `x,y,radius` triples or from a table. The
+    /// table columns are looked up by the `x`, `y` and `radius` names, falling back to the first
+    /// three columns. Rows with a missing value are skipped.
+    fn bubbles_from_binary(binary:&Binary) -> Result<Vec<Vector3<f32>>,DataError> {
+        match binary {
+            Binary::Array(array) => {
+                if array.len() % 3 != 0 { return Err(DataError::InvalidDataType) }
+                let values = array.iter().map(|value| value as f32).collect_vec();
+                Ok(values.chunks(3).map(|t| Vector3::new(t[0],t[1],t[2])).collect())
+            }
+            Binary::Table(table) => {
+                let named   = |ix:usize, name| table.column(name).or_else(|| table.columns.get(ix));
+                let x       = named(0,"x").ok_or(DataError::InvalidDataType)?;
+                let y       = named(1,"y").ok_or(DataError::InvalidDataType)?;
+                let radius  = named(2,"radius").ok_or(DataError::InvalidDataType)?;
+                let bubbles = (0..table.row_count).filter_map(|row| {
+                    let position = Vector3::new(x.get(row)?,y.get(row)?,radius.get(row)?);
+                    Some(position.map(|value| value as f32))
+                });
+                Ok(bubbles.collect())
+            }
+            Binary::Image(_) => Err(DataError::InvalidDataType),
+        }
+    }
 }
 
 
@@ -0,0 +1,389 @@
+//! Line, scatter and histogram charts with axes. The charts are rendered as SVG into a DOM
+//! element. The axis and tick computations are in the `axis` module, and the reading of the
+//! displayed values in the `series` module.
+
+pub mod axis;
+pub mod series;
+
+pub use axis::Axis;
+pub use axis::Scale;
+pub use series::Histogram;
+pub use series::Series;
+
+use crate::prelude::*;
+
+use crate::component::visualization::*;
+use crate::component::visualization;
+use crate::data::EnsoType;
+use crate::frp;
+
+use ensogl::display::DomSymbol;
+use ensogl::display::scene::Scene;
+use ensogl::display;
+use ensogl::system::web;
+use ensogl::system::web::StyleSetter;
+use std::fmt::Write;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The Enso type of the vector of numbers.
+pub const NUMBER_VECTOR_TYPE : &str = "[Number]";
+/// The Enso type of the vector of number pairs.
+pub const PAIR_VECTOR_TYPE : &str = "[(Number,Number)]";
+
+const MARGIN_LEFT   : f32   = 44.0;
+const MARGIN_RIGHT  : f32   = 14.0;
+const MARGIN_TOP    : f32   = 10.0;
+const MARGIN_BOTTOM : f32   = 24.0;
+const TICK_COUNT    : usize = 5;
+const TICK_LENGTH   : f32   = 4.0;
+const POINT_RADIUS  : f32   = 3.0;
+const AXIS_COLOR    : &str  = "rgba(255,255,255,0.5)";
+const SERIES_COLOR  : &str  = "rgb(43,117,38)";
+
+
+
+// =================
+// === ChartKind ===
+// =================
+
+/// The kind of the chart drawn by `Plot`.
+#[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
+pub enum ChartKind {
+    /// Points connected in their order.
+    Line,
+    /// Unconnected points.
+    Scatter,
+    /// Bars counting the values in the ranges of `y` coordinates.
+    Histogram,
+}
+
+impl ChartKind {
+    /// The path of the visualization drawing this chart.
+    pub fn path(self) -> Path {
+        match self {
+            Self::Line      => Path::builtin("Line Chart (native)"),
+            Self::Scatter   => Path::builtin("Scatter Plot (native)"),
+            Self::Histogram => Path::builtin("Histogram (native)"),
+        }
+    }
+
+    /// The Enso type this chart is meant for.
+    pub fn input_type(self) -> EnsoType {
+        match self {
+            Self::Line      => NUMBER_VECTOR_TYPE.into(),
+            Self::Scatter   => PAIR_VECTOR_TYPE.into(),
+            Self::Histogram => NUMBER_VECTOR_TYPE.into(),
+        }
+    }
+}
+
+
+
+// =============
+// === Frame ===
+// =============
+
+/// The plotting area with the axes, mapping the values to the SVG coordinates.
+#[derive(Clone,Copy,Debug)]
+pub struct Frame {
+    x    : Axis,
+    y    : Axis,
+    size : Vector2<f32>,
+}
+
+impl Frame {
+    /// Constructor.
+    pub fn new(x:Axis, y:Axis, size:Vector2<f32>) -> Self {
+        Self {x,y,size}
+    }
+
+    fn width(&self) -> f32 {
+        (self.size.x - MARGIN_LEFT - MARGIN_RIGHT).max(0.0)
+    }
+
+    fn height(&self) -> f32 {
+        (self.size.y - MARGIN_TOP - MARGIN_BOTTOM).max(0.0)
+    }
+
+    fn screen_x(&self, x:f64) -> Option<f32> {
+        self.x.project(x).map(|x| MARGIN_LEFT + x as f32 * self.width())
+    }
+
+    fn screen_y(&self, y:f64) -> Option<f32> {
+        self.y.project(y).map(|y| MARGIN_TOP + (1.0 - y as f32) * self.height())
+    }
+
+    /// The SVG coordinates of the point, or `None` if it cannot be displayed in the axes scales.
+    pub fn to_screen(&self, point:series::Point) -> Option<Vector2<f32>> {
+        Some(Vector2(self.screen_x(point.x)?,self.screen_y(point.y)?))
+    }
+
+    fn render_axes(&self, svg:&mut String) {
+        let left   = MARGIN_LEFT;
+        let bottom = MARGIN_TOP + self.height();
+        let right  = left + self.width();
+        line(svg,left,bottom,right,bottom);
+        line(svg,left,MARGIN_TOP,left,bottom);
+        let x_ticks = self.x.ticks(TICK_COUNT);
+        for value in &x_ticks.values {
+            if let Some(x) = self.screen_x(*value) {
+                line(svg,x,bottom,x,bottom + TICK_LENGTH);
+                let label = x_ticks.label(*value);
+                text(svg,x,bottom + TICK_LENGTH + 11.0,"middle",&label);
+            }
+        }
+        let y_ticks = self.y.ticks(TICK_COUNT);
+        for value in &y_ticks.values {
+            if let Some(y) = self.screen_y(*value) {
+                line(svg,left - TICK_LENGTH,y,left,y);
+                let label = y_ticks.label(*value);
+                text(svg,left - TICK_LENGTH - 2.0,y + 3.0,"end",&label);
+            }
+        }
+    }
+}
+
+fn line(svg:&mut String, x1:f32, y1:f32, x2:f32, y2:f32) {
+    let _ = write!(svg,r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}"/>"#,
+        x1,y1,x2,y2,AXIS_COLOR);
+}
+
+fn text(svg:&mut String, x:f32, y:f32, anchor:&str, content:&str) {
+    let _ = write!(svg,r#"<text x="{}" y="{}" text-anchor="{}" fill="{}">{}</text>"#,
+        x,y,anchor,AXIS_COLOR,content);
+}
+
+
+
+// =================
+// === Rendering ===
+// =================
+
+/// Renders the chart of the given kind as an SVG document of the given size. The `y_scale` is
+/// used for the vertical axis, the horizontal one is always linear.
+pub fn render(kind:ChartKind, series:&Series, size:Vector2<f32>, y_scale:Scale) -> String {
+    let mut svg = format!(r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}">"#,
+        size.x,size.y);
+    match kind {
+        ChartKind::Line | ChartKind::Scatter => {
+            let x     = Axis::auto(series.xs(),Scale::Linear,TICK_COUNT);
+            let y     = Axis::auto(series.ys(),y_scale,TICK_COUNT);
+            let frame = Frame::new(x,y,size);
+            frame.render_axes(&mut svg);
+            let points = series.points.iter().filter_map(|point| frame.to_screen(*point));
+            if kind == ChartKind::Line {
+                let points = points.map(|p| format!("{},{}",p.x,p.y)).join(" ");
+                let _ = write!(svg,r#"<polyline points="{}" fill="none" stroke="{}"/>"#,
+                    points,SERIES_COLOR);
+            } else {
+                for p in points {
+                    let _ = write!(svg,r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+                        p.x,p.y,POINT_RADIUS,SERIES_COLOR);
+                }
+            }
+        }
+        ChartKind::Histogram => {
+            let histogram = Histogram::new(series.ys());
+            let edges     = histogram.bins.iter().flat_map(|bin| vec![bin.start,bin.end]);
+            let x         = Axis::new(Scale::Linear,axis::Domain::auto(edges,Scale::Linear));
+            let counts    = vec![0.0,histogram.max_count() as f64];
+            let y         = Axis::auto(counts,y_scale,TICK_COUNT);
+            let frame     = Frame::new(x,y,size);
+            let bottom    = MARGIN_TOP + frame.height();
+            frame.render_axes(&mut svg);
+            for bin in &histogram.bins {
+                let left  = frame.screen_x(bin.start);
+                let right = frame.screen_x(bin.end);
+                let top   = frame.screen_y(bin.count as f64);
+                if let (Some(left),Some(right),Some(top)) = (left,right,top) {
+                    let _ = write!(svg,
+                        r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="{}"/>"#,
+                        left,top,right - left,bottom - top,SERIES_COLOR,AXIS_COLOR);
+                }
+            }
+        }
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+
+
+// =================
+// === PlotModel ===
+// =================
+
+#[derive(Clone,CloneRef,Debug)]
+#[allow(missing_docs)]
+pub struct PlotModel {
+    logger  : Logger,
+    dom     : DomSymbol,
+    kind    : ChartKind,
+    series  : Rc<RefCell<Series>>,
+    y_scale : Rc<Cell<Scale>>,
+    size    : Rc<Cell<Vector2>>,
+}
+
+impl PlotModel {
+    fn new(scene:&Scene, kind:ChartKind) -> Self {
+        let logger  = Logger::new("Plot");
+        let div     = web::create_div();
+        let dom     = DomSymbol::new(&div);
+        let series  = default();
+        let y_scale = default();
+        let size    = Rc::new(Cell::new(Vector2(200.0,200.0)));
+
+        dom.dom().set_style_or_warn("font-family"   ,"dejavuSansMono",&logger);
+        dom.dom().set_style_or_warn("font-size"     ,"10px"          ,&logger);
+        dom.dom().set_style_or_warn("pointer-events","auto"          ,&logger);
+
+        scene.dom.layers.main.manage(&dom);
+        PlotModel{logger,dom,kind,series,y_scale,size}.init()
+    }
+
+    fn init(self) -> Self {
+        self.redraw();
+        self
+    }
+
+    /// Sets the scale of the vertical axis.
+    pub fn set_y_scale(&self, scale:Scale) {
+        self.y_scale.set(scale);
+        self.redraw();
+    }
+
+    fn set_size(&self, size:Vector2) {
+        self.size.set(size);
+        self.redraw();
+    }
+
+    fn receive_data(&self, data:&Data) -> Result<(),DataError> {
+        *self.series.borrow_mut() = Series::from_data(data)?;
+        self.redraw();
+        Ok(())
+    }
+
+    fn redraw(&self) {
+        let size = self.size.get();
+        let svg  = render(self.kind,&self.series.borrow(),size,self.y_scale.get());
+        self.dom.set_size(size);
+        self.dom.dom().set_inner_html(&svg);
+    }
+}
+
+
+
+// ============
+// === Plot ===
+// ============
+
+/// Visualization drawing a chart of numbers or number pairs.
+#[derive(Debug,Shrinkwrap)]
+#[allow(missing_docs)]
+pub struct Plot {
+    #[shrinkwrap(main_field)]
+    model   : PlotModel,
+    frp     : visualization::instance::Frp,
+    network : frp::Network,
+}
+
+impl Plot {
+    /// Definition of the line chart visualization.
+    pub fn line_chart_definition() -> Definition {
+        Self::definition(ChartKind::Line)
+    }
+
+    /// Definition of the scatter plot visualization.
+    pub fn scatter_plot_definition() -> Definition {
+        Self::definition(ChartKind::Scatter)
+    }
+
+    /// Definition of the histogram visualization.
+    pub fn histogram_definition() -> Definition {
+        Self::definition(ChartKind::Histogram)
+    }
+
+    fn definition(kind:ChartKind) -> Definition {
+        Definition::new(
+            Signature::new(kind.path(),kind.input_type(),Format::Json),
+            move |scene| { Ok(Self::new(scene,kind).into()) }
+        )
+    }
+
+    /// Constructor.
+    pub fn new(scene:&Scene, kind:ChartKind) -> Self {
+        let network = default();
+        let frp     = visualization::instance::Frp::new(&network);
+        let model   = PlotModel::new(scene,kind);
+        Self {model,frp,network} . init()
+    }
+
+    fn init(self) -> Self {
+        let network = &self.network;
+        let model   = self.model.clone_ref();
+        let frp     = self.frp.clone_ref();
+        frp::extend! { network
+            eval frp.set_size  ((size) model.set_size(*size));
+            eval frp.send_data ([frp](data) {
+                if let Err(e) = model.receive_data(data) {
+                    frp.data_receive_error.emit(Some(e));
+                }
+             });
+        }
+        self
+    }
+}
+
+impl From<Plot> for Instance {
+    fn from(t:Plot) -> Self {
+        Self::new(&t,&t.frp,&t.network)
+    }
+}
+
+impl display::Object for Plot {
+    fn display_object(&self) -> &display::object::Instance {
+        &self.dom.display_object()
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use series::Point;
+
+    #[test]
+    fn mapping_to_screen() {
+        let x     = Axis::new(Scale::Linear,axis::Domain::new(0.0,10.0));
+        let y     = Axis::new(Scale::Log,axis::Domain::new(1.0,100.0));
+        let size  = Vector2(MARGIN_LEFT + MARGIN_RIGHT + 100.0, MARGIN_TOP + MARGIN_BOTTOM + 50.0);
+        let frame = Frame::new(x,y,size);
+        let point = frame.to_screen(Point::new(5.0,10.0));
+        assert_eq!(point,Some(Vector2(MARGIN_LEFT + 50.0,MARGIN_TOP + 25.0)));
+        assert_eq!(frame.to_screen(Point::new(5.0,-1.0)),None);
+    }
+
+    #[test]
+    fn rendering_charts() {
+        let series = Series {points:vec![Point::new(0.0,1.0),Point::new(1.0,3.0)]};
+        let size   = Vector2(200.0,100.0);
+        let line   = render(ChartKind::Line,&series,size,Scale::Linear);
+        let points = render(ChartKind::Scatter,&series,size,Scale::Linear);
+        let bars   = render(ChartKind::Histogram,&series,size,Scale::Linear);
+        assert_eq!(line.matches("<polyline").count(),1);
+        assert_eq!(points.matches("<circle").count(),2);
+        assert!(bars.matches("<rect").count() > 0);
+        assert!(line.ends_with("</svg>"));
+    }
+}
@@ -0,0 +1,295 @@
+//! Scales and axes of the plots. Computes the displayed range of values and the positions of the
+//! ticks, so they fall on "nice" numbers like 0.2, 50 or 1000.
+
+use crate::prelude::*;
+
+
+
+// =============
+// === Scale ===
+// =============
+
+/// The mapping of values onto the axis.
+#[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
+pub enum Scale {
+    /// The distance on the axis is proportional to the difference of values.
+    Linear,
+    /// The distance on the axis is proportional to the ratio of values. Only positive values can be
+    /// displayed.
+    Log,
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Self::Linear
+    }
+}
+
+impl Scale {
+    /// Whether the value can be displayed in this scale.
+    pub fn accepts(self, value:f64) -> bool {
+        value.is_finite() && (self == Self::Linear || value > 0.0)
+    }
+
+    fn transform(self, value:f64) -> f64 {
+        match self {
+            Self::Linear => value,
+            Self::Log    => value.log10(),
+        }
+    }
+}
+
+
+
+// ==============
+// === Domain ===
+// ==============
+
+/// The range of values displayed on the axis.
+#[derive(Clone,Copy,Debug,PartialEq)]
+#[allow(missing_docs)]
+pub struct Domain {
+    pub min : f64,
+    pub max : f64,
+}
+
+impl Domain {
+    /// Constructor.
+    pub fn new(min:f64, max:f64) -> Self {
+        Self {min,max}
+    }
+
+    /// The smallest domain containing all the values which can be displayed in the given scale.
+    /// The empty and single-value domains are widened, so they can be displayed.
+    pub fn auto(values:impl IntoIterator<Item=f64>, scale:Scale) -> Self {
+        let values = values.into_iter().filter(|value| scale.accepts(*value));
+        let bounds = values.fold(None, |bounds:Option<(f64,f64)>,value| match bounds {
+            None           => Some((value,value)),
+            Some((lo,hi))  => Some((lo.min(value),hi.max(value))),
+        });
+        match (scale,bounds) {
+            (Scale::Linear,None)                      => Self::new(0.0,1.0),
+            (Scale::Log   ,None)                      => Self::new(1.0,10.0),
+            (Scale::Linear,Some((lo,hi))) if lo == hi => Self::new(lo - 1.0,hi + 1.0),
+            (Scale::Log   ,Some((lo,hi))) if lo == hi => Self::new(lo / 10.0,hi * 10.0),
+            (_            ,Some((lo,hi)))             => Self::new(lo,hi),
+        }
+    }
+}
+
+
+
+// =============
+// === Ticks ===
+// =============
+
+/// The tolerance of rounding the values to tick steps, so the floating point errors like in
+/// `0.3 / 0.1 = 2.9999999999999996` do not drop the ticks at the domain bounds.
+const ROUNDING_TOLERANCE : f64 = 1e-9;
+
+fn floor(value:f64) -> f64 {
+    (value + ROUNDING_TOLERANCE).floor()
+}
+
+fn ceil(value:f64) -> f64 {
+    (value - ROUNDING_TOLERANCE).ceil()
+}
+
+/// A number close to `value` which is 1, 2, 5 or 10 times a power of ten. If `round` is set, the
+/// closest such number is chosen, otherwise the smallest one not lower than `value`.
+pub fn nice_number(value:f64, round:bool) -> f64 {
+    let exponent = value.log10().floor();
+    let power    = 10_f64.powf(exponent);
+    let fraction = value / power;
+    let nice     = if round {
+        if      fraction < 1.5 { 1.0 }
+        else if fraction < 3.0 { 2.0 }
+        else if fraction < 7.0 { 5.0 }
+        else                   { 10.0 }
+    } else {
+        if      fraction <= 1.0 { 1.0 }
+        else if fraction <= 2.0 { 2.0 }
+        else if fraction <= 5.0 { 5.0 }
+        else                    { 10.0 }
+    };
+    nice * power
+}
+
+/// The tick values of an axis.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Ticks {
+    /// The values at which ticks are placed, in the ascending order.
+    pub values : Vec<f64>,
+    /// The number of decimal places needed to tell the tick labels apart.
+    pub precision : usize,
+}
+
+impl Ticks {
+    /// The label of the tick with the given value.
+    pub fn label(&self, value:f64) -> String {
+        format!("{:.*}",self.precision,value)
+    }
+
+    fn linear(domain:Domain, count:usize) -> Self {
+        let span = domain.max - domain.min;
+        if !span.is_finite() || span <= 0.0 {
+            return Self {values:vec![domain.min],precision:0}
+        }
+        let count = count.max(2);
+        let range = nice_number(domain.max - domain.min, false);
+        let step  = nice_number(range / (count - 1) as f64, true);
+        let first = ceil(domain.min / step) as i64;
+        let last  = floor(domain.max / step) as i64;
+        let values    = (first..=last).map(|i| i as f64 * step).collect();
+        let precision = (-floor(step.log10())).max(0.0) as usize;
+        Self {values,precision}
+    }
+
+    fn log(domain:Domain, count:usize) -> Self {
+        let first     = ceil(domain.min.log10()) as i64;
+        let last      = floor(domain.max.log10()) as i64;
+        let decades   = (last - first + 1).max(1) as usize;
+        let step      = ((decades + count - 1) / count.max(1)).max(1);
+        let values    = (first..=last).step_by(step).map(|i| 10_f64.powi(i as i32)).collect();
+        let precision = (-first).max(0) as usize;
+        Self {values,precision}
+    }
+}
+
+
+
+// ============
+// === Axis ===
+// ============
+
+/// A single axis of a plot.
+#[derive(Clone,Copy,Debug,PartialEq)]
+#[allow(missing_docs)]
+pub struct Axis {
+    pub scale  : Scale,
+    pub domain : Domain,
+}
+
+impl Axis {
+    /// Constructor.
+    pub fn new(scale:Scale, domain:Domain) -> Self {
+        Self {scale,domain}
+    }
+
+    /// The axis displaying all the given values. The domain is extended, so it starts and ends with
+    /// a tick, assuming there will be about `tick_count` of them.
+    pub fn auto(values:impl IntoIterator<Item=f64>, scale:Scale, tick_count:usize) -> Self {
+        let domain = Domain::auto(values,scale);
+        let domain = match scale {
+            Scale::Linear => {
+                let count = tick_count.max(2);
+                let range = nice_number(domain.max - domain.min, false);
+                let step  = nice_number(range / (count - 1) as f64, true);
+                let min   = floor(domain.min / step) * step;
+                let max   = ceil(domain.max / step)  * step;
+                Domain::new(min,max)
+            }
+            Scale::Log => {
+                let min = 10_f64.powf(floor(domain.min.log10()));
+                let max = 10_f64.powf(ceil(domain.max.log10()));
+                Domain::new(min,max)
+            }
+        };
+        Self {scale,domain}
+    }
+
+    /// About `count` ticks inside the domain.
+    pub fn ticks(&self, count:usize) -> Ticks {
+        match self.scale {
+            Scale::Linear => Ticks::linear(self.domain,count),
+            Scale::Log    => Ticks::log(self.domain,count),
+        }
+    }
+
+    /// The position of the value on the axis, where 0 is the start and 1 is the end of the domain.
+    /// Returns `None` if the value cannot be displayed in the axis scale.
+    pub fn project(&self, value:f64) -> Option<f64> {
+        if !self.scale.accepts(value) { return None }
+        let min = self.scale.transform(self.domain.min);
+        let max = self.scale.transform(self.domain.max);
+        let len = max - min;
+        Some(if len == 0.0 { 0.5 } else { (self.scale.transform(value) - min) / len })
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual:&[f64], expected:&[f64]) {
+        assert_eq!(actual.len(),expected.len(),"{:?} != {:?}",actual,expected);
+        for (a,e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{:?} != {:?}",actual,expected);
+        }
+    }
+
+    #[test]
+    fn nice_numbers() {
+        assert_eq!(nice_number(0.6,true),0.5);
+        assert_eq!(nice_number(1.2,true),1.0);
+        assert_eq!(nice_number(2.9,true),2.0);
+        assert_eq!(nice_number(34.0,true),50.0);
+        assert_eq!(nice_number(1.2,false),2.0);
+        assert_eq!(nice_number(3.0,false),5.0);
+        assert_eq!(nice_number(600.0,false),1000.0);
+    }
+
+    #[test]
+    fn auto_domain() {
+        assert_eq!(Domain::auto(vec![3.0,-1.0,2.0],Scale::Linear),Domain::new(-1.0,3.0));
+        assert_eq!(Domain::auto(vec![],Scale::Linear),Domain::new(0.0,1.0));
+        assert_eq!(Domain::auto(vec![2.0],Scale::Linear),Domain::new(1.0,3.0));
+        assert_eq!(Domain::auto(vec![f64::NAN,5.0,2.0],Scale::Linear),Domain::new(2.0,5.0));
+        assert_eq!(Domain::auto(vec![-1.0,0.0,4.0,20.0],Scale::Log),Domain::new(4.0,20.0));
+        assert_eq!(Domain::auto(vec![5.0],Scale::Log),Domain::new(0.5,50.0));
+    }
+
+    #[test]
+    fn linear_ticks() {
+        let axis = Axis::auto(vec![0.3,9.7],Scale::Linear,5);
+        assert_eq!(axis.domain,Domain::new(0.0,10.0));
+        let ticks = axis.ticks(5);
+        assert_close(&ticks.values,&[0.0,2.0,4.0,6.0,8.0,10.0]);
+        assert_eq!(ticks.precision,0);
+        assert_eq!(ticks.label(4.0),"4");
+
+        let ticks = Axis::new(Scale::Linear,Domain::new(-0.13,0.3)).ticks(5);
+        assert_close(&ticks.values,&[-0.1,0.0,0.1,0.2,0.3]);
+        assert_eq!(ticks.precision,1);
+        assert_eq!(ticks.label(0.2),"0.2");
+    }
+
+    #[test]
+    fn log_ticks() {
+        let axis = Axis::auto(vec![3.0,420.0],Scale::Log,5);
+        assert_eq!(axis.domain,Domain::new(1.0,1000.0));
+        assert_close(&axis.ticks(5).values,&[1.0,10.0,100.0,1000.0]);
+
+        let axis  = Axis::new(Scale::Log,Domain::new(0.001,1e9));
+        let ticks = axis.ticks(4);
+        assert_close(&ticks.values,&[0.001,10.0,1e5,1e9]);
+        assert_eq!(ticks.label(0.001),"0.001");
+    }
+
+    #[test]
+    fn projecting_values() {
+        let axis = Axis::new(Scale::Linear,Domain::new(-10.0,10.0));
+        assert_eq!(axis.project(0.0),Some(0.5));
+        assert_eq!(axis.project(-10.0),Some(0.0));
+        assert_eq!(axis.project(f64::INFINITY),None);
+        let axis = Axis::new(Scale::Log,Domain::new(1.0,100.0));
+        assert_eq!(axis.project(10.0),Some(0.5));
+        assert_eq!(axis.project(0.0),None);
+    }
+}
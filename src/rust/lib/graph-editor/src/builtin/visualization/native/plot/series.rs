@@ -0,0 +1,222 @@
+//! The data displayed by the plots: a series of points and the histogram of values.
+
+use crate::prelude::*;
+
+use crate::component::visualization::*;
+
+use super::axis::Axis;
+use super::axis::Scale;
+
+
+
+// =============
+// === Point ===
+// =============
+
+/// A single point of the series.
+#[derive(Clone,Copy,Debug,PartialEq)]
+#[allow(missing_docs)]
+pub struct Point {
+    pub x : f64,
+    pub y : f64,
+}
+
+impl Point {
+    /// Constructor.
+    pub fn new(x:f64, y:f64) -> Self {
+        Self {x,y}
+    }
+
+    fn from_json(index:usize, json:&serde_json::Value) -> Option<Self> {
+        let number = |json:&serde_json::Value| json.as_f64();
+        match json {
+            serde_json::Value::Number(n) => Some(Self::new(index as f64,n.as_f64()?)),
+            serde_json::Value::Array(pair) if pair.len() == 2 =>
+                Some(Self::new(number(&pair[0])?,number(&pair[1])?)),
+            serde_json::Value::Object(fields) =>
+                Some(Self::new(number(fields.get("x")?)?,number(fields.get("y")?)?)),
+            _ => None,
+        }
+    }
+}
+
+
+
+// ==============
+// === Series ===
+// ==============
+
+/// The points displayed by a plot.
+#[derive(Clone,Debug,Default,PartialEq)]
+#[allow(missing_docs)]
+pub struct Series {
+    pub points : Vec<Point>,
+}
+
+impl Series {
+    /// Reads the series from the visualization data. Both the vectors of numbers and the vectors
+    /// of pairs are accepted. The number vector elements are placed at their indices.
+    ///
+    /// In JSON, each pair can be given either as a two-element array or as an object with `x` and
+    /// `y` fields. In the binary format, a table with the `x` and `y` columns (or just two columns)
+    /// describes pairs, while an array or a single-column table describes numbers.
+    pub fn from_data(data:&Data) -> Result<Self,DataError> {
+        match data {
+            Data::Json   {content} => Self::from_json(content),
+            Data::Binary {content} => Self::from_binary(content),
+        }
+    }
+
+    /// Reads the series from a JSON array.
+    pub fn from_json(json:&serde_json::Value) -> Result<Self,DataError> {
+        let items  = json.as_array().ok_or(DataError::InvalidDataType)?;
+        let points = items.iter().enumerate().map(|(index,item)| Point::from_json(index,item));
+        let points = points.collect::<Option<Vec<_>>>().ok_or(DataError::InvalidDataType)?;
+        Ok(Self {points})
+    }
+
+    /// Reads the series from the binary data. Rows with missing values are skipped.
+    pub fn from_binary(binary:&Binary) -> Result<Self,DataError> {
+        match binary {
+            Binary::Array(array) => Ok(Self::from_numbers(array.iter().map(Some))),
+            Binary::Table(table) => {
+                let rows = 0..table.row_count;
+                match table.columns.as_slice() {
+                    []       => Err(DataError::InvalidDataType),
+                    [column] => Ok(Self::from_numbers(rows.map(|row| column.get(row)))),
+                    columns  => {
+                        let x      = table.column("x").unwrap_or(&columns[0]);
+                        let y      = table.column("y").unwrap_or(&columns[1]);
+                        let point  = |row| Some(Point::new(x.get(row)?,y.get(row)?));
+                        let points = rows.filter_map(point).collect();
+                        Ok(Self {points})
+                    }
+                }
+            }
+            Binary::Image(_) => Err(DataError::InvalidDataType),
+        }
+    }
+
+    fn from_numbers(values:impl Iterator<Item=Option<f64>>) -> Self {
+        let points = values.enumerate().filter_map(|(ix,y)| Some(Point::new(ix as f64,y?)));
+        Self {points:points.collect()}
+    }
+
+    /// The `x` coordinates of all points.
+    pub fn xs(&self) -> impl Iterator<Item=f64> + '_ {
+        self.points.iter().map(|point| point.x)
+    }
+
+    /// The `y` coordinates of all points.
+    pub fn ys(&self) -> impl Iterator<Item=f64> + '_ {
+        self.points.iter().map(|point| point.y)
+    }
+}
+
+
+
+// =================
+// === Histogram ===
+// =================
+
+/// A single bar of the histogram, counting the values in the `start..end` range.
+#[derive(Clone,Copy,Debug,PartialEq)]
+#[allow(missing_docs)]
+pub struct Bin {
+    pub start : f64,
+    pub end   : f64,
+    pub count : usize,
+}
+
+/// The distribution of values. The bin edges are placed on the nice numbers, like the axis ticks.
+#[derive(Clone,Debug,Default,PartialEq)]
+#[allow(missing_docs)]
+pub struct Histogram {
+    pub bins : Vec<Bin>,
+}
+
+impl Histogram {
+    /// The histogram of the given values. The number of bins is about the one suggested by the
+    /// Sturges' rule. The values which are not finite are skipped.
+    pub fn new(values:impl IntoIterator<Item=f64>) -> Self {
+        let values    = values.into_iter().filter(|value| value.is_finite()).collect_vec();
+        let bin_count = Self::suggested_bin_count(values.len());
+        let axis      = Axis::auto(values.iter().copied(),Scale::Linear,bin_count + 1);
+        let edges     = axis.ticks(bin_count + 1).values;
+        let bins      = edges.windows(2).map(|edge| Bin {start:edge[0],end:edge[1],count:0});
+        let mut bins  = bins.collect_vec();
+        if let (Some(first),Some(last)) = (bins.first().copied(),bins.last().copied()) {
+            let width = first.end - first.start;
+            for value in values {
+                let index = ((value - first.start) / width).floor().max(0.0) as usize;
+                // The values at the end of the last bin belong to it.
+                let index = if value >= last.end { bins.len() - 1 } else { index };
+                bins[index.min(bins.len() - 1)].count += 1;
+            }
+        }
+        Self {bins}
+    }
+
+    /// The Sturges' rule: `log2(n) + 1` bins.
+    fn suggested_bin_count(value_count:usize) -> usize {
+        (value_count.max(1) as f64).log2().ceil() as usize + 1
+    }
+
+    /// The highest count among the bins.
+    pub fn max_count(&self) -> usize {
+        self.bins.iter().map(|bin| bin.count).max().unwrap_or_default()
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::component::visualization::data::binary;
+
+    #[test]
+    fn reading_json_series() {
+        let json   = serde_json::json!([3, [1.5,2], {"x":4,"y":-1}]);
+        let series = Series::from_json(&json).unwrap();
+        let points = vec![Point::new(0.0,3.0),Point::new(1.5,2.0),Point::new(4.0,-1.0)];
+        assert_eq!(series.points,points);
+
+        assert!(Series::from_json(&serde_json::json!(["a"])).is_err());
+        assert!(Series::from_json(&serde_json::json!([[1,2,3]])).is_err());
+        assert!(Series::from_json(&serde_json::json!({"x":1})).is_err());
+    }
+
+    #[test]
+    fn reading_binary_series() {
+        let array  = binary::NumericArray::F32(vec![1.0,2.0]);
+        let series = Series::from_binary(&Binary::Array(array)).unwrap();
+        assert_eq!(series.points,vec![Point::new(0.0,1.0),Point::new(1.0,2.0)]);
+
+        let mut y  = binary::Column::new("y",binary::NumericArray::I32(vec![7,8,9]));
+        y.validity = Some(vec![true,false,true]);
+        let x      = binary::Column::new("x",binary::NumericArray::F64(vec![0.5,1.5,2.5]));
+        let other  = binary::Column::new("other",binary::NumericArray::U8(vec![0,0,0]));
+        let table  = binary::Table {columns:vec![y,other,x],row_count:3};
+        let series = Series::from_binary(&Binary::Table(table)).unwrap();
+        assert_eq!(series.points,vec![Point::new(0.5,7.0),Point::new(2.5,9.0)]);
+    }
+
+    #[test]
+    fn histogram_bins() {
+        let values    = vec![0.0,0.5,1.0,2.5,2.6,3.9,4.0,f64::NAN];
+        let histogram = Histogram::new(values);
+        let edges     = histogram.bins.iter().map(|bin| (bin.start,bin.end)).collect_vec();
+        let counts    = histogram.bins.iter().map(|bin| bin.count).collect_vec();
+        assert_eq!(edges,vec![(0.0,1.0),(1.0,2.0),(2.0,3.0),(3.0,4.0)]);
+        assert_eq!(counts,vec![2,1,2,2]);
+        assert_eq!(histogram.max_count(),2);
+
+        assert_eq!(Histogram::new(Vec::new()).max_count(),0);
+    }
+}
@@ -102,11 +102,8 @@ impl RawTextModel {
     }
 
     fn receive_data(&self, data:&Data) -> Result<(),DataError> {
-        let data_inner = match data {
-            Data::Json {content} => content,
-            _ => todo!() // FIXME
-        };
-        let data_str = serde_json::to_string_pretty(&**data_inner);
+        let data_inner = data.to_json();
+        let data_str   = serde_json::to_string_pretty(&*data_inner);
         let data_str = data_str.unwrap_or_else(|e| format!("<Cannot render data: {}>", e));
         let data_str = format!("\n{}",data_str);
         self.dom.dom().set_inner_text(&data_str);
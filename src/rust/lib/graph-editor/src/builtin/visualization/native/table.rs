@@ -0,0 +1,922 @@
+//! Table visualization rendering rows of data in DOM. Only the rows visible in the viewport are
+//! present in the DOM, so previews of very large tables stay responsive. The columns can be sorted
+//! by clicking their header and resized by dragging the right edge of the header.
+
+use crate::prelude::*;
+
+use crate::component::visualization::*;
+use crate::component::visualization::data::binary;
+use crate::component::visualization;
+use crate::frp;
+
+use ensogl::display::DomSymbol;
+use ensogl::display::scene::Scene;
+use ensogl::display;
+use ensogl::system::web;
+use ensogl::system::web::NodeInserter;
+use ensogl::system::web::StyleSetter;
+use std::cmp::Ordering;
+use std::ops::Range;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::Closure;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The height of a single row in pixels.
+pub const ROW_HEIGHT : f32 = 20.0;
+/// The height of the header, showing the column names and types, in pixels.
+pub const HEADER_HEIGHT : f32 = 36.0;
+/// The width of the column before it is resized by the user.
+pub const DEFAULT_COLUMN_WIDTH : f32 = 120.0;
+/// The minimal width of the column the user can resize it to.
+pub const MIN_COLUMN_WIDTH : f32 = 40.0;
+/// The number of rows rendered above and below the viewport, so the scrolling does not reveal
+/// empty space before the rows are updated.
+pub const OVERSCAN_ROWS : usize = 10;
+/// The maximal height of the element giving the table its scrollbar. Browsers do not support
+/// elements taller than several million pixels, so the scroll position of bigger tables is scaled.
+pub const MAX_BODY_HEIGHT : f32 = 5_000_000.0;
+/// The preprocessor of the columnar table visualization, serializing the visualized value into the
+/// binary table payload described in `data::binary`.
+pub const COLUMNAR_PREPROCESSOR_CODE : &str = "x -> x.to_binary_table";
+
+const COLUMN_ATTRIBUTE : &str = "data-column";
+const RESIZE_ATTRIBUTE : &str = "data-resize";
+
+
+
+// =============
+// === Value ===
+// =============
+
+/// A single table cell.
+#[derive(Clone,Debug,PartialEq)]
+#[allow(missing_docs)]
+pub enum Value {
+    Null,
+    Boolean(bool),
+    Number(f64),
+    Text(String),
+}
+
+impl Value {
+    /// The type of the value, or `None` for a missing value.
+    pub fn column_type(&self) -> Option<ColumnType> {
+        match self {
+            Self::Null       => None,
+            Self::Boolean(_) => Some(ColumnType::Boolean),
+            Self::Number(_)  => Some(ColumnType::Number),
+            Self::Text(_)    => Some(ColumnType::Text),
+        }
+    }
+
+    /// Compares the values, placing the values of different types in the order: booleans,
+    /// numbers, texts. Missing values are not handled here, see `ColumnValues::compare_rows`.
+    fn compare(&self, other:&Self) -> Ordering {
+        match (self,other) {
+            (Self::Boolean(a),Self::Boolean(b)) => a.cmp(b),
+            (Self::Number(a) ,Self::Number(b))  => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+            (Self::Text(a)   ,Self::Text(b))    => a.cmp(b),
+            _ => self.type_rank().cmp(&other.type_rank()),
+        }
+    }
+
+    fn type_rank(&self) -> usize {
+        match self {
+            Self::Boolean(_) => 0,
+            Self::Number(_)  => 1,
+            Self::Text(_)    => 2,
+            Self::Null       => 3,
+        }
+    }
+}
+
+impl From<&serde_json::Value> for Value {
+    fn from(value:&serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null      => Self::Null,
+            serde_json::Value::Bool(b)   => Self::Boolean(*b),
+            serde_json::Value::Number(n) => Self::Number(n.as_f64().unwrap_or_default()),
+            serde_json::Value::String(s) => Self::Text(s.clone()),
+            other                        => Self::Text(other.to_string()),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Null       => Ok(()),
+            Self::Boolean(b) => write!(f,"{}",b),
+            Self::Number(n)  => write!(f,"{}",n),
+            Self::Text(s)    => write!(f,"{}",s),
+        }
+    }
+}
+
+
+
+// ==================
+// === ColumnType ===
+// ==================
+
+/// The type of the values in a column, displayed in the header.
+#[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
+#[allow(missing_docs)]
+pub enum ColumnType {Empty,Boolean,Number,Text,Mixed}
+
+impl ColumnType {
+    /// The type of a column containing values of the given types. Missing values are skipped.
+    pub fn infer(types:impl IntoIterator<Item=Option<ColumnType>>) -> Self {
+        let present = types.into_iter().flatten();
+        present.fold(Self::Empty, |acc,tp| match acc {
+            Self::Empty      => tp,
+            acc if acc == tp => acc,
+            _                => Self::Mixed,
+        })
+    }
+}
+
+impl Display for ColumnType {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Empty   => "Empty",
+            Self::Boolean => "Boolean",
+            Self::Number  => "Number",
+            Self::Text    => "Text",
+            Self::Mixed   => "Mixed",
+        };
+        write!(f,"{}",name)
+    }
+}
+
+
+
+// ==============
+// === Column ===
+// ==============
+
+/// The values of a single column, kept in the form they were received in.
+#[derive(Clone,Debug)]
+pub enum ColumnValues {
+    /// Values read from JSON.
+    Values(Vec<Value>),
+    /// A column of the binary columnar format.
+    Numeric(binary::Column),
+}
+
+impl ColumnValues {
+    /// The value in the given row.
+    pub fn get(&self, row:usize) -> Value {
+        match self {
+            Self::Values(values)  => values.get(row).cloned().unwrap_or(Value::Null),
+            Self::Numeric(column) => column.get(row).map_or(Value::Null,Value::Number),
+        }
+    }
+
+    /// Compares the values of the given rows. Missing values are placed last, regardless of the
+    /// sort order.
+    pub fn compare_rows(&self, a:usize, b:usize, order:SortOrder) -> Ordering {
+        let ordered = |ordering:Ordering| match order {
+            SortOrder::Ascending  => ordering,
+            SortOrder::Descending => ordering.reverse(),
+        };
+        match self {
+            Self::Values(values) => {
+                let a = values.get(a).unwrap_or(&Value::Null);
+                let b = values.get(b).unwrap_or(&Value::Null);
+                match (a,b) {
+                    (Value::Null,Value::Null) => Ordering::Equal,
+                    (Value::Null,_)           => Ordering::Greater,
+                    (_,Value::Null)           => Ordering::Less,
+                    (a,b)                     => ordered(a.compare(b)),
+                }
+            }
+            Self::Numeric(column) => match (column.get(a),column.get(b)) {
+                (None,None)       => Ordering::Equal,
+                (None,_)          => Ordering::Greater,
+                (_,None)          => Ordering::Less,
+                (Some(a),Some(b)) => ordered(a.partial_cmp(&b).unwrap_or(Ordering::Equal)),
+            }
+        }
+    }
+}
+
+/// A named and typed table column.
+#[derive(Clone,Debug)]
+#[allow(missing_docs)]
+pub struct Column {
+    pub name        : String,
+    pub column_type : ColumnType,
+    pub values      : ColumnValues,
+}
+
+
+
+// =================
+// === TableData ===
+// =================
+
+/// The data displayed by the table visualization.
+#[derive(Clone,Debug,Default)]
+#[allow(missing_docs)]
+pub struct TableData {
+    pub columns   : Vec<Column>,
+    pub row_count : usize,
+}
+
+impl TableData {
+    /// Reads the table from the visualization data. JSON data must be an array of objects, each
+    /// being a single row. The binary data may be either a table or a single array.
+    pub fn from_data(data:&Data) -> Result<Self,DataError> {
+        match data {
+            Data::Json   {content} => Self::from_json(content),
+            Data::Binary {content} => match &**content {
+                Binary::Table(table) => Ok(Self::from_binary_table(table)),
+                Binary::Array(array) => {
+                    let column = binary::Column::new("value",array.clone());
+                    let table  = binary::Table {columns:vec![column],row_count:array.len()};
+                    Ok(Self::from_binary_table(&table))
+                }
+                Binary::Image(_) => Err(DataError::InvalidDataType),
+            }
+        }
+    }
+
+    /// Reads the table from an array of JSON objects. The columns are ordered by their first
+    /// appearance, and the fields missing in some rows are treated as missing values.
+    pub fn from_json(json:&serde_json::Value) -> Result<Self,DataError> {
+        let rows      = json.as_array().ok_or(DataError::InvalidDataType)?;
+        let row_count = rows.len();
+        let mut names : Vec<String>           = default();
+        let mut index : HashMap<String,usize> = default();
+        let mut cells : Vec<Vec<Value>>       = default();
+        for (row_index,row) in rows.iter().enumerate() {
+            let row = row.as_object().ok_or(DataError::InvalidDataType)?;
+            for (name,value) in row {
+                let column = *index.entry(name.clone()).or_insert_with(|| {
+                    names.push(name.clone());
+                    cells.push(vec![Value::Null;row_count]);
+                    cells.len() - 1
+                });
+                cells[column][row_index] = value.into();
+            }
+        }
+        let columns = names.into_iter().zip(cells).map(|(name,values)| {
+            let column_type = ColumnType::infer(values.iter().map(Value::column_type));
+            let values      = ColumnValues::Values(values);
+            Column {name,column_type,values}
+        }).collect();
+        Ok(Self {columns,row_count})
+    }
+
+    /// Reads the table from the binary columnar format.
+    pub fn from_binary_table(table:&binary::Table) -> Self {
+        let row_count = table.row_count;
+        let columns   = table.columns.iter().map(|column| {
+            let name        = column.name.clone();
+            let column_type = ColumnType::Number;
+            let values      = ColumnValues::Numeric(column.clone());
+            Column {name,column_type,values}
+        }).collect();
+        Self {columns,row_count}
+    }
+}
+
+
+
+// ===============
+// === Sorting ===
+// ===============
+
+/// The order of sorted rows.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+#[allow(missing_docs)]
+pub enum SortOrder {Ascending,Descending}
+
+impl SortOrder {
+    /// The opposite order.
+    pub fn reversed(self) -> Self {
+        match self {
+            Self::Ascending  => Self::Descending,
+            Self::Descending => Self::Ascending,
+        }
+    }
+
+    fn indicator(self) -> &'static str {
+        match self {
+            Self::Ascending  => " ▲",
+            Self::Descending => " ▼",
+        }
+    }
+}
+
+/// The column the table is sorted by. The column is identified by name, so the sorting is kept
+/// when new data with the same columns arrive.
+#[derive(Clone,Debug,Eq,PartialEq)]
+#[allow(missing_docs)]
+pub struct Sorting {
+    pub column : String,
+    pub order  : SortOrder,
+}
+
+
+
+// ==================
+// === TableState ===
+// ==================
+
+/// The table data together with the state of user interactions: sorting and column widths.
+#[derive(Clone,Debug,Default)]
+pub struct TableState {
+    data      : TableData,
+    sorting   : Option<Sorting>,
+    row_order : Vec<usize>,
+    widths    : HashMap<String,f32>,
+    resizing  : Option<Resizing>,
+    rendered  : Option<Range<usize>>,
+}
+
+#[derive(Clone,Copy,Debug)]
+struct Resizing {
+    column      : usize,
+    start_x     : i32,
+    start_width : f32,
+}
+
+impl TableState {
+    /// Replaces the displayed data. The sorting and widths of the columns present in the new data
+    /// are kept.
+    pub fn set_data(&mut self, data:TableData) {
+        self.data      = data;
+        self.row_order = (0..self.data.row_count).collect();
+        self.rendered  = None;
+        self.resizing  = None;
+        let has_column = |name:&String| self.data.columns.iter().any(|c| &c.name == name);
+        if !self.sorting.as_ref().map_or(false, |sorting| has_column(&sorting.column)) {
+            self.sorting = None;
+        }
+        self.apply_sorting();
+    }
+
+    /// The displayed data.
+    pub fn data(&self) -> &TableData {
+        &self.data
+    }
+
+    /// The current sorting, if any.
+    pub fn sorting(&self) -> Option<&Sorting> {
+        self.sorting.as_ref()
+    }
+
+    /// Sorts the rows by the given column. If the rows are already sorted by it, the order is
+    /// reversed.
+    pub fn sort_by_column(&mut self, column:usize) {
+        if let Some(name) = self.data.columns.get(column).map(|c| c.name.clone()) {
+            let order = match &self.sorting {
+                Some(sorting) if sorting.column == name => sorting.order.reversed(),
+                _                                       => SortOrder::Ascending,
+            };
+            self.sorting  = Some(Sorting {column:name,order});
+            self.rendered = None;
+            self.apply_sorting();
+        }
+    }
+
+    fn apply_sorting(&mut self) {
+        self.row_order = (0..self.data.row_count).collect();
+        if let Some(sorting) = &self.sorting {
+            let column = self.data.columns.iter().find(|c| c.name == sorting.column);
+            if let Some(column) = column {
+                let order = sorting.order;
+                self.row_order.sort_by(|a,b| column.values.compare_rows(*a,*b,order));
+            }
+        }
+    }
+
+    /// The index of the data row displayed at the given position.
+    pub fn row_at(&self, position:usize) -> Option<usize> {
+        self.row_order.get(position).copied()
+    }
+
+    /// The width of the given column in pixels.
+    pub fn column_width(&self, column:usize) -> f32 {
+        let name = self.data.columns.get(column).map(|c| &c.name);
+        name.and_then(|name| self.widths.get(name)).copied().unwrap_or(DEFAULT_COLUMN_WIDTH)
+    }
+
+    /// Sets the width of the given column, but not below `MIN_COLUMN_WIDTH`.
+    pub fn resize_column(&mut self, column:usize, width:f32) {
+        if let Some(name) = self.data.columns.get(column).map(|c| c.name.clone()) {
+            self.widths.insert(name,width.max(MIN_COLUMN_WIDTH));
+            self.rendered = None;
+        }
+    }
+
+    /// The height of all the rows together.
+    pub fn content_height(&self) -> f32 {
+        self.data.row_count as f32 * ROW_HEIGHT
+    }
+
+    /// The height of the element giving the table its scrollbar. It is the height of all the rows,
+    /// but not more than `MAX_BODY_HEIGHT`.
+    pub fn body_height(&self) -> f32 {
+        self.content_height().min(MAX_BODY_HEIGHT)
+    }
+
+    /// Converts the scroll position of the table element to the position within all the rows. They
+    /// differ only if the rows are taller than `MAX_BODY_HEIGHT`. Then the scroll position is
+    /// scaled, so scrolling to the end of the element shows the last rows.
+    pub fn content_scroll(&self, scroll_top:f32, viewport_height:f32) -> f32 {
+        let max_scroll         = (self.body_height() - viewport_height).max(0.0);
+        let max_content_scroll = (self.content_height() - viewport_height).max(0.0);
+        let scroll_top         = scroll_top.max(0.0).min(max_scroll);
+        if max_scroll > 0.0 && max_content_scroll > max_scroll {
+            scroll_top * max_content_scroll / max_scroll
+        } else {
+            scroll_top
+        }
+    }
+
+    /// The positions of the rows which should be present in the DOM when the viewport of the given
+    /// height is scrolled by `scroll_top` pixels within all the rows (see `content_scroll`).
+    pub fn visible_rows(&self, scroll_top:f32, viewport_height:f32) -> Range<usize> {
+        let row_count = self.data.row_count;
+        let first     = (scroll_top.max(0.0) / ROW_HEIGHT).floor() as usize;
+        let last      = ((scroll_top.max(0.0) + viewport_height) / ROW_HEIGHT).ceil() as usize;
+        let first     = first.saturating_sub(OVERSCAN_ROWS).min(row_count);
+        let last      = (last + OVERSCAN_ROWS).min(row_count);
+        first..last
+    }
+}
+
+
+
+// ==================
+// === TableModel ===
+// ==================
+
+type EventClosure = Closure<dyn FnMut(web_sys::Event)>;
+
+/// The DOM elements of the table. The rows are placed in the `body`, which is as tall as all rows
+/// together, so the scrollbar of the `root` reflects the whole table.
+#[derive(Debug)]
+struct TableView {
+    logger : Logger,
+    root   : web::HtmlDivElement,
+    header : web::HtmlDivElement,
+    body   : web::HtmlDivElement,
+    rows   : web::HtmlDivElement,
+}
+
+impl TableView {
+    fn new(logger:&Logger, root:&web::HtmlDivElement) -> Self {
+        let logger = logger.clone_ref();
+        let root   = root.clone();
+        let header = web::create_div();
+        let body   = web::create_div();
+        let rows   = web::create_div();
+        header.set_style_or_warn("position"        ,"sticky"              ,&logger);
+        header.set_style_or_warn("top"             ,"0"                   ,&logger);
+        header.set_style_or_warn("display"         ,"flex"                ,&logger);
+        header.set_style_or_warn("height"          ,px(HEADER_HEIGHT)     ,&logger);
+        header.set_style_or_warn("background-color","rgba(40,40,40,0.95)" ,&logger);
+        header.set_style_or_warn("z-index"         ,"1"                   ,&logger);
+        header.set_style_or_warn("user-select"     ,"none"                ,&logger);
+        body.set_style_or_warn  ("position"        ,"relative"            ,&logger);
+        rows.set_style_or_warn  ("position"        ,"absolute"            ,&logger);
+        rows.set_style_or_warn  ("left"            ,"0"                   ,&logger);
+        root.append_or_warn(&header,&logger);
+        root.append_or_warn(&body,&logger);
+        body.append_or_warn(&rows,&logger);
+        Self {logger,root,header,body,rows}
+    }
+
+    fn render(&self, state:&mut TableState) {
+        self.render_header(state);
+        self.render_rows(state);
+    }
+
+    fn render_header(&self, state:&TableState) {
+        let logger = &self.logger;
+        self.header.set_inner_html("");
+        for (index,column) in state.data.columns.iter().enumerate() {
+            let cell   = self.cell(state.column_width(index));
+            let name   = web::create_div();
+            let info   = web::create_div();
+            let handle = web::create_div();
+            let order  = state.sorting.as_ref().filter(|sorting| sorting.column == column.name);
+            let order  = order.map_or("", |sorting| sorting.order.indicator());
+            name.set_inner_text(&format!("{}{}",column.name,order));
+            info.set_inner_text(&column.column_type.to_string());
+            name.set_style_or_warn  ("font-weight"  ,"bold"                  ,logger);
+            name.set_style_or_warn  ("overflow"     ,"hidden"                ,logger);
+            name.set_style_or_warn  ("text-overflow","ellipsis"              ,logger);
+            info.set_style_or_warn  ("color"        ,"rgba(255,255,255,0.4)" ,logger);
+            handle.set_style_or_warn("position"     ,"absolute"              ,logger);
+            handle.set_style_or_warn("top"          ,"0"                     ,logger);
+            handle.set_style_or_warn("right"        ,"0"                     ,logger);
+            handle.set_style_or_warn("width"        ,"6px"                   ,logger);
+            handle.set_style_or_warn("height"       ,"100%"                  ,logger);
+            handle.set_style_or_warn("cursor"       ,"col-resize"            ,logger);
+            cell.set_style_or_warn  ("position"     ,"relative"              ,logger);
+            cell.set_style_or_warn  ("cursor"       ,"pointer"               ,logger);
+            cell.set_style_or_warn  ("white-space"  ,"nowrap"                ,logger);
+            set_attribute(&cell,COLUMN_ATTRIBUTE,index,logger);
+            set_attribute(&handle,RESIZE_ATTRIBUTE,index,logger);
+            cell.append_or_warn(&name,logger);
+            cell.append_or_warn(&info,logger);
+            cell.append_or_warn(&handle,logger);
+            self.header.append_or_warn(&cell,logger);
+        }
+    }
+
+    fn render_rows(&self, state:&mut TableState) {
+        let logger          = &self.logger;
+        let scroll_top      = self.root.scroll_top() as f32;
+        let viewport_height = self.root.client_height() as f32 - HEADER_HEIGHT;
+        let content_scroll  = state.content_scroll(scroll_top,viewport_height);
+        let visible         = state.visible_rows(content_scroll,viewport_height);
+        // The rows are placed relative to the scroll position, as it may be scaled.
+        let rows_top        = scroll_top + visible.start as f32 * ROW_HEIGHT - content_scroll;
+        self.rows.set_style_or_warn("top",px(rows_top),logger);
+        if state.rendered.as_ref() == Some(&visible) { return }
+        self.body.set_style_or_warn("height",px(state.body_height()),logger);
+        self.rows.set_inner_html("");
+        for position in visible.clone() {
+            let row_view = web::create_div();
+            row_view.set_style_or_warn("display","flex"            ,logger);
+            row_view.set_style_or_warn("height" ,px(ROW_HEIGHT)    ,logger);
+            if let Some(row) = state.row_at(position) {
+                for (index,column) in state.data.columns.iter().enumerate() {
+                    let cell = self.cell(state.column_width(index));
+                    cell.set_inner_text(&column.values.get(row).to_string());
+                    row_view.append_or_warn(&cell,logger);
+                }
+            }
+            self.rows.append_or_warn(&row_view,logger);
+        }
+        state.rendered = Some(visible);
+    }
+
+    fn cell(&self, width:f32) -> web::HtmlDivElement {
+        let logger = &self.logger;
+        let cell   = web::create_div();
+        cell.set_style_or_warn("flex"         ,format!("0 0 {}",px(width)),logger);
+        cell.set_style_or_warn("overflow"     ,"hidden"                   ,logger);
+        cell.set_style_or_warn("text-overflow","ellipsis"                 ,logger);
+        cell.set_style_or_warn("padding-left" ,"6px"                      ,logger);
+        cell.set_style_or_warn("box-sizing"   ,"border-box"               ,logger);
+        cell
+    }
+}
+
+fn px(value:f32) -> String {
+    format!("{}px",value)
+}
+
+fn set_attribute(element:&web::HtmlElement, name:&str, index:usize, logger:&Logger) {
+    if element.set_attribute(name,&index.to_string()).is_err() {
+        logger.warning(|| format!("Failed to set attribute {}.",name));
+    }
+}
+
+/// The index stored in the given attribute of the event target or its closest ancestor.
+fn target_index(event:&web_sys::Event, attribute:&str) -> Option<usize> {
+    let target  = event.target()?.dyn_into::<web::Element>().ok()?;
+    let element = target.closest(&format!("[{}]",attribute)).ok()??;
+    element.get_attribute(attribute)?.parse().ok()
+}
+
+#[derive(Clone,CloneRef,Debug)]
+#[allow(missing_docs)]
+pub struct TableModel {
+    logger    : Logger,
+    dom       : DomSymbol,
+    view      : Rc<TableView>,
+    state     : Rc<RefCell<TableState>>,
+    size      : Rc<Cell<Vector2>>,
+    listeners : Rc<RefCell<Vec<(&'static str,EventClosure)>>>,
+}
+
+impl TableModel {
+    /// Constructor.
+    fn new(scene:&Scene) -> Self {
+        let logger    = Logger::new("Table");
+        let div       = web::create_div();
+        let dom       = DomSymbol::new(&div);
+        let size      = Rc::new(Cell::new(Vector2(200.0,200.0)));
+        let view      = Rc::new(TableView::new(&logger,dom.dom()));
+        let state     = default();
+        let listeners = default();
+
+        dom.dom().set_style_or_warn("overflow"        ,"auto"                 ,&logger);
+        dom.dom().set_style_or_warn("font-family"     ,"dejavuSansMono"       ,&logger);
+        dom.dom().set_style_or_warn("font-size"       ,"11px"                 ,&logger);
+        dom.dom().set_style_or_warn("line-height"     ,px(ROW_HEIGHT)         ,&logger);
+        dom.dom().set_style_or_warn("color"           ,"rgba(255,255,255,0.7)",&logger);
+        dom.dom().set_style_or_warn("pointer-events"  ,"auto"                 ,&logger);
+
+        scene.dom.layers.main.manage(&dom);
+        TableModel{logger,dom,view,state,size,listeners}.init()
+    }
+
+    fn init(self) -> Self {
+        self.init_listeners();
+        self.reload_style();
+        self
+    }
+
+    /// Registers the DOM event handlers. The handlers refer to the state weakly, as they are owned
+    /// by the model.
+    fn init_listeners(&self) {
+        let view  = self.view.clone_ref();
+        let state = Rc::downgrade(&self.state);
+        self.add_listener("scroll", move |_| {
+            if let Some(state) = state.upgrade() {
+                view.render_rows(&mut state.borrow_mut());
+            }
+        });
+        let state = Rc::downgrade(&self.state);
+        self.add_listener("mousedown", move |event| {
+            let column = target_index(&event,RESIZE_ATTRIBUTE);
+            let mouse  = event.dyn_ref::<web::MouseEvent>();
+            if let (Some(state),Some(column),Some(mouse)) = (state.upgrade(),column,mouse) {
+                event.prevent_default();
+                let mut state   = state.borrow_mut();
+                let start_x     = mouse.client_x();
+                let start_width = state.column_width(column);
+                state.resizing  = Some(Resizing {column,start_x,start_width});
+            }
+        });
+        let view  = self.view.clone_ref();
+        let state = Rc::downgrade(&self.state);
+        self.add_listener("mousemove", move |event| {
+            let mouse = event.dyn_ref::<web::MouseEvent>();
+            if let (Some(state),Some(mouse)) = (state.upgrade(),mouse) {
+                let mut state = state.borrow_mut();
+                if let Some(resizing) = state.resizing {
+                    let delta = (mouse.client_x() - resizing.start_x) as f32;
+                    state.resize_column(resizing.column,resizing.start_width + delta);
+                    view.render(&mut state);
+                }
+            }
+        });
+        let state = Rc::downgrade(&self.state);
+        let stop_resizing = move |_| {
+            if let Some(state) = state.upgrade() {
+                state.borrow_mut().resizing = None;
+            }
+        };
+        self.add_listener("mouseup",stop_resizing.clone());
+        self.add_listener("mouseleave",stop_resizing);
+        let view  = self.view.clone_ref();
+        let state = Rc::downgrade(&self.state);
+        self.add_listener("click", move |event| {
+            let is_resize_handle = target_index(&event,RESIZE_ATTRIBUTE).is_some();
+            let column           = target_index(&event,COLUMN_ATTRIBUTE);
+            if let (Some(state),Some(column),false) = (state.upgrade(),column,is_resize_handle) {
+                let mut state = state.borrow_mut();
+                state.sort_by_column(column);
+                view.render(&mut state);
+            }
+        });
+    }
+
+    fn add_listener(&self, event_type:&'static str, f:impl FnMut(web_sys::Event) + 'static) {
+        let closure : EventClosure = Closure::wrap(Box::new(f));
+        let callback = closure.as_ref().unchecked_ref();
+        if self.dom.dom().add_event_listener_with_callback(event_type,callback).is_err() {
+            self.logger.warning(|| format!("Couldn't add {} event listener.",event_type));
+        }
+        self.listeners.borrow_mut().push((event_type,closure));
+    }
+
+    fn set_size(&self, size:Vector2) {
+        self.size.set(size);
+        self.reload_style();
+        let mut state = self.state.borrow_mut();
+        state.rendered = None;
+        self.view.render_rows(&mut state);
+    }
+
+    fn receive_data(&self, data:&Data) -> Result<(),DataError> {
+        let data      = TableData::from_data(data)?;
+        let mut state = self.state.borrow_mut();
+        state.set_data(data);
+        self.view.render(&mut state);
+        Ok(())
+    }
+
+    fn reload_style(&self) {
+        self.dom.set_size(self.size.get());
+    }
+}
+
+
+
+// =============
+// === Table ===
+// =============
+
+/// Visualization displaying the data as a table with sortable and resizable columns.
+#[derive(Debug,Shrinkwrap)]
+#[allow(missing_docs)]
+pub struct Table {
+    #[shrinkwrap(main_field)]
+    model   : TableModel,
+    frp     : visualization::instance::Frp,
+    network : frp::Network,
+}
+
+impl Table {
+    /// Definition of this visualization.
+    pub fn definition() -> Definition {
+        let path = Path::builtin("Table Visualization (native)");
+        Definition::new(
+            Signature::new_for_any_type(path,Format::Json),
+            |scene| { Ok(Self::new(scene).into()) }
+        )
+    }
+
+    /// Definition of this visualization receiving the data in the binary columnar format, which
+    /// is much faster to transfer and decode for big tables.
+    pub fn columnar_definition() -> Definition {
+        let path         = Path::builtin("Table Visualization (native, columnar)");
+        let preprocessor = Preprocessor::new(COLUMNAR_PREPROCESSOR_CODE);
+        let signature    = Signature::new_for_any_type(path,Format::Binary);
+        Definition::new(
+            signature.with_preprocessor(preprocessor),
+            |scene| { Ok(Self::new(scene).into()) }
+        )
+    }
+
+    /// Constructor.
+    pub fn new(scene:&Scene) -> Self {
+        let network = default();
+        let frp     = visualization::instance::Frp::new(&network);
+        let model   = TableModel::new(scene);
+        Self {model,frp,network} . init()
+    }
+
+    fn init(self) -> Self {
+        let network = &self.network;
+        let model   = self.model.clone_ref();
+        let frp     = self.frp.clone_ref();
+        frp::extend! { network
+            eval frp.set_size  ((size) model.set_size(*size));
+            eval frp.send_data ([frp](data) {
+                if let Err(e) = model.receive_data(data) {
+                    frp.data_receive_error.emit(Some(e));
+                }
+             });
+        }
+        self
+    }
+}
+
+impl From<Table> for Instance {
+    fn from(t:Table) -> Self {
+        Self::new(&t,&t.frp,&t.network)
+    }
+}
+
+impl display::Object for Table {
+    fn display_object(&self) -> &display::object::Instance {
+        &self.dom.display_object()
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use binary::NumericArray;
+
+    fn json_table() -> TableData {
+        let json = serde_json::json!([
+            {"name":"b", "age":3},
+            {"name":"a", "age":null, "alive":true},
+            {"name":"c", "age":1},
+            {"name":7,   "age":2},
+        ]);
+        TableData::from_json(&json).unwrap()
+    }
+
+    fn column_order(state:&TableState, column:usize) -> Vec<Value> {
+        let column = &state.data().columns[column];
+        let rows   = (0..state.data().row_count).filter_map(|pos| state.row_at(pos));
+        rows.map(|row| column.values.get(row)).collect()
+    }
+
+    #[test]
+    fn reading_json_rows() {
+        let table = json_table();
+        let names = table.columns.iter().map(|c| c.name.as_str()).collect_vec();
+        let types = table.columns.iter().map(|c| c.column_type).collect_vec();
+        assert_eq!(table.row_count,4);
+        assert_eq!(names,vec!["name","age","alive"]);
+        assert_eq!(types,vec![ColumnType::Mixed,ColumnType::Number,ColumnType::Boolean]);
+        assert_eq!(table.columns[2].values.get(0),Value::Null);
+        assert_eq!(table.columns[2].values.get(1),Value::Boolean(true));
+
+        assert!(TableData::from_json(&serde_json::json!({"a":1})).is_err());
+        assert!(TableData::from_json(&serde_json::json!([1,2])).is_err());
+    }
+
+    #[test]
+    fn reading_columnar_table() {
+        let mut column = binary::Column::new("x",NumericArray::I32(vec![5,6]));
+        column.validity = Some(vec![true,false]);
+        let table = binary::Table {columns:vec![column],row_count:2};
+        let data  = Data::from(Binary::Table(table));
+        let table = TableData::from_data(&data).unwrap();
+        assert_eq!(table.row_count,2);
+        assert_eq!(table.columns[0].column_type,ColumnType::Number);
+        assert_eq!(table.columns[0].values.get(0),Value::Number(5.0));
+        assert_eq!(table.columns[0].values.get(1),Value::Null);
+    }
+
+    #[test]
+    fn sorting_rows() {
+        let mut state = TableState::default();
+        state.set_data(json_table());
+        let number = |n:f64| Value::Number(n);
+        let text   = |s:&str| Value::Text(s.into());
+
+        state.sort_by_column(1);
+        assert_eq!(column_order(&state,1),vec![number(1.0),number(2.0),number(3.0),Value::Null]);
+        state.sort_by_column(1);
+        assert_eq!(state.sorting().unwrap().order,SortOrder::Descending);
+        assert_eq!(column_order(&state,1),vec![number(3.0),number(2.0),number(1.0),Value::Null]);
+
+        state.sort_by_column(0);
+        let expected = vec![number(7.0),text("a"),text("b"),text("c")];
+        assert_eq!(column_order(&state,0),expected);
+
+        // The sorting is kept when new data arrive.
+        state.set_data(json_table());
+        assert_eq!(column_order(&state,0),expected);
+    }
+
+    #[test]
+    fn resizing_columns() {
+        let mut state = TableState::default();
+        state.set_data(json_table());
+        assert_eq!(state.column_width(0),DEFAULT_COLUMN_WIDTH);
+        state.resize_column(0,200.0);
+        state.resize_column(1,1.0);
+        assert_eq!(state.column_width(0),200.0);
+        assert_eq!(state.column_width(1),MIN_COLUMN_WIDTH);
+    }
+
+    #[test]
+    fn virtualizing_rows() {
+        let values    = NumericArray::F64((0..1_000_000).map(|i| i as f64).collect());
+        let column    = binary::Column::new("value",values);
+        let table     = binary::Table {columns:vec![column],row_count:1_000_000};
+        let mut state = TableState::default();
+        state.set_data(TableData::from_binary_table(&table));
+
+        let rows_in_view = 10;
+        let height       = rows_in_view as f32 * ROW_HEIGHT;
+        assert_eq!(state.visible_rows(0.0,height),0..rows_in_view+OVERSCAN_ROWS);
+        let scroll_top = 500_000.0 * ROW_HEIGHT;
+        let expected   = 500_000-OVERSCAN_ROWS..500_000+rows_in_view+OVERSCAN_ROWS;
+        assert_eq!(state.visible_rows(scroll_top,height),expected);
+        assert_eq!(state.visible_rows(1e12,height),1_000_000..1_000_000);
+    }
+
+    #[test]
+    fn scaling_scroll_of_big_tables() {
+        let values    = NumericArray::F64((0..1_000_000).map(|i| i as f64).collect());
+        let column    = binary::Column::new("value",values);
+        let table     = binary::Table {columns:vec![column],row_count:1_000_000};
+        let mut state = TableState::default();
+        state.set_data(TableData::from_binary_table(&table));
+
+        let height = 10.0 * ROW_HEIGHT;
+        assert_eq!(state.body_height(),MAX_BODY_HEIGHT);
+        assert_eq!(state.content_scroll(0.0,height),0.0);
+        let max_scroll = MAX_BODY_HEIGHT - height;
+        let last_rows  = state.visible_rows(state.content_scroll(max_scroll,height),height);
+        assert_eq!(last_rows.end,1_000_000);
+        let end_scroll = state.content_scroll(2.0 * max_scroll,height);
+        assert!((end_scroll - (state.content_height() - height)).abs() < ROW_HEIGHT);
+
+        let mut small = TableState::default();
+        small.set_data(json_table());
+        assert_eq!(small.body_height(),small.content_height());
+        assert_eq!(small.content_scroll(15.0,ROW_HEIGHT),15.0);
+    }
+}
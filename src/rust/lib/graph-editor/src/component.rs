@@ -1,11 +1,13 @@
 //! Root module for graph component definitions.
 
+pub mod comment_editor;
 pub mod edge;
 pub mod edge2;
 pub mod cursor;
 pub mod node;
 pub mod visualization;
 
+pub use comment_editor::CommentEditor;
 pub use cursor::Cursor;
 pub use edge::Edge;
 pub use node::Node;
@@ -0,0 +1,128 @@
+//! Definition of the inline editor of node documentation comments.
+
+use crate::prelude::*;
+
+use crate::NodeId;
+
+use enso_frp as frp;
+use ensogl::data::color;
+use ensogl::display::shape::text::text_field::TextField;
+use ensogl::display::shape::text::text_field::TextFieldProperties;
+use ensogl::display::world::World;
+use ensogl::display;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+const TEXT_SIZE  : f32 = 12.0;
+const FIELD_SIZE : f32 = 400.0;
+const MAX_LINES  : usize = 10;
+
+
+
+// =====================
+// === CommentEditor ===
+// =====================
+
+/// A multi-line text field placed over the comment of the edited node. Pressing enter starts a new
+/// line, and the `commit` method commits the comment, which is then emitted by the `committed`
+/// stream. The `is_editing` sampler tells whether any comment is being edited.
+#[derive(Clone,CloneRef,Debug)]
+#[allow(missing_docs)]
+pub struct CommentEditor {
+    pub display_object : display::object::Instance,
+    pub committed      : frp::Source<(NodeId,String)>,
+    pub is_editing     : frp::Sampler<bool>,
+    editing            : frp::Source<bool>,
+    text_field         : TextField,
+    edited_node        : Rc<Cell<Option<NodeId>>>,
+}
+
+impl CommentEditor {
+    /// Constructor. The `committed` and `is_editing` streams are created in the given network.
+    pub fn new(world:&World, network:&frp::Network) -> Self {
+        let logger         = Logger::new("CommentEditor");
+        let scene          = world.scene();
+        let display_object = display::object::Instance::new(&logger);
+        let properties     = TextFieldProperties {
+            font       : scene.fonts.get_or_load_embedded_font("DejaVuSansMono").unwrap(),
+            text_size  : TEXT_SIZE,
+            base_color : color::Rgba::new(1.0,1.0,1.0,0.7),
+            size       : Vector2::new(FIELD_SIZE,TEXT_SIZE * MAX_LINES as f32),
+        };
+        let text_field  = TextField::new(world,properties);
+        let edited_node = default();
+        frp::extend! { network
+            def committed  = source();
+            def editing    = source();
+            def is_editing = editing.sampler();
+        }
+        Self {display_object,committed,is_editing,editing,text_field,edited_node}
+    }
+
+    /// The node whose comment is being edited, if any.
+    pub fn edited_node(&self) -> Option<NodeId> {
+        self.edited_node.get()
+    }
+
+    /// Shows the editor at the given position, filled with the current comment of the node.
+    pub fn edit(&mut self, node_id:NodeId, position:Vector2<f32>, comment:&str) {
+        self.edited_node.set(Some(node_id));
+        self.display_object.set_position(Vector3::new(position.x,position.y,0.0));
+        self.display_object.add_child(&self.text_field.display_object());
+        self.text_field.set_content(comment);
+        self.text_field.set_focus();
+        self.editing.emit(true);
+    }
+
+    /// Hides the editor and emits the edited comment by the `committed` stream.
+    pub fn commit(&mut self) {
+        if let Some(node_id) = self.edited_node.get() {
+            let comment = normalize(&self.text_field.get_content());
+            self.committed.emit((node_id,comment));
+        }
+        self.cancel();
+    }
+
+    /// Hides the editor without committing the edited comment.
+    pub fn cancel(&mut self) {
+        if self.edited_node.get().is_some() {
+            self.edited_node.set(None);
+            self.text_field.clear_content();
+            self.display_object.remove_child(&self.text_field.display_object());
+            self.editing.emit(false);
+        }
+    }
+}
+
+impl display::Object for CommentEditor {
+    fn display_object(&self) -> &display::object::Instance {
+        &self.display_object
+    }
+}
+
+/// Trims the line ends and drops the empty lines of the edited text.
+fn normalize(text:&str) -> String {
+    text.lines().map(|line| line.trim_end()).filter(|line| !line.is_empty()).join("\n")
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizing_comment() {
+        assert_eq!(normalize("Computes the sum.  \n\n"), "Computes the sum.");
+        assert_eq!(normalize("First.\n  \nSecond."),     "First.\nSecond.");
+        assert_eq!(normalize(""),                        "");
+    }
+}
@@ -26,6 +26,7 @@ use std::num::NonZeroU32;
 use super::edge;
 use crate::component::visualization;
 use crate::component::node::port::output::OutputPorts;
+use crate::component::node::port::label::Shape as CommentLabel;
 
 
 
@@ -125,6 +126,28 @@ pub mod drag_area {
 
 
 
+// ============
+// === Kind ===
+// ============
+
+/// The way the node is displayed.
+#[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
+pub enum Kind {
+    /// The node displays its expression.
+    Expression,
+    /// The node displays its visualization instead of the expression. When the user edits the
+    /// data in the visualization, the node's expression is regenerated from it.
+    Widget,
+}
+
+impl Default for Kind {
+    fn default() -> Self {
+        Self::Expression
+    }
+}
+
+
+
 // ===========
 // === Frp ===
 // ===========
@@ -136,7 +159,9 @@ pub struct InputEvents {
     pub select            : frp::Source,
     pub deselect          : frp::Source,
     pub set_expression    : frp::Source<Expression>,
+    pub set_comment       : frp::Source<String>,
     pub set_visualization : frp::Source<Option<visualization::Instance>>,
+    pub set_kind          : frp::Source<Kind>,
 }
 
 impl InputEvents {
@@ -145,9 +170,11 @@ impl InputEvents {
             def select            = source();
             def deselect          = source();
             def set_expression    = source();
+            def set_comment       = source();
             def set_visualization = source();
+            def set_kind          = source();
         }
-        Self {select,deselect,set_expression,set_visualization}
+        Self {select,deselect,set_expression,set_comment,set_visualization,set_kind}
     }
 }
 
@@ -205,14 +232,18 @@ pub struct NodeModel {
     pub main_area      : component::ShapeView<shape::Shape>,
     pub drag_area      : component::ShapeView<drag_area::Shape>,
     pub ports          : port::Manager,
+    pub comment_label  : component::ShapeView<CommentLabel>,
     pub visualization  : visualization::Container,
     pub output_ports   : OutputPorts,
+    kind               : Rc<Cell<Kind>>,
+    comment            : Rc<RefCell<String>>,
 }
 
 pub const CORNER_RADIUS : f32 = 14.0;
 pub const NODE_HEIGHT   : f32 = 28.0;
 pub const TEXT_OFF      : f32 = 10.0;
 pub const SHADOW_SIZE   : f32 = 10.0;
+pub const COMMENT_OFF   : f32 = 8.0;
 
 
 impl NodeModel {
@@ -257,6 +288,15 @@ impl NodeModel {
         });
         display_object.add_child(&ports);
 
+        let comment_logger = Logger::sub(&logger,"comment");
+        let comment_label  = component::ShapeView::<CommentLabel>::new(&comment_logger,&scene);
+        comment_label.shape.label.set_font_color(color::Rgba::new(1.0,1.0,1.0,0.4));
+        comment_label.mod_position(|p| {
+            p.x = TEXT_OFF;
+            p.y = NODE_HEIGHT + COMMENT_OFF;
+        });
+        display_object.add_child(&comment_label);
+
         let frp = Frp{input};
 
 
@@ -265,8 +305,10 @@ impl NodeModel {
         display_object.add_child(&output_ports);
 
 
+        let kind    = default();
+        let comment = default();
         Self {scene,display_object,logger,frp,main_area,drag_area,output_ports,ports
-             ,visualization} . init()
+             ,comment_label,visualization,kind,comment} . init()
     }
 
     fn init(self) -> Self {
@@ -302,9 +344,40 @@ impl NodeModel {
         self.output_ports.mod_position(|t| t.y = height/2.0);
     }
 
+    /// The documentation comment displayed above the node. Empty if the node has none.
+    pub fn comment(&self) -> String {
+        self.comment.borrow().clone()
+    }
+
+    /// The label shows a single line, so multi-line comments are joined with spaces.
+    fn set_comment(&self, comment:&str) {
+        *self.comment.borrow_mut() = comment.to_string();
+        let text = comment.lines().collect_vec().join(" ");
+        self.comment_label.shape.label.set_text(&text);
+    }
+
     pub fn visualization(&self) -> &visualization::Container {
         &self.visualization
     }
+
+    /// The way the node is displayed.
+    pub fn kind(&self) -> Kind {
+        self.kind.get()
+    }
+
+    /// Widgets hide the expression and always show the visualization, which is switched to the
+    /// widget mode.
+    fn set_kind(&self, kind:Kind) {
+        self.kind.set(kind);
+        let is_widget = kind == Kind::Widget;
+        if is_widget {
+            self.display_object.remove_child(&self.ports);
+            self.visualization.frp.set_visibility.emit(true);
+        } else {
+            self.display_object.add_child(&self.ports);
+        }
+        self.visualization.frp.set_widget_mode.emit(is_widget);
+    }
 }
 
 impl Node {
@@ -321,6 +394,8 @@ impl Node {
             eval_ inputs.deselect (selection.set_target_value(0.0));
 
             eval inputs.set_expression ((expr) model.set_expression(expr));
+            eval inputs.set_comment    ((comment) model.set_comment(comment));
+            eval inputs.set_kind       ((kind) model.set_kind(*kind));
 
             eval inputs.set_visualization ((content)
                 model.visualization.frp.set_visualization.emit(content)
@@ -10,6 +10,7 @@
 
 use crate::prelude::*;
 
+use crate::component::node::port;
 use crate::data::EnsoCode;
 use crate::frp;
 use crate::visualization;
@@ -31,8 +32,11 @@ use ensogl::gui::component;
 // === Constants ===
 // =================
 
-const DEFAULT_SIZE  : (f32,f32) = (200.0,200.0);
-const CORNER_RADIUS : f32       = super::super::node::CORNER_RADIUS;
+const DEFAULT_SIZE       : (f32,f32) = (200.0,200.0);
+const CORNER_RADIUS      : f32       = super::super::node::CORNER_RADIUS;
+const RESIZE_HANDLE_SIZE : f32       = 12.0;
+const MIN_PANEL_SIZE     : f32       = 50.0;
+const ERROR_MARGIN       : f32       = 10.0;
 
 
 
@@ -105,6 +109,63 @@ pub mod overlay {
     }
 }
 
+/// Handle in the bottom right corner of a container shown as a panel. Dragging it resizes the
+/// panel.
+pub mod resize_handle {
+    use super::*;
+
+    ensogl::define_shape_system! {
+        (selected:f32) {
+            let width  : Var<Distance<Pixels>> = "input_size.x".into();
+            let height : Var<Distance<Pixels>> = "input_size.y".into();
+            let color  = color::Rgba::new(1.0,1.0,1.0,0.3);
+            let handle = Rect((&width,&height)).corners_radius(2.px());
+            let handle = handle.fill(color);
+            handle.into()
+        }
+    }
+}
+
+
+
+// ===================
+// === PanelLayout ===
+// ===================
+
+/// Placement of a container shown as a panel on the scene, detached from its node. The panel
+/// keeps displaying the data of the node, but it is moved and resized independently of it.
+#[derive(Clone,Copy,Debug,Default,PartialEq)]
+pub struct PanelLayout {
+    /// The position of the panel's center in the scene coordinates.
+    pub position : Vector2,
+    /// The size of the panel.
+    pub size : Vector2,
+}
+
+impl PanelLayout {
+    /// Constructor.
+    pub fn new(position:Vector2, size:Vector2) -> Self {
+        Self {position,size}
+    }
+
+    /// The layout moved by the given offset.
+    pub fn moved_by(self, offset:Vector2) -> Self {
+        let position = self.position + offset;
+        Self {position,..self}
+    }
+
+    /// The layout with its bottom right corner moved by the given offset, while the top left
+    /// corner stays in place. Neither dimension gets below `MIN_PANEL_SIZE`.
+    pub fn resized_by(self, offset:Vector2) -> Self {
+        let width    = (self.size.x + offset.x).max(MIN_PANEL_SIZE);
+        let height   = (self.size.y - offset.y).max(MIN_PANEL_SIZE);
+        let size     = Vector2(width,height);
+        let growth   = size - self.size;
+        let position = self.position + Vector2(growth.x,-growth.y) / 2.0;
+        Self {position,size}
+    }
+}
+
 
 
 // ===========
@@ -119,17 +180,27 @@ pub struct Frp {
     pub toggle_visibility  : frp::Source,
     pub set_visualization  : frp::Source<Option<visualization::Instance>>,
     pub set_data           : frp::Source<visualization::Data>,
+    pub set_data_error     : frp::Source<Option<visualization::DataError>>,
+    pub set_widget_mode    : frp::Source<bool>,
     pub select             : frp::Source,
     pub deselect           : frp::Source,
     pub set_size           : frp::Source<Vector2>,
+    /// Show the container as a panel with the given layout, or attach it back to its node.
+    pub set_panel_layout   : frp::Source<Option<PanelLayout>>,
     pub enable_fullscreen  : frp::Source,
     pub disable_fullscreen : frp::Source,
     pub clicked            : frp::Stream,
     pub preprocessor       : frp::Stream<EnsoCode>,
+    /// The data entered by the user into the visualization displayed as a widget.
+    pub widget_data        : frp::Stream<serde_json::Value>,
+    /// The layout of the panel, emitted when the user finishes moving or resizing it.
+    pub panel_layout       : frp::Stream<Option<PanelLayout>>,
     on_click               : frp::Source,
     scene_shape            : frp::Sampler<scene::Shape>,
     size                   : frp::Sampler<Vector2>,
     preprocessor_select    : frp::Source<EnsoCode>,
+    widget_data_select     : frp::Source<serde_json::Value>,
+    panel_layout_select    : frp::Source<Option<PanelLayout>>,
 }
 
 impl Frp {
@@ -139,21 +210,29 @@ impl Frp {
             toggle_visibility   <- source();
             set_visualization   <- source();
             set_data            <- source();
+            set_data_error      <- source();
+            set_widget_mode     <- source();
             select              <- source();
             deselect            <- source();
             on_click            <- source();
             set_size            <- source();
+            set_panel_layout    <- source();
             enable_fullscreen   <- source();
             disable_fullscreen  <- source();
             preprocessor_select <- source();
+            widget_data_select  <- source();
+            panel_layout_select <- source();
             size                <- set_size.sampler();
             let clicked          = on_click.clone_ref().into();
             let preprocessor     = preprocessor_select.clone_ref().into();
+            let widget_data      = widget_data_select.clone_ref().into();
+            let panel_layout     = panel_layout_select.clone_ref().into();
         };
         let scene_shape = scene.shape().clone_ref();
-        Self {set_visibility,set_visualization,toggle_visibility,set_data,select,deselect,
-              clicked,set_size,on_click,enable_fullscreen,disable_fullscreen,scene_shape,size,
-              preprocessor,preprocessor_select}
+        Self {set_visibility,set_visualization,toggle_visibility,set_data,set_data_error,select,
+              deselect,clicked,set_size,on_click,enable_fullscreen,disable_fullscreen,scene_shape,
+              size,preprocessor,preprocessor_select,set_widget_mode,widget_data
+             ,widget_data_select,set_panel_layout,panel_layout,panel_layout_select}
     }
 }
 
@@ -171,6 +250,9 @@ pub struct View {
     display_object : display::object::Instance,
     background     : component::ShapeView<background::Shape>,
     overlay        : component::ShapeView<overlay::Shape>,
+    resize_handle  : component::ShapeView<resize_handle::Shape>,
+    /// Describes the error of the last data update, if it failed.
+    error_label    : component::ShapeView<port::label::Shape>,
 }
 
 impl View {
@@ -180,14 +262,18 @@ impl View {
         let display_object = display::object::Instance::new(&logger);
         let background     = component::ShapeView::<background::Shape>::new(&logger,scene);
         let overlay        = component::ShapeView::<overlay::Shape>::new(&logger,scene);
+        let resize_handle  = component::ShapeView::<resize_handle::Shape>::new(&logger,scene);
+        let error_label    = component::ShapeView::<port::label::Shape>::new(&logger,scene);
         display_object.add_child(&overlay);
         display_object.add_child(&background);
+        display_object.add_child(&resize_handle);
+        display_object.add_child(&error_label);
 
         let shape_system = scene.shapes.shape_system(PhantomData::<background::Shape>);
         scene.views.main.remove(&shape_system.shape_system.symbol);
         scene.views.viz.add(&shape_system.shape_system.symbol);
 
-        Self {logger,display_object,background,overlay}
+        Self {logger,display_object,background,overlay,resize_handle,error_label}
     }
 }
 
@@ -253,6 +339,11 @@ pub struct ContainerModel {
     view            : View,
     fullscreen_view : FullscreenView,
     is_fullscreen   : Rc<Cell<bool>>,
+    is_widget       : Rc<Cell<bool>>,
+    panel           : Cell<Option<PanelLayout>>,
+    /// Relays the widget data of the current visualization while the node is a widget. It is
+    /// replaced together with the visualization, so the previous ones no longer emit the data.
+    widget_network  : RefCell<Option<frp::Network>>,
 }
 
 impl ContainerModel {
@@ -266,8 +357,11 @@ impl ContainerModel {
         let fullscreen_view = FullscreenView::new(&logger,scene);
         let scene           = scene.clone_ref();
         let is_fullscreen   = default();
-        Self {logger,frp,visualization,display_object,view,fullscreen_view,scene,is_fullscreen}
-            . init()
+        let is_widget       = default();
+        let widget_network  = default();
+        let panel           = default();
+        Self {logger,frp,visualization,display_object,view,fullscreen_view,scene,is_fullscreen
+             ,is_widget,panel,widget_network} . init()
     }
 
     fn init(self) -> Self {
@@ -283,6 +377,16 @@ impl ContainerModel {
     pub fn is_visible(&self) -> bool {
         self.view.has_parent()
     }
+
+    /// The size of the container when it is attached to its node.
+    pub fn size(&self) -> Vector2 {
+        self.frp.size.value()
+    }
+
+    /// The layout of the container if it is shown as a panel detached from its node.
+    pub fn panel_layout(&self) -> Option<PanelLayout> {
+        self.panel.get()
+    }
 }
 
 
@@ -291,15 +395,29 @@ impl ContainerModel {
 impl ContainerModel {
     fn set_visibility(&self, visibility:bool) {
         if visibility {
-            self.add_child(&self.view);
+            if self.panel.get().is_some() {
+                self.scene.add_child(&self.view);
+            } else {
+                self.add_child(&self.view);
+            }
             self.scene.add_child(&self.fullscreen_view);
         }
         else {
-            self.remove_child(&self.view);
+            self.view.unset_parent();
             self.scene.remove_child(&self.fullscreen_view);
         }
     }
 
+    /// Panels are placed directly in the scene, so they do not follow the node when it moves.
+    fn set_panel_layout(&self, layout:Option<PanelLayout>) {
+        self.panel.set(layout);
+        let position = layout.map_or_else(zero,|layout| layout.position);
+        self.view.set_position(Vector3(position.x,position.y,0.0));
+        if self.is_visible() {
+            self.set_visibility(true);
+        }
+    }
+
     fn enable_fullscreen(&self) {
         self.is_fullscreen.set(true);
         if let Some(viz) = &*self.visualization.borrow() {
@@ -315,15 +433,40 @@ impl ContainerModel {
         if let Some(visualization) = visualization {
             let size = self.frp.size.value();
             visualization.set_size.emit(size);
+            visualization.set_widget_mode.emit(self.is_widget.get());
+            let network            = frp::Network::new();
+            let widget_data_select = &self.frp.widget_data_select;
+            let is_widget          = &self.is_widget;
+            frp::extend! { network
+                eval visualization.on_widget_data ([widget_data_select,is_widget](data)
+                    if is_widget.get() { widget_data_select.emit(data) }
+                );
+            }
+            self.widget_network.replace(Some(network));
             self.view.add_child(&visualization);
             self.visualization.replace(Some(visualization));
+        } else {
+            self.widget_network.replace(None);
         }
     }
 
+    fn set_widget_mode(&self, is_widget:bool) {
+        self.is_widget.set(is_widget);
+        self.visualization.borrow().for_each_ref(|vis| vis.set_widget_mode.emit(is_widget))
+    }
+
     fn set_visualization_data(&self, data:&visualization::Data) {
         self.visualization.borrow().for_each_ref(|vis| vis.send_data.emit(data))
     }
 
+    /// Shows the error over the visualization, or hides it if `error` is `None`, and passes it to
+    /// the visualization.
+    fn set_visualization_data_error(&self, error:&Option<visualization::DataError>) {
+        let message = error.as_ref().map(|error| error.to_string()).unwrap_or_default();
+        self.view.error_label.shape.label.set_text(&message);
+        self.visualization.borrow().for_each_ref(|vis| vis.send_data_error.emit(error))
+    }
+
     fn update_shape_sizes(&self) {
         let size = self.frp.size.value();
         self.set_size(size);
@@ -344,6 +487,15 @@ impl ContainerModel {
             self.fullscreen_view.background . shape.sprite.size.set(zero());
         }
 
+        let handle_size = if self.panel.get().is_some() {RESIZE_HANDLE_SIZE} else {0.0};
+        let handle_x    = (size.x - handle_size) / 2.0;
+        let handle_y    = (handle_size - size.y) / 2.0;
+        self.view.resize_handle.shape.sprite.size.set(Vector2(handle_size,handle_size));
+        self.view.resize_handle.set_position(Vector3(handle_x,handle_y,0.0));
+        let error_x = ERROR_MARGIN - size.x / 2.0;
+        let error_y = size.y / 2.0 - ERROR_MARGIN;
+        self.view.error_label.set_position(Vector3(error_x,error_y,0.0));
+
         if let Some(viz) = &*self.visualization.borrow() {
             viz.set_size.emit(size);
         }
@@ -399,6 +551,7 @@ impl Container {
         let inputs     = &self.frp;
         let network    = &self.network;
         let model      = &self.model;
+        let mouse      = &scene.mouse.frp;
         let fullscreen = Animation::new(network);
         let size       = Animation::<Vector2>::new(network);
         let fullscreen_position = Animation::<Vector3>::new(network);
@@ -408,10 +561,14 @@ impl Container {
             eval_ inputs.toggle_visibility (model.toggle_visibility());
             eval  inputs.set_visualization ((v) model.set_visualization(v.clone()));
             eval  inputs.set_data          ((t) model.set_visualization_data(t));
+            eval  inputs.set_data_error    ((e) model.set_visualization_data_error(e));
+            eval  inputs.set_widget_mode   ((w) model.set_widget_mode(*w));
             eval_ inputs.enable_fullscreen (model.set_visibility(true));
             eval_ inputs.enable_fullscreen (model.enable_fullscreen());
             eval_ inputs.enable_fullscreen (fullscreen.set_target_value(1.0));
-            eval  inputs.set_size          ((s) size.set_target_value(*s));
+            is_panel     <- inputs.set_panel_layout.map(|layout| layout.is_some());
+            attached_size <- inputs.set_size.gate_not(&is_panel);
+            eval attached_size ((s) size.set_target_value(*s));
 
             _eval <- fullscreen.value.all_with3(&size.value,&inputs.scene_shape,
                 f!([model] (weight,viz_size,scene_size) {
@@ -436,6 +593,43 @@ impl Container {
             eval model.frp.preprocessor    ((code) inputs.preprocessor_select.emit(code));
         }
 
+
+        // === Panel ===
+
+        frp::extend! { network
+            eval inputs.set_panel_layout ((layout) model.set_panel_layout(*layout));
+            panel_size <- inputs.set_panel_layout.map(f!([inputs](layout)
+                layout.map_or_else(|| inputs.size.value(),|layout| layout.size)
+            ));
+            eval panel_size ((s) size.set_target_value(*s));
+
+            move_start   <- model.view.overlay.events.mouse_down.gate(&is_panel);
+            resize_start <- model.view.resize_handle.events.mouse_down.gate(&is_panel);
+            drag_start   <- any(&move_start,&resize_start);
+            is_moving    <- bool(&mouse.up,&move_start);
+            is_resizing  <- bool(&mouse.up,&resize_start);
+            is_dragging  <- bool(&mouse.up,&drag_start);
+            was_dragging <- is_dragging.previous();
+            drag_end     <- mouse.up.gate(&was_dragging);
+
+            mouse_on_start  <- mouse.position.sample(&drag_start);
+            layout_on_start <- drag_start.map(f_!(model.panel_layout().unwrap_or_default()));
+            drag_offset     <- mouse.position.map2(&mouse_on_start,f!([model](pos,start)
+                (pos - start) / model.scene.camera().zoom()
+            )).gate(&is_dragging);
+            moved   <- drag_offset.gate(&is_moving).map2(&layout_on_start,
+                |offset,layout| layout.moved_by(*offset));
+            resized <- drag_offset.gate(&is_resizing).map2(&layout_on_start,
+                |offset,layout| layout.resized_by(*offset));
+            dragged <- any(&moved,&resized);
+            eval dragged ([model,size](layout) {
+                model.set_panel_layout(Some(*layout));
+                size.set_target_value(layout.size);
+                size.skip();
+            });
+            eval_ drag_end (inputs.panel_layout_select.emit(model.panel_layout()));
+        }
+
         inputs.set_size.emit(Vector2(DEFAULT_SIZE.0,DEFAULT_SIZE.1));
         size.skip();
         model.set_visualization(Some(visualization::Registry::default_visualisation(scene)));
@@ -448,3 +642,29 @@ impl display::Object for Container {
         &self.model.display_object
     }
 }
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resizing_panel_keeps_top_left_corner() {
+        let layout  = PanelLayout::new(Vector2(100.0,100.0),Vector2(200.0,100.0));
+        let resized = layout.resized_by(Vector2(20.0,-40.0));
+        assert_eq!(resized.size,Vector2(220.0,140.0));
+        assert_eq!(resized.position,Vector2(110.0,80.0));
+
+        let shrunk = layout.resized_by(Vector2(-500.0,500.0));
+        assert_eq!(shrunk.size,Vector2(MIN_PANEL_SIZE,MIN_PANEL_SIZE));
+        assert_eq!(shrunk.position,Vector2(25.0,125.0));
+
+        let moved = layout.moved_by(Vector2(10.0,-10.0));
+        assert_eq!(moved,PanelLayout::new(Vector2(110.0,90.0),Vector2(200.0,100.0)));
+    }
+}
@@ -1,5 +1,10 @@
 //! Definition of data understandable by visualizations.
 
+pub mod binary;
+
+pub use binary::Binary;
+pub use binary::DecodeError;
+
 use crate::prelude::*;
 
 use std::str::FromStr;
@@ -77,8 +82,30 @@ impl Default for Format {
 #[derive(Clone,Debug)]
 #[allow(missing_docs)]
 pub enum Data {
-    Json { content : Json },
-    Binary, // TODO replace with actual binary data stream.
+    Json   { content : Json       },
+    Binary { content : Rc<Binary> },
+}
+
+impl Data {
+    /// Decodes the data received in the given format.
+    pub fn decode(format:Format, bytes:&[u8]) -> Result<Self,DecodeError> {
+        match format {
+            Format::Json => {
+                let json = serde_json::from_slice::<serde_json::Value>(bytes);
+                let json = json.map_err(|err| DecodeError::InvalidJson(err.to_string()))?;
+                Ok(json.into())
+            }
+            Format::Binary => Ok(Binary::decode(bytes)?.into()),
+        }
+    }
+
+    /// The data as JSON. The binary data is converted as described in `Binary::to_json`.
+    pub fn to_json(&self) -> Json {
+        match self {
+            Self::Json   {content} => content.clone_ref(),
+            Self::Binary {content} => content.to_json().into(),
+        }
+    }
 }
 
 impl Default for Data {
@@ -95,6 +122,13 @@ impl From<serde_json::Value> for Data {
     }
 }
 
+impl From<Binary> for Data {
+    fn from(t:Binary) -> Self {
+        let content = Rc::new(t);
+        Self::Binary {content}
+    }
+}
+
 
 
 // ==============
@@ -103,12 +137,30 @@ impl From<serde_json::Value> for Data {
 
 /// Indicates a problem with the provided data. That is, the data has the wrong format, or maybe
 /// violates some other assumption of the visualization.
-#[derive(Copy,Clone,Debug)]
+#[derive(Clone,Debug)]
 pub enum DataError {
     /// Indicates that that the provided data type does not match the expected data type.
     InvalidDataType,
     /// The data caused an error in the computation of the visualization.
     InternalComputationError,
+    /// The received data could not be decoded.
+    DecodingError(DecodeError),
+}
+
+impl Display for DataError {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidDataType          => write!(f,"The data has unexpected type."),
+            Self::InternalComputationError => write!(f,"The visualization failed to process data."),
+            Self::DecodingError(error)     => write!(f,"{}",error),
+        }
+    }
+}
+
+impl From<DecodeError> for DataError {
+    fn from(error:DecodeError) -> Self {
+        Self::DecodingError(error)
+    }
 }
 
 
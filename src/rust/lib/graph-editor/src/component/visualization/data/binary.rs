@@ -0,0 +1,517 @@
+//! Binary payloads of visualization data.
+//!
+//! Sending big arrays or tables as JSON is slow, so the data can also be transferred in a compact
+//! binary form. All numbers are little-endian. The payload starts with a single byte describing
+//! its kind, followed by the kind-specific content:
+//!
+//! - `Array` (kind `0`): element type (`u8`), element count (`u32`) and the elements.
+//! - `Table` (kind `1`): column count (`u32`), row count (`u32`), then for each column its name
+//!   length (`u32`), the UTF-8 name, element type (`u8`), validity flag (`u8`), the validity bitmap
+//!   if the flag is set, and the values. Like in Apache Arrow, the columns are stored one after
+//!   another and the bitmap has a bit per row, the least significant bit first. A cleared bit
+//!   marks a missing value.
+//! - `Image` (kind `2`): width (`u32`), height (`u32`), channel count (`u8`, 1 to 4) and the pixel
+//!   bytes, row by row.
+
+use crate::prelude::*;
+
+use std::convert::TryInto;
+
+
+
+// ==============
+// === Errors ===
+// ==============
+
+/// Error occurring when the binary payload cannot be decoded.
+#[derive(Clone,Debug,Eq,PartialEq)]
+#[allow(missing_docs)]
+pub enum DecodeError {
+    /// The payload ends before the value being read at the given byte offset.
+    UnexpectedEnd {offset:usize},
+    UnknownKind(u8),
+    UnknownElementType(u8),
+    InvalidColumnName {column:usize},
+    InvalidChannelCount(u8),
+    /// The image pixels would take more bytes than can be addressed.
+    ImageTooLarge {width:u32, height:u32},
+    /// There are bytes left after the decoded value.
+    TrailingBytes {count:usize},
+    /// The payload was expected to be JSON, but is not.
+    InvalidJson(String),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd {offset} =>
+                write!(f,"Unexpected end of data at byte {}.",offset),
+            Self::UnknownKind(kind) =>
+                write!(f,"Unknown kind of binary data: {}.",kind),
+            Self::UnknownElementType(tag) =>
+                write!(f,"Unknown array element type: {}.",tag),
+            Self::InvalidColumnName {column} =>
+                write!(f,"The name of column {} is not valid UTF-8.",column),
+            Self::InvalidChannelCount(count) =>
+                write!(f,"Images must have 1 to 4 channels, got {}.",count),
+            Self::ImageTooLarge {width,height} =>
+                write!(f,"The image of size {}x{} is too large.",width,height),
+            Self::TrailingBytes {count} =>
+                write!(f,"{} unexpected bytes after the data.",count),
+            Self::InvalidJson(message) =>
+                write!(f,"Invalid JSON: {}",message),
+        }
+    }
+}
+
+
+
+// ====================
+// === NumericArray ===
+// ====================
+
+/// Type of numeric array elements.
+#[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
+#[allow(missing_docs)]
+pub enum ElementType {F32,F64,I32,I64,U8}
+
+impl ElementType {
+    /// The tag identifying the type in the binary payload.
+    pub fn tag(self) -> u8 {
+        match self {
+            Self::F32 => 0,
+            Self::F64 => 1,
+            Self::I32 => 2,
+            Self::I64 => 3,
+            Self::U8  => 4,
+        }
+    }
+
+    /// The type identified by the given tag.
+    pub fn from_tag(tag:u8) -> Result<Self,DecodeError> {
+        match tag {
+            0 => Ok(Self::F32),
+            1 => Ok(Self::F64),
+            2 => Ok(Self::I32),
+            3 => Ok(Self::I64),
+            4 => Ok(Self::U8),
+            _ => Err(DecodeError::UnknownElementType(tag)),
+        }
+    }
+
+    /// Size of a single element in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            Self::F32 | Self::I32 => 4,
+            Self::F64 | Self::I64 => 8,
+            Self::U8              => 1,
+        }
+    }
+}
+
+/// An array of numbers of a single type.
+#[derive(Clone,Debug,PartialEq)]
+#[allow(missing_docs)]
+pub enum NumericArray {
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+    I32(Vec<i32>),
+    I64(Vec<i64>),
+    U8 (Vec<u8>),
+}
+
+impl NumericArray {
+    /// The type of the elements.
+    pub fn element_type(&self) -> ElementType {
+        match self {
+            Self::F32(_) => ElementType::F32,
+            Self::F64(_) => ElementType::F64,
+            Self::I32(_) => ElementType::I32,
+            Self::I64(_) => ElementType::I64,
+            Self::U8 (_) => ElementType::U8,
+        }
+    }
+
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::F32(values) => values.len(),
+            Self::F64(values) => values.len(),
+            Self::I32(values) => values.len(),
+            Self::I64(values) => values.len(),
+            Self::U8 (values) => values.len(),
+        }
+    }
+
+    /// Checks if the array has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The element at the given index, converted to `f64`.
+    pub fn get(&self, index:usize) -> Option<f64> {
+        match self {
+            Self::F32(values) => values.get(index).map(|t| *t as f64),
+            Self::F64(values) => values.get(index).copied(),
+            Self::I32(values) => values.get(index).map(|t| *t as f64),
+            Self::I64(values) => values.get(index).map(|t| *t as f64),
+            Self::U8 (values) => values.get(index).map(|t| *t as f64),
+        }
+    }
+
+    /// Iterates over the elements converted to `f64`.
+    pub fn iter(&self) -> impl Iterator<Item=f64> + '_ {
+        (0..self.len()).filter_map(move |index| self.get(index))
+    }
+
+    fn decode(element_type:ElementType, bytes:&[u8]) -> Self {
+        let size = element_type.size();
+        // The `chunks_exact` guarantees the chunk size, so the conversions never fail.
+        match element_type {
+            ElementType::F32 => Self::F32(decode_le(bytes,size,f32::from_le_bytes)),
+            ElementType::F64 => Self::F64(decode_le(bytes,size,f64::from_le_bytes)),
+            ElementType::I32 => Self::I32(decode_le(bytes,size,i32::from_le_bytes)),
+            ElementType::I64 => Self::I64(decode_le(bytes,size,i64::from_le_bytes)),
+            ElementType::U8  => Self::U8(bytes.to_vec()),
+        }
+    }
+
+    fn encode(&self, out:&mut Vec<u8>) {
+        match self {
+            Self::F32(values) => values.iter().for_each(|t| out.extend(&t.to_le_bytes())),
+            Self::F64(values) => values.iter().for_each(|t| out.extend(&t.to_le_bytes())),
+            Self::I32(values) => values.iter().for_each(|t| out.extend(&t.to_le_bytes())),
+            Self::I64(values) => values.iter().for_each(|t| out.extend(&t.to_le_bytes())),
+            Self::U8 (values) => out.extend(values),
+        }
+    }
+}
+
+fn decode_le<T,Bytes>(bytes:&[u8], size:usize, from_le_bytes:fn(Bytes)->T) -> Vec<T>
+where Bytes:Default, for<'a> &'a [u8]:TryInto<Bytes> {
+    bytes.chunks_exact(size).map(|chunk| from_le_bytes(chunk.try_into().unwrap_or_default()))
+        .collect()
+}
+
+
+
+// =============
+// === Table ===
+// =============
+
+/// A named column of a table.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Column {
+    /// The column name.
+    pub name     : String,
+    /// The values of all rows, including the missing ones.
+    pub values   : NumericArray,
+    /// The validity of the values, if any can be missing.
+    pub validity : Option<Vec<bool>>,
+}
+
+impl Column {
+    /// Constructor of a column without missing values.
+    pub fn new(name:impl Into<String>, values:NumericArray) -> Self {
+        let name     = name.into();
+        let validity = None;
+        Self {name,values,validity}
+    }
+
+    /// The value in the given row, if it is present.
+    pub fn get(&self, row:usize) -> Option<f64> {
+        let validity = self.validity.as_ref();
+        let is_valid = validity.map_or(true, |validity| validity.get(row) == Some(&true));
+        if is_valid { self.values.get(row) } else { None }
+    }
+}
+
+/// A table stored column by column.
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct Table {
+    /// The columns of the table.
+    pub columns   : Vec<Column>,
+    /// The number of rows.
+    pub row_count : usize,
+}
+
+impl Table {
+    /// The column of the given name.
+    pub fn column(&self, name:&str) -> Option<&Column> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+}
+
+
+
+// =============
+// === Image ===
+// =============
+
+/// A raster image.
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct Image {
+    /// The width in pixels.
+    pub width    : u32,
+    /// The height in pixels.
+    pub height   : u32,
+    /// The number of channels of each pixel, e.g. 3 for RGB.
+    pub channels : u8,
+    /// The pixel bytes, row by row.
+    pub pixels   : Vec<u8>,
+}
+
+impl Image {
+    /// The number of pixel bytes, or `None` if it does not fit in `usize`, which is only 32 bits
+    /// wide on wasm32.
+    fn byte_count(width:u32, height:u32, channels:u8) -> Option<usize> {
+        let pixel_count = (width as usize).checked_mul(height as usize)?;
+        pixel_count.checked_mul(channels as usize)
+    }
+}
+
+
+
+// ==============
+// === Binary ===
+// ==============
+
+/// Visualization data in binary form.
+#[derive(Clone,Debug,PartialEq)]
+#[allow(missing_docs)]
+pub enum Binary {
+    Array (NumericArray),
+    Table (Table),
+    Image (Image),
+}
+
+const ARRAY_KIND : u8 = 0;
+const TABLE_KIND : u8 = 1;
+const IMAGE_KIND : u8 = 2;
+
+impl Binary {
+    /// Decodes the binary payload. See the module docs for the expected layout.
+    pub fn decode(bytes:&[u8]) -> Result<Self,DecodeError> {
+        let mut reader = Reader::new(bytes);
+        let result     = match reader.u8()? {
+            ARRAY_KIND => Self::Array(reader.array()?),
+            TABLE_KIND => Self::Table(reader.table()?),
+            IMAGE_KIND => Self::Image(reader.image()?),
+            other      => return Err(DecodeError::UnknownKind(other)),
+        };
+        match bytes.len() - reader.offset {
+            0     => Ok(result),
+            count => Err(DecodeError::TrailingBytes {count}),
+        }
+    }
+
+    /// Encodes the data as a binary payload. See the module docs for the layout.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out  = Vec::new();
+        let push_u32 = |out:&mut Vec<u8>, value:usize| out.extend(&(value as u32).to_le_bytes());
+        match self {
+            Self::Array(array) => {
+                out.push(ARRAY_KIND);
+                out.push(array.element_type().tag());
+                push_u32(&mut out,array.len());
+                array.encode(&mut out);
+            }
+            Self::Table(table) => {
+                out.push(TABLE_KIND);
+                push_u32(&mut out,table.columns.len());
+                push_u32(&mut out,table.row_count);
+                for column in &table.columns {
+                    push_u32(&mut out,column.name.len());
+                    out.extend(column.name.as_bytes());
+                    out.push(column.values.element_type().tag());
+                    out.push(column.validity.is_some() as u8);
+                    if let Some(validity) = &column.validity {
+                        let mut bitmap = vec![0; (validity.len() + 7) / 8];
+                        for (row,_) in validity.iter().enumerate().filter(|(_,valid)| **valid) {
+                            bitmap[row / 8] |= 1 << (row % 8);
+                        }
+                        out.extend(bitmap);
+                    }
+                    column.values.encode(&mut out);
+                }
+            }
+            Self::Image(image) => {
+                out.push(IMAGE_KIND);
+                out.extend(&image.width.to_le_bytes());
+                out.extend(&image.height.to_le_bytes());
+                out.push(image.channels);
+                out.extend(&image.pixels);
+            }
+        }
+        out
+    }
+
+    /// Converts the data to JSON, for consumers which do not understand the binary form. Arrays
+    /// become JSON arrays, tables become objects mapping column names to arrays of values (with
+    /// `null` for missing ones) and images become objects with their shape and pixels.
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::json;
+        match self {
+            Self::Array(array) => json!(array.iter().collect_vec()),
+            Self::Table(table) => {
+                let columns = table.columns.iter().map(|column| {
+                    let values = (0..table.row_count).map(|row| column.get(row)).collect_vec();
+                    (column.name.clone(),json!(values))
+                });
+                serde_json::Value::Object(columns.collect())
+            }
+            Self::Image(image) => json!({
+                "width"    : image.width,
+                "height"   : image.height,
+                "channels" : image.channels,
+                "pixels"   : image.pixels,
+            }),
+        }
+    }
+}
+
+
+
+// ==============
+// === Reader ===
+// ==============
+
+/// Reads consecutive values from the binary payload.
+#[derive(Clone,Copy,Debug)]
+struct Reader<'a> {
+    bytes  : &'a [u8],
+    offset : usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes:&'a [u8]) -> Self {
+        let offset = 0;
+        Self {bytes,offset}
+    }
+
+    fn take(&mut self, count:usize) -> Result<&'a [u8],DecodeError> {
+        let end     = self.offset.checked_add(count).filter(|end| *end <= self.bytes.len());
+        let end     = end.ok_or(DecodeError::UnexpectedEnd {offset:self.offset})?;
+        let taken   = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8,DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32,DecodeError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0],bytes[1],bytes[2],bytes[3]]))
+    }
+
+    fn values
+    (&mut self, element_type:ElementType, count:usize) -> Result<NumericArray,DecodeError> {
+        let size  = count.checked_mul(element_type.size());
+        let size  = size.ok_or(DecodeError::UnexpectedEnd {offset:self.offset})?;
+        let bytes = self.take(size)?;
+        Ok(NumericArray::decode(element_type,bytes))
+    }
+
+    fn array(&mut self) -> Result<NumericArray,DecodeError> {
+        let element_type = ElementType::from_tag(self.u8()?)?;
+        let count        = self.u32()? as usize;
+        self.values(element_type,count)
+    }
+
+    fn table(&mut self) -> Result<Table,DecodeError> {
+        let column_count = self.u32()? as usize;
+        let row_count    = self.u32()? as usize;
+        let mut columns  = Vec::new();
+        for column in 0..column_count {
+            let name_length  = self.u32()? as usize;
+            let name         = self.take(name_length)?.to_vec();
+            let name         = String::from_utf8(name);
+            let name         = name.map_err(|_| DecodeError::InvalidColumnName {column})?;
+            let element_type = ElementType::from_tag(self.u8()?)?;
+            let validity     = if self.u8()? == 0 { None } else {
+                let bitmap = self.take((row_count + 7) / 8)?;
+                Some((0..row_count).map(|row| bitmap[row / 8] & (1 << (row % 8)) != 0).collect())
+            };
+            let values = self.values(element_type,row_count)?;
+            columns.push(Column {name,values,validity});
+        }
+        Ok(Table {columns,row_count})
+    }
+
+    fn image(&mut self) -> Result<Image,DecodeError> {
+        let width    = self.u32()?;
+        let height   = self.u32()?;
+        let channels = self.u8()?;
+        if !(1..=4).contains(&channels) {
+            return Err(DecodeError::InvalidChannelCount(channels))
+        }
+        let too_large  = DecodeError::ImageTooLarge {width,height};
+        let byte_count = Image::byte_count(width,height,channels).ok_or(too_large)?;
+        let pixels     = self.take(byte_count)?.to_vec();
+        Ok(Image {width,height,channels,pixels})
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decoding_array() {
+        let mut bytes = vec![ARRAY_KIND,ElementType::F32.tag(),2,0,0,0];
+        bytes.extend(&1.5_f32.to_le_bytes());
+        bytes.extend(&(-2.0_f32).to_le_bytes());
+        let decoded = Binary::decode(&bytes).unwrap();
+        assert_eq!(decoded, Binary::Array(NumericArray::F32(vec![1.5,-2.0])));
+    }
+
+    #[test]
+    fn table_round_trip() {
+        let mut prices   = Column::new("price",NumericArray::F64(vec![1.0,2.5,0.0]));
+        prices.validity  = Some(vec![true,true,false]);
+        let counts       = Column::new("count",NumericArray::I32(vec![3,-1,7]));
+        let table        = Table {columns:vec![prices,counts],row_count:3};
+        let binary       = Binary::Table(table);
+        let decoded      = Binary::decode(&binary.encode()).unwrap();
+        assert_eq!(decoded, binary);
+        if let Binary::Table(table) = decoded {
+            assert_eq!(table.column("price").unwrap().get(2), None);
+            assert_eq!(table.column("count").unwrap().get(1), Some(-1.0));
+        }
+        let json = binary.to_json();
+        assert_eq!(json["price"], serde_json::json!([1.0,2.5,null]));
+    }
+
+    #[test]
+    fn image_round_trip() {
+        let pixels = vec![0,255,0,255,255,0,0,255];
+        let image  = Binary::Image(Image {width:2,height:1,channels:4,pixels});
+        assert_eq!(Binary::decode(&image.encode()).unwrap(), image);
+    }
+
+    #[test]
+    fn decoding_errors() {
+        assert_eq!(Binary::decode(&[]), Err(DecodeError::UnexpectedEnd {offset:0}));
+        assert_eq!(Binary::decode(&[7]), Err(DecodeError::UnknownKind(7)));
+        let unknown_type = [ARRAY_KIND,9,0,0,0,0];
+        assert_eq!(Binary::decode(&unknown_type), Err(DecodeError::UnknownElementType(9)));
+        let truncated = [ARRAY_KIND,ElementType::I32.tag(),2,0,0,0,1,0,0,0];
+        assert_eq!(Binary::decode(&truncated), Err(DecodeError::UnexpectedEnd {offset:6}));
+        let trailing = [ARRAY_KIND,ElementType::U8.tag(),1,0,0,0,5,6];
+        assert_eq!(Binary::decode(&trailing), Err(DecodeError::TrailingBytes {count:1}));
+        let image = [IMAGE_KIND,1,0,0,0,1,0,0,0,5];
+        assert_eq!(Binary::decode(&image), Err(DecodeError::InvalidChannelCount(5)));
+        let too_large = [IMAGE_KIND,255,255,255,255,255,255,255,255,4];
+        let max       = u32::max_value();
+        let expected  = DecodeError::ImageTooLarge {width:max,height:max};
+        assert_eq!(Binary::decode(&too_large), Err(expected));
+    }
+}
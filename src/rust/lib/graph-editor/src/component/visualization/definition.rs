@@ -11,6 +11,47 @@ use visualization::java_script;
 
 
 
+// ====================
+// === Preprocessor ===
+// ====================
+
+/// The code of the default preprocessor, serializing the visualized value to JSON.
+pub const DEFAULT_PREPROCESSOR_CODE : &str = "x -> x.json_serialize";
+
+/// The Enso code run on the visualized value before sending it to the visualization, and the
+/// module in which context it should be evaluated.
+#[derive(Clone,CloneRef,Debug,Eq,Hash,PartialEq)]
+#[allow(missing_docs)]
+pub struct Preprocessor {
+    pub code   : EnsoCode,
+    /// The module of the preprocessor. If not set, the module of the visualized node is used.
+    pub module : Option<ModuleName>,
+}
+
+impl Preprocessor {
+    /// Constructor of preprocessor evaluated in the module of the visualized node.
+    pub fn new(code:impl Into<EnsoCode>) -> Self {
+        let code   = code.into();
+        let module = None;
+        Self {code,module}
+    }
+
+    /// Constructor of preprocessor evaluated in the given module.
+    pub fn in_module(code:impl Into<EnsoCode>, module:impl Into<ModuleName>) -> Self {
+        let code   = code.into();
+        let module = Some(module.into());
+        Self {code,module}
+    }
+}
+
+impl Default for Preprocessor {
+    fn default() -> Self {
+        Self::new(DEFAULT_PREPROCESSOR_CODE)
+    }
+}
+
+
+
 // =================
 // === Signature ===
 // =================
@@ -23,6 +64,7 @@ pub struct Signature {
     pub path         : visualization::Path,
     pub input_type   : EnsoType,
     pub input_format : Rc<visualization::data::Format>,
+    pub preprocessor : Preprocessor,
 }
 
 impl Signature {
@@ -33,8 +75,8 @@ impl Signature {
         let input_type   = input_type.into();
         let input_format = input_format.into();
         let input_format = Rc::new(input_format);
-
-        Self {path,input_type,input_format}
+        let preprocessor = default();
+        Self {path,input_type,input_format,preprocessor}
     }
 
     /// Constructor of signature valid for any Enso type.
@@ -44,6 +86,12 @@ impl Signature {
         let input_type = EnsoType::any();
         Self::new(path,input_type,input_format)
     }
+
+    /// Sets the preprocessor which should be used for the visualization's input.
+    pub fn with_preprocessor(mut self, preprocessor:Preprocessor) -> Self {
+        self.preprocessor = preprocessor;
+        self
+    }
 }
 
 
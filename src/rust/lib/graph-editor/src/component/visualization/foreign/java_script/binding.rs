@@ -3,6 +3,7 @@
 use crate::prelude::*;
 
 use super::PreprocessorCallback;
+use super::WidgetDataCallback;
 
 use ensogl::display::DomSymbol;
 use fmt::Formatter;
@@ -60,6 +61,8 @@ pub struct JsConsArgs {
     pub root : HtmlDivElement,
     #[wasm_bindgen(skip)]
     pub set_preprocessor : Box<dyn PreprocessorCallback>,
+    #[wasm_bindgen(skip)]
+    pub set_widget_data : Box<dyn WidgetDataCallback>,
 }
 
 impl Debug for JsConsArgs {
@@ -70,10 +73,12 @@ impl Debug for JsConsArgs {
 
 impl JsConsArgs {
     /// Constructor.
-    pub fn new<F:'static+PreprocessorCallback>(root:DomSymbol, closure:F) -> Self {
+    pub fn new<F,G>(root:DomSymbol, closure:F, widget_data_closure:G) -> Self
+    where F:'static+PreprocessorCallback, G:'static+WidgetDataCallback {
         let set_preprocessor = Box::new(closure);
+        let set_widget_data  = Box::new(widget_data_closure);
         let root = root.dom().clone();
-        JsConsArgs {root,set_preprocessor}
+        JsConsArgs {root,set_preprocessor,set_widget_data}
     }
 }
 
@@ -89,4 +94,10 @@ impl JsConsArgs {
         let closure = &self.set_preprocessor;
         (*closure)(code);
     }
+
+    /// Helper method to emit the data entered by the user in the widget mode.
+    pub fn emit_widget_data(&self, data:JsValue){
+        let closure = &self.set_widget_data;
+        (*closure)(data);
+    }
 }
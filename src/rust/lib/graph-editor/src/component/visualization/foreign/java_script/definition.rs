@@ -54,6 +54,8 @@
 //!   superclass defines a default constructor and a set of utils:
 //!   - The `setPreprocessor(code)` method allowing setting an Enso code which will be evaluated on
 //!     server-side before sending data to visualization.
+//!   - The `emitWidgetData(data)` method reporting the data entered by the user when the
+//!     visualization is displayed as a widget. The data is ignored outside of the widget mode.
 //!   - The `dom` field, which will be initialized in the constructor to the DOM symbol used to host
 //!     the visualization content. You are free to modify the DOM element, including adding other
 //!     elements as its children.
@@ -92,6 +94,11 @@
 //!   draw outside of the provided area, however, if you do so, it will be clipped to the provided
 //!   area automatically. The `size` parameter contains two fields `width` and `height` expressed in
 //!   pixels.
+//!
+//! - [Optional] **Function `setWidgetMode`**
+//!   The `setWidgetMode(isWidget)` method is called when the node of the visualization becomes a
+//!   widget or stops being one. Widgets should let the user edit the displayed data and report the
+//!   changes with the `emitWidgetData` method.
 
 // TODO: Connect the `setPreprocessor` method on Rust side.
 
@@ -131,6 +138,7 @@ pub mod field {
 pub mod method {
     pub const ON_DATA_RECEIVED : &str = "onDataReceived";
     pub const SET_SIZE         : &str = "setSize";
+    pub const SET_WIDGET_MODE  : &str = "setWidgetMode";
 }
 
 
@@ -222,7 +230,17 @@ impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Error::InvalidFunction(value)  => {
-                f.write_fmt(format_args!("Provided value is not a valid function: {:?}",value))
+                // The JavaScript exceptions are displayed by their messages, like "Unexpected
+                // token" for syntax errors, which are more helpful than the raw values.
+                match value.dyn_ref::<js_sys::Error>() {
+                    Some(error) => {
+                        let message = String::from(error.message());
+                        f.write_fmt(format_args!("Provided value is not a valid function: {}",
+                            message))
+                    }
+                    None => f.write_fmt(format_args!("Provided value is not a valid function: \
+                        {:?}",value)),
+                }
             },
             Error::InvalidClass(value)  => {
                 f.write_fmt(format_args!("Provided value is not a valid class: {:?}",value))
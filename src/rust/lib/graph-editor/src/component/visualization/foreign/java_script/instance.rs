@@ -78,6 +78,12 @@ pub trait PreprocessorCallback = Fn(String);
 /// Internal helper type to store the preprocessor callback.
 type PreprocessorCallbackCell = Rc<RefCell<Option<Box<dyn PreprocessorCallback>>>>;
 
+/// Helper type for the callback used to emit the data entered in the widget mode.
+pub trait WidgetDataCallback = Fn(JsValue);
+
+/// Internal helper type to store the widget data callback.
+type WidgetDataCallbackCell = Rc<RefCell<Option<Box<dyn WidgetDataCallback>>>>;
+
 /// `JsVisualizationGeneric` allows the use of arbitrary javascript to create visualizations. It
 /// takes function definitions as strings and proved those functions with data.
 #[derive(Clone,CloneRef,Derivative)]
@@ -88,9 +94,12 @@ pub struct InstanceModel {
     pub logger              : Logger,
         on_data_received    : Rc<Option<js_sys::Function>>,
         set_size            : Rc<Option<js_sys::Function>>,
+        set_widget_mode     : Rc<Option<js_sys::Function>>,
         object              : Rc<js_sys::Object>,
         #[derivative(Debug="ignore")]
         preprocessor_change : PreprocessorCallbackCell,
+        #[derivative(Debug="ignore")]
+        widget_data         : WidgetDataCallbackCell,
 }
 
 impl InstanceModel {
@@ -122,6 +131,19 @@ impl InstanceModel {
         (closure_cell,closure)
     }
 
+    /// Creates the cell and the callback passing the widget data from JavaScript, the same way as
+    /// `preprocessor_change_callback` does.
+    fn widget_data_callback() -> (WidgetDataCallbackCell,impl WidgetDataCallback) {
+        let closure_cell      = WidgetDataCallbackCell::default();
+        let weak_closure_cell = Rc::downgrade(&closure_cell);
+        let closure = move |data:JsValue| {
+            if let Some(callback) = weak_closure_cell.upgrade() {
+                callback.borrow().map_ref(|f|f(data));
+            }
+        };
+        (closure_cell,closure)
+    }
+
     fn instantiate_class_with_args(class:&JsValue, args:JsConsArgs)
     -> result::Result<js_sys::Object,Error> {
         let js_new  = js_sys::Function::new_with_args("cls,arg", "return new cls(arg)");
@@ -139,15 +161,19 @@ impl InstanceModel {
     pub fn from_class(class:&JsValue) -> result::Result<Self, Error> {
         let root_node                     = Self::create_root()?;
         let (preprocessor_change,closure) = Self::preprocessor_change_callback();
-        let init_data                     = JsConsArgs::new(root_node.clone_ref(), closure);
+        let (widget_data,widget_closure)  = Self::widget_data_callback();
+        let init_data = JsConsArgs::new(root_node.clone_ref(),closure,widget_closure);
         let object                        = Self::instantiate_class_with_args(class,init_data)?;
         let on_data_received              = get_method(&object,method::ON_DATA_RECEIVED).ok();
         let on_data_received              = Rc::new(on_data_received);
         let set_size                      = get_method(&object,method::SET_SIZE).ok();
         let set_size                      = Rc::new(set_size);
+        let set_widget_mode               = get_method(&object,method::SET_WIDGET_MODE).ok();
+        let set_widget_mode               = Rc::new(set_widget_mode);
         let logger                        = Logger::new("Instance");
         let object                        = Rc::new(object);
-        Ok(InstanceModel{object,on_data_received,set_size,root_node,logger,preprocessor_change})
+        Ok(InstanceModel{object,on_data_received,set_size,set_widget_mode,root_node,logger
+                        ,preprocessor_change,widget_data})
     }
 
     /// Hooks the root node into the given scene.
@@ -163,11 +189,12 @@ impl InstanceModel {
         self.root_node.set_size(size);
     }
 
+    fn set_widget_mode(&self, is_widget:bool) {
+        let _ = self.try_call1(&self.set_widget_mode,&is_widget.into());
+    }
+
    fn receive_data(&self, data:&Data) -> result::Result<(),DataError> {
-        let data_json = match data {
-           Data::Json {content} => content,
-           _ => todo!() // FIXME
-        };
+        let data_json = data.to_json();
         let data_json:&serde_json::Value = data_json.deref();
         let data_js   = match JsValue::from_serde(data_json) {
             Ok(value) => value,
@@ -215,7 +242,8 @@ impl Instance {
         let frp     = visualization::instance::Frp::new(&network);
         let model   = InstanceModel::from_class(class)?;
         model.set_dom_layer(&scene.dom.layers.main);
-        Ok(Instance{model,frp,network}.init_frp().inti_preprocessor_change_callback())
+        let instance = Instance{model,frp,network}.init_frp();
+        Ok(instance.inti_preprocessor_change_callback().init_widget_data_callback())
     }
 
     fn init_frp(self) -> Self {
@@ -223,7 +251,8 @@ impl Instance {
         let model   = self.model.clone_ref();
         let frp     = self.frp.clone_ref();
         frp::extend! { network
-            eval frp.set_size  ((size) model.set_size(*size));
+            eval frp.set_size        ((size) model.set_size(*size));
+            eval frp.set_widget_mode ((is_widget) model.set_widget_mode(*is_widget));
             eval frp.send_data ([frp](data) {
                 if let Err(e) = model.receive_data(data) {
                     frp.data_receive_error.emit(Some(e));
@@ -242,6 +271,17 @@ impl Instance {
         self
     }
 
+    fn init_widget_data_callback(self) -> Self {
+        let widget_data = &self.frp.widget_data;
+        let logger      = self.model.logger.clone_ref();
+        let callback    = f!((data:JsValue) match data.into_serde::<serde_json::Value>() {
+            Ok(data)   => widget_data.emit(data),
+            Err(error) => logger.warning(|| format!("Invalid widget data: {:?}",error)),
+        });
+        self.model.widget_data.borrow_mut().replace(Box::new(callback));
+        self
+    }
+
 }
 
 impl From<Instance> for visualization::Instance {
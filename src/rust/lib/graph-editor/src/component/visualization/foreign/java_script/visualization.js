@@ -6,6 +6,9 @@ export class Visualization {
     setPreprocessor(code) {
         this.__api__.emit_preprocessor_change(code)
     }
+    emitWidgetData(data) {
+        this.__api__.emit_widget_data(data)
+    }
 }
 
 export function __Visualization__() {
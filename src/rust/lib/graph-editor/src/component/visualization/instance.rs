@@ -21,8 +21,14 @@ use crate::data::EnsoCode;
 #[derive(Clone,CloneRef,Debug)]
 #[allow(missing_docs)]
 pub struct FrpInputs {
-    pub set_size  : frp::Source<Vector2>,
-    pub send_data : frp::Source<Data>,
+    pub set_size        : frp::Source<Vector2>,
+    pub send_data       : frp::Source<Data>,
+    /// Informs the visualization that the data could not be delivered, e.g. due to a decoding
+    /// failure.
+    pub send_data_error : frp::Source<Option<DataError>>,
+    /// Informs the visualization whether it is displayed as a widget. Widgets let the user edit
+    /// the displayed data and report the changes through the `widget_data` endpoint.
+    pub set_widget_mode : frp::Source<bool>,
 }
 
 /// Visualization FRP network.
@@ -35,10 +41,13 @@ pub struct Frp {
     pub on_change             : frp::Stream<EnsoCode>,
     pub on_preprocess_change  : frp::Stream<EnsoCode>,
     pub on_data_receive_error : frp::Stream<Option<DataError>>,
+    pub on_widget_data        : frp::Stream<serde_json::Value>,
 
     pub data_receive_error    : frp::Source<Option<DataError>>,
     pub change                : frp::Source<EnsoCode>,
     pub preprocess_change     : frp::Source<EnsoCode>,
+    /// Emitted by the visualization in the widget mode with the data entered by the user.
+    pub widget_data           : frp::Source<serde_json::Value>,
 }
 
 impl FrpInputs {
@@ -47,25 +56,30 @@ impl FrpInputs {
         frp::extend! { network
             set_size           <- source();
             send_data          <- source();
+            send_data_error    <- source();
+            set_widget_mode    <- source();
         };
-        Self {set_size,send_data}
+        Self {set_size,send_data,send_data_error,set_widget_mode}
     }
 }
 
 impl Frp {
     /// Constructor.
     pub fn new(network:&frp::Network) -> Self {
+        let inputs = FrpInputs::new(&network);
         frp::extend! { network
             def change             = source();
             def preprocess_change  = source();
             def data_receive_error = source();
+            def widget_data        = source();
+            on_data_receive_error <- any(&data_receive_error,&inputs.send_data_error);
         };
         let on_change             = change.clone_ref().into();
         let on_preprocess_change  = preprocess_change.clone_ref().into();
-        let on_data_receive_error = data_receive_error.clone_ref().into();
-        let inputs                = FrpInputs::new(&network);
-        Self {on_change,on_preprocess_change,on_data_receive_error,change,preprocess_change
-             ,inputs,data_receive_error}
+        let on_data_receive_error = on_data_receive_error.into();
+        let on_widget_data        = widget_data.clone_ref().into();
+        Self {on_change,on_preprocess_change,on_data_receive_error,on_widget_data,change
+             ,preprocess_change,inputs,data_receive_error,widget_data}
     }
 }
 
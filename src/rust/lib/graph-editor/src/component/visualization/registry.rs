@@ -1,6 +1,10 @@
 //! The `Registry` provides a mechanism to store `visualization::Class`es for all available visualizations. It
 //! provides functionality to register new factories, as well as get suitable factories for
 //! a specific data type.
+//!
+//! The data types are matched against the visualizations' input types as described in the
+//! `data::enso_type` module. Each type pattern can also be assigned a default visualization, which
+//! is shown when a visualization of a value of matching type is opened for the first time.
 
 use crate::prelude::*;
 
@@ -10,6 +14,7 @@ use crate::data::EnsoType;
 
 use ensogl::display::scene::Scene;
 use enso_prelude::CloneRef;
+use std::cmp::Reverse;
 
 
 
@@ -21,18 +26,20 @@ use enso_prelude::CloneRef;
 #[derive(Clone,CloneRef,Debug)]
 #[allow(missing_docs)]
 pub struct Registry {
-    path_map : Rc<RefCell<HashMap<visualization::Path,visualization::Definition>>>,
-    type_map : Rc<RefCell<HashMap<EnsoType,Vec<visualization::Definition>>>>,
-    logger   : Logger,
+    path_map    : Rc<RefCell<HashMap<visualization::Path,visualization::Definition>>>,
+    definitions : Rc<RefCell<Vec<visualization::Definition>>>,
+    defaults    : Rc<RefCell<Vec<(EnsoType,visualization::Path)>>>,
+    logger      : Logger,
 }
 
 impl Registry {
     /// Constructor.
     pub fn new() -> Self {
-        let path_map = default();
-        let type_map = default();
-        let logger   = Logger::new("Registry");
-        Registry{path_map,type_map,logger}
+        let path_map    = default();
+        let definitions = default();
+        let defaults    = default();
+        let logger      = Logger::new("Registry");
+        Registry{path_map,definitions,defaults,logger}
     }
 
     /// Return a `Registry` pre-populated with default visualizations.
@@ -40,16 +47,47 @@ impl Registry {
         let registry = Self::new();
         registry.add(builtin::visualization::native::BubbleChart::definition());
         registry.add(builtin::visualization::native::RawText::definition());
+        registry.add(builtin::visualization::native::Table::definition());
+        registry.add(builtin::visualization::native::Table::columnar_definition());
+        registry.add(builtin::visualization::native::Plot::line_chart_definition());
+        registry.add(builtin::visualization::native::Plot::scatter_plot_definition());
+        registry.add(builtin::visualization::native::Plot::histogram_definition());
         registry.try_add_java_script(builtin::visualization::java_script::bubble_visualization());
+        let plot = builtin::visualization::native::plot::ChartKind::Line;
+        registry.set_default(plot.input_type(),plot.path());
+        let plot = builtin::visualization::native::plot::ChartKind::Scatter;
+        registry.set_default(plot.input_type(),plot.path());
         registry
     }
 
-    /// Register a new `visualization::Definition`.
+    /// Register a new `visualization::Definition`. The definition of the same path registered
+    /// before is replaced.
     pub fn add(&self, class:impl Into<visualization::Definition>) {
         let class = class.into();
-        let sig   = &class.signature;
-        self.type_map.borrow_mut().entry(sig.input_type.clone()).or_default().push(class.clone_ref());
-        self.path_map.borrow_mut().entry(sig.path.clone()).insert(class);
+        let path  = class.signature.path.clone_ref();
+        if let Err(err) = class.signature.input_type.pattern() {
+            warning!(&self.logger,"The input type of visualization {path:?} is not a valid type \
+                                   pattern, it will match only the same type: {err}");
+        }
+        let mut definitions = self.definitions.borrow_mut();
+        definitions.retain(|definition| definition.signature.path != path);
+        definitions.push(class.clone_ref());
+        self.path_map.borrow_mut().insert(path,class);
+    }
+
+    /// Remove the `visualization::Definition` registered for the given path. The defaults referring
+    /// to it are kept, so they apply again once a definition of the same path is registered.
+    pub fn remove(&self, path:&visualization::Path) {
+        self.definitions.borrow_mut().retain(|definition| &definition.signature.path != path);
+        self.path_map.borrow_mut().remove(path);
+    }
+
+    /// Set the visualization shown by default for the values matching the given type pattern.
+    pub fn set_default(&self, tp:impl Into<EnsoType>, path:visualization::Path) {
+        let tp           = tp.into();
+        let mut defaults = self.defaults.borrow_mut();
+        defaults.retain(|(default_tp,_)| default_tp != &tp);
+        defaults.push((tp,path));
     }
 
     /// Register a new `visualization::java_script::Definition`. If creating the class fails, it
@@ -66,9 +104,31 @@ impl Registry {
     }
 
     /// Return all `visualization::Class`es that can create a visualization for the given datatype.
+    /// The visualizations with the most specific input types go first.
     pub fn valid_sources(&self, tp:&EnsoType) -> Vec<visualization::Definition>{
-        let type_map = self.type_map.borrow();
-        type_map.get(tp).cloned().unwrap_or_default()
+        let definitions = self.definitions.borrow();
+        let valid       = definitions.iter().filter(|d| tp.is_matched_by(&d.signature.input_type));
+        let mut valid   = valid.cloned().collect_vec();
+        valid.sort_by_key(|d| Reverse(Self::specificity(&d.signature.input_type)));
+        valid
+    }
+
+    /// Return the visualization which should be shown by default for the values of the given type.
+    /// It is the default set for the most specific type pattern matching the type. If there is
+    /// none, the raw text visualization of JSON is used.
+    pub fn default_for(&self, tp:&EnsoType) -> visualization::Definition {
+        let defaults = self.defaults.borrow();
+        let matching = defaults.iter().filter(|(pattern,_)| tp.is_matched_by(pattern));
+        let matching = matching.filter_map(|(pattern,path)| {
+            self.definition_from_path(path).map(|definition| (pattern,definition))
+        });
+        let best = matching.rev().max_by_key(|(pattern,_)| Self::specificity(pattern));
+        let best = best.map(|(_,definition)| definition);
+        best.unwrap_or_else(builtin::visualization::native::RawText::definition)
+    }
+
+    fn specificity(tp:&EnsoType) -> usize {
+        tp.pattern().map(|pattern| pattern.specificity()).unwrap_or_default()
     }
 
     /// Return the `visualization::Definition` registered for the given `visualization::Path`.
@@ -90,3 +150,67 @@ impl Default for Registry {
         Registry::new()
     }
 }
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::component::visualization::Definition;
+    use crate::component::visualization::Format;
+    use crate::component::visualization::Path;
+    use crate::component::visualization::Signature;
+
+    fn definition(name:&str, tp:&str) -> Definition {
+        let signature = Signature::new(Path::builtin(name),tp,Format::Json);
+        Definition::new(signature,|scene| Ok(Registry::default_visualisation(scene)))
+    }
+
+    fn names(definitions:Vec<Definition>) -> Vec<String> {
+        definitions.iter().map(|d| d.signature.path.name.to_string()).collect()
+    }
+
+    #[test]
+    fn finding_valid_sources() {
+        let registry = Registry::new();
+        registry.add(definition("any"      ,"Any"));
+        registry.add(definition("variable" ,"a"));
+        registry.add(definition("list"     ,"[a]"));
+        registry.add(definition("numbers"  ,"[Number]"));
+        registry.add(definition("ints"     ,"[Int]"));
+        registry.add(definition("texts"    ,"[Text]"));
+        let expected = vec!["ints","numbers","list","variable","any"];
+        assert_eq!(names(registry.valid_sources(&"[Int]".into())),expected);
+        assert_eq!(names(registry.valid_sources(&"Text".into())),vec!["variable","any"]);
+
+        registry.add(definition("ints","Int"));
+        let expected = vec!["numbers","list","variable","any"];
+        assert_eq!(names(registry.valid_sources(&"[Int]".into())),expected);
+
+        registry.remove(&Path::builtin("list"));
+        let expected = vec!["numbers","variable","any"];
+        assert_eq!(names(registry.valid_sources(&"[Int]".into())),expected);
+        assert!(registry.definition_from_path(&Path::builtin("list")).is_none());
+    }
+
+    #[test]
+    fn choosing_default() {
+        let registry = Registry::new();
+        registry.add(definition("list"   ,"[a]"));
+        registry.add(definition("numbers","[Number]"));
+        registry.set_default("[a]",Path::builtin("list"));
+        registry.set_default("[Number]",Path::builtin("numbers"));
+        registry.set_default("[Text]",Path::builtin("missing"));
+        let default_name = |tp:&str| registry.default_for(&tp.into()).signature.path.name.clone();
+        assert_eq!(&*default_name("[Int]")   ,"numbers");
+        assert_eq!(&*default_name("[Vector]"),"list");
+        assert_eq!(&*default_name("[Text]")  ,"list");
+        assert_eq!(default_name("Int"),builtin::visualization::native::RawText::definition()
+            .signature.path.name);
+    }
+}
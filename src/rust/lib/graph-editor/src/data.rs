@@ -1,3 +1,7 @@
+pub mod enso_type;
+
+pub use enso_type::TypePattern;
+
 use crate::prelude::*;
 
 
@@ -15,6 +19,9 @@ im_string_newtype!{
 
     /// The Enso type representation. Can be a complex type, like `String|Int`.
     EnsoType,
+
+    /// The qualified name of Enso module, like `Project.Main`.
+    ModuleName,
 }
 
 impl EnsoType {
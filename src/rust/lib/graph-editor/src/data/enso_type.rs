@@ -0,0 +1,466 @@
+//! A small language of Enso type patterns, used to find the visualizations able to display a
+//! value of a given type.
+//!
+//! The supported forms are:
+//! - `Any`, the type of all values;
+//! - type names, like `Number` or `Standard.Base.Text`, optionally applied to arguments, like
+//!   `Maybe Int`;
+//! - type variables, which are names starting with a lowercase letter, like `a`;
+//! - lists, like `[Int]`;
+//! - tuples, like `(Int,Text)`;
+//! - unions, like `Int|Text`.
+//!
+//! A pattern matches a type if they can be unified, taking into account that a type name matches
+//! also its subtypes, e.g. `Number` matches `Int`. So the `[Int]` type is matched by `[Int]`,
+//! `[Number]`, `[a]`, `a` and `Any`.
+//!
+//! Only the type variables of the pattern are wildcards. A variable in the matched type stands for
+//! some unknown type, so it is matched only by the pattern variables and `Any`, e.g. `[a]` is
+//! matched by `[b]` but not by `[Int]`.
+
+use crate::prelude::*;
+
+use super::EnsoType;
+
+
+
+// =================
+// === Hierarchy ===
+// =================
+
+/// The direct supertype of the given type other than `Any`.
+fn parent_type(name:&str) -> Option<&'static str> {
+    match name {
+        "Int" | "Integer" | "Float" | "Decimal" => Some("Number"),
+        _                                       => None,
+    }
+}
+
+/// Whether `sub` is `sup` or one of its subtypes.
+fn is_subtype(sub:&str, sup:&str) -> bool {
+    let mut current = Some(sub);
+    while let Some(name) = current {
+        if name == sup { return true }
+        current = parent_type(name);
+    }
+    false
+}
+
+/// The number of supertypes of the given type, not counting `Any`.
+fn type_depth(name:&str) -> usize {
+    let mut depth   = 0;
+    let mut current = parent_type(name);
+    while let Some(name) = current {
+        depth  += 1;
+        current = parent_type(name);
+    }
+    depth
+}
+
+
+
+// ==================
+// === ParseError ===
+// ==================
+
+/// Error of parsing the type pattern.
+#[derive(Clone,Debug,Eq,PartialEq)]
+#[allow(missing_docs)]
+pub enum ParseError {
+    UnexpectedEnd,
+    UnexpectedToken {token:String},
+    AppliedVariable {name:String},
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd           => write!(f,"Unexpected end of the type."),
+            Self::UnexpectedToken {token} => write!(f,"Unexpected `{}` in the type.",token),
+            Self::AppliedVariable {name}  =>
+                write!(f,"The type variable `{}` cannot be applied to arguments.",name),
+        }
+    }
+}
+
+
+
+// ===================
+// === TypePattern ===
+// ===================
+
+/// A parsed type pattern. See the module docs for the syntax.
+#[derive(Clone,Debug,Eq,Hash,PartialEq)]
+#[allow(missing_docs)]
+pub enum TypePattern {
+    Any,
+    Variable    (String),
+    Constructor {name:String, args:Vec<TypePattern>},
+    List        (Box<TypePattern>),
+    Tuple       (Vec<TypePattern>),
+    Union       (Vec<TypePattern>),
+}
+
+impl TypePattern {
+    /// Parses the type pattern.
+    pub fn parse(code:&str) -> Result<Self,ParseError> {
+        let mut parser = Parser::new(code);
+        let pattern    = parser.union()?;
+        match parser.next() {
+            None        => Ok(pattern),
+            Some(token) => Err(ParseError::UnexpectedToken {token}),
+        }
+    }
+
+    /// Whether a value of the given type can be passed where this pattern is expected.
+    pub fn matches(&self, tp:&TypePattern) -> bool {
+        let tp = tp.with_rigid_variables();
+        Unifier::default().unify(self,&tp)
+    }
+
+    /// How specific the pattern is. When several patterns match a type, the one with the highest
+    /// specificity describes it best. For example, `[Int]` is more specific than `[Number]`, which
+    /// is more specific than `[a]`, which in turn is more specific than `a`.
+    pub fn specificity(&self) -> usize {
+        match self {
+            Self::Any                      => 0,
+            Self::Variable(_)              => 1,
+            Self::Constructor {name,args}  =>
+                2 + type_depth(name) + args.iter().map(Self::specificity).sum::<usize>(),
+            Self::List(element)            => 2 + element.specificity(),
+            Self::Tuple(elements)          =>
+                2 + elements.iter().map(Self::specificity).sum::<usize>(),
+            Self::Union(alternatives)      =>
+                alternatives.iter().map(Self::specificity).min().unwrap_or_default(),
+        }
+    }
+
+    /// Whether the variable of the given name is used in this pattern.
+    fn contains_variable(&self, variable:&str) -> bool {
+        match self {
+            Self::Any                    => false,
+            Self::Variable(name)         => name == variable,
+            Self::List(element)          => element.contains_variable(variable),
+            Self::Constructor {args:ps,..}
+            | Self::Tuple(ps)
+            | Self::Union(ps)            => ps.iter().any(|p| p.contains_variable(variable)),
+        }
+    }
+
+    /// A copy of the type with the variables replaced by type names, so they are not bound when
+    /// matched with a pattern and cannot clash with its variables. The new names cannot be written
+    /// in the pattern syntax.
+    fn with_rigid_variables(&self) -> Self {
+        let rename_all = |ps:&[Self]| ps.iter().map(Self::with_rigid_variables).collect();
+        match self {
+            Self::Any                     => Self::Any,
+            Self::Variable(name)          => {
+                let name = format!("'{}",name);
+                let args = default();
+                Self::Constructor {name,args}
+            }
+            Self::List(element)           => Self::List(Box::new(element.with_rigid_variables())),
+            Self::Constructor {name,args} => {
+                let name = name.clone();
+                let args = rename_all(args);
+                Self::Constructor {name,args}
+            }
+            Self::Tuple(elements)         => Self::Tuple(rename_all(elements)),
+            Self::Union(alternatives)     => Self::Union(rename_all(alternatives)),
+        }
+    }
+}
+
+impl EnsoType {
+    /// The parsed pattern of this type.
+    pub fn pattern(&self) -> Result<TypePattern,ParseError> {
+        TypePattern::parse(self)
+    }
+
+    /// Whether the value of this type can be passed where the `pattern` type is expected. The
+    /// types which cannot be parsed match only the same types.
+    pub fn is_matched_by(&self, pattern:&EnsoType) -> bool {
+        match (pattern.pattern(),self.pattern()) {
+            (Ok(pattern),Ok(tp)) => pattern.matches(&tp),
+            _                    => pattern == self,
+        }
+    }
+}
+
+
+
+// ===============
+// === Unifier ===
+// ===============
+
+/// Unifies patterns with types, remembering the variables bound in the process.
+#[derive(Clone,Debug,Default)]
+struct Unifier {
+    bindings : HashMap<String,TypePattern>,
+}
+
+impl Unifier {
+    fn resolve(&self, pattern:&TypePattern) -> TypePattern {
+        let mut pattern = pattern;
+        while let TypePattern::Variable(name) = pattern {
+            match self.bindings.get(name) {
+                Some(bound) => pattern = bound,
+                None        => break,
+            }
+        }
+        pattern.clone()
+    }
+
+    fn bind(&mut self, variable:&str, value:&TypePattern) -> bool {
+        let occurs = value.contains_variable(variable);
+        if !occurs {
+            self.bindings.insert(variable.to_string(),value.clone());
+        }
+        !occurs
+    }
+
+    /// Unifies the pattern with the type, where the type may be a subtype of the pattern.
+    fn unify(&mut self, pattern:&TypePattern, tp:&TypePattern) -> bool {
+        use TypePattern::*;
+        let pattern = self.resolve(pattern);
+        let tp      = self.resolve(tp);
+        match (&pattern,&tp) {
+            (Variable(name),_)                  => self.bind(name,&tp),
+            (Any,_)                             => true,
+            (_,Union(alternatives))             =>
+                alternatives.iter().all(|alternative| self.unify(&pattern,alternative)),
+            (Union(alternatives),_)             =>
+                alternatives.iter().any(|alternative| self.try_unify(alternative,&tp)),
+            (List(pattern),List(tp))            => self.unify(pattern,tp),
+            (Tuple(patterns),Tuple(tps))        => self.unify_all(patterns,tps),
+            (Constructor {name,args},Constructor {name:tp_name,args:tp_args}) =>
+                if args.is_empty() && tp_args.is_empty() {
+                    is_subtype(tp_name,name)
+                } else {
+                    name == tp_name && self.unify_all(args,tp_args)
+                }
+            _ => false,
+        }
+    }
+
+    fn unify_all(&mut self, patterns:&[TypePattern], tps:&[TypePattern]) -> bool {
+        patterns.len() == tps.len() && patterns.iter().zip(tps).all(|(p,t)| self.unify(p,t))
+    }
+
+    /// Unifies the patterns, reverting the bindings if it fails.
+    fn try_unify(&mut self, pattern:&TypePattern, tp:&TypePattern) -> bool {
+        let bindings = self.bindings.clone();
+        let success  = self.unify(pattern,tp);
+        if !success {
+            self.bindings = bindings;
+        }
+        success
+    }
+}
+
+
+
+// ==============
+// === Parser ===
+// ==============
+
+/// A recursive descent parser of the type patterns.
+#[derive(Debug)]
+struct Parser {
+    tokens : Vec<String>,
+    offset : usize,
+}
+
+impl Parser {
+    fn new(code:&str) -> Self {
+        let is_name_char = |c:char| c.is_alphanumeric() || c == '_' || c == '.';
+        let mut tokens   = Vec::<String>::new();
+        let mut chars    = code.chars().peekable();
+        while let Some(c) = chars.next() {
+            if is_name_char(c) {
+                let mut name = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if !is_name_char(c) { break }
+                    name.push(c);
+                    chars.next();
+                }
+                tokens.push(name);
+            } else if !c.is_whitespace() {
+                tokens.push(c.to_string());
+            }
+        }
+        let offset = 0;
+        Self {tokens,offset}
+    }
+
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.offset).map(|token| token.as_str())
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.offset).cloned();
+        self.offset += 1;
+        token
+    }
+
+    fn expect(&mut self, expected:&str) -> Result<(),ParseError> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token)                      => Err(ParseError::UnexpectedToken {token}),
+            None                             => Err(ParseError::UnexpectedEnd),
+        }
+    }
+
+    fn union(&mut self) -> Result<TypePattern,ParseError> {
+        let mut alternatives = vec![self.application()?];
+        while self.peek() == Some("|") {
+            self.next();
+            alternatives.push(self.application()?);
+        }
+        Ok(if alternatives.len() == 1 { alternatives.remove(0) }
+           else                       { TypePattern::Union(alternatives) })
+    }
+
+    fn application(&mut self) -> Result<TypePattern,ParseError> {
+        let head = self.atom()?;
+        let mut args = Vec::new();
+        while self.peek().map_or(false, |token| token != "|" && token != "," && token != ")"
+                                                && token != "]") {
+            args.push(self.atom()?);
+        }
+        match head {
+            _ if args.is_empty()                  => Ok(head),
+            TypePattern::Constructor {name,..}    => Ok(TypePattern::Constructor {name,args}),
+            TypePattern::Variable(name)           => Err(ParseError::AppliedVariable {name}),
+            _                                     => {
+                let token = self.tokens[self.offset - 1].clone();
+                Err(ParseError::UnexpectedToken {token})
+            }
+        }
+    }
+
+    fn atom(&mut self) -> Result<TypePattern,ParseError> {
+        let token = self.next().ok_or(ParseError::UnexpectedEnd)?;
+        let is_name = token.starts_with(char::is_alphanumeric);
+        match token.as_str() {
+            "[" => {
+                let element = self.union()?;
+                self.expect("]")?;
+                Ok(TypePattern::List(Box::new(element)))
+            }
+            "(" => {
+                let mut elements = vec![self.union()?];
+                while self.peek() == Some(",") {
+                    self.next();
+                    elements.push(self.union()?);
+                }
+                self.expect(")")?;
+                Ok(if elements.len() == 1 { elements.remove(0) }
+                   else                   { TypePattern::Tuple(elements) })
+            }
+            "Any" => Ok(TypePattern::Any),
+            name if name.starts_with(char::is_lowercase) =>
+                Ok(TypePattern::Variable(name.to_string())),
+            name if is_name => {
+                let name = name.to_string();
+                let args = default();
+                Ok(TypePattern::Constructor {name,args})
+            }
+            _ => Err(ParseError::UnexpectedToken {token:token.clone()}),
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(code:&str) -> TypePattern {
+        TypePattern::parse(code).unwrap()
+    }
+
+    fn matches(pattern:&str, tp:&str) -> bool {
+        parse(pattern).matches(&parse(tp))
+    }
+
+    fn name(name:&str) -> TypePattern {
+        TypePattern::Constructor {name:name.into(),args:default()}
+    }
+
+    #[test]
+    fn parsing() {
+        use TypePattern::*;
+        assert_eq!(parse("Any"),Any);
+        assert_eq!(parse("a"),Variable("a".into()));
+        assert_eq!(parse("[Int]"),List(Box::new(name("Int"))));
+        assert_eq!(parse("(Int, a)"),Tuple(vec![name("Int"),Variable("a".into())]));
+        assert_eq!(parse("(Int)"),name("Int"));
+        assert_eq!(parse("Int|Text"),Union(vec![name("Int"),name("Text")]));
+        let maybe = Constructor {name:"Maybe".into(),args:vec![List(Box::new(name("Int")))]};
+        assert_eq!(parse("Maybe [Int]"),maybe);
+        assert_eq!(parse("Standard.Base.Text"),name("Standard.Base.Text"));
+
+        assert_eq!(TypePattern::parse("[Int"),Err(ParseError::UnexpectedEnd));
+        assert_eq!(TypePattern::parse("Int]"),Err(ParseError::UnexpectedToken {token:"]".into()}));
+        let applied = ParseError::AppliedVariable {name:"a".into()};
+        assert_eq!(TypePattern::parse("a Int"),Err(applied));
+    }
+
+    #[test]
+    fn matching() {
+        for pattern in &["[Int]","[Number]","[a]","a","Any"] {
+            assert!(matches(pattern,"[Int]"),"`{}` should match `[Int]`",pattern);
+        }
+        assert!(!matches("[Text]","[Int]"));
+        assert!(!matches("[Int]","[Number]"));
+        assert!(!matches("Int","[Int]"));
+        assert!(!matches("[Int]","Any"));
+
+        assert!(matches("(a,a)","(Int,Int)"));
+        assert!(!matches("(a,a)","(Int,Text)"));
+        assert!(matches("(Number,Number)","(Int,Float)"));
+        assert!(matches("[(Number,Number)]","[(Int,Float)]"));
+
+        assert!(matches("Int|Text","Text"));
+        assert!(matches("Int|Text","Int|Text"));
+        assert!(!matches("Int","Int|Text"));
+        assert!(matches("Number","Int|Float"));
+
+        assert!(matches("Maybe a","Maybe Int"));
+        assert!(!matches("Maybe Int","Either Int"));
+        assert!(matches("[b]","[a]"));
+        assert!(matches("a","b"));
+        assert!(matches("(a,a)","(b,b)"));
+        assert!(!matches("(a,a)","(b,c)"));
+        assert!(!matches("[Int]","[a]"));
+        assert!(!matches("[a]","b"));
+        assert!(!matches("Int","b"));
+    }
+
+    #[test]
+    fn specificity() {
+        let ordered = ["Any","a","[a]","[Number]","[Int]"];
+        for pair in ordered.windows(2) {
+            let less = parse(pair[0]).specificity();
+            let more = parse(pair[1]).specificity();
+            assert!(less < more,"`{}` should be less specific than `{}`",pair[0],pair[1]);
+        }
+        assert!(parse("Int|Text").specificity() < parse("Int").specificity());
+    }
+
+    #[test]
+    fn matching_enso_types() {
+        let tp = EnsoType::from("[Int]");
+        assert!(tp.is_matched_by(&"[a]".into()));
+        assert!(!tp.is_matched_by(&"[Text]".into()));
+        let unparsable = EnsoType::from("[Int");
+        assert!(unparsable.is_matched_by(&"[Int".into()));
+        assert!(!unparsable.is_matched_by(&"a".into()));
+    }
+}
@@ -0,0 +1,455 @@
+//! Automatic layout of graph nodes, following the layered (Sugiyama-style) approach.
+//!
+//! The nodes are assigned to layers so that every connection goes downwards, following the data
+//! flow. Then the nodes in each layer are reordered to minimise the number of crossing
+//! connections, and finally they get their coordinates. Nodes with fixed positions are never
+//! moved, but the connections to them are taken into account: the arranged nodes are placed below
+//! and in line with their fixed inputs, without overlapping any fixed node. The layers of nodes
+//! without fixed inputs are placed below all the fixed nodes.
+
+use crate::prelude::*;
+
+use std::collections::VecDeque;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// Horizontal gap between neighbouring nodes in the same layer.
+pub const NODE_GAP : f32 = 30.0;
+
+/// Vertical gap between consecutive layers.
+pub const LAYER_GAP : f32 = 44.0;
+
+/// How many times the layers are swept (down and up) when minimising crossings.
+const ORDERING_SWEEPS : usize = 8;
+
+
+
+// ============
+// === Node ===
+// ============
+
+/// A node to be laid out.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct Node<Id> {
+    /// The node identifier.
+    pub id    : Id,
+    /// The node size.
+    pub size  : Vector2,
+    /// The node position, if it should be kept as is.
+    pub fixed : Option<Vector2>,
+}
+
+impl<Id> Node<Id> {
+    /// Creates a node which should be arranged.
+    pub fn new(id:Id, size:Vector2) -> Self {
+        let fixed = None;
+        Node {id,size,fixed}
+    }
+
+    /// Creates a node which should stay at the given position.
+    pub fn fixed(id:Id, size:Vector2, position:Vector2) -> Self {
+        let fixed = Some(position);
+        Node {id,size,fixed}
+    }
+}
+
+
+
+// ==============
+// === Layout ===
+// ==============
+
+/// Vertex of the layered graph: either an arranged node or a dummy vertex placed on a connection
+/// spanning more than one layer.
+#[derive(Clone,Copy,Debug)]
+struct Vertex {
+    /// Index of the arranged node, `None` for dummy vertices.
+    node  : Option<usize>,
+    layer : usize,
+    width : f32,
+}
+
+/// Area occupied by a node with fixed position.
+#[derive(Clone,Copy,Debug)]
+struct Rect {
+    position : Vector2,
+    size     : Vector2,
+}
+
+impl Rect {
+    fn center_x(&self) -> f32 {
+        self.position.x + self.size.x / 2.0
+    }
+
+    /// Checks whether the rectangle is closer than `NODE_GAP` horizontally to the other one, while
+    /// overlapping with it vertically.
+    fn collides_with(&self, other:&Rect) -> bool {
+        let horizontal = self.position.x < other.position.x + other.size.x + NODE_GAP
+            && other.position.x < self.position.x + self.size.x + NODE_GAP;
+        let vertical   = self.position.y < other.position.y + other.size.y
+            && other.position.y < self.position.y + self.size.y;
+        horizontal && vertical
+    }
+}
+
+/// The layered graph layout of the given nodes and connections between them.
+#[derive(Clone,Debug)]
+pub struct Layout<Id> {
+    nodes : Vec<Node<Id>>,
+    edges : Vec<(Id,Id)>,
+}
+
+impl<Id:Copy+Eq+Hash> Layout<Id> {
+    /// Constructor. Each edge is a pair of source and target node ids, i.e. the data flows from
+    /// the first to the second one. Edges referring to unknown nodes are ignored.
+    pub fn new(nodes:Vec<Node<Id>>, edges:Vec<(Id,Id)>) -> Self {
+        Layout {nodes,edges}
+    }
+
+    /// Computes positions of all the nodes which do not have fixed positions.
+    pub fn arrange(&self) -> HashMap<Id,Vector2> {
+        let free = self.nodes.iter().filter(|node| node.fixed.is_none()).collect_vec();
+        if free.is_empty() {
+            return default()
+        }
+        let index : HashMap<Id,usize> = free.iter().enumerate().map(|(ix,n)| (n.id,ix)).collect();
+        let edges = self.edges.iter().filter_map(|(source,target)| {
+            let source = *index.get(source)?;
+            let target = *index.get(target)?;
+            (source != target).as_some((source,target))
+        }).collect_vec();
+        let edges            = acyclic(free.len(),&edges);
+        let layers           = assign_layers(free.len(),&edges);
+        let widths           = free.iter().map(|node| node.size.x).collect_vec();
+        let (vertices,links) = insert_dummies(&layers,&widths,&edges);
+        let ordering         = order_layers(&vertices,&links);
+        self.coordinates(&free,&vertices,&links,&ordering)
+    }
+
+    /// Assigns coordinates to ordered layers. Every layer is placed below the previous one and
+    /// below the fixed inputs of its nodes. The first layer, if none of its nodes has fixed
+    /// inputs, is placed below all the fixed nodes.
+    fn coordinates
+    ( &self
+    , free     : &[&Node<Id>]
+    , vertices : &[Vertex]
+    , links    : &[(usize,usize)]
+    , ordering : &[Vec<usize>]
+    ) -> HashMap<Id,Vector2> {
+        let fixed : HashMap<Id,Rect> = self.nodes.iter().filter_map(|node| {
+            node.fixed.map(|position| (node.id,Rect {position,size:node.size}))
+        }).collect();
+        let fixed_neighbours = |node:usize, incoming:bool| {
+            let id = free[node].id;
+            self.edges.iter().filter_map(|(source,target)| {
+                let (this,other) = if incoming { (target,source) } else { (source,target) };
+                if *this == id { fixed.get(other) } else { None }
+            }).collect_vec()
+        };
+        let inputs  = (0..free.len()).map(|node| fixed_neighbours(node,true)).collect_vec();
+        let outputs = (0..free.len()).map(|node| fixed_neighbours(node,false)).collect_vec();
+        let left    = fixed.values().map(|rect| rect.position.x).fold(None,min_opt).unwrap_or(0.0);
+        let bottom  = fixed.values().map(|rect| rect.position.y).fold(None,min_opt);
+        let height  = |vertex:&usize| vertices[*vertex].node.map_or(0.0,|node| free[node].size.y);
+        let heights = ordering.iter().map(|layer| layer.iter().map(height).fold(0.0,f32::max));
+        let heights = heights.collect_vec();
+
+        let mut previous_y = None;
+        let mut positions  = vec![Vector2(0.0,0.0); vertices.len()];
+        for (layer_ix,layer) in ordering.iter().enumerate() {
+            let nodes        = layer.iter().filter_map(|vertex| vertices[*vertex].node);
+            let below_inputs = nodes.flat_map(|node| inputs[node].iter().map(|r| r.position.y));
+            let below_inputs = below_inputs.fold(None,min_opt);
+            let top          = match (previous_y,below_inputs) {
+                (Some(previous),Some(inputs)) => Some(previous.min(inputs)),
+                (Some(previous),None)         => Some(previous),
+                (None,inputs)                 => inputs.or(bottom),
+            };
+            let y = top.map_or(0.0, |top| top - LAYER_GAP - heights[layer_ix]);
+            let mut next_free_x = left;
+            for vertex in layer {
+                let width    = vertices[*vertex].width;
+                let node     = vertices[*vertex].node;
+                let parents  = links.iter().filter(|(_,lower)| lower == vertex).map(|(upper,_)| {
+                    positions[*upper].x + vertices[*upper].width / 2.0
+                });
+                let parents  = parents.chain(node.into_iter().flat_map(|node| {
+                    inputs[node].iter().map(|input| input.center_x())
+                })).collect_vec();
+                let children = node.into_iter().flat_map(|node| outputs[node].iter());
+                let children = children.map(|output| output.center_x()).collect_vec();
+                let aligned  = if parents.is_empty() { children } else { parents };
+                // Prefer placing the vertex right below its parents (or above its children, if it
+                // has no parents), but never overlapping the vertex on its left or fixed nodes.
+                let desired  = if aligned.is_empty() { next_free_x } else {
+                    aligned.iter().sum::<f32>() / aligned.len() as f32 - width / 2.0
+                };
+                let size     = Vector2(width,height(vertex));
+                let mut rect = Rect {position:Vector2(desired.max(next_free_x),y),size};
+                while let Some(obstacle) = fixed.values().find(|other| rect.collides_with(other)) {
+                    rect.position.x = obstacle.position.x + obstacle.size.x + NODE_GAP;
+                }
+                positions[*vertex] = rect.position;
+                next_free_x        = rect.position.x + width + NODE_GAP;
+            }
+            previous_y = Some(y);
+        }
+        vertices.iter().zip(positions).filter_map(|(vertex,position)| {
+            vertex.node.map(|node| (free[node].id,position))
+        }).collect()
+    }
+}
+
+
+
+// ==================
+// === Algorithms ===
+// ==================
+
+fn min_opt(acc:Option<f32>, value:f32) -> Option<f32> {
+    Some(acc.map_or(value, |acc| acc.min(value)))
+}
+
+/// Reverses the edges closing cycles, so the graph becomes acyclic. The edges are given as pairs
+/// of node indices.
+fn acyclic(node_count:usize, edges:&[(usize,usize)]) -> Vec<(usize,usize)> {
+    #[derive(Clone,Copy,PartialEq)]
+    enum State { New, OnStack, Done }
+    let mut state  = vec![State::New; node_count];
+    let mut result = Vec::with_capacity(edges.len());
+    for root in 0..node_count {
+        if state[root] != State::New { continue }
+        // Stack of (node, index of the next outgoing edge to visit).
+        let mut stack = vec![(root,0)];
+        state[root]   = State::OnStack;
+        while let Some((node,next_edge)) = stack.pop() {
+            let outgoing = edges.iter().enumerate().skip(next_edge).find(|(_,(s,_))| *s == node);
+            match outgoing {
+                None => state[node] = State::Done,
+                Some((edge_ix,(_,target))) => {
+                    stack.push((node,edge_ix + 1));
+                    match state[*target] {
+                        State::OnStack => result.push((*target,node)),
+                        State::Done    => result.push((node,*target)),
+                        State::New     => {
+                            result.push((node,*target));
+                            state[*target] = State::OnStack;
+                            stack.push((*target,0));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Assigns each node the layer equal to the longest path leading to it in the acyclic graph.
+fn assign_layers(node_count:usize, edges:&[(usize,usize)]) -> Vec<usize> {
+    let mut in_degree = vec![0; node_count];
+    for (_,target) in edges {
+        in_degree[*target] += 1;
+    }
+    let mut layers = vec![0; node_count];
+    let mut queue  = (0..node_count).filter(|node| in_degree[*node] == 0).collect::<VecDeque<_>>();
+    while let Some(node) = queue.pop_front() {
+        for (_,target) in edges.iter().filter(|(source,_)| *source == node) {
+            layers[*target]     = layers[*target].max(layers[node] + 1);
+            in_degree[*target] -= 1;
+            if in_degree[*target] == 0 {
+                queue.push_back(*target);
+            }
+        }
+    }
+    layers
+}
+
+/// Creates the layered graph vertices, splitting edges spanning multiple layers with dummy
+/// vertices, so each link connects consecutive layers. Returns vertices and links, the links
+/// going from an upper to a lower vertex.
+fn insert_dummies
+(layers:&[usize], widths:&[f32], edges:&[(usize,usize)]) -> (Vec<Vertex>,Vec<(usize,usize)>) {
+    let mut vertices = layers.iter().zip(widths).enumerate().map(|(node,(layer,width))| {
+        Vertex {node:Some(node),layer:*layer,width:*width}
+    }).collect_vec();
+    let mut links = Vec::new();
+    for (source,target) in edges {
+        let mut upper = *source;
+        for layer in layers[*source] + 1 .. layers[*target] {
+            vertices.push(Vertex {node:None,layer,width:0.0});
+            links.push((upper,vertices.len() - 1));
+            upper = vertices.len() - 1;
+        }
+        links.push((upper,*target));
+    }
+    (vertices,links)
+}
+
+/// Orders vertices in each layer to minimise the crossings, using the barycenter heuristic.
+fn order_layers(vertices:&[Vertex], links:&[(usize,usize)]) -> Vec<Vec<usize>> {
+    let layer_count = vertices.iter().map(|vertex| vertex.layer + 1).max().unwrap_or(0);
+    let mut layers  = vec![Vec::new(); layer_count];
+    for (ix,vertex) in vertices.iter().enumerate() {
+        layers[vertex.layer].push(ix);
+    }
+    let mut best           = layers.clone();
+    let mut best_crossings = count_crossings(&layers,links);
+    for _ in 0..ORDERING_SWEEPS {
+        for layer in 1..layer_count {
+            reorder_by_barycenter(&mut layers,layer,layer - 1,links);
+        }
+        for layer in (0..layer_count.saturating_sub(1)).rev() {
+            reorder_by_barycenter(&mut layers,layer,layer + 1,links);
+        }
+        let crossings = count_crossings(&layers,links);
+        if crossings < best_crossings {
+            best           = layers.clone();
+            best_crossings = crossings;
+        }
+        if best_crossings == 0 { break }
+    }
+    best
+}
+
+/// Sorts the vertices of the `layer` by the mean position of their neighbours in the `fixed`
+/// layer. Vertices without such neighbours keep their current position.
+fn reorder_by_barycenter
+(layers:&mut [Vec<usize>], layer:usize, fixed:usize, links:&[(usize,usize)]) {
+    let position  = |vertex:usize| layers[fixed].iter().position(|v| *v == vertex);
+    let neighbour = |(upper,lower):&(usize,usize), vertex:usize| {
+        if *lower == vertex { Some(*upper) } else if *upper == vertex { Some(*lower) } else { None }
+    };
+    let mut keyed = layers[layer].iter().enumerate().map(|(current,vertex)| {
+        let positions = links.iter().filter_map(|link| neighbour(link,*vertex).and_then(&position));
+        let positions = positions.collect_vec();
+        let key       = if positions.is_empty() { current as f32 } else {
+            positions.iter().sum::<usize>() as f32 / positions.len() as f32
+        };
+        (key,*vertex)
+    }).collect_vec();
+    keyed.sort_by(|(a,_),(b,_)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    layers[layer] = keyed.into_iter().map(|(_,vertex)| vertex).collect();
+}
+
+/// Counts the pairs of crossing links.
+fn count_crossings(layers:&[Vec<usize>], links:&[(usize,usize)]) -> usize {
+    let position = |vertex:usize| {
+        layers.iter().find_map(|layer| layer.iter().position(|v| *v == vertex)).unwrap_or(0)
+    };
+    let links = links.iter().map(|(upper,lower)| (position(*upper),position(*lower))).collect_vec();
+    let mut crossings = 0;
+    for (ix,(upper1,lower1)) in links.iter().enumerate() {
+        for (upper2,lower2) in &links[ix + 1..] {
+            let upper_order = *upper1 as isize - *upper2 as isize;
+            let lower_order = *lower1 as isize - *lower2 as isize;
+            if upper_order * lower_order < 0 {
+                crossings += 1;
+            }
+        }
+    }
+    crossings
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id:usize) -> Node<usize> {
+        Node::new(id,Vector2(100.0,28.0))
+    }
+
+    #[test]
+    fn chain_follows_data_flow() {
+        let layout    = Layout::new(vec![node(0),node(1),node(2)],vec![(1,2),(0,1)]);
+        let positions = layout.arrange();
+        assert_eq!(positions.len(), 3);
+        assert!(positions[&0].y > positions[&1].y);
+        assert!(positions[&1].y > positions[&2].y);
+        assert_eq!(positions[&0].x, positions[&1].x);
+        assert_eq!(positions[&1].x, positions[&2].x);
+    }
+
+    #[test]
+    fn nodes_in_layer_do_not_overlap() {
+        let layout    = Layout::new(vec![node(0),node(1),node(2)],vec![(0,1),(0,2)]);
+        let positions = layout.arrange();
+        assert_eq!(positions[&1].y, positions[&2].y);
+        assert!((positions[&1].x - positions[&2].x).abs() >= 100.0 + NODE_GAP);
+    }
+
+    #[test]
+    fn crossings_are_removed() {
+        let nodes     = vec![node(0),node(1),node(2),node(3)];
+        let layout    = Layout::new(nodes,vec![(0,3),(1,2)]);
+        let positions = layout.arrange();
+        assert!(positions[&0].x < positions[&1].x);
+        assert!(positions[&3].x < positions[&2].x);
+    }
+
+    #[test]
+    fn long_edges_get_dummy_vertices() {
+        let layers           = [0,1,2];
+        let widths           = [10.0,10.0,10.0];
+        let edges            = [(0,1),(1,2),(0,2)];
+        let (vertices,links) = insert_dummies(&layers,&widths,&edges);
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(vertices[3].node, None);
+        assert_eq!(vertices[3].layer, 1);
+        assert_eq!(links, vec![(0,1),(1,2),(0,3),(3,2)]);
+    }
+
+    #[test]
+    fn cycles_are_broken() {
+        let layout    = Layout::new(vec![node(0),node(1)],vec![(0,1),(1,0)]);
+        let positions = layout.arrange();
+        assert_eq!(positions.len(), 2);
+        assert!(positions[&0].y > positions[&1].y);
+    }
+
+    #[test]
+    fn fixed_nodes_are_kept() {
+        let fixed     = Node::fixed(0,Vector2(100.0,28.0),Vector2(50.0,-100.0));
+        let layout    = Layout::new(vec![fixed,node(1),node(2)],vec![(0,1),(1,2)]);
+        let positions = layout.arrange();
+        assert!(!positions.contains_key(&0));
+        assert_eq!(positions[&1].x, 50.0);
+        assert!(positions[&1].y + 28.0 <= -100.0);
+        assert!(positions[&2].y < positions[&1].y);
+    }
+
+    #[test]
+    fn nodes_are_placed_below_their_fixed_inputs() {
+        let size      = Vector2(100.0,28.0);
+        let input     = Node::fixed(0,size,Vector2(500.0,-100.0));
+        let other     = Node::fixed(1,size,Vector2(0.0,-400.0));
+        let nodes     = vec![input,other,node(2),node(3)];
+        let layout    = Layout::new(nodes,vec![(0,2),(2,3)]);
+        let positions = layout.arrange();
+        assert_eq!(positions[&2].x, 500.0);
+        assert!(positions[&2].y + 28.0 <= -100.0);
+        assert!(positions[&2].y > -400.0);
+        assert_eq!(positions[&3].x, 500.0);
+        assert!(positions[&3].y < positions[&2].y);
+    }
+
+    #[test]
+    fn arranged_nodes_do_not_overlap_fixed_ones() {
+        let size      = Vector2(100.0,28.0);
+        let input     = Node::fixed(0,size,Vector2(0.0,0.0));
+        let output    = Node::fixed(1,size,Vector2(0.0,-72.0));
+        let layout    = Layout::new(vec![input,output,node(2)],vec![(0,1),(0,2)]);
+        let positions = layout.arrange();
+        assert_eq!(positions[&2].y, -72.0);
+        assert!(positions[&2].x >= 100.0 + NODE_GAP);
+    }
+}
@@ -32,6 +32,7 @@ pub mod component;
 
 pub mod builtin;
 pub mod data;
+pub mod layout;
 
 /// Common types and functions usable in all modules of this crate.
 pub mod prelude {
@@ -66,7 +67,7 @@ use ensogl::gui::component::Tween;
 // =================
 
 const SNAP_DISTANCE_THRESHOLD         : f32 = 10.0;
-const VIZ_PREVIEW_MODE_TOGGLE_TIME_MS : f32 = 300.0;
+const VIZ_PREVIEW_MODE_TOGGLE_TIME_MS : frp::clock::Time = 300.0;
 
 
 
@@ -299,6 +300,10 @@ ensogl::def_status_api! { FrpStatus
     is_active,
     /// Checks whether this graph editor instance is empty.
     is_empty,
+    /// Checks whether the comment of a node is being edited.
+    is_editing_comment,
+    /// Checks whether the command palette is shown over the graph editor.
+    is_command_palette_visible,
 }
 
 ensogl::def_command_api! { Commands
@@ -356,6 +361,20 @@ ensogl::def_command_api! { Commands
 
     /// Switches the selected visualisation to/from fullscreen mode.
     toggle_fullscreen_for_selected_visualization,
+    /// Detaches the visualization of the selected node into a panel which can be freely moved and resized, or attaches it back to the node.
+    toggle_panel_for_selected_visualization,
+
+    /// Turns the selected nodes into widgets displaying their visualizations, or turns the widgets back into nodes displaying their expressions.
+    toggle_widget_for_selected_node,
+
+    /// Arrange the selected nodes with the automatic layout, keeping the other nodes in place.
+    auto_arrange_selected_nodes,
+
+    /// Open the inline editor of the documentation comment of the selected node.
+    edit_comment_for_selected_node,
+
+    /// Save the comment being edited in the inline editor and close the editor.
+    commit_comment,
 
 
     /// Cancel the operation being currently performed. Often mapped to the escape key.
@@ -394,6 +413,13 @@ impl Commands {
             cycle_visualization_for_selected_node         <- source();
 
             toggle_fullscreen_for_selected_visualization <- source();
+            toggle_panel_for_selected_visualization      <- source();
+            toggle_widget_for_selected_node              <- source();
+
+            auto_arrange_selected_nodes <- source();
+
+            edit_comment_for_selected_node <- source();
+            commit_comment                 <- source();
 
             cancel <- source();
         }
@@ -405,7 +431,9 @@ impl Commands {
              ,enable_node_subtract_select,disable_node_subtract_select,toggle_node_subtract_select
              ,enable_node_inverse_select,disable_node_inverse_select,toggle_node_inverse_select
              ,set_test_visualization_data_for_selected_node,cycle_visualization_for_selected_node
-             ,toggle_fullscreen_for_selected_visualization,cancel}
+             ,toggle_fullscreen_for_selected_visualization,toggle_panel_for_selected_visualization
+             ,toggle_widget_for_selected_node,auto_arrange_selected_nodes
+             ,edit_comment_for_selected_node,commit_comment,cancel}
     }
 }
 
@@ -437,12 +465,24 @@ pub struct FrpInputs {
     pub remove_node                  : frp::Source<NodeId>,
     pub set_node_expression          : frp::Source<(NodeId,node::Expression)>,
     pub set_node_position            : frp::Source<(NodeId,Vector2)>,
+    pub set_node_type                : frp::Source<(NodeId,Option<data::EnsoType>)>,
+    pub set_node_kind                : frp::Source<(NodeId,node::Kind)>,
+    pub set_node_comment             : frp::Source<(NodeId,String)>,
     pub cycle_visualization          : frp::Source<NodeId>,
     pub set_visualization            : frp::Source<(NodeId,Option<visualization::Path>)>,
-    pub register_visualization : frp::Source<Option<visualization::Definition>>,
+    pub enable_visualization         : frp::Source<NodeId>,
+    pub disable_visualization        : frp::Source<NodeId>,
+    pub register_visualization       : frp::Source<Option<visualization::Definition>>,
+    pub unregister_visualization     : frp::Source<Option<visualization::Path>>,
     pub set_visualization_data       : frp::Source<(NodeId,visualization::Data)>,
+    pub set_visualization_data_error : frp::Source<(NodeId,Option<visualization::DataError>)>,
+    pub set_visualization_panel      : frp::Source<(NodeId,Option<visualization::PanelLayout>)>,
+
+    pub set_command_palette_visibility : frp::Source<bool>,
 
     hover_node_input           : frp::Source<Option<EdgeTarget>>,
+    node_widget_data           : frp::Source<(NodeId,serde_json::Value)>,
+    node_panel_layout          : frp::Source<(NodeId,Option<visualization::PanelLayout>)>,
     hover_node_output          : frp::Source<Option<EdgeTarget>>,
     some_edge_targets_detached : frp::Source,
     some_edge_sources_detached : frp::Source,
@@ -473,12 +513,24 @@ impl FrpInputs {
             def remove_node                  = source();
             def set_node_expression          = source();
             def set_node_position            = source();
+            def set_node_type                = source();
+            def set_node_kind                = source();
+            def set_node_comment             = source();
             def set_visualization_data       = source();
+            def set_visualization_data_error = source();
             def cycle_visualization          = source();
             def set_visualization            = source();
-            def register_visualization = source();
+            def enable_visualization         = source();
+            def disable_visualization        = source();
+            def register_visualization       = source();
+            def unregister_visualization     = source();
+            def set_visualization_panel      = source();
+
+            def set_command_palette_visibility = source();
 
             def hover_node_input           = source();
+            def node_widget_data           = source();
+            def node_panel_layout          = source();
             def hover_node_output          = source();
             def some_edge_targets_detached = source();
             def some_edge_sources_detached = source();
@@ -491,9 +543,13 @@ impl FrpInputs {
              ,remove_all_node_input_edges,remove_all_node_output_edges,set_visualization_data
              ,set_detached_edge_targets,set_edge_source,set_edge_target
              ,unset_edge_source,unset_edge_target
-             ,set_node_position,select_node,remove_node,set_node_expression
+             ,set_node_position,set_node_type,set_node_kind,set_node_comment,select_node,remove_node
+             ,set_node_expression,node_widget_data,set_visualization_panel,node_panel_layout
+             ,set_command_palette_visibility
              ,connect_nodes,deselect_all_nodes,cycle_visualization,set_visualization
-             ,register_visualization,some_edge_targets_detached,some_edge_sources_detached
+             ,enable_visualization,disable_visualization,set_visualization_data_error
+             ,register_visualization,unregister_visualization,some_edge_targets_detached
+             ,some_edge_sources_detached
              ,all_edge_targets_attached,hover_node_input,all_edge_sources_attached
              ,hover_node_output,press_node_output,set_detached_edge_sources,all_edges_attached
              }
@@ -596,6 +652,11 @@ generate_frp_outputs! {
     visualization_disabled : NodeId,
     visualization_enable_fullscreen : NodeId,
     visualization_set_preprocessor  : (NodeId,data::EnsoCode),
+    visualization_changed           : (NodeId,Option<visualization::Signature>),
+    node_kind_changed               : (NodeId,node::Kind),
+    widget_data_changed             : (NodeId,serde_json::Value),
+    visualization_panel_changed     : (NodeId,Option<visualization::PanelLayout>),
+    node_comment_changed            : (NodeId,String),
 
 }
 
@@ -609,9 +670,11 @@ generate_frp_outputs! {
 #[derive(Clone,CloneRef,Debug,Shrinkwrap)]
 pub struct Node {
     #[shrinkwrap(main_field)]
-    pub view      : component::Node,
-    pub in_edges  : SharedHashSet<EdgeId>,
-    pub out_edges : SharedHashSet<EdgeId>,
+    pub view             : component::Node,
+    pub in_edges         : SharedHashSet<EdgeId>,
+    pub out_edges        : SharedHashSet<EdgeId>,
+    chosen_visualization : Rc<RefCell<Option<visualization::Signature>>>,
+    value_type           : Rc<RefCell<Option<data::EnsoType>>>,
 }
 
 #[derive(Clone,CloneRef,Copy,Debug,Default,Display,Eq,From,Hash,Into,PartialEq)]
@@ -619,14 +682,44 @@ pub struct NodeId(pub Id);
 
 impl Node {
     pub fn new(view:component::Node) -> Self {
-        let in_edges  = default();
-        let out_edges = default();
-        Self {view,in_edges,out_edges}
+        let in_edges             = default();
+        let out_edges            = default();
+        let chosen_visualization = default();
+        let value_type           = default();
+        Self {view,in_edges,out_edges,chosen_visualization,value_type}
     }
 
     pub fn id(&self) -> NodeId {
         self.view.id().into()
     }
+
+    /// The type of the node's value. If it is not known, it is `Any`.
+    pub fn value_type(&self) -> data::EnsoType {
+        self.value_type.borrow().clone().unwrap_or_else(data::EnsoType::any)
+    }
+
+    /// Sets the type of the node's value.
+    pub fn set_value_type(&self, tp:Option<data::EnsoType>) {
+        self.value_type.replace(tp);
+    }
+
+    /// The signature of the visualization chosen for this node, if any was chosen.
+    pub fn visualization_signature(&self) -> Option<visualization::Signature> {
+        self.chosen_visualization.borrow().clone()
+    }
+
+    /// Sets the visualization of this node, remembering its signature.
+    pub fn set_visualization
+    (&self, signature:visualization::Signature, instance:visualization::Instance) {
+        self.chosen_visualization.replace(Some(signature));
+        self.view.visualization.frp.set_visualization.emit(Some(instance));
+    }
+
+    /// Removes the visualization of this node.
+    pub fn unset_visualization(&self) {
+        self.chosen_visualization.replace(None);
+        self.view.visualization.frp.set_visualization.emit(None);
+    }
 }
 
 impl display::Object for Node {
@@ -984,6 +1077,14 @@ impl GraphEditorModelWithNetwork {
              });
 
              eval_ node.view.output_ports.frp.port_mouse_out ( model.frp.hover_node_output.emit(None));
+
+             eval node.visualization.frp.widget_data ([model](data)
+                model.frp.node_widget_data.emit((node_id,data.clone()));
+             );
+
+             eval node.visualization.frp.panel_layout ([model](layout)
+                model.frp.node_panel_layout.emit((node_id,*layout));
+             );
         }
 
 //        self.visualizations.push(node.visualization().clone_ref());
@@ -1130,7 +1231,10 @@ impl GraphEditorModel {
     fn disable_visualization(&self, node_id:impl Into<NodeId>) {
         let node_id = node_id.into();
         if let Some(node) = self.nodes.get_cloned_ref(&node_id) {
-            node.visualization.frp.set_visibility.emit(false);
+            // Widgets always display their visualizations.
+            if node.kind() != node::Kind::Widget {
+                node.visualization.frp.set_visibility.emit(false);
+            }
         }
     }
 
@@ -1141,10 +1245,71 @@ impl GraphEditorModel {
         }
     }
 
+    fn set_visualization_panel
+    (&self, node_id:impl Into<NodeId>, layout:Option<visualization::PanelLayout>) {
+        let node_id = node_id.into();
+        if let Some(node) = self.nodes.get_cloned_ref(&node_id) {
+            node.visualization.frp.set_panel_layout.emit(layout);
+        }
+    }
+
+    fn set_node_comment(&self, node_id:impl Into<NodeId>, comment:&str) {
+        let node_id = node_id.into();
+        if let Some(node) = self.nodes.get_cloned_ref(&node_id) {
+            node.frp.set_comment.emit(comment.to_string());
+        }
+    }
+
+    /// The place where the comment of the given node is edited and its current content.
+    fn node_comment_edit_info(&self, node_id:impl Into<NodeId>) -> Option<(Vector2,String)> {
+        let node_id  = node_id.into();
+        let node     = self.nodes.get_cloned_ref(&node_id)?;
+        let offset   = Vector2::new(node::TEXT_OFF,node::NODE_HEIGHT+node::COMMENT_OFF);
+        let position = node.position().xy() + offset;
+        Some((position,node.comment()))
+    }
+
+    /// The panel layouts of the visible visualizations of the given nodes after toggling them.
+    /// A visualization is detached into a panel at the place where it is currently displayed.
+    fn toggled_visualization_panels
+    (&self, node_ids:&[NodeId]) -> Vec<(NodeId,Option<visualization::PanelLayout>)> {
+        node_ids.iter().filter_map(|node_id| {
+            let node          = self.nodes.get_cloned_ref(node_id)?;
+            let visualization = &node.visualization;
+            visualization.is_visible().as_some_from(|| {
+                let layout = match visualization.panel_layout() {
+                    Some(_) => None,
+                    None    => {
+                        let position = visualization.global_position().xy();
+                        Some(visualization::PanelLayout::new(position,visualization.size()))
+                    }
+                };
+                (*node_id,layout)
+            })
+        }).collect()
+    }
+
+    /// The kinds the given nodes get when toggled between the widgets and the nodes displaying
+    /// their expressions.
+    fn toggled_node_kinds(&self, node_ids:&[NodeId]) -> Vec<(NodeId,node::Kind)> {
+        node_ids.iter().filter_map(|node_id| {
+            let node = self.nodes.get_cloned_ref(node_id)?;
+            let kind = match node.kind() {
+                node::Kind::Widget     => node::Kind::Expression,
+                node::Kind::Expression => node::Kind::Widget,
+            };
+            Some((*node_id,kind))
+        }).collect()
+    }
+
     /// Warning! This function does not remove connected edges. It needs to be handled by the
     /// implementation.
     fn remove_node(&self, node_id:impl Into<NodeId>) {
         let node_id = node_id.into();
+        if let Some(node) = self.nodes.get_cloned_ref(&node_id) {
+            // The panels are placed directly in the scene, so they are not removed with the node.
+            node.visualization.frp.set_panel_layout.emit(None);
+        }
         self.nodes.remove(&node_id);
         self.nodes.selected.remove_item(&node_id);
     }
@@ -1295,6 +1460,25 @@ impl GraphEditorModel {
         }
     }
 
+    /// Computes new positions of the given nodes using the automatic layout. All the other nodes
+    /// stay where they are.
+    pub fn auto_arranged_positions(&self, node_ids:&[NodeId]) -> Vec<(NodeId,Vector2)> {
+        let mut nodes = Vec::new();
+        self.nodes.all.for_each(|(id,node)| {
+            let size = Vector2(node.width(),node.height());
+            nodes.push(if node_ids.contains(id) { layout::Node::new(*id,size) } else {
+                layout::Node::fixed(*id,size,node.position().xy())
+            });
+        });
+        let mut edges = Vec::new();
+        self.edges.all.for_each(|(_,edge)| {
+            if let (Some(source),Some(target)) = (edge.source(),edge.target()) {
+                edges.push((source.node_id,target.node_id));
+            }
+        });
+        layout::Layout::new(nodes,edges).arrange().into_iter().collect()
+    }
+
     fn disable_grid_snapping_for(&self, node_ids:&[NodeId]) {
         self.nodes.recompute_grid(node_ids.iter().cloned().collect());
     }
@@ -1404,8 +1588,18 @@ impl application::command::Provider for GraphEditor {
 impl application::shortcut::DefaultShortcutProvider for GraphEditor {
     fn default_shortcuts() -> Vec<application::shortcut::Shortcut> {
         use keyboard::Key;
-        vec! [ Self::self_shortcut(shortcut::Action::press        (&[Key::Character("n".into())])               , "add_node_at_cursor")
-             , Self::self_shortcut(shortcut::Action::press        (&[Key::Backspace])                           , "remove_selected_nodes")
+        use shortcut::Condition;
+        // The shortcuts must not fire while the command palette is shown, and the shortcuts of
+        // keys used for typing must not fire while the user types a comment either. The selection
+        // modes and the visualization preview react to both press and release, so their shortcuts
+        // are unconditional to keep the pairs balanced.
+        let palette_visible = || Condition::Simple("is_command_palette_visible".into());
+        let editing_comment = || Condition::Simple("is_editing_comment".into());
+        let palette_hidden  = || Condition::not(palette_visible());
+        let not_typing      = || Condition::not(Condition::or(palette_visible(),editing_comment()));
+        let editing         = || Condition::and(palette_hidden(),editing_comment());
+        vec! [ Self::self_shortcut_when(shortcut::Action::press   (&[Key::Character("n".into())])               , "add_node_at_cursor", not_typing())
+             , Self::self_shortcut_when(shortcut::Action::press   (&[Key::Backspace])                           , "remove_selected_nodes", not_typing())
              , Self::self_shortcut(shortcut::Action::press        (&[Key::Control,Key::Character(" ".into())])  , "press_visualization_visibility")
              , Self::self_shortcut(shortcut::Action::double_press (&[Key::Control,Key::Character(" ".into())])  , "double_press_visualization_visibility")
              , Self::self_shortcut(shortcut::Action::release      (&[Key::Control,Key::Character(" ".into())])  , "release_visualization_visibility")
@@ -1419,8 +1613,13 @@ impl application::shortcut::DefaultShortcutProvider for GraphEditor {
              , Self::self_shortcut(shortcut::Action::release      (&[Key::Alt])                                 , "toggle_node_subtract_select")
              , Self::self_shortcut(shortcut::Action::press        (&[Key::Shift,Key::Alt])                      , "toggle_node_inverse_select")
              , Self::self_shortcut(shortcut::Action::release      (&[Key::Shift,Key::Alt])                      , "toggle_node_inverse_select")
-             , Self::self_shortcut(shortcut::Action::press        (&[Key::Character("d".into())])               , "set_test_visualization_data_for_selected_node")
-             , Self::self_shortcut(shortcut::Action::press        (&[Key::Character("f".into())]) , "cycle_visualization_for_selected_node")
+             , Self::self_shortcut_when(shortcut::Action::press   (&[Key::Character("d".into())])               , "set_test_visualization_data_for_selected_node", not_typing())
+             , Self::self_shortcut_when(shortcut::Action::press   (&[Key::Character("f".into())])               , "cycle_visualization_for_selected_node", not_typing())
+             , Self::self_shortcut_when(shortcut::Action::press   (&[Key::Control,Key::Character("l".into())])  , "auto_arrange_selected_nodes", palette_hidden())
+             , Self::self_shortcut_when(shortcut::Action::press   (&[Key::Alt,Key::Character("p".into())])      , "toggle_panel_for_selected_visualization", palette_hidden())
+             , Self::self_shortcut_when(shortcut::Action::press   (&[Key::Control,Key::Character("/".into())])  , "edit_comment_for_selected_node", palette_hidden())
+             , Self::self_shortcut_when(shortcut::Action::press   (&[Key::Control,Key::Enter])                  , "commit_comment", editing())
+             , Self::self_shortcut_when(shortcut::Action::press   (&[Key::Alt,Key::Character("w".into())])      , "toggle_widget_for_selected_node", palette_hidden())
              ]
     }
 }
@@ -1497,9 +1696,12 @@ fn new_graph_editor(world:&World) -> GraphEditor {
     let mouse          = &scene.mouse.frp;
     let touch          = &model.touch_state;
     let visualizations = visualization::Registry::with_default_visualizations();
+    let comment_editor = component::CommentEditor::new(world,network);
+    let clock          = frp::Clock::web();
     let logger         = &model.logger;
     let outputs        = UnsealedFrpOutputs::new();
     let sealed_outputs = outputs.seal(); // Done here to keep right eval order.
+    model.add_child(&comment_editor);
 
     // === Mouse Cursor Transform ===
     frp::extend! { network
@@ -1512,7 +1714,7 @@ fn new_graph_editor(world:&World) -> GraphEditor {
     frp::extend! { network
     mouse_down_target <- mouse.down.map(f_!(model.scene.mouse.target.get()));
     mouse_up_target   <- mouse.up.map(f_!(model.scene.mouse.target.get()));
-    background_up     <- mouse_up_target.map(|t| if t==&display::scene::Target::Background {Some(())} else {None}).unwrap();
+    background_up     <- mouse_up_target.filter_map(|t| (t==&display::scene::Target::Background).as_some(()));
 
     eval mouse_down_target([touch,model](target) {
         match target {
@@ -1843,6 +2045,12 @@ fn new_graph_editor(world:&World) -> GraphEditor {
 
     outputs.node_position_set         <+ inputs.set_node_position;
     outputs.node_position_set_batched <+ inputs.set_node_position;
+
+    arranged_nodes <= inputs.auto_arrange_selected_nodes.map(f_!(
+        model.auto_arranged_positions(&model.selected_nodes())
+    ));
+    outputs.node_position_set         <+ arranged_nodes;
+    outputs.node_position_set_batched <+ arranged_nodes;
     eval outputs.node_position_set (((id,pos)) model.set_node_position(id,*pos));
 
 
@@ -1951,13 +2159,20 @@ fn new_graph_editor(world:&World) -> GraphEditor {
 //
    // === Vis Set ===
 
-   def _update_vis_data = inputs.set_visualization.map(f!([logger,nodes,scene,visualizations]((node_id,vis_path)) {
+   def visualization_changed = source::<(NodeId,Option<visualization::Signature>)>();
+   outputs.visualization_changed <+ visualization_changed;
+
+   def _update_vis_data = inputs.set_visualization.map(f!([logger,nodes,scene,visualizations,visualization_changed]((node_id,vis_path)) {
        match (&nodes.get_cloned_ref(node_id), vis_path) {
             (Some(node), Some(vis_path)) => {
                 let vis_definition = visualizations.definition_from_path(vis_path);
                 if let Some(definition) = vis_definition {
                     match definition.new_instance(&scene) {
-                        Ok(vis)  => node.visualization.frp.set_visualization.emit(Some(vis)),
+                        Ok(vis)  => {
+                            let signature = definition.signature.clone_ref();
+                            node.set_visualization(signature.clone_ref(),vis);
+                            visualization_changed.emit((*node_id,Some(signature)));
+                        },
                         Err(err) => {
                             logger.warning(
                                 || format!("Failed to instantiate visualisation: {:?}",err));
@@ -1967,7 +2182,10 @@ fn new_graph_editor(world:&World) -> GraphEditor {
                     logger.warning(|| format!("Failed to get visualisation: {:?}",vis_path));
                 }
             },
-            (Some(node), None) => node.visualization.frp.set_visualization.emit(None),
+            (Some(node), None) => {
+                node.unset_visualization();
+                visualization_changed.emit((*node_id,None));
+            },
              _                 => logger.warning(|| format!("Failed to get node: {:?}",node_id)),
 
        }
@@ -1993,19 +2211,79 @@ fn new_graph_editor(world:&World) -> GraphEditor {
          }
      }));
 
+    def _set_node_type = inputs.set_node_type.map(f!([nodes]((node_id,tp)) {
+         if let Some(node) = nodes.get_cloned(node_id) {
+             node.set_value_type(tp.clone());
+         }
+     }));
+
+    def _set_data_error = inputs.set_visualization_data_error.map(f!([nodes]((node_id,error)) {
+         if let Some(node) = nodes.get_cloned(node_id) {
+             node.visualization.frp.set_data_error.emit(error);
+         }
+     }));
+
+
+    // === Widgets ===
+
+    kind_toggled <= inputs.toggle_widget_for_selected_node.map(f_!(
+        model.toggled_node_kinds(&model.selected_nodes())
+    ));
+    kind_set <- any(&kind_toggled,&inputs.set_node_kind);
+    // Widgets always display their visualizations, so they are enabled when a node becomes one.
+    widget_node <= kind_set.map(f!([nodes]((node_id,kind)) {
+         if let Some(node) = nodes.get_cloned(node_id) {
+             node.frp.set_kind.emit(kind);
+         }
+         (*kind == node::Kind::Widget).as_some(*node_id)
+     }));
+    outputs.node_kind_changed   <+ kind_set;
+    outputs.widget_data_changed <+ inputs.node_widget_data;
+
+
+    // === Visualization Panels ===
+
+    panel_toggled <= inputs.toggle_panel_for_selected_visualization.map(f_!(
+        model.toggled_visualization_panels(&model.selected_nodes())
+    ));
+    panel_set <- any(&panel_toggled,&inputs.set_visualization_panel);
+    eval panel_set (((id,layout)) model.set_visualization_panel(id,*layout));
+    outputs.visualization_panel_changed <+ panel_set;
+    outputs.visualization_panel_changed <+ inputs.node_panel_layout;
+
+
+    // === Node Comments ===
+
+    eval inputs.set_node_comment (((id,comment)) model.set_node_comment(id,comment));
+
+    comment_edit_node <= inputs.edit_comment_for_selected_node.map(f_!(model.last_selected_node()));
+    eval comment_edit_node ([model,comment_editor](id) {
+        if let Some((position,comment)) = model.node_comment_edit_info(id) {
+            comment_editor.clone_ref().edit(*id,position,&comment);
+        }
+    });
+    eval_ inputs.commit_comment (comment_editor.clone_ref().commit());
+    eval_ inputs.cancel         (comment_editor.clone_ref().cancel());
+    eval comment_editor.committed (((id,comment)) model.set_node_comment(id,comment));
+    outputs.node_comment_changed <+ comment_editor.committed;
+
      nodes_to_cycle <= inputs.cycle_visualization_for_selected_node.map(f_!(model.selected_nodes()));
      node_to_cycle  <- any(nodes_to_cycle,inputs.cycle_visualization);
 
      let cycle_count = Rc::new(Cell::new(0));
-     def _cycle_visualization = node_to_cycle.map(f!([scene,nodes,visualizations,logger](node_id) {
-        let visualizations = visualizations.valid_sources(&"Any".into());
+     def _cycle_visualization = node_to_cycle.map(f!([scene,nodes,visualizations,logger,visualization_changed](node_id) {
+        let node           = nodes.get_cloned_ref(node_id);
+        let value_type     = node.as_ref().map(|node| node.value_type()).unwrap_or_default();
+        let visualizations = visualizations.valid_sources(&value_type);
+        if visualizations.is_empty() { return }
         cycle_count.set(cycle_count.get() % visualizations.len());
-        let vis  = &visualizations[cycle_count.get()];
-        let vis  = vis.new_instance(&scene);
-        let node = nodes.get_cloned_ref(node_id);
+        let definition = &visualizations[cycle_count.get()];
+        let vis        = definition.new_instance(&scene);
         match (vis, node) {
             (Ok(vis), Some(node))  => {
-                node.visualization.frp.set_visualization.emit(Some(vis));
+                let signature = definition.signature.clone_ref();
+                node.set_visualization(signature.clone_ref(),vis);
+                visualization_changed.emit((*node_id,Some(signature)));
             },
             (Err(e), _) => logger.warning(|| format!("Failed to cycle visualization: {:?}", e)),
             _           => {}
@@ -2028,10 +2306,7 @@ fn new_graph_editor(world:&World) -> GraphEditor {
     viz_pressed          <- bool(&viz_release,&viz_press_ev);
     viz_was_pressed      <- viz_pressed.previous();
     viz_press            <- viz_press_ev.gate_not(&viz_was_pressed);
-    viz_press_time       <- viz_press   . map(|_| web::performance().now() as f32);
-    viz_release_time     <- viz_release . map(|_| web::performance().now() as f32);
-    viz_press_time_diff  <- viz_release_time.map2(&viz_press_time,|t1,t0| t1-t0);
-    viz_preview_mode     <- viz_press_time_diff.map(|t| *t > VIZ_PREVIEW_MODE_TOGGLE_TIME_MS);
+    viz_preview_mode     <- viz_press.timeout(&clock,VIZ_PREVIEW_MODE_TOGGLE_TIME_MS);
     viz_preview_mode_end <- viz_release.gate(&viz_preview_mode);
     viz_tgt_nodes        <- viz_press.map(f_!(model.selected_nodes()));
     viz_tgt_nodes_off    <- viz_tgt_nodes.map(f!([model](node_ids) {
@@ -2048,9 +2323,30 @@ fn new_graph_editor(world:&World) -> GraphEditor {
     viz_preview_disable  <= viz_tgt_nodes_off.sample(&viz_preview_mode_end);
     viz_fullscreen_on    <= viz_d_press_ev.map(f_!(model.last_selected_node()));
 
-    outputs.visualization_enabled  <+ viz_enable;
+    viz_enable_any       <- any(&viz_enable,&inputs.enable_visualization,&widget_node);
+    viz_enable_defaulted <- viz_enable_any.map(
+        f!([nodes,scene,visualizations,logger,visualization_changed](node_id) {
+        // When the visualization is shown for the first time, the default one for the node's type
+        // is chosen.
+        let node = nodes.get_cloned_ref(node_id);
+        if let Some(node) = node.filter(|node| node.visualization_signature().is_none()) {
+            let definition = visualizations.default_for(&node.value_type());
+            match definition.new_instance(&scene) {
+                Ok(vis) => {
+                    let signature = definition.signature.clone_ref();
+                    node.set_visualization(signature.clone_ref(),vis);
+                    visualization_changed.emit((*node_id,Some(signature)));
+                }
+                Err(err) => logger.warning(
+                    || format!("Failed to instantiate default visualisation: {:?}",err)),
+            }
+        }
+        *node_id
+    }));
+    outputs.visualization_enabled  <+ viz_enable_defaulted;
     outputs.visualization_disabled <+ viz_disable;
     outputs.visualization_disabled <+ viz_preview_disable;
+    outputs.visualization_disabled <+ inputs.disable_visualization;
     outputs.visualization_enable_fullscreen <+ viz_fullscreen_on;
 
 
@@ -2061,6 +2357,11 @@ fn new_graph_editor(world:&World) -> GraphEditor {
             visualizations.add(handle);
         }
     }));
+    def _unregister_visualization = inputs.unregister_visualization.map(f!([visualizations](path) {
+        if let Some(path) = path {
+            visualizations.remove(path);
+        }
+    }));
 
 
     // === OUTPUTS REBIND ===
@@ -2098,6 +2399,7 @@ fn new_graph_editor(world:&World) -> GraphEditor {
     def is_empty_src  = source::<bool>();
     def is_active = is_active_src.sampler();
     def is_empty  = is_empty_src.sampler();
+    def is_command_palette_visible = inputs.set_command_palette_visibility.sampler();
 
     // === Remove implementation ===
     outputs.node_removed <+ inputs.remove_node;
@@ -2120,7 +2422,9 @@ fn new_graph_editor(world:&World) -> GraphEditor {
     //       management is implemented.
     is_active_src.emit(true);
 
-    let status = FrpStatus {is_active,is_empty};
+    let is_editing_comment = comment_editor.is_editing.clone_ref();
+    let status             = FrpStatus {is_active,is_empty,is_editing_comment
+                                        ,is_command_palette_visible};
 
     let node_release = touch.nodes.up.clone_ref();
 
@@ -503,6 +503,27 @@ pub async fn sleep(duration:Duration) {
 #[cfg(not(target_arch = "wasm32"))]
 pub use async_std::task::sleep;
 
+/// Calls the provided function once, after the specified amount of time.
+///
+/// Just like `sleep`, the callback might be called slightly later but never earlier.
+#[cfg(target_arch = "wasm32")]
+pub fn set_timeout(duration:Duration, callback:impl FnOnce() + 'static) {
+    let millis = duration.as_millis() as u32;
+    gloo_timers::callback::Timeout::new(millis,callback).forget();
+}
+
+/// Calls the provided function once, after the specified amount of time.
+///
+/// Just like `sleep`, the callback might be called slightly later but never earlier. The callback
+/// is called on another thread, so unlike in the browser, it needs to be `Send`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_timeout(duration:Duration, callback:impl FnOnce() + Send + 'static) {
+    async_std::task::spawn(async move {
+        sleep(duration).await;
+        callback()
+    });
+}
+
 
 
 // ============
@@ -558,4 +579,14 @@ mod tests {
     fn async_sleep_native() {
         async_std::task::block_on(async_sleep())
     }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn set_timeout_native() {
+        let (sender,receiver) = std::sync::mpsc::channel();
+        let instant           = helpers::now();
+        set_timeout(Duration::from_millis(100), move || sender.send(()).unwrap());
+        receiver.recv().unwrap();
+        assert!(helpers::elapsed(instant) >= 0.1);
+    }
 }